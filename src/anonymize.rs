@@ -0,0 +1,159 @@
+// src/anonymize.rs
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{content_hash, paths};
+
+// Consistent original → placeholder replacements for --anonymize
+pub struct Anonymizer {
+    replacements: Vec<(String, String)>,
+    // Root directory names, which are only replaced as path components since
+    // names like `crate`, `core` or `app` are also identifiers in the code
+    path_names: Vec<String>,
+    pattern: Option<Regex>,
+}
+
+impl Anonymizer {
+    // Placeholders for the project name, the copied root directories and the
+    // identifiers listed in the optional mapping file
+    pub fn new(project_name: Option<&str>, roots: &[PathBuf], identifiers_path: Option<&str>) -> io::Result<Anonymizer> {
+        let mut replacements: Vec<(String, String)> = Vec::new();
+        let mut path_names: Vec<String> = Vec::new();
+        let mut add = |original: String, placeholder: String| {
+            if !original.is_empty() && !replacements.iter().any(|(existing, _)| *existing == original) {
+                replacements.push((original, placeholder));
+            }
+        };
+        
+        // Crate names are written with underscores in Rust code
+        if let Some(name) = project_name {
+            add(name.to_string(), placeholder_like(name, "project-x"));
+            add(name.replace('-', "_"), "project_x".to_string());
+        }
+        
+        for (index, root) in roots.iter().enumerate() {
+            let placeholder = format!("root_{}", index + 1);
+            add(root.to_string_lossy().to_string(), placeholder.clone());
+            add(paths::normalize(root), placeholder.clone());
+            if let Some(name) = root.file_name() {
+                let name = name.to_string_lossy().to_string();
+                // A directory named after the project is replaced everywhere like the project name
+                if project_name.is_none_or(|project| name != project && name != project.replace('-', "_")) {
+                    path_names.push(name.clone());
+                }
+                add(name, placeholder);
+            }
+        }
+        
+        if let Some(path) = identifiers_path {
+            let content = fs::read_to_string(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("Failed to read identifier file {}: {}", path, e)))?;
+            let mut count = 0;
+            for line in content.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                match line.split_once('=') {
+                    Some((original, placeholder)) => add(original.trim().to_string(), placeholder.trim().to_string()),
+                    None => {
+                        count += 1;
+                        add(line.to_string(), format!("ident_{}", count));
+                    }
+                }
+            }
+        }
+        
+        // Longest first, so that a path wins over the directory name inside it
+        replacements.sort_by_key(|(original, _)| std::cmp::Reverse(original.len()));
+        let pattern = if replacements.is_empty() {
+            None
+        } else {
+            let alternatives: Vec<String> = replacements.iter().map(|(original, _)| regex::escape(original)).collect();
+            Some(Regex::new(&alternatives.join("|")).map_err(io::Error::other)?)
+        };
+        
+        Ok(Anonymizer { replacements, path_names, pattern })
+    }
+    
+    // Replaces whole-word occurrences only, so `acme` does not touch `acme_client`
+    pub fn apply(&self, text: &str) -> String {
+        let pattern = match self.pattern {
+            Some(ref pattern) => pattern,
+            None => return text.to_string(),
+        };
+        
+        let mut output = String::with_capacity(text.len());
+        let mut last = 0;
+        for found in pattern.find_iter(text) {
+            let matched = found.as_str();
+            let joins_before = starts_with_word_char(matched) && text[..found.start()].chars().next_back().is_some_and(is_word_char);
+            let joins_after = ends_with_word_char(matched) && text[found.end()..].chars().next().is_some_and(is_word_char);
+            if joins_before || joins_after {
+                continue;
+            }
+            if self.path_names.iter().any(|name| name == matched) && !in_path(text, found.start(), found.end()) {
+                continue;
+            }
+            
+            if let Some((_, placeholder)) = self.replacements.iter().find(|(original, _)| original == matched) {
+                output.push_str(&text[last..found.start()]);
+                output.push_str(placeholder);
+                last = found.end();
+            }
+        }
+        output.push_str(&text[last..]);
+        output
+    }
+    
+    // Writes the original → placeholder mapping for the project to the data
+    // directory so that answers can be translated back, and returns its path
+    pub fn save_mapping(&self, project_root: &Path) -> io::Result<PathBuf> {
+        let dir = dirs::data_dir()
+            .map(|dir| dir.join("codecopier").join("anonymize"))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not determine the data directory"))?;
+        fs::create_dir_all(&dir)?;
+        
+        let root_hash = content_hash(&project_root.to_string_lossy());
+        let path = dir.join(format!("{}.json", &root_hash[..16]));
+        
+        let mut mapping = Map::new();
+        for (original, placeholder) in &self.replacements {
+            mapping.insert(original.clone(), Value::String(placeholder.clone()));
+        }
+        let json = serde_json::to_string_pretty(&Value::Object(mapping)).map_err(io::Error::other)?;
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+}
+
+fn placeholder_like(name: &str, placeholder: &str) -> String {
+    if name.contains('-') {
+        placeholder.to_string()
+    } else {
+        placeholder.replace('-', "_")
+    }
+}
+
+// Whether the text at start..end is a path component: next to a path
+// separator, or a whole quoted value like `path="app"`
+fn in_path(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    matches!(before, Some('/' | '\\')) || matches!(after, Some('/' | '\\')) || (before == Some('"') && after == Some('"'))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn starts_with_word_char(text: &str) -> bool {
+    text.chars().next().is_some_and(is_word_char)
+}
+
+fn ends_with_word_char(text: &str) -> bool {
+    text.chars().next_back().is_some_and(is_word_char)
+}
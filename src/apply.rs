@@ -0,0 +1,332 @@
+// src/apply.rs
+use clap::{Args, ValueEnum};
+use log::{info, warn};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use crate::backup::Snapshot;
+use crate::{editor, patch, review};
+
+// How the model is asked to structure its reply with --reply-format, matching
+// the formats `apply` reads
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReplyFormat {
+    Xml,
+    Diff,
+    Json,
+}
+
+impl ReplyFormat {
+    pub fn instructions(self) -> &'static str {
+        match self {
+            ReplyFormat::Xml => "Reply with every file you change in full, in the same format as the files above: a <file path=\"...\"> line with the path exactly as given above, the complete new content of the file, and a </file> line. Do not put the blocks in Markdown code fences and do not include files you leave unchanged. Explanations go before or after the blocks, never inside them.",
+            ReplyFormat::Diff => "Reply with your changes as a single unified diff, as produced by `git diff`: `--- a/<path>` and `+++ b/<path>` headers with the paths exactly as given above, `@@` hunk headers, and three lines of unchanged context around every change. Use /dev/null as the old path of new files and as the new path of deleted files. Explanations go before or after the diff, never inside it.",
+            ReplyFormat::Json => "Reply with a single JSON object and nothing else, in this shape: {\"version\": 1, \"files\": [{\"path\": \"<path exactly as given above>\", \"content\": \"<complete new content of the file>\"}]}. List only the files you change.",
+        }
+    }
+}
+
+// A change `apply` makes to one file
+pub struct FileChange {
+    pub path: PathBuf,
+    pub edit: Edit,
+}
+
+pub enum Edit {
+    Replace(String),
+    Patch(Vec<patch::Hunk>),
+    Delete,
+}
+
+// Formats `apply` reads; `auto` recognizes them by their content
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum InputFormat {
+    Auto,
+    // An editor-json document or a --reply-format json reply
+    Json,
+    // <file path="..."> blocks
+    Xml,
+    // A unified diff
+    Diff,
+}
+
+// What `apply` does with diff hunks that match nowhere in the current file
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum ConflictMode {
+    // Write them between conflict markers
+    Markers,
+    // Leave them out and apply the rest
+    Skip,
+    // Leave the file unchanged
+    Fail,
+}
+
+// Arguments of the `apply` subcommand
+#[derive(Args)]
+pub struct ApplyArgs {
+    /// Model reply or editor-json document to apply (reads stdin when omitted or -)
+    #[arg(value_name = "FILE")]
+    input: Option<String>,
+    
+    /// Format of the input
+    #[arg(long, value_enum, default_value = "auto")]
+    format: InputFormat,
+    
+    /// Directory the paths in the input are relative to, usually the directory that was copied
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    root: PathBuf,
+    
+    /// What to do with diff hunks that do not match the current file
+    #[arg(long, value_enum, value_name = "MODE", default_value = "markers")]
+    on_conflict: ConflictMode,
+    
+    /// Step through the changes hunk by hunk and apply only the accepted ones
+    #[arg(short, long)]
+    interactive: bool,
+    
+    /// Print the files that would change without writing them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+// `apply` subcommand: writes a model's reply (full files, a diff or JSON) or
+// an editor-json document back to disk
+pub fn run_apply(args: ApplyArgs) -> io::Result<()> {
+    let mut input = String::new();
+    match args.input.as_deref() {
+        None | Some("-") => {
+            io::stdin().read_to_string(&mut input)?;
+        }
+        Some(path) => {
+            input = fs::read_to_string(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("Failed to read {}: {}", path, e)))?;
+        }
+    }
+    
+    let format = match args.format {
+        InputFormat::Auto => detect_format(&input)?,
+        format => format,
+    };
+    let changes = match format {
+        InputFormat::Json => editor::read_changes(json_object(&input).unwrap_or(&input), &args.root)?,
+        InputFormat::Xml => xml_changes(&input, &args.root)?,
+        InputFormat::Diff => diff_changes(&input, &args.root)?,
+        InputFormat::Auto => unreachable!(),
+    };
+    if changes.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "The input contains no file changes"));
+    }
+    let changes = if args.interactive { review::review(changes)? } else { changes };
+    
+    let mut snapshot = Snapshot::new(&args.root)?;
+    let mut failed = 0;
+    let mut conflicts = 0;
+    for change in changes {
+        match apply_change(&change, &mut snapshot, args.on_conflict, args.dry_run) {
+            Ok(count) => conflicts += count,
+            Err(e) => {
+                warn!("Could not apply the changes to {}: {}", change.path.display(), e);
+                failed += 1;
+            }
+        }
+    }
+    
+    if let Some(dir) = snapshot.dir() {
+        info!("Backed up {} files to {}; `undo` restores them", snapshot.files(), dir.display());
+    }
+    
+    if failed > 0 {
+        return Err(io::Error::other(format!("{} files could not be changed", failed)));
+    }
+    if conflicts > 0 {
+        return Err(io::Error::other(format!("{} hunks did not apply cleanly", conflicts)));
+    }
+    Ok(())
+}
+
+// Writes one change and returns the number of conflicting hunks
+fn apply_change(change: &FileChange, snapshot: &mut Snapshot, on_conflict: ConflictMode, dry_run: bool) -> io::Result<usize> {
+    let path = &change.path;
+    let current = fs::read_to_string(path).ok();
+    let mut conflicts = 0;
+    let updated = match change.edit {
+        Edit::Replace(ref content) => Some(content.clone()),
+        Edit::Delete => None,
+        Edit::Patch(ref hunks) => {
+            let patched = patch::apply(current.as_deref().unwrap_or(""), hunks, on_conflict == ConflictMode::Markers);
+            report_hunks(path, &patched, on_conflict);
+            conflicts = patched.conflicts();
+            if conflicts > 0 && on_conflict == ConflictMode::Fail {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} of {} hunks do not match the current file", conflicts, hunks.len()),
+                ));
+            }
+            Some(patched.content)
+        }
+    };
+    
+    match updated {
+        Some(ref content) if current.as_ref() == Some(content) => {}
+        None if current.is_none() => {}
+        Some(_) | None if dry_run => {
+            let action = if updated.is_none() { "delete" } else if current.is_none() { "create" } else { "update" };
+            println!("Would {} {}", action, path.display());
+        }
+        Some(content) => {
+            snapshot.save(path)?;
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, content)?;
+            println!("{} {}", if current.is_none() { "Created" } else { "Updated" }, path.display());
+        }
+        None => {
+            snapshot.save(path)?;
+            fs::remove_file(path)?;
+            println!("Deleted {}", path.display());
+        }
+    }
+    Ok(conflicts)
+}
+
+fn report_hunks(path: &Path, patched: &patch::Patched, on_conflict: ConflictMode) {
+    for (index, outcome) in patched.outcomes.iter().enumerate() {
+        match *outcome {
+            patch::Outcome::Applied { line, fuzzy: true } => {
+                info!("{}: hunk {} applied at line {} with differing context", path.display(), index + 1, line)
+            }
+            patch::Outcome::Applied { .. } => {}
+            patch::Outcome::AlreadyApplied => {
+                info!("{}: hunk {} is already applied, skipping", path.display(), index + 1)
+            }
+            patch::Outcome::Conflict { line } => match on_conflict {
+                ConflictMode::Markers => {
+                    warn!("{}: hunk {} conflicts with the current file, marked at line {}", path.display(), index + 1, line)
+                }
+                ConflictMode::Skip => {
+                    warn!("{}: hunk {} (near line {}) does not match the current file, skipped", path.display(), index + 1, line)
+                }
+                ConflictMode::Fail => {
+                    warn!("{}: hunk {} (near line {}) does not match the current file", path.display(), index + 1, line)
+                }
+            },
+        }
+    }
+}
+
+fn detect_format(input: &str) -> io::Result<InputFormat> {
+    let trimmed = input.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with("```json") {
+        Ok(InputFormat::Json)
+    } else if input.contains("<file path=\"") {
+        Ok(InputFormat::Xml)
+    } else if input.lines().any(|line| line.starts_with("+++ ")) && input.lines().any(|line| line.starts_with("@@ ")) {
+        Ok(InputFormat::Diff)
+    } else if json_object(input).is_some() {
+        // A JSON reply after some prose
+        Ok(InputFormat::Json)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Could not recognize the input; expected <file> blocks, a unified diff or JSON",
+        ))
+    }
+}
+
+// The first JSON object in a ```json fence or starting a line, without the
+// fences and text around it
+fn json_object(input: &str) -> Option<&str> {
+    let fenced = input.match_indices("```json").filter_map(|(index, fence)| {
+        let body = index + fence.len();
+        input[body..].find('\n').map(|newline| body + newline + 1)
+    });
+    let mut offset = 0;
+    let line_starts = input.split_inclusive('\n').filter_map(move |line| {
+        let start = offset + line.len() - line.trim_start().len();
+        offset += line.len();
+        input[start..].starts_with('{').then_some(start)
+    });
+    fenced.chain(line_starts).find_map(|start| {
+        let text = input[start..].trim_start();
+        let start = input.len() - text.len();
+        let mut values = serde_json::Deserializer::from_str(text).into_iter::<serde_json::Value>();
+        match values.next() {
+            Some(Ok(value)) if value.is_object() => Some(&input[start..start + values.byte_offset()]),
+            _ => None,
+        }
+    })
+}
+
+// Full files in <file path="..."> blocks, as in the copied output
+fn xml_changes(input: &str, root: &Path) -> io::Result<Vec<FileChange>> {
+    let mut changes = Vec::new();
+    let mut rest = input;
+    
+    while let Some(start) = rest.find("<file path=\"") {
+        let tag_end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let tag = &rest[start..tag_end];
+        rest = &rest[tag_end + 1..];
+        // References like <file path="..." same-as="..."/> carry no content
+        if tag.ends_with('/') {
+            continue;
+        }
+        
+        let path = tag.trim_start_matches("<file path=\"").split('"').next().unwrap_or("");
+        let close = rest.find("</file>").ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("<file path=\"{}\"> is not closed", path))
+        })?;
+        let content = strip_code_fence(rest[..close].strip_prefix('\n').unwrap_or(&rest[..close]));
+        rest = &rest[close + "</file>".len()..];
+        
+        let mut content = content.trim_end_matches('\n').to_string();
+        content.push('\n');
+        changes.push(FileChange { path: resolve(root, path)?, edit: Edit::Replace(content) });
+    }
+    
+    Ok(changes)
+}
+
+// Models sometimes fence file contents despite being asked not to
+fn strip_code_fence(content: &str) -> &str {
+    let trimmed = content.trim();
+    match (trimmed.strip_prefix("```"), trimmed.strip_suffix("```")) {
+        (Some(_), Some(_)) if trimmed.len() > 6 => {
+            let inner = &trimmed[..trimmed.len() - 3];
+            inner.split_once('\n').map(|(_, body)| body).unwrap_or("")
+        }
+        _ => content,
+    }
+}
+
+fn diff_changes(input: &str, root: &Path) -> io::Result<Vec<FileChange>> {
+    let mut changes = Vec::new();
+    for file in patch::parse(input) {
+        let change = match (file.old_path, file.new_path) {
+            (Some(path), None) => FileChange { path: resolve(root, &path)?, edit: Edit::Delete },
+            (_, Some(path)) => FileChange { path: resolve(root, &path)?, edit: Edit::Patch(file.hunks) },
+            (None, None) => continue,
+        };
+        changes.push(change);
+    }
+    Ok(changes)
+}
+
+// Paths from a reply stay inside the root, whatever the model wrote
+pub fn resolve(root: &Path, path: &str) -> io::Result<PathBuf> {
+    let relative = Path::new(path);
+    if relative.components().any(|component| !matches!(component, Component::Normal(_) | Component::CurDir)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Refusing to write {}: paths must be relative and stay inside {}", path, root.display()),
+        ));
+    }
+    if root == Path::new(".") {
+        return Ok(relative.to_path_buf());
+    }
+    Ok(root.join(relative))
+}
@@ -0,0 +1,52 @@
+// src/architecture.rs
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::imports;
+use crate::FileEntry;
+use crate::paths;
+
+// Builds a Mermaid `graph TD` of the dependencies between the included files
+pub fn dependency_graph(files: &[FileEntry], project_root: &Path) -> Option<String> {
+    let canonical: Vec<PathBuf> = files
+        .iter()
+        .map(|file| paths::canonicalize(&file.source_path).unwrap_or_else(|_| file.source_path.clone()))
+        .collect();
+    let index_by_path: HashMap<&PathBuf, usize> = canonical.iter().enumerate().map(|(i, p)| (p, i)).collect();
+    let crate_names = imports::rust_crate_names(project_root);
+    
+    let mut edges = BTreeSet::new();
+    for (i, file) in files.iter().enumerate() {
+        for import in imports::extract_imports(&canonical[i], &file.content) {
+            for target in imports::resolve(&import, &canonical[i], project_root, &crate_names) {
+                let target = paths::canonicalize(&target).unwrap_or(target);
+                if let Some(&j) = index_by_path.get(&target) {
+                    if i != j {
+                        edges.insert((i, j));
+                    }
+                }
+            }
+        }
+    }
+    
+    if edges.is_empty() {
+        return None;
+    }
+    
+    // Only files that take part in a dependency become nodes
+    let nodes: BTreeSet<usize> = edges.iter().flat_map(|&(from, to)| [from, to]).collect();
+    
+    let mut graph = String::from("graph TD\n");
+    for &node in &nodes {
+        graph.push_str(&format!("    n{}[\"{}\"]\n", node, files[node].path.replace('"', "'")));
+    }
+    for (from, to) in edges {
+        graph.push_str(&format!("    n{} --> n{}\n", from, to));
+    }
+    
+    Some(graph)
+}
+
+pub fn format_architecture_section(graph: &str) -> String {
+    format!("<architecture>\n```mermaid\n{}```\n</architecture>\n\n", graph)
+}
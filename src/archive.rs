@@ -0,0 +1,82 @@
+// src/archive.rs
+use flate2::read::GzDecoder;
+use log::info;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::scratch::ScratchDir;
+
+// Archive formats accepted as paths, by file name suffix
+const SUFFIXES: &[&str] = &[".zip", ".tar.gz", ".tgz", ".tar"];
+
+// Replaces archive paths with directories they were extracted to; the
+// returned directories must be kept alive until the output is generated
+pub fn extract_archive_paths(paths: &mut [String]) -> io::Result<Vec<ScratchDir>> {
+    let mut extracted = Vec::new();
+    for path in paths.iter_mut() {
+        let archive = Path::new(path.as_str());
+        let name = match archive.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let suffix = match SUFFIXES.iter().find(|suffix| name.to_lowercase().ends_with(*suffix)) {
+            Some(suffix) => *suffix,
+            None => continue,
+        };
+        if !archive.is_file() {
+            continue;
+        }
+        
+        // Extracting into a directory named after the archive keeps its name in the output
+        let scratch = ScratchDir::create("archive")?;
+        let dir = scratch.path().join(&name[..name.len() - suffix.len()]);
+        fs::create_dir_all(&dir)?;
+        
+        info!("Extracting {}...", archive.display());
+        let file = File::open(archive)?;
+        let result = match suffix {
+            ".zip" => extract_zip(file, &dir),
+            ".tar" => tar::Archive::new(file).unpack(&dir),
+            _ => tar::Archive::new(GzDecoder::new(file)).unpack(&dir),
+        };
+        result.map_err(|e| io::Error::new(e.kind(), format!("Failed to extract {}: {}", archive.display(), e)))?;
+        
+        *path = single_top_level_dir(&dir).unwrap_or(dir).to_string_lossy().to_string();
+        extracted.push(scratch);
+    }
+    Ok(extracted)
+}
+
+// Entries with absolute paths or `..` are skipped rather than written outside `dir`
+fn extract_zip(file: File, dir: &Path) -> io::Result<()> {
+    let mut zip = zip::ZipArchive::new(file).map_err(io::Error::other)?;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(io::Error::other)?;
+        let relative = match entry.enclosed_name() {
+            Some(relative) => relative,
+            None => continue,
+        };
+        let target = dir.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut File::create(&target)?)?;
+    }
+    Ok(())
+}
+
+// Release archives usually wrap everything in a `name-version/` directory,
+// which would otherwise prefix every path
+fn single_top_level_dir(dir: &Path) -> Option<PathBuf> {
+    let mut entries = fs::read_dir(dir).ok()?.filter_map(|entry| entry.ok());
+    let only = entries.next()?;
+    if entries.next().is_some() || !only.path().is_dir() {
+        return None;
+    }
+    Some(only.path())
+}
@@ -0,0 +1,163 @@
+// src/backup.rs
+use chrono::Local;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+
+const BACKUP_DIR: &str = ".codecopier/backups";
+const MANIFEST: &str = "manifest.json";
+
+// The files `apply` was about to change, relative to the project root
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    files: Vec<BackedUpFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BackedUpFile {
+    path: String,
+    // Files that did not exist yet are removed again on undo
+    existed: bool,
+}
+
+// Arguments of the `undo` subcommand
+#[derive(Args)]
+pub struct UndoArgs {
+    /// Directory inside the project whose last apply to undo
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    root: PathBuf,
+}
+
+// Copies of the files `apply` changes in .codecopier/backups/<timestamp>/
+// under the project root, created with the first file
+pub struct Snapshot {
+    project_root: PathBuf,
+    dir: Option<PathBuf>,
+    manifest: Manifest,
+}
+
+impl Snapshot {
+    pub fn new(root: &Path) -> io::Result<Snapshot> {
+        Ok(Snapshot { project_root: project_root(root)?, dir: None, manifest: Manifest { files: Vec::new() } })
+    }
+    
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+    
+    pub fn files(&self) -> usize {
+        self.manifest.files.len()
+    }
+    
+    // Backs up a file before it is written or deleted
+    pub fn save(&mut self, path: &Path) -> io::Result<()> {
+        // New files do not exist yet to be canonicalized
+        let absolute = match paths::canonicalize(path) {
+            Ok(absolute) => absolute,
+            Err(_) if path.is_absolute() => path.to_path_buf(),
+            Err(_) => paths::canonicalize(".")?.join(path),
+        };
+        let relative = absolute.strip_prefix(&self.project_root).map_err(|_| {
+            io::Error::other(format!(
+                "{} is outside of {}, refusing to change it without a backup",
+                path.display(),
+                self.project_root.display()
+            ))
+        })?;
+        
+        let dir = match self.dir {
+            Some(ref dir) => dir.clone(),
+            None => {
+                let dir = create_snapshot_dir(&self.project_root.join(BACKUP_DIR))?;
+                self.dir = Some(dir.clone());
+                dir
+            }
+        };
+        let existed = path.is_file();
+        if existed {
+            let backup = dir.join(relative);
+            if let Some(parent) = backup.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(path, &backup)?;
+        }
+        self.manifest.files.push(BackedUpFile { path: paths::normalize(relative), existed });
+        
+        // Rewritten after every file so an interrupted apply can still be undone
+        let json = serde_json::to_string_pretty(&self.manifest).map_err(io::Error::other)?;
+        fs::write(dir.join(MANIFEST), json)
+    }
+}
+
+fn create_snapshot_dir(backups: &Path) -> io::Result<PathBuf> {
+    fs::create_dir_all(backups)?;
+    
+    // Several applies within the same second get a numeric suffix
+    let base_id = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut id = base_id.clone();
+    let mut suffix = 2;
+    while backups.join(&id).exists() {
+        id = format!("{}-{}", base_id, suffix);
+        suffix += 1;
+    }
+    let dir = backups.join(&id);
+    fs::create_dir(&dir)?;
+    Ok(dir)
+}
+
+// `undo` subcommand: restores the latest snapshot and removes it, so running
+// it again goes back one more apply
+pub fn run_undo(args: UndoArgs) -> io::Result<()> {
+    let project_root = project_root(&args.root)?;
+    let backups = project_root.join(BACKUP_DIR);
+    
+    let mut snapshots: Vec<PathBuf> = match fs::read_dir(&backups) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).filter(|path| path.join(MANIFEST).is_file()).collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    // Ids sort by time, with suffixes after the unsuffixed id of the same second
+    snapshots.sort_by_key(|path| {
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let (time, suffix) = match name.get(15..).and_then(|rest| rest.strip_prefix('-')) {
+            Some(suffix) => (name[..15].to_string(), suffix.parse::<u32>().unwrap_or(0)),
+            None => (name.clone(), 0),
+        };
+        (time, suffix)
+    });
+    let latest = snapshots.pop().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, format!("No backups found in {}", backups.display()))
+    })?;
+    
+    let manifest: Manifest = serde_json::from_str(&fs::read_to_string(latest.join(MANIFEST))?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid backup manifest: {}", e)))?;
+    for file in &manifest.files {
+        let target = project_root.join(&file.path);
+        if file.existed {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(latest.join(&file.path), &target)?;
+            println!("Restored {}", file.path);
+        } else {
+            match fs::remove_file(&target) {
+                Ok(()) => println!("Removed {}", file.path),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    
+    fs::remove_dir_all(&latest)?;
+    Ok(())
+}
+
+fn project_root(root: &Path) -> io::Result<PathBuf> {
+    let root = paths::canonicalize(root)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to resolve {}: {}", root.display(), e)))?;
+    Ok(crate::find_project_root(&[root.to_string_lossy().to_string()]).unwrap_or(root))
+}
@@ -0,0 +1,380 @@
+// src/bazel.rs
+use globset::{GlobBuilder, GlobSetBuilder};
+use log::{debug, warn};
+use regex::Regex;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::paths;
+
+// Files marking the root of a Bazel or Buck repository
+const ROOT_MARKERS: &[&str] = &["MODULE.bazel", "WORKSPACE.bazel", "WORKSPACE", ".buckconfig"];
+
+// Package build files, in the order Bazel prefers them
+const BUILD_FILES: &[&str] = &["BUILD.bazel", "BUILD", "BUCK"];
+
+// Rule attributes holding the target's own files, and its direct dependencies
+const SOURCE_ATTRIBUTES: &[&str] = &["srcs", "hdrs", "textual_hdrs"];
+const DEPENDENCY_ATTRIBUTES: &[&str] = &["deps", "runtime_deps"];
+
+// A `//package:name` label of the main repository
+#[derive(Clone, PartialEq)]
+struct Label {
+    package: String,
+    name: String,
+}
+
+impl Label {
+    fn display(&self) -> String {
+        format!("//{}:{}", self.package, self.name)
+    }
+}
+
+// The repository holding `dir`, and the name the main repository goes by in
+// labels (`@name//...`), from MODULE.bazel or WORKSPACE
+struct Repository {
+    root: PathBuf,
+    name: Option<String>,
+}
+
+// The source files of `target` and of its direct dependencies, with the
+// build files declaring them. `bazel query` answers when it is installed;
+// otherwise, and in Buck repositories, the build files are read directly
+pub fn target_files(target: &str, dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let repository = find_repository(dir).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("--target requires a Bazel or Buck repository, and none was found at or above {}", dir.display()),
+        )
+    })?;
+    let current_package = paths::normalize(dir.strip_prefix(&repository.root).unwrap_or(Path::new("")));
+    let label = parse_label(target, &current_package, &repository)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a label of this repository", target)))?;
+    
+    if !repository.root.join(".buckconfig").is_file() {
+        match query(&repository, &label) {
+            Ok(files) => return Ok(files),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => debug!("bazel is not installed, reading the BUILD files"),
+            Err(e) => warn!("{}; reading the BUILD files instead", e),
+        }
+    }
+    
+    let rule = read_rule(&repository, &label)?;
+    let mut files = vec![rule.build_file.clone()];
+    files.extend(sources(&repository, &rule, true));
+    for dependency in &rule.deps {
+        match read_rule(&repository, dependency) {
+            Ok(dependency) => {
+                files.push(dependency.build_file.clone());
+                files.extend(sources(&repository, &dependency, true));
+            }
+            // Dependencies may be generated or come from macros the build files do not show
+            Err(e) => debug!("Skipping dependency {}: {}", dependency.display(), e),
+        }
+    }
+    let mut unique = Vec::new();
+    for file in files {
+        if !unique.contains(&file) {
+            unique.push(file);
+        }
+    }
+    Ok(unique)
+}
+
+fn find_repository(dir: &Path) -> Option<Repository> {
+    let root = dir.ancestors().find(|dir| ROOT_MARKERS.iter().any(|marker| dir.join(marker).is_file()))?;
+    let name = ["MODULE.bazel", "WORKSPACE.bazel", "WORKSPACE"].iter().find_map(|file| {
+        let content = fs::read_to_string(root.join(file)).ok()?;
+        let declaration = Regex::new(r#"(?:module|workspace)\s*\(\s*name\s*=\s*"([^"]+)""#).expect("valid regex");
+        declaration.captures(&content).map(|caps| caps[1].to_string())
+    });
+    Some(Repository { root: root.to_path_buf(), name })
+}
+
+// `//pkg:name`, `//pkg` (for `//pkg:pkg`), `:name` or `name` in the current
+// package, and `@main//...` for the main repository; other repositories' labels
+// are not followed
+fn parse_label(label: &str, current_package: &str, repository: &Repository) -> Option<Label> {
+    let label = match label.strip_prefix('@') {
+        Some(rest) => {
+            let (repo, rest) = rest.split_once("//")?;
+            let repo = repo.trim_start_matches('@');
+            if !repo.is_empty() && Some(repo) != repository.name.as_deref() {
+                return None;
+            }
+            format!("//{}", rest)
+        }
+        None => label.to_string(),
+    };
+    let (package, name) = match label.strip_prefix("//") {
+        Some(absolute) => match absolute.split_once(':') {
+            Some((package, name)) => (package.to_string(), name.to_string()),
+            None => (absolute.to_string(), absolute.rsplit('/').next().unwrap_or(absolute).to_string()),
+        },
+        None => (current_package.to_string(), label.trim_start_matches(':').to_string()),
+    };
+    if name.is_empty() || name.contains("...") {
+        return None;
+    }
+    Some(Label { package, name })
+}
+
+fn query(repository: &Repository, label: &Label) -> io::Result<Vec<PathBuf>> {
+    let program = if cfg!(windows) { "bazel.exe" } else { "bazel" };
+    let run = |output: &str, expression: String| -> io::Result<Vec<String>> {
+        let result = Command::new(program)
+            .args(["query", "--noimplicit_deps", "--keep_going", &format!("--output={}", output), &expression])
+            .current_dir(&repository.root)
+            .stdin(Stdio::null())
+            .output()?;
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        if stdout.trim().is_empty() && !result.status.success() {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            let message = stderr.lines().rev().find(|line| line.contains("ERROR")).unwrap_or("no output").trim().to_string();
+            return Err(io::Error::other(format!("bazel query failed: {}", message)));
+        }
+        Ok(stdout.lines().map(str::to_string).collect())
+    };
+    
+    // Sources are one level below the target and its direct dependencies
+    let target = label.display();
+    let packages = run("package", format!("deps({}, 1)", target))?;
+    let sources = run("label", format!("kind(\"source file\", deps({}, 2))", target))?;
+    let mut files: Vec<PathBuf> = packages.iter().filter_map(|package| build_file(&repository.root.join(package))).collect();
+    for source in &sources {
+        if let Some(label) = parse_label(source, "", repository) {
+            let path = repository.root.join(&label.package).join(&label.name);
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn build_file(package_dir: &Path) -> Option<PathBuf> {
+    BUILD_FILES.iter().map(|name| package_dir.join(name)).find(|path| path.is_file())
+}
+
+// A rule as declared in its package's build file
+struct Rule {
+    label: Label,
+    build_file: PathBuf,
+    sources: Vec<Source>,
+    deps: Vec<Label>,
+}
+
+enum Source {
+    // A file name or label
+    Entry(String),
+    Glob { include: Vec<String>, exclude: Vec<String> },
+}
+
+fn read_rule(repository: &Repository, label: &Label) -> io::Result<Rule> {
+    let package_dir = repository.root.join(&label.package);
+    let build_file = build_file(&package_dir).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, format!("No BUILD file in {}", package_dir.display()))
+    })?;
+    let content = fs::read_to_string(&build_file)?;
+    let body = calls(&content)
+        .into_iter()
+        .find(|body| attribute(body, "name").and_then(|name| strings(name).into_iter().next()).as_ref() == Some(&label.name))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No rule named {} in {}", label.name, build_file.display()),
+            )
+        })?;
+    
+    let sources = SOURCE_ATTRIBUTES.iter().filter_map(|key| attribute(body, key)).flat_map(source_entries).collect();
+    let deps = DEPENDENCY_ATTRIBUTES
+        .iter()
+        .filter_map(|key| attribute(body, key))
+        .flat_map(strings)
+        .filter_map(|dependency| parse_label(&dependency, &label.package, repository))
+        .collect();
+    Ok(Rule { label: label.clone(), build_file, sources, deps })
+}
+
+// The files a rule's sources stand for; labels of other rules in the
+// repository (a `filegroup`, say) are replaced with their sources once
+fn sources(repository: &Repository, rule: &Rule, follow_rules: bool) -> Vec<PathBuf> {
+    let package_dir = repository.root.join(&rule.label.package);
+    let mut files = Vec::new();
+    for source in &rule.sources {
+        match source {
+            Source::Glob { include, exclude } => files.extend(expand_glob(&package_dir, include, exclude)),
+            Source::Entry(entry) => {
+                let label = match parse_label(entry, &rule.label.package, repository) {
+                    Some(label) => label,
+                    None => continue,
+                };
+                let path = repository.root.join(&label.package).join(&label.name);
+                if path.is_file() {
+                    files.push(path);
+                } else if follow_rules {
+                    if let Ok(nested) = read_rule(repository, &label) {
+                        files.extend(sources(repository, &nested, false));
+                    }
+                }
+            }
+        }
+    }
+    files
+}
+
+// Files in the package matching a `glob()`, without descending into
+// subpackages, which own their files
+fn expand_glob(package_dir: &Path, include: &[String], exclude: &[String]) -> Vec<PathBuf> {
+    let build = |patterns: &[String]| {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            if let Ok(glob) = GlobBuilder::new(pattern).literal_separator(true).build() {
+                builder.add(glob);
+            }
+        }
+        builder.build().ok()
+    };
+    let (include, exclude) = match (build(include), build(exclude)) {
+        (Some(include), Some(exclude)) => (include, exclude),
+        _ => return Vec::new(),
+    };
+    let mut files = Vec::new();
+    let mut pending = vec![package_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut entries: Vec<PathBuf> = match fs::read_dir(&dir) {
+            Ok(entries) => entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect(),
+            Err(_) => continue,
+        };
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                if build_file(&path).is_none() {
+                    pending.push(path);
+                }
+                continue;
+            }
+            let relative = paths::normalize(path.strip_prefix(package_dir).unwrap_or(&path));
+            if include.is_match(&relative) && !exclude.is_match(&relative) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+// The argument lists of the top-level calls in a build file
+fn calls(content: &str) -> Vec<&str> {
+    let mut calls = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in code_chars(content) {
+        match c {
+            '(' | '[' | '{' => {
+                if depth == 0 && c == '(' {
+                    start = index + 1;
+                }
+                depth += 1;
+            }
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 && c == ')' {
+                    calls.push(&content[start..index]);
+                }
+            }
+            _ => {}
+        }
+    }
+    calls
+}
+
+// The expression assigned to `key` among the arguments of a call
+fn attribute<'a>(arguments: &'a str, key: &str) -> Option<&'a str> {
+    let mut depth = 0;
+    let mut start = None;
+    for (index, c) in code_chars(arguments) {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                if let Some(start) = start {
+                    return Some(&arguments[start..index]);
+                }
+            }
+            _ if depth == 0 && start.is_none() && is_key_at(arguments, index, key) => {
+                start = Some(index + arguments[index..].find('=')? + 1);
+            }
+            _ => {}
+        }
+    }
+    start.map(|start| &arguments[start..])
+}
+
+// Whether `key =` starts at `index`, as a whole word
+fn is_key_at(arguments: &str, index: usize, key: &str) -> bool {
+    let before = arguments[..index].chars().next_back();
+    if before.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+        return false;
+    }
+    match arguments[index..].strip_prefix(key) {
+        Some(rest) => rest.trim_start().starts_with('=') && !rest.trim_start().starts_with("=="),
+        None => false,
+    }
+}
+
+// The characters outside of string literals and comments, with their offsets
+fn code_chars(content: &str) -> Vec<(usize, char)> {
+    let mut chars = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut comment = false;
+    let mut escaped = false;
+    for (index, c) in content.char_indices() {
+        if comment {
+            comment = c != '\n';
+        } else if let Some(open) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == open {
+                quote = None;
+            }
+        } else if c == '"' || c == '\'' {
+            quote = Some(c);
+        } else if c == '#' {
+            comment = true;
+        } else {
+            chars.push((index, c));
+        }
+    }
+    chars
+}
+
+// `["a.cc", ":gen"] + glob(["*.h"], exclude = ["x.h"])`
+fn source_entries(expression: &str) -> Vec<Source> {
+    let glob = Regex::new(r"\bglob\s*\(").expect("valid regex");
+    let mut sources = Vec::new();
+    let mut rest = expression;
+    while let Some(found) = glob.find(rest) {
+        sources.extend(strings(&rest[..found.start()]).into_iter().map(Source::Entry));
+        let arguments_start = found.end();
+        let arguments = calls(&rest[found.start()..]).into_iter().next().unwrap_or("");
+        let include = strings(attribute(arguments, "include").unwrap_or_else(|| arguments.split("exclude").next().unwrap_or("")));
+        let exclude = attribute(arguments, "exclude").map(strings).unwrap_or_default();
+        sources.push(Source::Glob { include, exclude });
+        rest = &rest[(arguments_start + arguments.len() + 1).min(rest.len())..];
+    }
+    sources.extend(strings(rest).into_iter().map(Source::Entry));
+    sources
+}
+
+fn strings(expression: &str) -> Vec<String> {
+    let literal = Regex::new(r#""([^"]*)"|'([^']*)'"#).expect("valid regex");
+    literal
+        .captures_iter(expression)
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)).map(|m| m.as_str().to_string()))
+        .collect()
+}
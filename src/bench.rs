@@ -0,0 +1,213 @@
+// src/bench.rs
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::languages::format_size;
+
+// Keep a failed `cargo bench` short enough to be useful in a prompt
+const MAX_ERROR_LINES: usize = 100;
+
+// Where --with-bench takes its numbers from
+#[derive(Debug, Clone)]
+pub enum BenchSource {
+    // Results criterion saved in this directory (target/criterion when empty)
+    Saved(Option<PathBuf>),
+    // Run `cargo bench`, optionally only the benchmarks matching a filter
+    Run(Option<String>),
+}
+
+pub fn parse_source(value: &str) -> Result<BenchSource, String> {
+    match value {
+        "" => Ok(BenchSource::Saved(None)),
+        "run" => Ok(BenchSource::Run(None)),
+        _ => match value.strip_prefix("run:") {
+            Some(filter) if !filter.is_empty() => Ok(BenchSource::Run(Some(filter.to_string()))),
+            Some(_) => Err("expected a filter after run:".to_string()),
+            None => Ok(BenchSource::Saved(Some(PathBuf::from(value)))),
+        },
+    }
+}
+
+pub struct Benchmark {
+    pub id: String,
+    // Nanoseconds per iteration
+    pub mean: f64,
+    pub lower: f64,
+    pub upper: f64,
+    // Relative change of the mean against the previous run
+    pub change: Option<f64>,
+    pub throughput: Option<String>,
+}
+
+pub struct BenchResults {
+    pub source: String,
+    pub benchmarks: Vec<Benchmark>,
+    // Output of a failed `cargo bench`
+    pub error_output: Option<String>,
+}
+
+// Reads criterion's saved results, running `cargo bench` first when asked to
+pub fn collect(project_root: &Path, source: &BenchSource) -> io::Result<BenchResults> {
+    let mut error_output = None;
+    let mut filter = None;
+    let (dir, label) = match source {
+        BenchSource::Saved(Some(dir)) => {
+            let dir = if dir.is_relative() && !dir.exists() { project_root.join(dir) } else { dir.clone() };
+            let label = dir.display().to_string();
+            (dir, label)
+        }
+        BenchSource::Saved(None) => (criterion_dir(project_root), "target/criterion".to_string()),
+        BenchSource::Run(run_filter) => {
+            let mut args = vec!["bench".to_string()];
+            if let Some(run_filter) = run_filter {
+                args.push("--".to_string());
+                args.push(run_filter.clone());
+            }
+            let command = format!("cargo {}", args.join(" "));
+            let output = Command::new("cargo")
+                .args(&args)
+                .current_dir(project_root)
+                .stdin(Stdio::null())
+                .output()
+                .map_err(|e| io::Error::other(format!("Failed to run {}: {}", command, e)))?;
+            if !output.status.success() {
+                let combined = format!(
+                    "{}\n{}",
+                    String::from_utf8_lossy(&output.stdout).trim(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                let lines: Vec<&str> = combined.trim().lines().collect();
+                error_output = Some(lines[lines.len().saturating_sub(MAX_ERROR_LINES)..].join("\n"));
+            }
+            filter = run_filter.as_deref();
+            (criterion_dir(project_root), command)
+        }
+    };
+    
+    let mut benchmarks = Vec::new();
+    if dir.is_dir() {
+        read_benchmarks(&dir, &mut benchmarks);
+    } else if error_output.is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No criterion results in {}; run cargo bench first or use --with-bench=run", dir.display()),
+        ));
+    }
+    if let Some(filter) = filter {
+        benchmarks.retain(|benchmark| benchmark.id.contains(filter));
+    }
+    benchmarks.sort_by(|a, b| a.id.cmp(&b.id));
+    
+    Ok(BenchResults { source: label, benchmarks, error_output })
+}
+
+fn criterion_dir(project_root: &Path) -> PathBuf {
+    let target = env::var_os("CARGO_TARGET_DIR").map(PathBuf::from).unwrap_or_else(|| project_root.join("target"));
+    target.join("criterion")
+}
+
+// Every benchmark has a `new` directory with the latest estimates; `base`
+// holds the previous run and `change` the comparison between the two
+fn read_benchmarks(dir: &Path, benchmarks: &mut Vec<Benchmark>) {
+    let new = dir.join("new");
+    if let Some(benchmark) = read_benchmark(dir, &new) {
+        benchmarks.push(benchmark);
+        return;
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() && entry.file_name() != "report" {
+            read_benchmarks(&path, benchmarks);
+        }
+    }
+}
+
+fn read_benchmark(dir: &Path, new: &Path) -> Option<Benchmark> {
+    let estimates = read_json(&new.join("estimates.json"))?;
+    let info = read_json(&new.join("benchmark.json"));
+    let mean = &estimates["mean"];
+    
+    let id = info
+        .as_ref()
+        .and_then(|info| info["full_id"].as_str().map(str::to_string))
+        .unwrap_or_else(|| dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default());
+    let point = mean["point_estimate"].as_f64()?;
+    let change = read_json(&dir.join("change").join("estimates.json"))
+        .and_then(|change| change["mean"]["point_estimate"].as_f64());
+    let throughput = info.as_ref().and_then(|info| throughput(&info["throughput"], point));
+    
+    Some(Benchmark {
+        id,
+        mean: point,
+        lower: mean["confidence_interval"]["lower_bound"].as_f64().unwrap_or(point),
+        upper: mean["confidence_interval"]["upper_bound"].as_f64().unwrap_or(point),
+        change,
+        throughput,
+    })
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+// {"Bytes": 1024} or {"Elements": 100} per iteration, as a rate
+fn throughput(value: &Value, nanoseconds: f64) -> Option<String> {
+    if nanoseconds <= 0.0 {
+        return None;
+    }
+    let per_second = 1e9 / nanoseconds;
+    if let Some(bytes) = value["Bytes"].as_f64().or_else(|| value["BytesDecimal"].as_f64()) {
+        return Some(format!("{}/s", format_size((bytes * per_second) as usize)));
+    }
+    value["Elements"].as_f64().map(|elements| format!("{:.0} elements/s", elements * per_second))
+}
+
+fn format_time(nanoseconds: f64) -> String {
+    let (value, unit) = if nanoseconds < 1e3 {
+        (nanoseconds, "ns")
+    } else if nanoseconds < 1e6 {
+        (nanoseconds / 1e3, "µs")
+    } else if nanoseconds < 1e9 {
+        (nanoseconds / 1e6, "ms")
+    } else {
+        (nanoseconds / 1e9, "s")
+    };
+    format!("{:.2} {}", value, unit)
+}
+
+pub fn format_bench_section(results: &BenchResults) -> String {
+    let mut section = format!(
+        "<benchmarks source=\"{}\" count=\"{}\">\n",
+        results.source,
+        results.benchmarks.len()
+    );
+    for benchmark in &results.benchmarks {
+        section.push_str(&format!(
+            "{}: mean {} ({} – {})",
+            benchmark.id,
+            format_time(benchmark.mean),
+            format_time(benchmark.lower),
+            format_time(benchmark.upper)
+        ));
+        if let Some(change) = benchmark.change {
+            section.push_str(&format!(", {:+.1}% vs previous run", change * 100.0));
+        }
+        if let Some(ref throughput) = benchmark.throughput {
+            section.push_str(&format!(", {}", throughput));
+        }
+        section.push('\n');
+    }
+    if let Some(ref error_output) = results.error_output {
+        section.push_str(&format!("<error_output>\n{}\n</error_output>\n", error_output));
+    }
+    section.push_str("</benchmarks>\n\n");
+    section
+}
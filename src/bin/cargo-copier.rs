@@ -0,0 +1,39 @@
+// src/bin/cargo-copier.rs
+//
+// Cargo runs `cargo copier <args>` as `cargo-copier copier <args>`. This
+// wrapper forwards the arguments to the main binary installed next to it.
+use std::env;
+use std::process::{self, Command};
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("copier") {
+        args.remove(0);
+    }
+    
+    let binary = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(format!("llm-cocop-rs{}", env::consts::EXE_SUFFIX))))
+        .filter(|path| path.is_file());
+    let binary = match binary {
+        Some(binary) => binary,
+        None => {
+            eprintln!("Error: llm-cocop-rs was not found next to cargo-copier. Install both with `cargo install --path .`");
+            process::exit(1);
+        }
+    };
+    
+    // Tells the main binary to fall back to the Cargo workspace root when no paths are given
+    let status = Command::new(binary)
+        .args(&args)
+        .env("CODECOPIER_CARGO_SUBCOMMAND", "1")
+        .status();
+    
+    match status {
+        Ok(status) => process::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+}
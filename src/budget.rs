@@ -0,0 +1,228 @@
+// src/budget.rs
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use log::{debug, info};
+
+use crate::config::BudgetShare;
+use crate::{paths, tokens, FileEntry};
+
+// Commits older than this count half as much towards a file's churn
+const CHURN_HALF_LIFE_DAYS: f64 = 90.0;
+
+// Only the most recent commits are read on large histories
+const CHURN_MAX_COMMITS: usize = 2000;
+
+// Tokens added around each file by its `<file>` tags
+const FILE_TAG_TOKENS: usize = 10;
+
+// Which files are kept first when trimming to --max-tokens
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Rank {
+    // Files changed often and recently in git first
+    Churn,
+    // Most recently modified files first
+    Mtime,
+    // Smallest files first, so that as many files as possible fit
+    Size,
+    // Files in path order
+    Path,
+}
+
+// Tokens a file takes up in the output
+pub fn file_tokens(file: &FileEntry) -> usize {
+    tokens::estimate(&file.content) + tokens::estimate(&file.path) + FILE_TAG_TOKENS
+}
+
+// A directory and the fraction of the budget reserved for it
+pub struct Area {
+    pub prefix: String,
+    pub share: f64,
+}
+
+// Reads the [budget] table, checking that the shares add up to at most 100%
+pub fn areas(config: &BTreeMap<String, BudgetShare>) -> io::Result<Vec<Area>> {
+    let mut areas = Vec::new();
+    for (directory, share) in config {
+        let percent = match share {
+            BudgetShare::Percent(percent) => Some(*percent),
+            BudgetShare::Text(text) => text.trim().trim_end_matches('%').trim().parse::<f64>().ok(),
+        };
+        let percent = percent.filter(|percent| (0.0..=100.0).contains(percent)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid [budget] share for \"{}\": expected a percentage such as \"60%\"", directory),
+            )
+        })?;
+        let prefix = directory.trim_start_matches("./").trim_end_matches('/').to_string();
+        areas.push(Area { prefix, share: percent / 100.0 });
+    }
+    
+    if areas.iter().map(|area| area.share).sum::<f64>() > 1.0 + f64::EPSILON {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "The [budget] shares add up to more than 100%"));
+    }
+    Ok(areas)
+}
+
+// Keeps the best-ranked files that fit into `max_tokens` together, in their
+// original order, and returns the ones that did not fit. Each area first gets
+// its share of the budget, files outside all areas get what is left, and
+// budget an area does not use goes to the best-ranked remaining files.
+// Files `important` accepts, like a preset's key files, rank before the rest
+pub fn trim(
+    files: &mut Vec<FileEntry>,
+    max_tokens: usize,
+    rank: Rank,
+    areas: &[Area],
+    project_root: &Path,
+    important: &dyn Fn(&str) -> bool,
+) -> Vec<FileEntry> {
+    let total: usize = files.iter().map(file_tokens).sum();
+    if total <= max_tokens {
+        return Vec::new();
+    }
+    
+    let mut order = ranked_order(files, rank, project_root);
+    order.sort_by_key(|&index| !important(&files[index].path));
+    let mut keep = vec![false; files.len()];
+    let mut used = 0;
+    
+    if !areas.is_empty() {
+        let unassigned_share = 1.0 - areas.iter().map(|area| area.share).sum::<f64>();
+        let area_of: Vec<Option<usize>> = files.iter().map(|file| area_index(&file.path, areas)).collect();
+        let mut area_budgets: Vec<usize> = areas.iter().map(|area| (max_tokens as f64 * area.share) as usize).collect();
+        let mut unassigned_budget = (max_tokens as f64 * unassigned_share.max(0.0)) as usize;
+        for &index in &order {
+            let size = file_tokens(&files[index]);
+            let budget = match area_of[index] {
+                Some(area) => &mut area_budgets[area],
+                None => &mut unassigned_budget,
+            };
+            if size <= *budget {
+                *budget -= size;
+                used += size;
+                keep[index] = true;
+            }
+        }
+    }
+    
+    for index in order {
+        let size = file_tokens(&files[index]);
+        // Smaller files further down the ranking may still fit
+        if !keep[index] && used + size <= max_tokens {
+            used += size;
+            keep[index] = true;
+        }
+    }
+    
+    let mut dropped = Vec::new();
+    let mut kept = Vec::new();
+    for (file, keep) in files.drain(..).zip(keep) {
+        if keep {
+            kept.push(file);
+        } else {
+            dropped.push(file);
+        }
+    }
+    *files = kept;
+    
+    info!(
+        "Kept {} files (~{} tokens) of ~{} tokens to fit --max-tokens {}, dropped {}",
+        files.len(),
+        used,
+        total,
+        max_tokens,
+        dropped.len()
+    );
+    dropped
+}
+
+// The area with the longest prefix containing `path`
+fn area_index(path: &str, areas: &[Area]) -> Option<usize> {
+    let path = path.trim_start_matches("./");
+    areas
+        .iter()
+        .enumerate()
+        .filter(|(_, area)| {
+            area.prefix.is_empty()
+                || path == area.prefix
+                || path.strip_prefix(&area.prefix).is_some_and(|rest| rest.starts_with('/'))
+        })
+        .max_by_key(|(_, area)| area.prefix.len())
+        .map(|(index, _)| index)
+}
+
+// Indices of `files` from the most to the least important
+fn ranked_order(files: &[FileEntry], rank: Rank, project_root: &Path) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..files.len()).collect();
+    match rank {
+        Rank::Path => {}
+        Rank::Size => order.sort_by_key(|&index| files[index].content.len()),
+        Rank::Mtime => {
+            let modified: Vec<SystemTime> = files
+                .iter()
+                .map(|file| fs::metadata(&file.source_path).and_then(|m| m.modified()).unwrap_or(UNIX_EPOCH))
+                .collect();
+            order.sort_by_key(|&index| std::cmp::Reverse(modified[index]));
+        }
+        Rank::Churn => {
+            let churn = git_churn(project_root);
+            let scores: Vec<f64> = files
+                .iter()
+                .map(|file| {
+                    let path = paths::canonicalize(&file.source_path).unwrap_or_else(|_| file.source_path.clone());
+                    churn.get(&path).copied().unwrap_or(0.0)
+                })
+                .collect();
+            order.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+        }
+    }
+    order
+}
+
+// Churn score per file: every commit touching it counts 1, halved for every
+// CHURN_HALF_LIFE_DAYS of age
+fn git_churn(project_root: &Path) -> HashMap<PathBuf, f64> {
+    let mut churn = HashMap::new();
+    let repo_root = match paths::repo_root(project_root) {
+        Some(root) => root,
+        None => {
+            debug!("{} is not in a git repository, ranking by path", project_root.display());
+            return churn;
+        }
+    };
+    
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&repo_root)
+        .args(["log", "--numstat", "--no-renames", "--format=@%ct"])
+        .arg(format!("--max-count={}", CHURN_MAX_COMMITS))
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        _ => {
+            debug!("git log failed in {}, ranking by path", repo_root.display());
+            return churn;
+        }
+    };
+    
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut weight = 0.0;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(timestamp) = line.strip_prefix('@') {
+            let age_days = now.saturating_sub(timestamp.parse().unwrap_or(now)) as f64 / 86400.0;
+            weight = 0.5f64.powf(age_days / CHURN_HALF_LIFE_DAYS);
+        } else if let Some(path) = line.splitn(3, '\t').nth(2) {
+            let path = paths::canonicalize(repo_root.join(path)).unwrap_or_else(|_| repo_root.join(path));
+            *churn.entry(path).or_insert(0.0) += weight;
+        }
+    }
+    churn
+}
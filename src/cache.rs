@@ -0,0 +1,143 @@
+// src/cache.rs
+use log::{debug, trace};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+// Set by --no-cache
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+// Bytes of pass results the daemon keeps in memory
+const MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+// Entries also kept in memory, for the daemon
+static MEMORY: Mutex<Option<MemoryCache<String, String>>> = Mutex::new(None);
+
+pub fn set_disabled(disabled: bool) {
+    DISABLED.store(disabled, Ordering::Relaxed);
+}
+
+pub fn keep_in_memory() {
+    *MEMORY.lock().unwrap() = Some(MemoryCache::new(MEMORY_LIMIT));
+}
+
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("codecopier").join("passes"))
+}
+
+// Returns the result of an expensive per-file pass (parsing, outlining, ...)
+// from the on-disk cache, computing and storing it on a miss. Entries are
+// keyed by the pass name, the file content and the tool version, so they
+// never go stale; failed passes are not cached
+pub fn get_or_compute<F>(pass: &str, content: &str, compute: F) -> Option<String>
+where
+    F: FnOnce() -> Option<String>,
+{
+    let dir = match cache_dir() {
+        Some(dir) if !DISABLED.load(Ordering::Relaxed) => dir,
+        _ => return compute(),
+    };
+    
+    let key = cache_key(pass, content);
+    if let Some(cached) = MEMORY.lock().unwrap().as_mut().and_then(|memory| memory.get(&key)) {
+        return Some(cached);
+    }
+    
+    let path = dir.join(&key[..2]).join(&key[2..]);
+    let result = match fs::read_to_string(&path) {
+        Ok(cached) => {
+            trace!("Cache hit for {} ({})", pass, &key[..12]);
+            cached
+        }
+        Err(_) => {
+            let result = compute()?;
+            if let Err(e) = store(&path, &result) {
+                debug!("Could not write cache entry {}: {}", path.display(), e);
+            }
+            result
+        }
+    };
+    
+    if let Some(memory) = MEMORY.lock().unwrap().as_mut() {
+        memory.insert(key, result.clone(), result.len());
+    }
+    Some(result)
+}
+
+fn cache_key(pass: &str, content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update([0]);
+    hasher.update(pass.as_bytes());
+    hasher.update([0]);
+    hasher.update(content.as_bytes());
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Written to a temporary file first so concurrent runs never read a partial entry
+fn store(path: &Path, value: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&temp_path, value)?;
+    fs::rename(&temp_path, path)
+}
+
+// Values kept in memory up to a total size in bytes; once it is exceeded,
+// the least recently used entries are dropped until a quarter is free again
+pub struct MemoryCache<K, V> {
+    // Value, its size and when it was last used
+    entries: HashMap<K, (V, usize, u64)>,
+    size: usize,
+    limit: usize,
+    clock: u64,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> MemoryCache<K, V> {
+    pub fn new(limit: usize) -> Self {
+        MemoryCache { entries: HashMap::new(), size: 0, limit, clock: 0 }
+    }
+    
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.2 = self.clock;
+        Some(entry.0.clone())
+    }
+    
+    pub fn insert(&mut self, key: K, value: V, size: usize) {
+        if let Some((_, old_size, _)) = self.entries.remove(&key) {
+            self.size -= old_size;
+        }
+        if size > self.limit {
+            return;
+        }
+        if self.size + size > self.limit {
+            let mut by_use: Vec<(u64, K)> = self.entries.iter().map(|(key, entry)| (entry.2, key.clone())).collect();
+            by_use.sort_unstable_by_key(|(used, _)| *used);
+            for (_, key) in by_use {
+                if self.size + size <= self.limit / 4 * 3 {
+                    break;
+                }
+                if let Some((_, evicted, _)) = self.entries.remove(&key) {
+                    self.size -= evicted;
+                }
+            }
+        }
+        self.clock += 1;
+        self.size += size;
+        self.entries.insert(key, (value, size, self.clock));
+    }
+    
+    pub fn remove(&mut self, key: &K) {
+        if let Some((_, size, _)) = self.entries.remove(key) {
+            self.size -= size;
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// src/cancel.rs
+use log::{debug, warn};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Exit code of a copy stopped with Ctrl-C, the one shells use for SIGINT
+pub const EXIT_CANCELLED: u8 = 130;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// Set while waiting for an answer on the terminal
+static PROMPTING: AtomicBool = AtomicBool::new(false);
+
+// Makes Ctrl-C stop the copy at the next file instead of killing the
+// process halfway through writing the clipboard or the output file; a
+// second Ctrl-C still exits right away, and so does one at a prompt, since
+// the blocking read would otherwise swallow it
+pub fn install() {
+    let handler = ctrlc::set_handler(|| {
+        if PROMPTING.load(Ordering::SeqCst) {
+            eprintln!();
+            process::exit(EXIT_CANCELLED as i32);
+        }
+        if REQUESTED.swap(true, Ordering::SeqCst) {
+            process::exit(EXIT_CANCELLED as i32);
+        }
+        warn!("Interrupted, stopping after the current file (press Ctrl-C again to exit right away)");
+    });
+    if let Err(e) = handler {
+        debug!("Could not install the Ctrl-C handler: {}", e);
+    }
+}
+
+// Whether Ctrl-C was pressed since `install`
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+// Runs `read`, a blocking read of an answer, so that Ctrl-C exits meanwhile
+pub fn while_prompting<T>(read: impl FnOnce() -> T) -> T {
+    PROMPTING.store(true, Ordering::SeqCst);
+    let result = read();
+    PROMPTING.store(false, Ordering::SeqCst);
+    result
+}
+
+// Tells the model that the files after the interruption are missing
+pub fn format_truncated_section(files: usize) -> String {
+    format!(
+        "<truncated reason=\"cancelled\">\nCopying was interrupted after {} files; the remaining files of the selected paths are missing.\n</truncated>\n\n",
+        files
+    )
+}
@@ -0,0 +1,191 @@
+// src/chunks.rs
+use serde_json::json;
+use std::io;
+
+use crate::formatter::{Formatter, RenderContext};
+use crate::outline::{self, Definition};
+use crate::{languages, tokens, FileEntry, Options};
+
+// A piece of a file, as 0-based line indices [start, end)
+#[derive(Clone, Copy)]
+struct Span {
+    start: usize,
+    end: usize,
+}
+
+// --format jsonl: one JSON object per chunk, for embedding and RAG pipelines
+pub struct Jsonl;
+
+impl Formatter for Jsonl {
+    fn name(&self) -> &'static str {
+        "jsonl"
+    }
+    
+    fn render(&self, context: &RenderContext) -> String {
+        let options = context.options;
+        let mut output = String::new();
+        for file in context.files {
+            for record in records(file, options.chunk_tokens.max(1), options.overlap, context.post_process) {
+                output.push_str(&record);
+                output.push('\n');
+            }
+        }
+        output
+    }
+}
+
+// `chunks` subcommand: a copy with --format jsonl, written to stdout unless
+// another destination is given
+pub fn copy_options(mut options: Options) -> io::Result<Options> {
+    match options.output_format.as_str() {
+        "xml" => options.output_format = "jsonl".to_string(),
+        "jsonl" => {}
+        other => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("chunks writes --format jsonl, not {}", other),
+            ));
+        }
+    }
+    if options.output_path.is_none() && options.pipe_to.is_none() && options.post_to.is_none() {
+        options.output_path = Some("-".to_string());
+    }
+    Ok(options)
+}
+
+fn records(file: &FileEntry, chunk_tokens: usize, overlap: usize, post_process: &dyn Fn(&str) -> String) -> Vec<String> {
+    let lines: Vec<&str> = file.content.lines().collect();
+    let sizes: Vec<usize> = lines.iter().map(|line| tokens::estimate(line) + 1).collect();
+    let language = languages::detect_language(&file.source_path, &file.content);
+    let definitions = outline::definitions(&file.source_path, &file.content);
+    
+    let units = match definitions {
+        Some(ref definitions) => {
+            split_at_definitions(Span { start: 0, end: lines.len() }, definitions, 0, &sizes, chunk_tokens)
+        }
+        None => split_at_blank_lines(&lines, &sizes, chunk_tokens),
+    };
+    let chunks = merge(&units, &sizes, chunk_tokens);
+    
+    // Excerpts number their lines from the first excerpt's start
+    let first_line = file.line_ranges.first().map(|(start, _)| *start).unwrap_or(1);
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            // Lines of the previous chunk repeated for context
+            let mut start = chunk.start;
+            let mut repeated = 0;
+            while index > 0 && start > 0 && repeated + sizes[start - 1] <= overlap {
+                start -= 1;
+                repeated += sizes[start];
+            }
+            let text = lines[start..chunk.end].join("\n");
+            let symbols: Vec<&str> = definitions
+                .iter()
+                .flatten()
+                .filter(|definition| (chunk.start..chunk.end).contains(&(definition.start_line - 1)))
+                .map(|definition| definition.name.as_str())
+                .filter(|name| !name.is_empty())
+                .collect();
+            json!({
+                "id": format!("{}#{}", file.path, index),
+                "path": file.path,
+                "language": language,
+                "start_line": start + first_line,
+                "end_line": chunk.end + first_line - 1,
+                "tokens": tokens::estimate(&text),
+                "symbols": symbols,
+                "text": post_process(&text),
+            })
+            .to_string()
+        })
+        .collect()
+}
+
+// Spans of whole definitions at `depth`, each with the comments and
+// attributes above it; spans still over the limit are split at the
+// definitions nested in them, or else into runs of lines
+fn split_at_definitions(span: Span, definitions: &[Definition], depth: usize, sizes: &[usize], limit: usize) -> Vec<Span> {
+    let mut units = Vec::new();
+    let mut cursor = span.start;
+    let at_depth = definitions
+        .iter()
+        .filter(|definition| definition.depth == depth)
+        .filter(|definition| definition.start_line > span.start && definition.end_line <= span.end);
+    for definition in at_depth {
+        if definition.end_line > cursor {
+            units.push(Span { start: cursor, end: definition.end_line });
+            cursor = definition.end_line;
+        }
+    }
+    if cursor < span.end {
+        units.push(Span { start: cursor, end: span.end });
+    }
+    
+    let mut split = Vec::new();
+    for unit in units {
+        if size(unit, sizes) <= limit {
+            split.push(unit);
+        } else if definitions.iter().any(|definition| {
+            definition.depth == depth + 1 && definition.start_line > unit.start && definition.end_line <= unit.end
+        }) {
+            split.extend(split_at_definitions(unit, definitions, depth + 1, sizes, limit));
+        } else {
+            split.extend(split_lines(unit, sizes, limit));
+        }
+    }
+    split
+}
+
+// Paragraphs separated by blank lines, for languages without a grammar
+fn split_at_blank_lines(lines: &[&str], sizes: &[usize], limit: usize) -> Vec<Span> {
+    let mut units = Vec::new();
+    let mut start = 0;
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() && index + 1 > start {
+            units.push(Span { start, end: index + 1 });
+            start = index + 1;
+        }
+    }
+    if start < lines.len() {
+        units.push(Span { start, end: lines.len() });
+    }
+    units.into_iter().flat_map(|unit| split_lines(unit, sizes, limit)).collect()
+}
+
+fn split_lines(span: Span, sizes: &[usize], limit: usize) -> Vec<Span> {
+    let mut pieces = Vec::new();
+    let mut start = span.start;
+    let mut used = 0;
+    for (index, &line_size) in sizes.iter().enumerate().take(span.end).skip(span.start) {
+        if used + line_size > limit && index > start {
+            pieces.push(Span { start, end: index });
+            start = index;
+            used = 0;
+        }
+        used += line_size;
+    }
+    if start < span.end {
+        pieces.push(Span { start, end: span.end });
+    }
+    pieces
+}
+
+// Consecutive units joined into chunks of at most `limit` tokens
+fn merge(units: &[Span], sizes: &[usize], limit: usize) -> Vec<Span> {
+    let mut chunks: Vec<Span> = Vec::new();
+    for &unit in units {
+        match chunks.last_mut() {
+            Some(last) if size(Span { start: last.start, end: unit.end }, sizes) <= limit => last.end = unit.end,
+            _ => chunks.push(unit),
+        }
+    }
+    // Chunks made only of blank lines carry nothing to embed
+    chunks.retain(|chunk| size(*chunk, sizes) > chunk.end - chunk.start);
+    chunks
+}
+
+fn size(span: Span, sizes: &[usize]) -> usize {
+    sizes[span.start..span.end].iter().sum()
+}
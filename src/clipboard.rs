@@ -0,0 +1,295 @@
+// src/clipboard.rs
+use clap::Args;
+use log::warn;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(not(windows))]
+use clipboard::{ClipboardContext, ClipboardProvider};
+
+#[cfg(windows)]
+use clipboard_win::{formats, get_clipboard, Clipboard, Setter};
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF_MS: u64 = 100;
+
+// Where the output ended up
+pub enum CopyOutcome {
+    Clipboard,
+    TempFile(PathBuf),
+}
+
+pub trait ClipboardBackend {
+    fn name(&self) -> &'static str;
+    
+    // Largest payload in bytes the backend reliably accepts, if it has a known limit
+    fn max_size(&self) -> Option<usize>;
+    
+    fn set_text(&mut self, text: &str) -> io::Result<()>;
+    
+    // Places an HTML flavor next to the plain text, for targets that prefer it
+    fn set_html(&mut self, _html: &str, _text: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "HTML is not supported by this clipboard"))
+    }
+    
+    // Used to verify that the text actually landed on the clipboard, and by `paste`
+    fn get_text(&mut self) -> io::Result<String>;
+}
+
+#[cfg(not(windows))]
+pub struct SystemClipboard;
+
+#[cfg(not(windows))]
+impl ClipboardBackend for SystemClipboard {
+    fn name(&self) -> &'static str {
+        if cfg!(target_os = "macos") {
+            "macOS pasteboard"
+        } else {
+            "X11 clipboard"
+        }
+    }
+    
+    fn max_size(&self) -> Option<usize> {
+        if cfg!(target_os = "macos") {
+            None
+        } else {
+            // Large X11 selections are transferred incrementally, which many
+            // receiving applications handle poorly
+            Some(64 * 1024 * 1024)
+        }
+    }
+    
+    fn set_text(&mut self, text: &str) -> io::Result<()> {
+        let provider: Result<ClipboardContext, _> = ClipboardProvider::new();
+        match provider {
+            Ok(mut ctx) => {
+                ctx.set_contents(text.to_owned()).map_err(|e| {
+                    io::Error::other(format!("Clipboard error: {}", e))
+                })
+            }
+            Err(e) => {
+                Err(io::Error::other(format!("Clipboard error: {}", e)))
+            }
+        }
+    }
+    
+    // The `clipboard` crate only handles text, so both flavors are set through arboard
+    fn set_html(&mut self, html: &str, text: &str) -> io::Result<()> {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_html(html, Some(text)))
+            .map_err(|e| io::Error::other(format!("Clipboard error: {}", e)))
+    }
+    
+    fn get_text(&mut self) -> io::Result<String> {
+        let provider: Result<ClipboardContext, _> = ClipboardProvider::new();
+        provider
+            .and_then(|mut ctx| ctx.get_contents())
+            .map_err(|e| io::Error::other(format!("Clipboard error: {}", e)))
+    }
+}
+
+#[cfg(windows)]
+pub struct SystemClipboard;
+
+#[cfg(windows)]
+impl ClipboardBackend for SystemClipboard {
+    fn name(&self) -> &'static str {
+        "Windows clipboard"
+    }
+    
+    fn max_size(&self) -> Option<usize> {
+        // The text is converted to UTF-16 in a single global allocation, which
+        // becomes unreliable for very large payloads
+        Some(32 * 1024 * 1024)
+    }
+    
+    fn set_text(&mut self, text: &str) -> io::Result<()> {
+        match Clipboard::new_attempts(10) {
+            Ok(_clip) => {
+                formats::Unicode.write_clipboard(&text).map_err(|e| {
+                    io::Error::other(format!("Clipboard error: {:?}", e))
+                })
+            }
+            Err(e) => {
+                Err(io::Error::other(format!("Clipboard error: {:?}", e)))
+            }
+        }
+    }
+    
+    // Both formats are written while the clipboard is open; only the first write clears it
+    fn set_html(&mut self, html: &str, text: &str) -> io::Result<()> {
+        let _clip = Clipboard::new_attempts(10).map_err(|e| io::Error::other(format!("Clipboard error: {:?}", e)))?;
+        formats::Unicode
+            .write_clipboard(&text)
+            .map_err(|e| io::Error::other(format!("Clipboard error: {:?}", e)))?;
+        let html_format = formats::Html::new()
+            .ok_or_else(|| io::Error::other("Clipboard error: could not register the HTML format"))?;
+        html_format
+            .write_clipboard(&html)
+            .map_err(|e| io::Error::other(format!("Clipboard error: {:?}", e)))
+    }
+    
+    fn get_text(&mut self) -> io::Result<String> {
+        get_clipboard(formats::Unicode).map_err(|e| io::Error::other(format!("Clipboard error: {:?}", e)))
+    }
+}
+
+// Copies the text to the system clipboard, falling back to a temporary file
+pub fn copy(text: &str) -> io::Result<CopyOutcome> {
+    let mut backend = SystemClipboard;
+    copy_with_backend(&mut backend, text, None)
+}
+
+// Like `copy`, with an HTML flavor alongside the text; falls back to text
+// only where the clipboard cannot hold both
+pub fn copy_with_html(text: &str, html: &str) -> io::Result<CopyOutcome> {
+    let mut backend = SystemClipboard;
+    copy_with_backend(&mut backend, text, Some(html))
+}
+
+pub fn copy_with_backend(backend: &mut dyn ClipboardBackend, text: &str, html: Option<&str>) -> io::Result<CopyOutcome> {
+    if let Some(limit) = backend.max_size() {
+        if text.len() > limit {
+            warn!(
+                "Output is {} bytes, above the {} limit of {} bytes",
+                text.len(),
+                backend.name(),
+                limit
+            );
+            return write_temp_file(text).map(CopyOutcome::TempFile);
+        }
+    }
+    
+    // Retry with exponential backoff, since the clipboard can be briefly
+    // locked by other applications
+    let mut delay = Duration::from_millis(INITIAL_BACKOFF_MS);
+    let mut last_error = None;
+    let mut html = html;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let set = match html {
+            Some(html_text) => match backend.set_html(html_text, text) {
+                Err(e) if e.kind() == io::ErrorKind::Unsupported => {
+                    warn!("The {} cannot hold HTML, copying plain text only", backend.name());
+                    html = None;
+                    backend.set_text(text)
+                }
+                result => result,
+            },
+            None => backend.set_text(text),
+        };
+        match set.and_then(|()| verify(backend, text)) {
+            Ok(()) => return Ok(CopyOutcome::Clipboard),
+            Err(e) => {
+                if attempt < MAX_ATTEMPTS {
+                    warn!(
+                        "Clipboard attempt {}/{} failed: {}. Retrying in {}ms...",
+                        attempt,
+                        MAX_ATTEMPTS,
+                        e,
+                        delay.as_millis()
+                    );
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+                last_error = Some(e);
+            }
+        }
+    }
+    
+    if let Some(e) = last_error {
+        warn!("The {} rejected the output: {}", backend.name(), e);
+    }
+    write_temp_file(text).map(CopyOutcome::TempFile)
+}
+
+// Arguments of the `paste` subcommand
+#[derive(Args)]
+pub struct PasteArgs {
+    /// Write the clipboard to this file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+    
+    /// Convert Windows line endings to \n
+    #[arg(long)]
+    strip_cr: bool,
+}
+
+// `paste` subcommand: the text on the clipboard, to stdout or a file, so a
+// model's reply can be piped into `apply` the same way on every platform
+pub fn run_paste(args: PasteArgs) -> io::Result<()> {
+    let mut backend = SystemClipboard;
+    let mut text = paste_with_backend(&mut backend)?;
+    if args.strip_cr {
+        text = text.replace("\r\n", "\n");
+    }
+    match args.output {
+        Some(path) => fs::write(&path, text)
+            .map_err(|e| io::Error::new(e.kind(), format!("Failed to write {}: {}", path.display(), e))),
+        None => {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(text.as_bytes())?;
+            stdout.flush()
+        }
+    }
+}
+
+// Reads the clipboard with the same retries as copying, since it can be
+// briefly locked by other applications
+pub fn paste_with_backend(backend: &mut dyn ClipboardBackend) -> io::Result<String> {
+    let mut delay = Duration::from_millis(INITIAL_BACKOFF_MS);
+    let mut attempt = 1;
+    loop {
+        match backend.get_text() {
+            Ok(text) => return Ok(text),
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                warn!(
+                    "Clipboard attempt {}/{} failed: {}. Retrying in {}ms...",
+                    attempt,
+                    MAX_ATTEMPTS,
+                    e,
+                    delay.as_millis()
+                );
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(io::Error::new(e.kind(), format!("Could not read the {}: {}", backend.name(), e)));
+            }
+        }
+    }
+}
+
+// Reads the clipboard back, since some platforms intermittently drop the text
+// even though setting it reported success
+fn verify(backend: &mut dyn ClipboardBackend, text: &str) -> io::Result<()> {
+    let copied = backend.get_text()?;
+    if copied == text || copied.replace("\r\n", "\n") == text.replace("\r\n", "\n") {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "Clipboard verification failed: read back {} bytes instead of {}",
+            copied.len(),
+            text.len()
+        )))
+    }
+}
+
+fn write_temp_file(text: &str) -> io::Result<PathBuf> {
+    let path = temp_file_path();
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+// Where output that cannot go to the clipboard is written
+pub fn temp_file_path() -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    env::temp_dir().join(format!("codecopier-{}-{}.txt", timestamp, std::process::id()))
+}
@@ -0,0 +1,240 @@
+// src/comments.rs
+use std::path::Path;
+
+// How comments and strings are written in a family of languages
+struct Syntax {
+    line: Option<&'static str>,
+    block: Option<(&'static str, &'static str)>,
+    // Comments starting like this are documentation and kept
+    doc: &'static [&'static str],
+    quotes: &'static [char],
+    // ' starts a character literal, or a lifetime or label when it does not close right after
+    char_literals: bool,
+    // Line comments only start at the beginning of a line or after whitespace,
+    // so `color: #fff` and `$#` are left alone
+    line_after_space: bool,
+}
+
+const C_LIKE: Syntax = Syntax {
+    line: Some("//"),
+    block: Some(("/*", "*/")),
+    doc: &["///", "//!", "/**", "/*!"],
+    quotes: &['"', '`'],
+    char_literals: true,
+    line_after_space: false,
+};
+
+const SCRIPT: Syntax = Syntax {
+    line: Some("//"),
+    block: Some(("/*", "*/")),
+    doc: &["/**"],
+    quotes: &['"', '\'', '`'],
+    char_literals: false,
+    line_after_space: false,
+};
+
+const HASH: Syntax = Syntax {
+    line: Some("#"),
+    block: None,
+    doc: &[],
+    quotes: &['"', '\''],
+    char_literals: false,
+    line_after_space: true,
+};
+
+const SQL: Syntax = Syntax {
+    line: Some("--"),
+    block: Some(("/*", "*/")),
+    doc: &[],
+    quotes: &['\''],
+    char_literals: false,
+    line_after_space: false,
+};
+
+const LUA: Syntax = Syntax {
+    line: Some("--"),
+    block: Some(("--[[", "]]")),
+    doc: &["---"],
+    quotes: &['"', '\''],
+    char_literals: false,
+    line_after_space: false,
+};
+
+const HASKELL: Syntax = Syntax {
+    line: Some("--"),
+    block: Some(("{-", "-}")),
+    doc: &["-- |", "{- |"],
+    quotes: &['"'],
+    char_literals: true,
+    line_after_space: false,
+};
+
+const CSS: Syntax = Syntax {
+    line: None,
+    block: Some(("/*", "*/")),
+    doc: &[],
+    quotes: &['"', '\''],
+    char_literals: false,
+    line_after_space: false,
+};
+
+const MARKUP: Syntax = Syntax {
+    line: None,
+    block: Some(("<!--", "-->")),
+    doc: &[],
+    quotes: &[],
+    char_literals: false,
+    line_after_space: false,
+};
+
+fn syntax_for(path: &Path) -> Option<&'static Syntax> {
+    let name = path.file_name()?.to_str()?;
+    if matches!(name, "Makefile" | "Dockerfile" | "CMakeLists.txt" | "Gemfile" | "Rakefile") {
+        return Some(&HASH);
+    }
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let syntax = match extension.as_str() {
+        "rs" | "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "java" | "cs" | "go" | "swift" | "kt" | "kts"
+        | "scala" | "dart" | "proto" | "zig" => &C_LIKE,
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "php" => &SCRIPT,
+        "py" | "pyi" | "rb" | "sh" | "bash" | "zsh" | "fish" | "pl" | "r" | "yml" | "yaml" | "toml" | "cfg" | "ini"
+        | "conf" | "ps1" | "tf" | "nix" | "cmake" | "mk" => &HASH,
+        "sql" => &SQL,
+        "lua" => &LUA,
+        "hs" => &HASKELL,
+        "css" | "scss" | "less" => &CSS,
+        "html" | "htm" | "xml" | "svg" | "vue" | "svelte" | "md" => &MARKUP,
+        _ => return None,
+    };
+    Some(syntax)
+}
+
+// Removes ordinary comments and keeps doc comments, strings and shebangs.
+// Lines left empty by a removed comment are dropped; files in languages
+// without known comment syntax come back unchanged
+pub fn strip(path: &Path, content: &str) -> String {
+    let syntax = match syntax_for(path) {
+        Some(syntax) => syntax,
+        None => return content.to_string(),
+    };
+    
+    let mut output = String::with_capacity(content.len());
+    // Whether a comment was removed from the line being written
+    let mut stripped_line = false;
+    let mut lines: Vec<(String, bool)> = Vec::new();
+    let mut rest = content;
+    
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            lines.push((std::mem::take(&mut output), stripped_line));
+            stripped_line = false;
+            rest = &rest[1..];
+            continue;
+        }
+        
+        let at_line_start = output.trim().is_empty();
+        if let Some((open, close)) = syntax.block {
+            if rest.starts_with(open) {
+                let end = rest[open.len()..].find(close).map(|end| open.len() + end + close.len()).unwrap_or(rest.len());
+                if is_doc(syntax, rest) {
+                    output.push_str(&rest[..end]);
+                } else {
+                    // A block comment spanning lines ends all but its last line
+                    for _ in rest[..end].matches('\n') {
+                        lines.push((std::mem::take(&mut output), true));
+                    }
+                    stripped_line = true;
+                }
+                rest = &rest[end..];
+                continue;
+            }
+        }
+        if let Some(marker) = syntax.line {
+            let after_space = !syntax.line_after_space || output.is_empty() || output.ends_with([' ', '\t']);
+            if rest.starts_with(marker) && after_space {
+                let end = rest.find('\n').unwrap_or(rest.len());
+                // Shebangs only count on the first line
+                let shebang = lines.is_empty() && at_line_start && rest.starts_with("#!");
+                if shebang || is_doc(syntax, rest) {
+                    output.push_str(&rest[..end]);
+                } else {
+                    stripped_line = true;
+                }
+                rest = &rest[end..];
+                continue;
+            }
+        }
+        
+        let length = string_length(syntax, rest).unwrap_or(c.len_utf8());
+        let mut segments = rest[..length].split('\n');
+        output.push_str(segments.next().unwrap_or(""));
+        // Strings spanning lines keep them whole
+        for segment in segments {
+            lines.push((std::mem::take(&mut output), stripped_line));
+            stripped_line = false;
+            output.push_str(segment);
+        }
+        rest = &rest[length..];
+    }
+    
+    let ends_with_newline = content.ends_with('\n');
+    if !output.is_empty() || !ends_with_newline {
+        lines.push((output, stripped_line));
+    }
+    
+    let mut result = String::with_capacity(content.len());
+    for (line, stripped) in lines {
+        if stripped && line.trim().is_empty() {
+            continue;
+        }
+        result.push_str(if stripped { line.trim_end() } else { &line });
+        result.push('\n');
+    }
+    if !ends_with_newline {
+        result.pop();
+    }
+    result
+}
+
+fn is_doc(syntax: &Syntax, text: &str) -> bool {
+    // `/**/` and `////` are not doc comments
+    syntax.doc.iter().any(|doc| text.starts_with(doc))
+        && !text.starts_with("/**/")
+        && !text.starts_with("////")
+}
+
+// Length in bytes of the string or character literal at the start of `text`
+fn string_length(syntax: &Syntax, text: &str) -> Option<usize> {
+    let quote = text.chars().next()?;
+    if quote == '\'' && syntax.char_literals {
+        return char_literal_length(text);
+    }
+    if !syntax.quotes.contains(&quote) {
+        return None;
+    }
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return Some(index + 1);
+        }
+    }
+    // An unterminated string runs to the end of the line
+    Some(text.find('\n').unwrap_or(text.len()))
+}
+
+// 'x' or '\n', '\u{1F600}'; anything else is a lifetime or label
+fn char_literal_length(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1);
+    let (_, first) = chars.next()?;
+    if first == '\\' {
+        return text.get(3..)?.find('\'').filter(|&end| end < 10).map(|end| end + 4);
+    }
+    match chars.next() {
+        Some((index, '\'')) => Some(index + 1),
+        _ => None,
+    }
+}
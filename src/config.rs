@@ -0,0 +1,324 @@
+// src/config.rs
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+
+// Name of the per-project configuration file, searched for upwards from the first path
+pub const PROJECT_CONFIG_FILE: &str = ".codecopier.toml";
+
+// Environment variables named this prefix plus a flag name set that flag
+const ENV_PREFIX: &str = "CODECOPIER_";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub tasks: HashMap<String, TaskConfig>,
+    pub ask: AskConfig,
+    pub handlers: HashMap<String, HandlerConfig>,
+    pub hooks: HooksConfig,
+    // Regex → replacement rules applied to everything before it is copied, in
+    // the order they are written since one rule's output can feed the next
+    pub redact: IndexMap<String, String>,
+    pub hidden: HiddenConfig,
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    // Share of --max-tokens reserved per directory, e.g. "src/core" = "60%"
+    pub budget: BTreeMap<String, BudgetShare>,
+    pub sensitive: SensitiveConfig,
+    // Framework presets for --preset, added to or replacing the built-in ones
+    pub presets: BTreeMap<String, PresetConfig>,
+    pub embeddings: EmbeddingsConfig,
+}
+
+// A task can be overridden either with a plain string or with separate
+// instructions and answer format
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum TaskConfig {
+    Text(String),
+    Detailed {
+        instructions: String,
+        #[serde(default)]
+        answer_format: Option<String>,
+    },
+}
+
+// What to do with files matching a [handlers] glob: "skip", "summarize",
+// { truncate = N } or { command = "..." }
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum HandlerConfig {
+    Action(String),
+    Truncate { truncate: usize },
+    Command { command: String },
+}
+
+// A Rhai script defining `should_include(path, metadata)` and/or `transform(path, content)`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub script: Option<PathBuf>,
+}
+
+// Dotfiles and dot-directories that are copied even without --hidden, as
+// paths relative to the copied directory (e.g. ".github/workflows")
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HiddenConfig {
+    pub allow: Vec<String>,
+}
+
+// A [budget] share, written as "60%" or as the number 60
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum BudgetShare {
+    Percent(f64),
+    Text(String),
+}
+
+// Path patterns that are only copied with --force, on top of the built-in
+// list unless `defaults = false`
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SensitiveConfig {
+    pub paths: Vec<String>,
+    pub defaults: bool,
+}
+
+impl Default for SensitiveConfig {
+    fn default() -> Self {
+        SensitiveConfig { paths: Vec::new(), defaults: true }
+    }
+}
+
+// A [presets.<name>] entry: path patterns that are kept first with
+// --max-tokens and ones that are dropped from the selection
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PresetConfig {
+    pub important: Vec<String>,
+    pub skip: Vec<String>,
+}
+
+// Arguments applied with --profile <name>, e.g. args = ["--no-tests", "--task", "review"]
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    pub args: Vec<String>,
+}
+
+// Settings for the `ask` subcommand
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AskConfig {
+    pub provider: Option<String>,
+    pub openai: ProviderConfig,
+    pub anthropic: ProviderConfig,
+    pub ollama: ProviderConfig,
+}
+
+// Settings for --semantic; the API key and base URL come from the
+// provider's [ask.<provider>] table
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingsConfig {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProviderConfig {
+    pub api_key: Option<String>,
+    pub model: Option<String>,
+    pub base_url: Option<String>,
+}
+
+impl ProviderConfig {
+    fn merge(&mut self, other: ProviderConfig) {
+        if other.api_key.is_some() {
+            self.api_key = other.api_key;
+        }
+        if other.model.is_some() {
+            self.model = other.model;
+        }
+        if other.base_url.is_some() {
+            self.base_url = other.base_url;
+        }
+    }
+}
+
+impl Config {
+    // Entries from `other` take precedence over the ones already loaded
+    fn merge(&mut self, other: Config) {
+        self.tasks.extend(other.tasks);
+        self.handlers.extend(other.handlers);
+        self.redact.extend(other.redact);
+        self.hidden.allow.extend(other.hidden.allow);
+        self.profiles.extend(other.profiles);
+        self.presets.extend(other.presets);
+        self.budget.extend(other.budget);
+        self.sensitive.paths.extend(other.sensitive.paths);
+        self.sensitive.defaults &= other.sensitive.defaults;
+        if other.hooks.script.is_some() {
+            self.hooks.script = other.hooks.script;
+        }
+        
+        if other.embeddings.provider.is_some() {
+            self.embeddings.provider = other.embeddings.provider;
+        }
+        if other.embeddings.model.is_some() {
+            self.embeddings.model = other.embeddings.model;
+        }
+        
+        if other.ask.provider.is_some() {
+            self.ask.provider = other.ask.provider;
+        }
+        self.ask.openai.merge(other.ask.openai);
+        self.ask.anthropic.merge(other.ask.anthropic);
+        self.ask.ollama.merge(other.ask.ollama);
+    }
+}
+
+pub fn user_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("codecopier").join("config.toml"))
+}
+
+// Loads the user config and the nearest project config, or only the given file
+// when an explicit --config path is passed
+pub fn load(paths: &[String], explicit_path: Option<&str>) -> io::Result<Config> {
+    if let Some(path) = explicit_path {
+        return load_file(Path::new(path));
+    }
+    
+    let mut config = Config::default();
+    
+    if let Some(path) = user_config_path() {
+        if path.is_file() {
+            config.merge(load_file(&path)?);
+        }
+    }
+    
+    if let Some(path) = find_project_config(paths) {
+        config.merge(load_file(&path)?);
+    }
+    
+    Ok(config)
+}
+
+fn load_file(path: &Path) -> io::Result<Config> {
+    let content = fs::read_to_string(path)?;
+    let mut config: Config = toml::from_str(&content).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid config file {}: {}", path.display(), e),
+        )
+    })?;
+    
+    // Script paths are relative to the config file that names them
+    if let Some(ref script) = config.hooks.script {
+        if script.is_relative() {
+            if let Some(dir) = path.parent() {
+                config.hooks.script = Some(dir.join(script));
+            }
+        }
+    }
+    
+    Ok(config)
+}
+
+fn find_project_config(paths: &[String]) -> Option<PathBuf> {
+    let first = paths::canonicalize(paths.first()?).ok()?;
+    let mut current_dir = if first.is_file() {
+        first.parent()?.to_path_buf()
+    } else {
+        first.to_path_buf()
+    };
+    
+    loop {
+        let config_path = current_dir.join(PROJECT_CONFIG_FILE);
+        if config_path.is_file() {
+            return Some(config_path);
+        }
+        
+        // Go up one directory
+        if !current_dir.pop() {
+            break;
+        }
+    }
+    
+    None
+}
+
+// Flags set through CODECOPIER_<FLAG> environment variables, as command-line
+// arguments: CODECOPIER_MAX_TOKENS=5000 becomes --max-tokens=5000,
+// CODECOPIER_LINE_NUMBERS=1 becomes --line-numbers, and CODECOPIER_CLIPBOARD=0
+// becomes --no-clipboard. Variables for flags that conflict with one given in
+// `matches`, the command line, are left out since the command line wins
+pub fn env_args(command: &Command, matches: &ArgMatches) -> Vec<String> {
+    let is_true = |value: &str| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+    let is_false = |value: &str| matches!(value.to_lowercase().as_str(), "0" | "false" | "no" | "off");
+    let given: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .collect();
+    let conflict = |a: &Arg, b: &Arg| command.get_arg_conflicts_with(a).iter().any(|other| other.get_id() == b.get_id());
+    let mut args = Vec::new();
+    
+    for arg in command.get_arguments() {
+        let long = match arg.get_long() {
+            Some(long) => long,
+            None => continue,
+        };
+        if given.iter().any(|other| conflict(arg, other) || conflict(other, arg)) {
+            continue;
+        }
+        let value = env::var(env_name(long)).ok().filter(|value| !value.is_empty());
+        // `--no-x` flags can also be set with CODECOPIER_X=0
+        let negated = long
+            .strip_prefix("no-")
+            .and_then(|flag| env::var(env_name(flag)).ok())
+            .is_some_and(|value| is_false(&value));
+        
+        match arg.get_action() {
+            ArgAction::SetTrue | ArgAction::SetFalse if negated || value.as_deref().is_some_and(is_true) => {
+                args.push(format!("--{}", long));
+            }
+            ArgAction::Count => {
+                let count = match value {
+                    Some(value) => value.parse().unwrap_or(if is_true(&value) { 1 } else { 0 }),
+                    None => 0,
+                };
+                args.extend((0..count).map(|_| format!("--{}", long)));
+            }
+            action if action.takes_values() => {
+                let value = match value {
+                    Some(value) => value,
+                    None => continue,
+                };
+                // Flags with an optional value, like --outline, are switched on
+                // with "true" or "1" and take any other value as theirs
+                let optional_value = arg.get_num_args().is_some_and(|range| range.min_values() == 0);
+                if optional_value && is_true(&value) {
+                    args.push(format!("--{}", long));
+                } else {
+                    args.push(format!("--{}={}", long, value));
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    args
+}
+
+fn env_name(flag: &str) -> String {
+    format!("{}{}", ENV_PREFIX, flag.to_uppercase().replace('-', "_"))
+}
@@ -0,0 +1,91 @@
+// src/confirm.rs
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use crate::languages::format_size;
+use crate::summary::SkippedFile;
+use crate::FileEntry;
+
+// Lines of each file shown in the preview
+const PREVIEW_LINES: usize = 8;
+
+// Summary of what is about to be copied, followed by the first lines of every file
+pub fn preview(files: &[FileEntry], skipped: &[SkippedFile], tokens: usize, destination: &str) -> String {
+    let bytes: usize = files.iter().map(|file| file.content.len()).sum();
+    let mut text = format!(
+        "{} files ({}, ~{} tokens) will be {}; {} files skipped\n",
+        files.len(),
+        format_size(bytes),
+        tokens,
+        destination,
+        skipped.len()
+    );
+    
+    for file in files {
+        text.push_str(&format!("\n── {} ({})\n", file.path, format_size(file.content.len())));
+        let mut lines = file.content.lines();
+        for line in lines.by_ref().take(PREVIEW_LINES) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        let remaining = lines.count();
+        if remaining > 0 {
+            text.push_str(&format!("   … {} more lines\n", remaining));
+        }
+    }
+    
+    text
+}
+
+// Shows the preview through $PAGER (less by default) when attached to a
+// terminal, otherwise writes it to stderr, then asks for a y/N answer
+pub fn ask(preview: &str, question: &str) -> io::Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--confirm needs an interactive terminal to ask on",
+        ));
+    }
+    
+    if !io::stdout().is_terminal() || page(preview).is_err() {
+        eprint!("{}", preview);
+    }
+    
+    yes_no(question)
+}
+
+// Whether there is a terminal to ask questions on
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+// Asks a y/N question on stderr; anything but yes counts as no
+pub fn yes_no(question: &str) -> io::Result<bool> {
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    crate::cancel::while_prompting(|| io::stdin().lock().read_line(&mut answer))?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn page(text: &str) -> io::Result<()> {
+    let pager = env::var("PAGER").ok().filter(|pager| !pager.trim().is_empty()).unwrap_or_else(|| "less".to_string());
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or("less");
+    
+    let mut command = Command::new(program);
+    command.args(words).stdin(Stdio::piped());
+    // Let less exit right away when the preview fits on one screen
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closing its input early (q) is not an error
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+// src/contracts.rs
+use ignore::WalkBuilder;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// Directories holding other projects' contracts, like vendored googleapis protos
+const VENDORED_DIRS: &[&str] = &["vendor", "third_party", "third-party"];
+
+// Protobuf definitions and OpenAPI or Swagger specs below `dir`, which are
+// copied whatever the other filters say, ignore files included
+pub fn find_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in WalkBuilder::new(dir)
+        .ignore(false)
+        .git_ignore(false)
+        .git_global(false)
+        .git_exclude(false)
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || !entry.file_type().is_some_and(|file_type| file_type.is_dir())
+                || !(crate::is_excluded_dir_name(&name) || VENDORED_DIRS.contains(&name.as_ref()))
+        })
+        .build()
+        .filter_map(|entry| entry.ok())
+    {
+        let path = entry.path();
+        if !entry.file_type().is_some_and(|file_type| file_type.is_file()) {
+            continue;
+        }
+        // Specs are only read when their extension could hold one
+        let found = is_proto(path)
+            || (spec_extension(path)
+                && fs::metadata(path).is_ok_and(|metadata| metadata.len() as usize <= crate::MAX_FILE_SIZE)
+                && fs::read_to_string(path).is_ok_and(|content| is_spec(&content)));
+        if found {
+            files.push(path.to_path_buf());
+        }
+    }
+    files.sort();
+    files
+}
+
+// Whether the file is a contract, for files given on the command line
+pub fn is_contract(path: &Path, content: &str) -> bool {
+    is_proto(path) || (spec_extension(path) && is_spec(content))
+}
+
+fn is_proto(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "proto")
+}
+
+fn spec_extension(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| matches!(ext, "yaml" | "yml" | "json"))
+}
+
+// A top-level `openapi: 3.x` or `swagger: "2.0"` key, in YAML or JSON
+fn is_spec(content: &str) -> bool {
+    static YAML: OnceLock<Regex> = OnceLock::new();
+    let yaml = YAML.get_or_init(|| Regex::new(r#"(?m)^(?:openapi|swagger):[ \t]*['"]?[23]\."#).expect("valid regex"));
+    yaml.is_match(content) || json_spec(content)
+}
+
+fn json_spec(content: &str) -> bool {
+    if !content.trim_start().starts_with('{') || !(content.contains("\"openapi\"") || content.contains("\"swagger\"")) {
+        return false;
+    }
+    match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(value) => ["openapi", "swagger"]
+            .iter()
+            .any(|key| value.get(key).and_then(|version| version.as_str()).is_some_and(|version| version.starts_with(['2', '3']))),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,138 @@
+// src/coverage.rs
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::paths;
+
+// Covered and coverable lines of a file or directory
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineCoverage {
+    pub covered: usize,
+    pub total: usize,
+}
+
+impl LineCoverage {
+    pub fn add(&mut self, other: LineCoverage) {
+        self.covered += other.covered;
+        self.total += other.total;
+    }
+    
+    // `83%`, or `-` for files without coverable lines
+    pub fn percent(&self) -> String {
+        if self.total == 0 {
+            return "-".to_string();
+        }
+        format!("{:.0}%", self.covered as f64 * 100.0 / self.total as f64)
+    }
+}
+
+// Line coverage from an lcov tracefile or a Cobertura XML report, keyed by
+// canonical source path
+pub struct CoverageReport {
+    files: HashMap<PathBuf, LineCoverage>,
+}
+
+impl CoverageReport {
+    pub fn get(&self, source_path: &Path) -> Option<LineCoverage> {
+        let path = paths::canonicalize(source_path).ok()?;
+        self.files.get(&path).copied()
+    }
+}
+
+fn class_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"(?s)<class\b[^>]*?\bfilename="([^"]*)"[^>]*>(.*?)</class>"#).unwrap())
+}
+
+fn line_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"<line\b[^>]*?\bnumber="(\d+)"[^>]*?\bhits="(\d+)""#).unwrap())
+}
+
+fn source_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"<source>\s*([^<]*?)\s*</source>").unwrap())
+}
+
+// Relative paths in the report are looked up in the report's <source>
+// directories, next to the report and in the project root
+pub fn load(report_path: &Path, project_root: &Path) -> io::Result<CoverageReport> {
+    let text = fs::read_to_string(report_path)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to read {}: {}", report_path.display(), e)))?;
+    
+    let mut bases: Vec<PathBuf> = Vec::new();
+    let hits = if text.trim_start().starts_with('<') {
+        bases.extend(source_regex().captures_iter(&text).map(|captures| PathBuf::from(&captures[1])));
+        parse_cobertura(&text)
+    } else {
+        parse_lcov(&text)
+    };
+    if hits.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is neither an lcov tracefile nor a Cobertura report with line data", report_path.display()),
+        ));
+    }
+    if let Some(dir) = report_path.parent() {
+        bases.push(if dir.as_os_str().is_empty() { PathBuf::from(".") } else { dir.to_path_buf() });
+    }
+    bases.push(project_root.to_path_buf());
+    
+    let mut files: HashMap<PathBuf, LineCoverage> = HashMap::new();
+    for (path, lines) in hits {
+        let path = Path::new(&path);
+        let candidates: Vec<PathBuf> =
+            if path.is_absolute() { vec![path.to_path_buf()] } else { bases.iter().map(|base| base.join(path)).collect() };
+        let resolved = match candidates.iter().find_map(|candidate| paths::canonicalize(candidate).ok()) {
+            Some(resolved) => resolved,
+            None => continue,
+        };
+        let coverage = LineCoverage { covered: lines.values().filter(|&&hits| hits > 0).count(), total: lines.len() };
+        files.entry(resolved).or_default().add(coverage);
+    }
+    
+    Ok(CoverageReport { files })
+}
+
+// SF:<path> starts a file's record, DA:<line>,<hits> are its lines
+fn parse_lcov(text: &str) -> HashMap<String, BTreeMap<usize, u64>> {
+    let mut files: HashMap<String, BTreeMap<usize, u64>> = HashMap::new();
+    let mut current: Option<String> = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(path) = line.strip_prefix("SF:") {
+            current = Some(path.to_string());
+            files.entry(path.to_string()).or_default();
+        } else if let Some(data) = line.strip_prefix("DA:") {
+            let mut fields = data.split(',');
+            let number = fields.next().and_then(|field| field.parse::<usize>().ok());
+            let hits = fields.next().and_then(|field| field.parse::<u64>().ok());
+            if let (Some(path), Some(number), Some(hits)) = (current.as_ref(), number, hits) {
+                *files.entry(path.clone()).or_default().entry(number).or_insert(0) += hits;
+            }
+        } else if line == "end_of_record" {
+            current = None;
+        }
+    }
+    files.retain(|_, lines| !lines.is_empty());
+    files
+}
+
+// Lines of the same file can be spread over several <class> elements
+fn parse_cobertura(text: &str) -> HashMap<String, BTreeMap<usize, u64>> {
+    let mut files: HashMap<String, BTreeMap<usize, u64>> = HashMap::new();
+    for class in class_regex().captures_iter(text) {
+        let lines = files.entry(class[1].to_string()).or_default();
+        for line in line_regex().captures_iter(&class[2]) {
+            if let (Ok(number), Ok(hits)) = (line[1].parse::<usize>(), line[2].parse::<u64>()) {
+                *lines.entry(number).or_insert(0) += hits;
+            }
+        }
+    }
+    files.retain(|_, lines| !lines.is_empty());
+    files
+}
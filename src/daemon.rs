@@ -0,0 +1,184 @@
+// src/daemon.rs
+use clap::{Args, Parser};
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{cache, config, content_hash, file_reader, paths, tokens, tree, Options};
+
+// Arguments of the `daemon` subcommand
+#[derive(Args)]
+pub struct DaemonArgs {
+    /// Directory copied by requests that name no paths; relative request paths are resolved against it
+    #[arg(value_name = "PATH", default_value = ".")]
+    root: String,
+    
+    /// Socket to listen on (defaults to codecopier-<hash>.sock in the runtime or temp directory)
+    #[arg(long, value_name = "PATH")]
+    socket: Option<PathBuf>,
+}
+
+// The copy options sent with a request, parsed like the command line
+#[derive(Parser)]
+#[command(name = "llm-cocop-rs", no_binary_name = true, args_override_self = true)]
+struct RequestArgs {
+    #[command(flatten)]
+    options: Options,
+}
+
+// One request per line: {"command": "copy", "args": ["--public-api", "src"]}
+#[derive(Deserialize)]
+struct Request {
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+// `daemon` subcommand: keeps file contents and pass results in memory and
+// answers copy, stats and tree requests over a local socket, so editor
+// plugins don't pay for a cold start on every copy
+pub fn run(args: DaemonArgs) -> io::Result<()> {
+    let root = paths::canonicalize(&args.root)
+        .map_err(|e| io::Error::new(e.kind(), format!("Cannot serve {}: {}", args.root, e)))?;
+    let socket = args.socket.unwrap_or_else(|| default_socket_path(&root));
+    
+    file_reader::keep_in_memory();
+    cache::keep_in_memory();
+    serve(&root, &socket)
+}
+
+fn default_socket_path(root: &Path) -> PathBuf {
+    let dir = dirs::runtime_dir().unwrap_or_else(env::temp_dir);
+    let root_hash = content_hash(&root.to_string_lossy());
+    dir.join(format!("codecopier-{}.sock", &root_hash[..16]))
+}
+
+#[cfg(unix)]
+fn serve(root: &Path, socket: &Path) -> io::Result<()> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+    
+    // A socket left behind by a daemon that is no longer running is replaced
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("A daemon is already listening on {}", socket.display()),
+            ));
+        }
+        fs::remove_file(socket)?;
+    }
+    
+    let listener = UnixListener::bind(socket)?;
+    println!("Serving {} on {}", root.display(), socket.display());
+    
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Could not accept a connection: {}", e);
+                continue;
+            }
+        };
+        match handle_connection(root, &stream, &stream) {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(e) => warn!("Connection error: {}", e),
+        }
+    }
+    
+    fs::remove_file(socket)?;
+    info!("Daemon stopped");
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve(_root: &Path, _socket: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "The daemon is only available on Unix systems"))
+}
+
+// Answers each request line with one JSON line; returns true after a shutdown request
+fn handle_connection(root: &Path, reader: impl io::Read, mut writer: impl Write) -> io::Result<bool> {
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) if request.command == "shutdown" => {
+                writeln!(writer, "{}", json!({ "ok": true }))?;
+                return Ok(true);
+            }
+            Ok(request) => match handle(root, &request) {
+                Ok(mut response) => {
+                    response["ok"] = json!(true);
+                    response
+                }
+                Err(e) => json!({ "ok": false, "error": e.to_string() }),
+            },
+            Err(e) => json!({ "ok": false, "error": format!("Invalid request: {}", e) }),
+        };
+        writeln!(writer, "{}", response)?;
+        writer.flush()?;
+    }
+    Ok(false)
+}
+
+fn handle(root: &Path, request: &Request) -> io::Result<Value> {
+    if !matches!(request.command.as_str(), "copy" | "stats" | "tree") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown command '{}'. Expected copy, stats, tree or shutdown", request.command),
+        ));
+    }
+    
+    let mut options = RequestArgs::try_parse_from(&request.args)
+        .map_err(|e| {
+            let message = e.to_string();
+            io::Error::new(io::ErrorKind::InvalidInput, message.lines().next().unwrap_or("").trim_start_matches("error: ").to_string())
+        })?
+        .options;
+    for path in options.paths.iter_mut() {
+        let resolved = root.join(&*path);
+        if Path::new(path).is_relative() && resolved.exists() {
+            *path = resolved.to_string_lossy().to_string();
+        }
+    }
+    if options.paths.is_empty() {
+        options.paths.push(root.to_string_lossy().to_string());
+    }
+    let _scratch_dirs = crate::resolve_inputs(&mut options)?;
+    
+    let config = config::load(&options.paths, options.config_path.as_deref())?;
+    let output = crate::generate_output(&options, &config)?;
+    
+    Ok(match request.command.as_str() {
+        "copy" => {
+            let text = output.text();
+            if let Some(ref pending) = output.incremental {
+                pending.save(&output.files)?;
+            }
+            json!({
+                "output": text,
+                "files": output.files.len(),
+                "tokens": tokens::estimate(text),
+            })
+        }
+        "stats" => {
+            let size = output.size();
+            json!({
+                "files": output.files.len(),
+                "skipped": output.skipped.len(),
+                "bytes": size.bytes,
+                "tokens": size.tokens(),
+            })
+        }
+        _ => json!({
+            "tree": tree::TreeNode::build(output.files.iter().map(|file| file.path.as_str())).render(),
+        }),
+    })
+}
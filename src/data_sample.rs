@@ -0,0 +1,166 @@
+// src/data_sample.rs
+use serde_json::Value;
+use std::path::Path;
+
+// Number of rows or records kept from each data file
+pub const DEFAULT_ROWS: usize = 10;
+
+// Pretty-printed JSON is cut after this many bytes
+const JSON_SAMPLE_BYTES: usize = 4 * 1024;
+
+// Records shown alongside the schema of a tabular JSON array
+const JSON_RECORD_SAMPLES: usize = 3;
+
+pub fn is_data_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_lowercase()).as_deref(),
+        Some("csv" | "tsv" | "json" | "jsonl" | "ndjson")
+    )
+}
+
+// Reduces a data file to a schema summary and a sample of its rows
+pub fn sample(path: &Path, content: &str, rows: usize) -> String {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "csv" => sample_delimited(content, ',', rows),
+        "tsv" => sample_delimited(content, '\t', rows),
+        "jsonl" | "ndjson" => sample_lines(content, rows),
+        "json" => sample_json(content, rows),
+        _ => content.to_string(),
+    }
+}
+
+fn sample_delimited(content: &str, delimiter: char, rows: usize) -> String {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header = match lines.next() {
+        Some(header) => header,
+        None => return String::new(),
+    };
+    let data: Vec<&str> = lines.collect();
+    
+    // Infer column types from the sampled rows
+    let columns: Vec<&str> = header.split(delimiter).map(|column| column.trim().trim_matches('"')).collect();
+    let sampled: Vec<Vec<&str>> = data
+        .iter()
+        .take(rows)
+        .map(|line| line.split(delimiter).map(|cell| cell.trim().trim_matches('"')).collect())
+        .collect();
+    let schema: Vec<String> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let values: Vec<&str> = sampled.iter().filter_map(|row| row.get(i).copied()).collect();
+            format!("{}: {}", column, infer_type(&values))
+        })
+        .collect();
+    
+    let mut output = format!("[{} rows; columns: {}]\n", data.len(), schema.join(", "));
+    output.push_str(header);
+    output.push('\n');
+    for line in data.iter().take(rows) {
+        output.push_str(line);
+        output.push('\n');
+    }
+    if data.len() > rows {
+        output.push_str(&format!("[... {} more rows ...]\n", data.len() - rows));
+    }
+    output
+}
+
+fn infer_type(values: &[&str]) -> &'static str {
+    let values: Vec<&str> = values.iter().copied().filter(|value| !value.is_empty()).collect();
+    if values.is_empty() {
+        "empty"
+    } else if values.iter().all(|value| value.parse::<i64>().is_ok()) {
+        "integer"
+    } else if values.iter().all(|value| value.parse::<f64>().is_ok()) {
+        "number"
+    } else if values.iter().all(|value| matches!(value.to_lowercase().as_str(), "true" | "false")) {
+        "boolean"
+    } else {
+        "text"
+    }
+}
+
+fn sample_lines(content: &str, rows: usize) -> String {
+    let lines: Vec<&str> = content.lines().filter(|line| !line.trim().is_empty()).collect();
+    let mut output: String = lines.iter().take(rows).map(|line| format!("{}\n", line)).collect();
+    if lines.len() > rows {
+        output.push_str(&format!("[... {} more records ...]\n", lines.len() - rows));
+    }
+    output
+}
+
+fn sample_json(content: &str, rows: usize) -> String {
+    let value: Value = match serde_json::from_str(content) {
+        Ok(value) => value,
+        Err(_) => return content.to_string(),
+    };
+    
+    // An array of objects with the same keys is summarized by its schema
+    if let Some(records) = value.as_array() {
+        if let Some(schema) = tabular_schema(records) {
+            let shown = records.len().min(rows).min(JSON_RECORD_SAMPLES);
+            let sample = Value::Array(records[..shown].to_vec());
+            let mut output = format!("[JSON array of {} records; fields: {}]\n", records.len(), schema);
+            output.push_str(&serde_json::to_string_pretty(&sample).unwrap_or_default());
+            output.push('\n');
+            if records.len() > shown {
+                output.push_str(&format!("[... {} more records ...]\n", records.len() - shown));
+            }
+            return output;
+        }
+    }
+    
+    let pretty = serde_json::to_string_pretty(&value).unwrap_or_else(|_| content.to_string());
+    if pretty.len() <= JSON_SAMPLE_BYTES {
+        return pretty;
+    }
+    
+    let lines: Vec<&str> = pretty.lines().collect();
+    let mut output = String::new();
+    let mut kept = 0;
+    for line in &lines {
+        if output.len() + line.len() + 1 > JSON_SAMPLE_BYTES {
+            break;
+        }
+        output.push_str(line);
+        output.push('\n');
+        kept += 1;
+    }
+    output.push_str(&format!("[... truncated {} lines ...]\n", lines.len() - kept));
+    output
+}
+
+fn tabular_schema(records: &[Value]) -> Option<String> {
+    let first = records.first()?.as_object()?;
+    if records.len() < 2 {
+        return None;
+    }
+    
+    let keys: Vec<&String> = first.keys().collect();
+    let uniform = records.iter().all(|record| {
+        record
+            .as_object()
+            .is_some_and(|object| object.len() == keys.len() && keys.iter().all(|key| object.contains_key(*key)))
+    });
+    if !uniform {
+        return None;
+    }
+    
+    let fields: Vec<String> = first
+        .iter()
+        .map(|(key, value)| {
+            let kind = match value {
+                Value::Null => "null",
+                Value::Bool(_) => "boolean",
+                Value::Number(_) => "number",
+                Value::String(_) => "string",
+                Value::Array(_) => "array",
+                Value::Object(_) => "object",
+            };
+            format!("{}: {}", key, kind)
+        })
+        .collect();
+    Some(fields.join(", "))
+}
@@ -0,0 +1,126 @@
+// src/dep_source.rs
+use log::info;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+// Finds the unpacked source of `name[@version]` in the Cargo registry,
+// downloading it with `cargo fetch` when it is not there yet. Without a
+// version, the one locked in the project's Cargo.lock is used, then the newest
+// one already downloaded
+pub fn locate(spec: &str, project_root: &Path) -> io::Result<PathBuf> {
+    let (name, version) = match spec.split_once('@') {
+        Some((name, version)) => (name, Some(version.trim_start_matches('=').to_string())),
+        None => (spec, None),
+    };
+    let version = version.or_else(|| locked_version(name, project_root));
+    
+    if let Some(dir) = find_in_registry(name, version.as_deref()) {
+        return Ok(dir);
+    }
+    
+    info!("Downloading {}{}...", name, version.as_deref().map(|v| format!(" {}", v)).unwrap_or_default());
+    fetch(name, version.as_deref())?;
+    find_in_registry(name, version.as_deref()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Could not find the source of {} in the Cargo registry", spec),
+        )
+    })
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+}
+
+// The version of `name` in the nearest Cargo.lock, the highest if several are locked
+fn locked_version(name: &str, project_root: &Path) -> Option<String> {
+    let lock_path = project_root.ancestors().map(|dir| dir.join("Cargo.lock")).find(|path| path.is_file())?;
+    let lock: toml::Value = fs::read_to_string(lock_path).ok()?.parse().ok()?;
+    lock.get("package")?
+        .as_array()?
+        .iter()
+        .filter(|package| package.get("name").and_then(|n| n.as_str()) == Some(name))
+        .filter_map(|package| package.get("version").and_then(|v| v.as_str()))
+        .max_by(|a, b| compare_versions(a, b))
+        .map(str::to_string)
+}
+
+// Looks through every registry index directory for `<name>-<version>`
+fn find_in_registry(name: &str, version: Option<&str>) -> Option<PathBuf> {
+    let registry = cargo_home()?.join("registry").join("src");
+    let prefix = format!("{}-", name);
+    let mut candidates: Vec<(String, PathBuf)> = Vec::new();
+    
+    for index in fs::read_dir(registry).ok()?.filter_map(|entry| entry.ok()) {
+        let entries = match fs::read_dir(index.path()) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            let candidate_version = match dir_name.strip_prefix(&prefix) {
+                // `foo-bar-1.0.0` must not match a search for `foo`
+                Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest.to_string(),
+                _ => continue,
+            };
+            if version.is_none_or(|wanted| wanted == candidate_version) {
+                candidates.push((candidate_version, entry.path()));
+            }
+        }
+    }
+    
+    candidates
+        .into_iter()
+        .max_by(|a, b| compare_versions(&a.0, &b.0))
+        .map(|(_, path)| path)
+}
+
+// Downloads the crate by fetching the dependencies of a throwaway manifest
+fn fetch(name: &str, version: Option<&str>) -> io::Result<()> {
+    let dir = env::temp_dir().join(format!("codecopier-fetch-{}", std::process::id()));
+    fs::create_dir_all(dir.join("src"))?;
+    let requirement = match version {
+        Some(version) => format!("={}", version),
+        None => "*".to_string(),
+    };
+    let manifest = format!(
+        "[package]\nname = \"codecopier-fetch\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n[dependencies]\n{} = \"{}\"\n",
+        name, requirement
+    );
+    fs::write(dir.join("Cargo.toml"), manifest)?;
+    fs::write(dir.join("src").join("lib.rs"), "")?;
+    
+    let output = Command::new("cargo")
+        .arg("fetch")
+        .current_dir(&dir)
+        .stdin(Stdio::null())
+        .output();
+    let _ = fs::remove_dir_all(&dir);
+    
+    let output = output.map_err(|e| io::Error::other(format!("Failed to run cargo fetch: {}", e)))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "cargo fetch for {} failed: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+// Compares dotted versions numerically, so that 1.10.0 sorts after 1.9.0
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .split(['.', '-', '+'])
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
@@ -0,0 +1,58 @@
+// src/dep_tree.rs
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::ProjectType;
+
+// Large workspaces can have thousands of transitive dependencies
+const MAX_LINES: usize = 500;
+
+pub struct DependencyTree {
+    pub command: String,
+    pub output: String,
+}
+
+// Runs `cargo tree`, `pip freeze` or `npm ls` depending on the project
+pub fn collect(project_root: &Path, project_type: &ProjectType, depth: usize) -> io::Result<Option<DependencyTree>> {
+    let depth = depth.to_string();
+    let (program, args): (&str, Vec<&str>) = match project_type {
+        ProjectType::Rust => ("cargo", vec!["tree", "--depth", &depth]),
+        ProjectType::Python => (if cfg!(windows) { "python" } else { "python3" }, vec!["-m", "pip", "freeze"]),
+        ProjectType::Unknown if project_root.join("package.json").is_file() => {
+            (if cfg!(windows) { "npm.cmd" } else { "npm" }, vec!["ls", "--depth", &depth])
+        }
+        ProjectType::Swift => ("swift", vec!["package", "show-dependencies"]),
+        ProjectType::Kotlin | ProjectType::Infrastructure | ProjectType::Unknown => return Ok(None),
+    };
+    
+    let command = format!("{} {}", program.trim_end_matches(".cmd"), args.join(" "));
+    let output = Command::new(program)
+        .args(&args)
+        .current_dir(project_root)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| io::Error::other(format!("Failed to run {}: {}", command, e)))?;
+    
+    // `npm ls` exits with an error for missing peer dependencies but still prints the tree
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("{} failed: {}", command, stderr.trim())));
+    }
+    
+    let lines: Vec<&str> = stdout.trim_end().lines().collect();
+    let mut tree = lines.iter().take(MAX_LINES).copied().collect::<Vec<_>>().join("\n");
+    if lines.len() > MAX_LINES {
+        tree.push_str(&format!("\n[... {} more lines ...]", lines.len() - MAX_LINES));
+    }
+    
+    Ok(Some(DependencyTree { command, output: tree }))
+}
+
+pub fn format_dependency_tree_section(tree: &DependencyTree) -> String {
+    format!(
+        "<dependency_tree command=\"{}\">\n{}\n</dependency_tree>\n\n",
+        tree.command, tree.output
+    )
+}
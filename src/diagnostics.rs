@@ -0,0 +1,119 @@
+// src/diagnostics.rs
+use clap::ValueEnum;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Tool {
+    Check,
+    Clippy,
+}
+
+impl Tool {
+    pub fn subcommand(self) -> &'static str {
+        match self {
+            Tool::Check => "check",
+            Tool::Clippy => "clippy",
+        }
+    }
+}
+
+// A compiler message with its primary location
+pub struct Diagnostic {
+    pub level: String,
+    pub code: Option<String>,
+    pub location: Option<String>,
+    pub rendered: String,
+}
+
+// Runs cargo with JSON output in the project root and collects errors and warnings
+pub fn collect(project_root: &Path, tool: Tool) -> io::Result<Vec<Diagnostic>> {
+    let output = Command::new("cargo")
+        .arg(tool.subcommand())
+        .arg("--message-format=json")
+        .current_dir(project_root)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| io::Error::other(format!("Failed to run cargo {}: {}", tool.subcommand(), e)))?;
+    
+    // A failing build still produces messages, so the exit status is not checked
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut seen = HashSet::new();
+    let mut diagnostics = Vec::new();
+    
+    for line in stdout.lines() {
+        let event: Value = match serde_json::from_str(line) {
+            Ok(event) => event,
+            Err(_) => continue,
+        };
+        if event["reason"] != "compiler-message" {
+            continue;
+        }
+        
+        if let Some(diagnostic) = parse_message(&event["message"]) {
+            // The same message is reported once per target that includes the file
+            if seen.insert(diagnostic.rendered.clone()) {
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+    
+    Ok(diagnostics)
+}
+
+fn parse_message(message: &Value) -> Option<Diagnostic> {
+    let level = message["level"].as_str()?;
+    if level != "error" && level != "warning" {
+        return None;
+    }
+    
+    let spans = message["spans"].as_array()?;
+    let primary = spans.iter().find(|span| span["is_primary"].as_bool() == Some(true));
+    
+    // Summary lines like "aborting due to previous error" have no location
+    let primary = primary?;
+    let location = match (
+        primary["file_name"].as_str(),
+        primary["line_start"].as_u64(),
+        primary["column_start"].as_u64(),
+    ) {
+        (Some(file), Some(line), Some(column)) => Some(format!("{}:{}:{}", file, line, column)),
+        (Some(file), _, _) => Some(file.to_string()),
+        _ => None,
+    };
+    
+    Some(Diagnostic {
+        level: level.to_string(),
+        code: message["code"]["code"].as_str().map(|code| code.to_string()),
+        location,
+        rendered: message["rendered"]
+            .as_str()
+            .or_else(|| message["message"].as_str())
+            .unwrap_or("")
+            .trim_end()
+            .to_string(),
+    })
+}
+
+pub fn format_diagnostics_section(diagnostics: &[Diagnostic]) -> String {
+    let errors = diagnostics.iter().filter(|d| d.level == "error").count();
+    let warnings = diagnostics.len() - errors;
+    
+    let mut section = format!("<diagnostics errors=\"{}\" warnings=\"{}\">\n", errors, warnings);
+    for diagnostic in diagnostics {
+        section.push_str(&format!("<diagnostic level=\"{}\"", diagnostic.level));
+        if let Some(ref code) = diagnostic.code {
+            section.push_str(&format!(" code=\"{}\"", code));
+        }
+        if let Some(ref location) = diagnostic.location {
+            section.push_str(&format!(" location=\"{}\"", location));
+        }
+        section.push_str(&format!(">\n{}\n</diagnostic>\n", diagnostic.rendered));
+    }
+    section.push_str("</diagnostics>\n\n");
+    section
+}
@@ -0,0 +1,333 @@
+// src/docker.rs
+use ignore::WalkBuilder;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// Dockerfiles and Compose files below `dir`, found with the usual ignore
+// rules, which are copied whatever the other filters say
+pub fn find_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in WalkBuilder::new(dir)
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_some_and(|file_type| file_type.is_dir())
+                || !crate::is_excluded_dir_name(&entry.file_name().to_string_lossy())
+        })
+        .build()
+        .filter_map(|entry| entry.ok())
+    {
+        let path = entry.path();
+        if entry.file_type().is_some_and(|file_type| file_type.is_file()) && (is_dockerfile(path) || is_compose_file(path)) {
+            files.push(path.to_path_buf());
+        }
+    }
+    files.sort();
+    files
+}
+
+// `Dockerfile`, `Containerfile`, `Dockerfile.dev`, `api.Dockerfile`
+fn is_dockerfile(path: &Path) -> bool {
+    let name = file_name(path).to_lowercase();
+    name == "dockerfile" || name == "containerfile" || name.starts_with("dockerfile.") || name.ends_with(".dockerfile")
+}
+
+// `compose.yaml`, `docker-compose.yml`, `docker-compose.override.yml`
+fn is_compose_file(path: &Path) -> bool {
+    static COMPOSE: OnceLock<Regex> = OnceLock::new();
+    let compose = COMPOSE.get_or_init(|| Regex::new(r"^(?:docker-)?compose(?:\.[\w.-]+)?\.ya?ml$").expect("valid regex"));
+    compose.is_match(&file_name(path).to_lowercase())
+}
+
+// <deployment_info> section summarizing the base images, exposed ports and
+// volumes of each Dockerfile, and the services and volumes of each Compose
+// file; files are named by their display path
+pub fn format_deployment_section(files: &[(String, PathBuf)]) -> String {
+    let mut summaries = Vec::new();
+    for (name, path) in files {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let summary = if is_dockerfile(path) { dockerfile_summary(&content) } else { compose_summary(&content) };
+        if !summary.is_empty() {
+            summaries.push(format!("{}:\n{}", name, summary));
+        }
+    }
+    if summaries.is_empty() {
+        return String::new();
+    }
+    format!("<deployment_info>\n{}</deployment_info>\n\n", summaries.join("\n"))
+}
+
+fn dockerfile_summary(content: &str) -> String {
+    let mut stages = Vec::new();
+    let mut ports = Vec::new();
+    let mut volumes = Vec::new();
+    for instruction in instructions(content) {
+        let (keyword, arguments) = match instruction.split_once(char::is_whitespace) {
+            Some((keyword, arguments)) => (keyword.to_uppercase(), arguments.trim()),
+            None => continue,
+        };
+        match keyword.as_str() {
+            // FROM [--platform=...] image [AS name]
+            "FROM" => {
+                let words: Vec<&str> = arguments.split_whitespace().filter(|word| !word.starts_with("--")).collect();
+                match words.as_slice() {
+                    [image, keyword, name, ..] if keyword.eq_ignore_ascii_case("as") => {
+                        stages.push(format!("{} (stage {})", image, name));
+                    }
+                    [image, ..] => stages.push(image.to_string()),
+                    [] => {}
+                }
+            }
+            "EXPOSE" => ports.extend(arguments.split_whitespace().map(str::to_string)),
+            // VOLUME /data or VOLUME ["/data", "/logs"]
+            "VOLUME" => volumes.extend(
+                arguments
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .split([',', ' '])
+                    .map(|volume| volume.trim().trim_matches('"'))
+                    .filter(|volume| !volume.is_empty())
+                    .map(str::to_string),
+            ),
+            _ => {}
+        }
+    }
+    
+    let mut summary = String::new();
+    if !stages.is_empty() {
+        summary.push_str(&format!("Base images: {}\n", stages.join(", ")));
+    }
+    if !ports.is_empty() {
+        summary.push_str(&format!("Exposed ports: {}\n", ports.join(", ")));
+    }
+    if !volumes.is_empty() {
+        summary.push_str(&format!("Volumes: {}\n", volumes.join(", ")));
+    }
+    summary
+}
+
+// Instructions with their continuation lines joined and comments left out
+fn instructions(content: &str) -> Vec<String> {
+    let mut instructions = Vec::new();
+    let mut current = String::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') || (line.is_empty() && current.is_empty()) {
+            continue;
+        }
+        match line.strip_suffix('\\') {
+            Some(continued) => {
+                current.push_str(continued);
+                current.push(' ');
+            }
+            None => {
+                current.push_str(line);
+                instructions.push(std::mem::take(&mut current));
+            }
+        }
+    }
+    if !current.trim().is_empty() {
+        instructions.push(current);
+    }
+    instructions
+}
+
+// A service of a Compose file and the settings listed for it
+#[derive(Default)]
+struct Service {
+    name: String,
+    image: Option<String>,
+    build: Option<String>,
+    ports: Vec<String>,
+    volumes: Vec<String>,
+    depends_on: Vec<String>,
+}
+
+// Compose files are read by indentation rather than as full YAML: the keys
+// summarized are plain scalars, flow lists or block lists
+fn compose_summary(content: &str) -> String {
+    let mut services: Vec<Service> = Vec::new();
+    let mut volumes = Vec::new();
+    let mut section = String::new();
+    // Indentation of the service names and of their keys
+    let mut service_indent = None;
+    let mut key_indent = None;
+    let mut key = String::new();
+    // `key: value` pairs of a long-syntax list item, like `target: 80`
+    let mut item: Vec<(String, String)> = Vec::new();
+    
+    for line in content.lines() {
+        let code = strip_comment(line);
+        if code.trim().is_empty() {
+            continue;
+        }
+        let indent = code.len() - code.trim_start().len();
+        let text = code.trim();
+        
+        if indent == 0 {
+            flush_item(&mut services, &key, &mut item);
+            section = text.trim_end_matches(':').to_string();
+            service_indent = None;
+            continue;
+        }
+        match section.as_str() {
+            "services" => {}
+            "volumes" => {
+                if service_indent.is_none_or(|first| indent <= first) {
+                    service_indent = Some(indent);
+                    volumes.push(text.trim_end_matches(':').split(':').next().unwrap_or(text).trim().to_string());
+                }
+                continue;
+            }
+            _ => continue,
+        }
+        
+        if service_indent.is_none_or(|first| indent <= first) {
+            flush_item(&mut services, &key, &mut item);
+            service_indent = Some(indent);
+            key_indent = None;
+            key.clear();
+            services.push(Service { name: text.trim_end_matches(':').trim_matches(['"', '\'']).to_string(), ..Service::default() });
+            continue;
+        }
+        if key_indent.is_none_or(|first| indent <= first) {
+            flush_item(&mut services, &key, &mut item);
+            key_indent = Some(indent);
+            let (name, value) = match text.split_once(':') {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => continue,
+            };
+            key = name.to_string();
+            if let Some(service) = services.last_mut() {
+                match key.as_str() {
+                    "image" => service.image = Some(unquote(value)),
+                    "build" if !value.is_empty() => service.build = Some(unquote(value)),
+                    _ => {}
+                }
+                if value.starts_with('[') {
+                    for entry in value.trim_matches(['[', ']']).split(',').map(unquote).filter(|entry| !entry.is_empty()) {
+                        push_entry(service, &key, entry);
+                    }
+                }
+            }
+            continue;
+        }
+        
+        // Entries of the current key: `- "8080:80"`, `- target: 80` and the
+        // lines following it, or `context: ./api` under `build:`
+        match text.strip_prefix("- ") {
+            Some(entry) => {
+                flush_item(&mut services, &key, &mut item);
+                match entry.split_once(": ") {
+                    Some((name, value)) => item.push((name.trim().to_string(), unquote(value))),
+                    None => {
+                        if let Some(service) = services.last_mut() {
+                            push_entry(service, &key, unquote(entry.trim_end_matches(':')));
+                        }
+                    }
+                }
+            }
+            None => {
+                if let Some((name, value)) = text.split_once(':') {
+                    if key == "build" && name.trim() == "context" {
+                        if let Some(service) = services.last_mut() {
+                            service.build = Some(unquote(value));
+                        }
+                    } else if key == "depends_on" && value.trim().is_empty() {
+                        if let Some(service) = services.last_mut() {
+                            push_entry(service, &key, name.trim().to_string());
+                        }
+                    } else if !item.is_empty() {
+                        item.push((name.trim().to_string(), unquote(value)));
+                    }
+                }
+            }
+        }
+    }
+    flush_item(&mut services, &key, &mut item);
+    
+    let mut summary = String::new();
+    if !services.is_empty() {
+        summary.push_str("Services:\n");
+        for service in &services {
+            let mut details = Vec::new();
+            if let Some(ref image) = service.image {
+                details.push(format!("image {}", image));
+            }
+            if let Some(ref build) = service.build {
+                details.push(format!("build {}", build));
+            }
+            if !service.ports.is_empty() {
+                details.push(format!("ports {}", service.ports.join(", ")));
+            }
+            if !service.volumes.is_empty() {
+                details.push(format!("volumes {}", service.volumes.join(", ")));
+            }
+            if !service.depends_on.is_empty() {
+                details.push(format!("depends on {}", service.depends_on.join(", ")));
+            }
+            if details.is_empty() {
+                summary.push_str(&format!("- {}\n", service.name));
+            } else {
+                summary.push_str(&format!("- {}: {}\n", service.name, details.join("; ")));
+            }
+        }
+    }
+    if !volumes.is_empty() {
+        summary.push_str(&format!("Volumes: {}\n", volumes.join(", ")));
+    }
+    summary
+}
+
+fn push_entry(service: &mut Service, key: &str, entry: String) {
+    match key {
+        "ports" => service.ports.push(entry),
+        "volumes" => service.volumes.push(entry),
+        "depends_on" => service.depends_on.push(entry),
+        _ => {}
+    }
+}
+
+// A long-syntax port (`published: 8080`, `target: 80`) or volume (`source`,
+// `target`) as the short syntax
+fn flush_item(services: &mut [Service], key: &str, item: &mut Vec<(String, String)>) {
+    if item.is_empty() {
+        return;
+    }
+    let get = |name: &str| item.iter().find(|(key, _)| key == name).map(|(_, value)| value.clone());
+    let entry = match (get("published").or_else(|| get("source")), get("target")) {
+        (Some(from), Some(to)) => format!("{}:{}", from, to),
+        (None, Some(to)) => to,
+        (Some(from), None) => from,
+        (None, None) => String::new(),
+    };
+    if let Some(service) = services.last_mut() {
+        if !entry.is_empty() {
+            push_entry(service, key, entry);
+        }
+    }
+    item.clear();
+}
+
+// The line without a trailing `# comment`, which YAML starts with a space
+fn strip_comment(line: &str) -> &str {
+    if line.trim_start().starts_with('#') {
+        return "";
+    }
+    match line.find(" #") {
+        Some(index) => &line[..index],
+        None => line,
+    }
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(['"', '\'']).to_string()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
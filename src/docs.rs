@@ -0,0 +1,271 @@
+// src/docs.rs
+use log::warn;
+use syn::{Attribute, Fields, ImplItem, Item, TraitItem};
+
+use crate::public_api::{print_without_bodies, stripped_body};
+use crate::FileEntry;
+
+// Replaces Rust and Python files with their doc comments and docstrings plus
+// the signatures they document, and drops everything else
+pub fn extract(files: Vec<FileEntry>) -> Vec<FileEntry> {
+    let mut doc_files = Vec::new();
+    
+    for mut file in files {
+        let docs = match file.source_path.extension().and_then(|ext| ext.to_str()) {
+            Some("rs") => match crate::cache::get_or_compute("rust-docs", &file.content, || rust_docs(&file.content)) {
+                Some(docs) => docs,
+                None => {
+                    warn!("Could not parse {} for --docs-only, skipping", file.path);
+                    continue;
+                }
+            },
+            Some("py") | Some("pyi") => python_docs(&file.content),
+            _ => continue,
+        };
+        if docs.trim().is_empty() {
+            continue;
+        }
+        
+        file.content = docs;
+        file.hash = crate::content_hash(&file.content);
+        doc_files.push(file);
+    }
+    
+    doc_files
+}
+
+fn rust_docs(content: &str) -> Option<String> {
+    let mut file = syn::parse_file(content).ok()?;
+    file.shebang = None;
+    file.attrs.retain(is_doc);
+    file.items = documented_items(file.items);
+    
+    if file.attrs.is_empty() && file.items.is_empty() {
+        return Some(String::new());
+    }
+    Some(print_without_bodies(&file))
+}
+
+fn documented_items(items: Vec<Item>) -> Vec<Item> {
+    items.into_iter().filter_map(documented_item).collect()
+}
+
+// Keeps items with doc comments, and containers (modules, impls, traits) that
+// have documented members, with bodies removed and other attributes dropped
+fn documented_item(item: Item) -> Option<Item> {
+    match item {
+        Item::Fn(mut item) if has_docs(&item.attrs) => {
+            item.attrs.retain(is_doc);
+            item.block = Box::new(stripped_body());
+            Some(Item::Fn(item))
+        }
+        Item::Struct(mut item) if has_docs(&item.attrs) || fields_have_docs(&item.fields) => {
+            item.attrs.retain(is_doc);
+            for field in item.fields.iter_mut() {
+                field.attrs.retain(is_doc);
+            }
+            Some(Item::Struct(item))
+        }
+        Item::Enum(mut item) if has_docs(&item.attrs) || item.variants.iter().any(|v| has_docs(&v.attrs)) => {
+            item.attrs.retain(is_doc);
+            for variant in item.variants.iter_mut() {
+                variant.attrs.retain(is_doc);
+            }
+            Some(Item::Enum(item))
+        }
+        Item::Trait(mut item) => {
+            item.items.retain(|trait_item| match trait_item {
+                TraitItem::Fn(method) => has_docs(&method.attrs),
+                TraitItem::Const(constant) => has_docs(&constant.attrs),
+                TraitItem::Type(alias) => has_docs(&alias.attrs),
+                _ => false,
+            });
+            if !has_docs(&item.attrs) && item.items.is_empty() {
+                return None;
+            }
+            item.attrs.retain(is_doc);
+            for trait_item in &mut item.items {
+                if let TraitItem::Fn(method) = trait_item {
+                    method.attrs.retain(is_doc);
+                    method.default = None;
+                    method.semi_token = Some(Default::default());
+                }
+            }
+            Some(Item::Trait(item))
+        }
+        Item::Impl(mut item) => {
+            item.items.retain(|impl_item| match impl_item {
+                ImplItem::Fn(method) => has_docs(&method.attrs),
+                ImplItem::Const(constant) => has_docs(&constant.attrs),
+                ImplItem::Type(alias) => has_docs(&alias.attrs),
+                _ => false,
+            });
+            if item.items.is_empty() {
+                return None;
+            }
+            item.attrs.retain(is_doc);
+            for impl_item in &mut item.items {
+                if let ImplItem::Fn(method) = impl_item {
+                    method.attrs.retain(is_doc);
+                    method.block = stripped_body();
+                }
+            }
+            Some(Item::Impl(item))
+        }
+        Item::Mod(mut item) => {
+            if let Some((brace, content)) = item.content.take() {
+                let content = documented_items(content);
+                if !content.is_empty() {
+                    item.content = Some((brace, content));
+                }
+            }
+            if !has_docs(&item.attrs) && item.content.is_none() {
+                return None;
+            }
+            item.attrs.retain(is_doc);
+            Some(Item::Mod(item))
+        }
+        Item::Type(mut item) if has_docs(&item.attrs) => {
+            item.attrs.retain(is_doc);
+            Some(Item::Type(item))
+        }
+        Item::Const(mut item) if has_docs(&item.attrs) => {
+            item.attrs.retain(is_doc);
+            Some(Item::Const(item))
+        }
+        Item::Static(mut item) if has_docs(&item.attrs) => {
+            item.attrs.retain(is_doc);
+            Some(Item::Static(item))
+        }
+        Item::Macro(mut item) if has_docs(&item.attrs) => {
+            item.attrs.retain(is_doc);
+            Some(Item::Macro(item))
+        }
+        _ => None,
+    }
+}
+
+fn is_doc(attr: &Attribute) -> bool {
+    attr.path().is_ident("doc")
+}
+
+fn has_docs(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(is_doc)
+}
+
+fn fields_have_docs(fields: &Fields) -> bool {
+    fields.iter().any(|field| has_docs(&field.attrs))
+}
+
+// The module docstring, plus each documented `def`/`class` with its docstring;
+// undocumented classes are kept as headers for their documented methods
+fn python_docs(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut output: Vec<String> = Vec::new();
+    let mut i = 0;
+    
+    // Module docstring, after any shebang, encoding line and comments
+    while i < lines.len() && (lines[i].trim().is_empty() || lines[i].trim_start().starts_with('#')) {
+        i += 1;
+    }
+    if let Some(end) = docstring_end(&lines, i) {
+        output.extend(lines[i..=end].iter().map(|line| line.to_string()));
+        i = end + 1;
+    }
+    
+    // Enclosing definitions without docstrings: (indent, header lines, emitted)
+    let mut pending: Vec<(usize, Vec<String>, bool)> = Vec::new();
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        let is_definition = trimmed.starts_with("def ")
+            || trimmed.starts_with("async def ")
+            || trimmed.starts_with("class ");
+        if !is_definition {
+            i += 1;
+            continue;
+        }
+        
+        let indent = line.len() - trimmed.len();
+        pending.retain(|(pending_indent, _, _)| *pending_indent < indent);
+        
+        // Decorators directly above the definition belong to its signature
+        let mut start = i;
+        while start > 0 && lines[start - 1].trim_start().starts_with('@') {
+            start -= 1;
+        }
+        let header_end = signature_end(&lines, i);
+        let header: Vec<String> = lines[start..=header_end].iter().map(|line| line.to_string()).collect();
+        
+        let mut body = header_end + 1;
+        while body < lines.len() && lines[body].trim().is_empty() {
+            body += 1;
+        }
+        match docstring_end(&lines, body) {
+            Some(end) => {
+                for (_, pending_header, emitted) in pending.iter_mut() {
+                    if !*emitted {
+                        if !output.is_empty() {
+                            output.push(String::new());
+                        }
+                        output.extend(pending_header.iter().cloned());
+                        *emitted = true;
+                    }
+                }
+                if !output.is_empty() {
+                    output.push(String::new());
+                }
+                output.extend(header.iter().cloned());
+                output.extend(lines[body..=end].iter().map(|line| line.to_string()));
+                pending.push((indent, header, true));
+                i = end + 1;
+            }
+            None => {
+                pending.push((indent, header, false));
+                i = header_end + 1;
+            }
+        }
+    }
+    
+    if output.is_empty() {
+        String::new()
+    } else {
+        output.join("\n") + "\n"
+    }
+}
+
+// The last line of a signature: the first line ending in `:` outside brackets
+fn signature_end(lines: &[&str], start: usize) -> usize {
+    let mut depth: i32 = 0;
+    for (offset, line) in lines[start..].iter().enumerate() {
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        if depth <= 0 && line.trim_end().ends_with(':') {
+            return start + offset;
+        }
+    }
+    start
+}
+
+// If a docstring starts at `start`, the index of the line that closes it
+fn docstring_end(lines: &[&str], start: usize) -> Option<usize> {
+    let trimmed = lines.get(start)?.trim_start();
+    let unprefixed = trimmed.trim_start_matches(['r', 'R', 'u', 'U', 'b', 'B']);
+    let quote = if unprefixed.starts_with("\"\"\"") {
+        "\"\"\""
+    } else if unprefixed.starts_with("'''") {
+        "'''"
+    } else {
+        return None;
+    };
+    
+    if unprefixed[3..].contains(quote) {
+        return Some(start);
+    }
+    (start + 1..lines.len()).find(|&index| lines[index].contains(quote))
+}
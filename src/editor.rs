@@ -0,0 +1,256 @@
+// src/editor.rs
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::apply::{self, Edit, FileChange};
+use crate::formatter::{Formatter, RenderContext};
+use crate::{languages, paths, FileEntry};
+
+// Bumped when the document layout changes incompatibly
+const SCHEMA_VERSION: u32 = 1;
+
+// The --format editor-json document, also read back by `apply`
+#[derive(Serialize, Deserialize)]
+struct Document {
+    version: u32,
+    files: Vec<EditorFile>,
+}
+
+// Whole files carry `content`; files narrowed down by line or symbol
+// references carry one range per excerpt instead
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EditorFile {
+    #[serde(default)]
+    uri: String,
+    #[serde(default)]
+    path: String,
+    #[serde(default)]
+    language_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ranges: Vec<EditorRange>,
+}
+
+// Zero-based like LSP ranges, with the end exclusive; a range of whole lines
+// ends at character 0 of the line after it
+#[derive(Serialize, Deserialize)]
+struct EditorRange {
+    start: Position,
+    end: Position,
+    text: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct Position {
+    line: usize,
+    character: usize,
+}
+
+// --format editor-json
+pub struct EditorJson;
+
+impl Formatter for EditorJson {
+    fn name(&self) -> &'static str {
+        "editor-json"
+    }
+    
+    fn render(&self, context: &RenderContext) -> String {
+        render(context.files, context.post_process)
+    }
+}
+
+// Renders the files as an editor-json document; `post_process` applies
+// redaction and anonymization to each text
+fn render(files: &[FileEntry], post_process: &dyn Fn(&str) -> String) -> String {
+    let files = files
+        .iter()
+        .map(|file| {
+            let source_path = paths::canonicalize(&file.source_path).unwrap_or_else(|_| file.source_path.clone());
+            let mut entry = EditorFile {
+                uri: file_uri(&source_path),
+                path: file.path.clone(),
+                language_id: languages::detect_language_id(&file.source_path, &file.content).unwrap_or("plaintext").to_string(),
+                content: None,
+                ranges: Vec::new(),
+            };
+            if file.line_ranges.is_empty() {
+                entry.content = Some(post_process(&file.content));
+            } else {
+                entry.ranges = excerpt_ranges(&file.content, &file.line_ranges, post_process);
+            }
+            entry
+        })
+        .collect();
+    
+    let document = Document { version: SCHEMA_VERSION, files };
+    serde_json::to_string_pretty(&document).unwrap_or_default()
+}
+
+// Splits excerpt content back into its ranges; excerpts are separated by one line
+fn excerpt_ranges(content: &str, line_ranges: &[(usize, usize)], post_process: &dyn Fn(&str) -> String) -> Vec<EditorRange> {
+    let mut lines = content.lines();
+    let mut ranges = Vec::new();
+    
+    for (index, &(start, end)) in line_ranges.iter().enumerate() {
+        if index > 0 {
+            lines.next();
+        }
+        let mut text = String::new();
+        for _ in start..=end {
+            text.push_str(lines.next().unwrap_or(""));
+            text.push('\n');
+        }
+        ranges.push(EditorRange {
+            start: Position { line: start - 1, character: 0 },
+            end: Position { line: end, character: 0 },
+            text: post_process(&text),
+        });
+    }
+    
+    ranges
+}
+
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    // Windows paths like C:/src need a leading slash
+    let path = if path.starts_with('/') { path } else { format!("/{}", path) };
+    
+    let mut uri = String::from("file://");
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~:".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+fn uri_path(uri: &str) -> io::Result<PathBuf> {
+    let encoded = uri.strip_prefix("file://").ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("Only file:// URIs are supported, got {}", uri))
+    })?;
+    
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = match tail {
+            [high, low, ..] if byte == b'%' => std::str::from_utf8(&[*high, *low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    let path = String::from_utf8(bytes)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid file URI {}", uri)))?;
+    
+    // file:///C:/src → C:/src
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+        _ => path,
+    };
+    Ok(PathBuf::from(path))
+}
+
+// URIs are absolute, so unlike paths they are checked against the root
+// after resolving symlinks, in the nearest directory that exists
+fn inside_root(root: &Path, path: PathBuf) -> io::Result<PathBuf> {
+    let root = paths::canonicalize(root)?;
+    let existing = path.ancestors().find(|ancestor| ancestor.exists()).and_then(|ancestor| paths::canonicalize(ancestor).ok());
+    let climbs = path.components().any(|component| component == Component::ParentDir);
+    if climbs || !existing.is_some_and(|existing| existing.starts_with(&root)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Refusing to write {}: files must stay inside {}", path.display(), root.display()),
+        ));
+    }
+    Ok(path)
+}
+
+// Changes described by an editor-json document, or a JSON reply in the same
+// shape; ranges are applied to the current file contents right away
+pub fn read_changes(json: &str, root: &Path) -> io::Result<Vec<FileChange>> {
+    let document: Document = serde_json::from_str(json)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid editor-json document: {}", e)))?;
+    if document.version != SCHEMA_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported editor-json version {} (expected {})", document.version, SCHEMA_VERSION),
+        ));
+    }
+    
+    let mut changes = Vec::new();
+    for file in document.files {
+        let path = if !file.uri.is_empty() {
+            inside_root(root, uri_path(&file.uri)?)?
+        } else if !file.path.is_empty() {
+            apply::resolve(root, &file.path)?
+        } else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "A file has neither a uri nor a path"));
+        };
+        
+        let content = match file.content {
+            Some(content) => content,
+            None if file.ranges.is_empty() => continue,
+            None => {
+                let current = fs::read_to_string(&path)
+                    .map_err(|e| io::Error::new(e.kind(), format!("Failed to read {}: {}", path.display(), e)))?;
+                replace_ranges(&current, file.ranges)
+                    .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message)))?
+            }
+        };
+        changes.push(FileChange { path, edit: Edit::Replace(content) });
+    }
+    
+    Ok(changes)
+}
+
+// Replaces the ranges from the last to the first, so earlier positions stay valid
+fn replace_ranges(content: &str, mut ranges: Vec<EditorRange>) -> Result<String, String> {
+    ranges.sort_by_key(|range| std::cmp::Reverse((range.start.line, range.start.character)));
+    
+    let mut updated = content.to_string();
+    let mut previous_start = None;
+    for range in ranges {
+        let start = offset(&updated, range.start).ok_or_else(|| format!("range start line {} is past the end", range.start.line))?;
+        let end = offset(&updated, range.end).ok_or_else(|| format!("range end line {} is past the end", range.end.line))?;
+        if end < start || previous_start.is_some_and(|previous| end > previous) {
+            return Err(format!("invalid or overlapping range at line {}", range.start.line));
+        }
+        updated.replace_range(start..end, &range.text);
+        previous_start = Some(start);
+    }
+    
+    Ok(updated)
+}
+
+// Byte offset of a position; the line just past the last one is the end of the file
+fn offset(content: &str, position: Position) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match content[line_start..].find('\n') {
+            Some(newline) => line_start += newline + 1,
+            None if line_start < content.len() => line_start = content.len(),
+            None => return None,
+        }
+    }
+    
+    let line = &content[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let character = line.char_indices().nth(position.character).map(|(index, _)| index).unwrap_or(line.len());
+    Some(line_start + character)
+}
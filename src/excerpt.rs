@@ -0,0 +1,248 @@
+// src/excerpt.rs
+use std::io;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::FileEntry;
+use crate::{paths, symbols};
+
+// Line placed between two excerpts of the same file
+pub const SEPARATOR: &str = "...";
+
+// A `file:line[:column]` argument, the form editors copy cursor positions in,
+// or a `file::symbol` argument naming items to include
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub path: PathBuf,
+    pub target: Target,
+}
+
+#[derive(Debug, Clone)]
+pub enum Target {
+    Line(usize),
+    // `name` or `Type::method`
+    Symbol(String),
+}
+
+// Replaces reference arguments with the file they point into and returns
+// the references, so the file is collected like any other path
+pub fn split_references(paths: &mut Vec<String>) -> Vec<Reference> {
+    let line_regex = Regex::new(r"^(.+?):(\d+)(?::\d+)?:?$").unwrap();
+    let mut references = Vec::new();
+    let mut resolved: Vec<String> = Vec::new();
+    
+    for path in paths.drain(..) {
+        let reference = if Path::new(&path).exists() {
+            None
+        } else {
+            parse_symbol_reference(&path).or_else(|| {
+                line_regex.captures(&path).and_then(|captures| {
+                    let file = captures[1].to_string();
+                    let line = captures[2].parse::<usize>().ok()?;
+                    (Path::new(&file).is_file() && line > 0).then_some((file, Target::Line(line)))
+                })
+            })
+        };
+        
+        match reference {
+            Some((file, target)) => {
+                let canonical = paths::canonicalize(&file).unwrap_or_else(|_| PathBuf::from(&file));
+                references.push(Reference { path: canonical, target });
+                if !resolved.contains(&file) {
+                    resolved.push(file);
+                }
+            }
+            None => resolved.push(path),
+        }
+    }
+    
+    *paths = resolved;
+    references
+}
+
+// The file is the part before the first `::` that names an existing file
+fn parse_symbol_reference(arg: &str) -> Option<(String, Target)> {
+    arg.match_indices("::").find_map(|(index, _)| {
+        let (file, symbol) = (&arg[..index], &arg[index + 2..]);
+        (Path::new(file).is_file() && !symbol.is_empty()).then(|| (file.to_string(), Target::Symbol(symbol.to_string())))
+    })
+}
+
+// Cuts files named by references down to the referenced items
+pub fn apply(files: &mut [FileEntry], references: &[Reference]) -> io::Result<()> {
+    if references.is_empty() {
+        return Ok(());
+    }
+    
+    for file in files.iter_mut() {
+        let source_path = paths::canonicalize(&file.source_path).unwrap_or_else(|_| file.source_path.clone());
+        let targets: Vec<&Target> = references
+            .iter()
+            .filter(|reference| reference.path == source_path)
+            .map(|reference| &reference.target)
+            .collect();
+        let lines: Vec<&str> = file.content.lines().collect();
+        if targets.is_empty() || lines.is_empty() {
+            continue;
+        }
+        
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for target in targets {
+            match target {
+                Target::Line(line) => {
+                    let line = (*line).min(lines.len());
+                    let range = symbols::enclosing_range(&file.source_path, &file.content, line)
+                        .unwrap_or_else(|| enclosing_block(&lines, line));
+                    ranges.push(range);
+                }
+                Target::Symbol(symbol) => {
+                    // Languages without a tree-sitter grammar fall back to indentation
+                    let found = symbols::symbol_ranges(&file.source_path, &file.content, symbol)
+                        .unwrap_or_else(|| symbol_blocks(&lines, symbol));
+                    if found.is_empty() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("No item named '{}' found in {}", symbol, file.path),
+                        ));
+                    }
+                    ranges.extend(found);
+                }
+            }
+        }
+        
+        // Several references into the same item produce it once
+        ranges.sort();
+        let mut merged: Vec<(usize, usize)> = Vec::new();
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        
+        file.content = merged
+            .iter()
+            .map(|&(start, end)| lines[start - 1..end].join("\n"))
+            .collect::<Vec<_>>()
+            .join(&format!("\n{}\n", SEPARATOR));
+        file.hash = crate::content_hash(&file.content);
+        file.line_ranges = merged;
+    }
+    
+    Ok(())
+}
+
+// Without a parser, a definition is a line declaring `symbol` (`def name`,
+// `class Name`, `function name`, `name = ...`) and the more deeply indented
+// lines after it; `Class::method` looks for the method inside the class
+fn symbol_blocks(lines: &[&str], symbol: &str) -> Vec<(usize, usize)> {
+    let (owner, name) = match symbol.rsplit_once("::") {
+        Some((owner, name)) => (Some(owner.rsplit("::").next().unwrap_or(owner)), name),
+        None => (None, symbol),
+    };
+    let within = match owner {
+        Some(owner) => match declarations(lines, owner, (1, lines.len())).first() {
+            Some(&line) => (line + 1, indented_block(lines, line).1),
+            None => return Vec::new(),
+        },
+        None => (1, lines.len()),
+    };
+    declarations(lines, name, within).into_iter().map(|line| indented_block(lines, line)).collect()
+}
+
+// Numbers of the lines between `within` that declare `name`
+fn declarations(lines: &[&str], name: &str, within: (usize, usize)) -> Vec<usize> {
+    let declaration = Regex::new(&format!(
+        r"^\s*(?:(?:export|default|public|private|protected|static|async|abstract|final|pub|fn|def|class|function|func|interface|struct|enum|type|trait|const|let|var|val|fun|object|module)\s+)*(?:\*\s*)?{}\b\s*(?:[(<:{{=]|extends\b|implements\b|$)",
+        regex::escape(name)
+    ))
+    .unwrap();
+    (within.0..=within.1).filter(|&number| declaration.is_match(lines[number - 1])).collect()
+}
+
+// A line and the lines after it that are indented more deeply, plus the
+// comments and decorators directly above it and a closing `}` or `end` line
+// at its own indentation
+fn indented_block(lines: &[&str], line: usize) -> (usize, usize) {
+    let indent = |text: &str| text.len() - text.trim_start().len();
+    let level = indent(lines[line - 1]);
+    let is_closing = |text: &str| {
+        let text = text.trim();
+        text.starts_with('}') || text.starts_with(')') || text.starts_with(']') || text == "end"
+    };
+    let start = preamble_start(lines, line);
+    
+    let mut end = line;
+    while end < lines.len() {
+        let next = lines[end];
+        if !next.trim().is_empty() && indent(next) <= level {
+            if indent(next) == level && is_closing(next) {
+                end += 1;
+            }
+            break;
+        }
+        end += 1;
+    }
+    while end > line && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    
+    (start, end)
+}
+
+// Without a parser, the enclosing item is the block from the nearest
+// unindented line above `line` up to the next one, plus the comments and
+// decorators directly above it and a closing `}` or `end` line below it
+fn enclosing_block(lines: &[&str], line: usize) -> (usize, usize) {
+    let is_top_level = |text: &str| !text.trim().is_empty() && !text.starts_with(char::is_whitespace);
+    let is_closing = |text: &str| {
+        let text = text.trim();
+        text.starts_with('}') || text.starts_with(')') || text.starts_with(']') || text == "end"
+    };
+    let opens_item = |text: &str| is_top_level(text) && !is_closing(text);
+    
+    let mut start = line;
+    while start > 1 && !opens_item(lines[start - 1]) {
+        start -= 1;
+    }
+    start = preamble_start(lines, start);
+    
+    let mut end = line;
+    if opens_item(lines[line - 1]) || !is_top_level(lines[line - 1]) {
+        while end < lines.len() {
+            let next = lines[end];
+            if is_top_level(next) {
+                if is_closing(next) {
+                    end += 1;
+                }
+                break;
+            }
+            end += 1;
+        }
+    }
+    while end > line && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    
+    (start, end)
+}
+
+// First line of the comments and decorators directly above `line` at its indentation
+pub fn preamble_start(lines: &[&str], line: usize) -> usize {
+    let indent = |text: &str| text.len() - text.trim_start().len();
+    let level = indent(lines[line - 1]);
+    let mut start = line;
+    while start > 1 {
+        let previous = lines[start - 2];
+        let text = previous.trim_start();
+        let is_comment = indent(previous) == level && ["//", "#", "@", "/*", "--"].iter().any(|prefix| text.starts_with(prefix));
+        // The ` * ` lines of a `/** ... */` block
+        let is_continuation = indent(previous) == level + 1 && text.starts_with('*');
+        if !is_comment && !is_continuation {
+            break;
+        }
+        start -= 1;
+    }
+    start
+}
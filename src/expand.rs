@@ -0,0 +1,162 @@
+// src/expand.rs
+use clap::ValueEnum;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::{content_hash, paths, FileEntry};
+
+// Whether --expand replaces file contents or is added next to them
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Mode {
+    Replace,
+    Alongside,
+}
+
+// The macro-expanded code of one module
+pub struct Expansion {
+    pub source_path: PathBuf,
+    pub path: String,
+    pub command: String,
+    pub code: String,
+}
+
+// Which target of the package a source file belongs to, and the module path
+// inside it (empty for the crate root)
+struct Module {
+    package_dir: PathBuf,
+    target: Vec<String>,
+    path: Vec<String>,
+}
+
+// Runs `cargo expand` for the module defined in `path`
+pub fn expand(path: &Path) -> io::Result<Expansion> {
+    let source_path = paths::canonicalize(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("Cannot expand {}: {}", path.display(), e)))?;
+    let module = module_of(&source_path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a Rust source file inside a Cargo package", path.display()),
+        )
+    })?;
+    
+    let mut args = module.target.clone();
+    if !module.path.is_empty() {
+        args.push(module.path.join("::"));
+    }
+    let command = format!("cargo expand {}", args.join(" "));
+    let output = Command::new("cargo")
+        .arg("expand")
+        .args(&args)
+        .current_dir(&module.package_dir)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| io::Error::other(format!("Failed to run {}: {}", command, e)))?;
+    
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("no such command") {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "--expand needs cargo-expand; install it with `cargo install cargo-expand`",
+        ));
+    }
+    let code = String::from_utf8_lossy(&output.stdout).trim_end().to_string();
+    if !output.status.success() || code.is_empty() {
+        // The last lines hold the compiler's error rather than cargo's progress
+        let lines: Vec<&str> = stderr.trim_end().lines().collect();
+        let tail = lines[lines.len().saturating_sub(20)..].join("\n");
+        return Err(io::Error::other(format!("{} failed:\n{}", command, tail)));
+    }
+    
+    Ok(Expansion { source_path, path: path.to_string_lossy().replace('\\', "/"), command, code })
+}
+
+// Maps src/lib.rs, src/main.rs, src/bin/<name>.rs, tests/<name>.rs and
+// examples/<name>.rs to their targets, and files below them to modules
+fn module_of(source_path: &Path) -> Option<Module> {
+    if source_path.extension()? != "rs" {
+        return None;
+    }
+    let package_dir = source_path.ancestors().skip(1).find(|dir| dir.join("Cargo.toml").is_file())?.to_path_buf();
+    let relative = source_path.strip_prefix(&package_dir).ok()?;
+    let mut components: Vec<String> = relative
+        .with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    // foo/mod.rs defines the module foo
+    if components.len() > 2 && components.last().is_some_and(|last| last == "mod") {
+        components.pop();
+    }
+    
+    let target = |kind: &str, name: &str| vec![format!("--{}", kind), name.to_string()];
+    let (target, path) = match components.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["src", "lib"] => (vec!["--lib".to_string()], Vec::new()),
+        ["src", "main"] => (target("bin", &package_name(&package_dir)?), Vec::new()),
+        ["src", "bin", name] | ["src", "bin", name, "main"] => (target("bin", name), Vec::new()),
+        ["src", "bin", name, rest @ ..] => (target("bin", name), rest.iter().map(|s| s.to_string()).collect()),
+        ["tests", name] | ["tests", name, "main"] => (target("test", name), Vec::new()),
+        ["examples", name] | ["examples", name, "main"] => (target("example", name), Vec::new()),
+        ["src", rest @ ..] => {
+            let path: Vec<String> = rest.iter().map(|s| s.to_string()).collect();
+            // Modules of a package with both a library and a binary belong to
+            // the one whose root declares them
+            let declared_in_lib = fs::read_to_string(package_dir.join("src").join("lib.rs"))
+                .is_ok_and(|lib| declares_module(&lib, &path[0]));
+            if declared_in_lib || !package_dir.join("src").join("main.rs").is_file() {
+                (vec!["--lib".to_string()], path)
+            } else {
+                (target("bin", &package_name(&package_dir)?), path)
+            }
+        }
+        _ => return None,
+    };
+    Some(Module { package_dir, target, path })
+}
+
+fn declares_module(root: &str, name: &str) -> bool {
+    root.lines().any(|line| {
+        let line = line.trim_start().trim_start_matches("pub ").trim_start_matches("pub(crate) ");
+        line.strip_prefix("mod ")
+            .is_some_and(|rest| rest.trim_start().trim_end_matches([';', '{', ' ']) == name)
+    })
+}
+
+fn package_name(package_dir: &Path) -> Option<String> {
+    let manifest: toml::Value = toml::from_str(&fs::read_to_string(package_dir.join("Cargo.toml")).ok()?).ok()?;
+    manifest.get("package")?.get("name")?.as_str().map(str::to_string)
+}
+
+// Swaps the contents of expanded files in the selection for their expansion,
+// returning the expansions of files that are not in it
+pub fn replace(files: &mut [FileEntry], expansions: Vec<Expansion>) -> Vec<Expansion> {
+    let mut unmatched = Vec::new();
+    for expansion in expansions {
+        let file = files
+            .iter_mut()
+            .find(|file| paths::canonicalize(&file.source_path).is_ok_and(|path| path == expansion.source_path));
+        match file {
+            Some(file) => {
+                file.content = expansion.code;
+                file.content.push('\n');
+                file.hash = content_hash(&file.content);
+                file.line_ranges.clear();
+            }
+            None => unmatched.push(expansion),
+        }
+    }
+    unmatched
+}
+
+pub fn format_expanded_section(expansions: &[Expansion]) -> String {
+    let mut section = String::from("<expanded_code>\n");
+    for expansion in expansions {
+        section.push_str(&format!(
+            "<file path=\"{}\" command=\"{}\">\n{}\n</file>\n",
+            expansion.path, expansion.command, expansion.code
+        ));
+    }
+    section.push_str("</expanded_code>\n\n");
+    section
+}
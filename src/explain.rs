@@ -0,0 +1,245 @@
+// src/explain.rs
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::summary::SkipReason;
+use crate::{config, handlers, hooks, path_spec, paths, presets, sensitive, test_code, Options};
+
+// Ignore files in the order the walk gives them precedence within a directory
+const IGNORE_FILES: &[&str] = &[crate::IGNORE_FILE, ".ignore", ".gitignore"];
+
+// `explain` subcommand: the first path is the file to explain, the others are
+// the paths that would be copied (the current directory by default)
+pub fn run(mut options: Options) -> io::Result<()> {
+    options.configure_logging();
+    if options.paths.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Name the file to explain"));
+    }
+    let target = options.paths.remove(0);
+    if options.paths.is_empty() {
+        options.paths.push(".".to_string());
+    }
+    let target_path = Path::new(&target);
+    if !target_path.is_file() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("{} is not a file", target)));
+    }
+    let absolute = paths::canonicalize(target_path)?;
+    let config = config::load(&options.paths, options.config_path.as_deref())?;
+    
+    // The copied path the file would be found through
+    let root = options.paths.iter().find_map(|root| {
+        let canonical = paths::canonicalize(root).ok()?;
+        absolute.starts_with(&canonical).then_some(canonical)
+    });
+    let root = match root {
+        Some(root) => root,
+        None => {
+            println!("{}: excluded", target);
+            println!("  not inside any of the copied paths ({})", options.paths.join(", "));
+            return Ok(());
+        }
+    };
+    
+    // Files given directly are not subject to the directory walk's rules
+    if root != absolute {
+        if let Some(reason) = walk_exclusion(&absolute, &root, &options, &config.hidden.allow) {
+            println!("{}: excluded", target);
+            println!("  {}", reason);
+            return Ok(());
+        }
+    }
+    
+    let handlers = handlers::Handlers::from_config(&config.handlers)?;
+    let hooks = hooks::Hooks::load(&config.hooks)?;
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    let base = if root.is_dir() { Some(root.as_path()) } else { None };
+    crate::process_file(&absolute, base, &mut files, &mut skipped, &options, &handlers, hooks.as_ref())?;
+    
+    if let Some(file) = skipped.first() {
+        println!("{}: excluded", target);
+        println!("  {}", skip_reason(file.reason, &absolute, &file.path, &options));
+        return Ok(());
+    }
+    
+    let preset = match options.preset {
+        Some(ref name) => Some(presets::load(name, &config)?),
+        None => None,
+    };
+    if let Some(ref preset) = preset {
+        if files.first().is_some_and(|file| preset.skips(&file.path)) {
+            println!("{}: excluded", target);
+            println!("  matches a skip rule of the {} preset", preset.name);
+            return Ok(());
+        }
+    }
+    
+    let sensitive = sensitive::SensitivePaths::from_config(&config.sensitive)?.matching(&files);
+    if !sensitive.is_empty() && !options.force {
+        println!("{}: excluded", target);
+        println!("  matches the sensitive path list; copying it fails unless --force is passed");
+        return Ok(());
+    }
+    
+    println!("{}: included", target);
+    if let Some(file) = files.first() {
+        println!("  as {} ({} lines)", file.path, file.content.lines().count());
+        if let Some(ref preset) = preset {
+            if preset.is_important(&file.path) {
+                println!("  a key file of the {} preset, kept first with --max-tokens", preset.name);
+            }
+        }
+    }
+    // Steps that look at the whole selection can still drop it
+    let mut later = Vec::new();
+    if options.max_tokens.is_some() {
+        later.push("--max-tokens when the selection does not fit");
+    }
+    if options.tests_only {
+        later.push("--tests-only unless it is a test or referenced by one");
+    }
+    if options.contracts_only {
+        later.push("--contracts-only unless it is a .proto file or an OpenAPI/Swagger spec");
+    }
+    if options.against.is_some() || options.output_format == "diff" {
+        later.push("--format diff unless it changed");
+    }
+    for step in later {
+        println!("  may still be dropped by {}", step);
+    }
+    Ok(())
+}
+
+// Why the directory walk would not reach the file, checking each path
+// component below the copied directory like the walk does
+fn walk_exclusion(absolute: &Path, root: &Path, options: &Options, hidden_allow: &[String]) -> Option<String> {
+    let relative = absolute.strip_prefix(root).ok()?;
+    let depth = relative.components().count();
+    if options.max_depth.is_some_and(|max_depth| depth > max_depth) {
+        return Some(format!("at depth {}, deeper than --max-depth {}", depth, options.max_depth.unwrap_or(0)));
+    }
+    if depth < options.min_depth.unwrap_or(0) {
+        return Some(format!("at depth {}, above --min-depth {}", depth, options.min_depth.unwrap_or(0)));
+    }
+    
+    let matchers = ignore_matchers(absolute);
+    let mut prefix = PathBuf::new();
+    for (index, component) in relative.components().enumerate() {
+        prefix.push(component);
+        let is_dir = index + 1 < depth;
+        let relative_prefix = paths::normalize(&prefix);
+        let shown = if is_dir { format!("{}/", relative_prefix) } else { relative_prefix.clone() };
+        let name = component.as_os_str().to_string_lossy();
+        
+        if let Some(rule) = ignore_rule(&matchers, &root.join(&prefix), is_dir) {
+            return Some(rule);
+        }
+        
+        // Allowlisted hidden paths are exempt from the default exclusions too
+        match crate::hidden_allowed(&relative_prefix, is_dir, hidden_allow) {
+            Some(true) => continue,
+            Some(false) if !options.hidden => {
+                return Some(format!(
+                    "{} is hidden; pass --hidden or allow it in the [hidden] table of the config",
+                    shown
+                ));
+            }
+            _ => {}
+        }
+        if is_dir && crate::is_excluded_dir_name(&name) {
+            return Some(format!("{} is a directory that is always skipped", shown));
+        }
+        let is_test = if is_dir { test_code::is_test_dir(&name) } else { test_code::is_test_file(&root.join(&prefix)) };
+        if options.no_tests && is_test {
+            return Some(format!("{} is test code, skipped with --no-tests", shown));
+        }
+    }
+    
+    if !options.no_path_spec {
+        if let Ok(Some(spec)) = path_spec::PathSpec::find(root) {
+            if !spec.includes(absolute) {
+                return Some(match spec.deciding_pattern(absolute) {
+                    Some(pattern) => format!("{}: excluded by the pattern {}", spec.path.display(), pattern),
+                    None => format!("not selected by any pattern in {}", spec.path.display()),
+                });
+            }
+        }
+    }
+    None
+}
+
+// The ignore files that apply to the file, highest precedence first: those in
+// deeper directories before those above, then .git/info/exclude and the
+// global excludes file. .gitignore files only count inside a git repository
+fn ignore_matchers(absolute: &Path) -> Vec<Gitignore> {
+    let repository = absolute.ancestors().find(|dir| dir.join(".git").exists());
+    let mut matchers = Vec::new();
+    for dir in absolute.ancestors().skip(1) {
+        for name in IGNORE_FILES {
+            if *name == ".gitignore" && repository.is_none() {
+                continue;
+            }
+            let path = dir.join(name);
+            if path.is_file() {
+                matchers.push(Gitignore::new(&path).0);
+            }
+        }
+    }
+    if let Some(repository) = repository {
+        // Both hold patterns relative to the repository root
+        let exclude = repository.join(".git").join("info").join("exclude");
+        if exclude.is_file() {
+            let mut builder = GitignoreBuilder::new(repository);
+            builder.add(&exclude);
+            if let Ok(matcher) = builder.build() {
+                matchers.push(matcher);
+            }
+        }
+        matchers.push(GitignoreBuilder::new(repository).build_global().0);
+    }
+    matchers
+}
+
+// `file:line:pattern` of the rule ignoring the path, like `git check-ignore -v`
+fn ignore_rule(matchers: &[Gitignore], path: &Path, is_dir: bool) -> Option<String> {
+    for matcher in matchers {
+        match matcher.matched(path, is_dir) {
+            Match::None => continue,
+            Match::Whitelist(_) => return None,
+            Match::Ignore(glob) => {
+                let from = glob.from().map(Path::to_path_buf).unwrap_or_default();
+                let line = fs::read_to_string(&from)
+                    .ok()
+                    .and_then(|text| text.lines().position(|line| line.trim_end() == glob.original()))
+                    .map(|index| (index + 1).to_string())
+                    .unwrap_or_else(|| "?".to_string());
+                return Some(format!("{}:{}:{}", from.display(), line, glob.original()));
+            }
+        }
+    }
+    None
+}
+
+fn skip_reason(reason: SkipReason, path: &Path, display_path: &str, options: &Options) -> String {
+    match reason {
+        SkipReason::Binary => match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if crate::has_excluded_extension(ext) => format!("binary: .{} files are always skipped", ext),
+            _ => "binary: the content is not text".to_string(),
+        },
+        SkipReason::TooLarge => format!(
+            "{} bytes, over the {} byte limit; pass --truncate-large to include it truncated",
+            fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0),
+            crate::MAX_FILE_SIZE
+        ),
+        SkipReason::Handler => format!("{} matches a skip rule in the [handlers] table of the config", display_path),
+        SkipReason::Hook => "the hook script's should_include returned false".to_string(),
+        SkipReason::ReadError => "it could not be read".to_string(),
+        SkipReason::Generated if !options.include_generated => {
+            "it looks generated; pass --include-generated to copy it".to_string()
+        }
+        other => other.label().to_string(),
+    }
+}
@@ -103,7 +103,13 @@ export function activate(context: vscode.ExtensionContext) {
                     console.log(`Executing command: ${command}`);
                     
                     exec(command, (error, stdout, stderr) => {
-                        if (error) {
+                        // Exit code 2: nothing matched, 3: clipboard unavailable (output saved to a file)
+                        if (error && error.code === 2) {
+                            vscode.window.showWarningMessage('No files matched the selection.');
+                            resolve();
+                            return;
+                        }
+                        if (error && error.code !== 3) {
                             vscode.window.showErrorMessage(`Error copying files: ${error.message}`);
                             console.error(`Error: ${error.message}`);
                             console.error(`Stderr: ${stderr}`);
@@ -113,18 +119,32 @@ export function activate(context: vscode.ExtensionContext) {
                         
                         progress.report({ increment: 100, message: 'Complete!' });
                         
-                        // Get the number of files processed from stdout if possible
-                        const filesMatch = stdout.match(/Files processed: (\d+)/);
-                        const sizeMatch = stdout.match(/Total size: (\d+) characters/);
-                        const typeMatch = stdout.match(/Project type: (\w+)/);
+                        // Get the file count, size and project type from the summary if possible
+                        const filesMatch = stdout.match(/Files included:\s+(\d+)/);
+                        const sizeMatch = stdout.match(/Output size:\s+(\d+) bytes/);
+                        const typeMatch = stdout.match(/Project type:\s+(\w+)/);
                         
                         const filesCount = filesMatch ? filesMatch[1] : 'multiple';
-                        const charCount = sizeMatch ? sizeMatch[1] : 'unknown';
+                        const byteCount = sizeMatch ? sizeMatch[1] : 'unknown';
                         const projectType = typeMatch ? typeMatch[1] : 'unknown';
                         
-                        vscode.window.showInformationMessage(
-                            `Files copied to clipboard! (${filesCount} files, ${charCount} characters, ${projectType} project)`
-                        );
+                        // The binary falls back to a temporary file when the clipboard rejects the output
+                        const fileMatch = stdout.match(/Output written to file: (.+)/);
+                        if (fileMatch) {
+                            const outputPath = fileMatch[1].trim();
+                            vscode.window.showWarningMessage(
+                                `Clipboard unavailable, output written to ${outputPath} (${filesCount} files, ${byteCount} bytes)`,
+                                'Open File'
+                            ).then(choice => {
+                                if (choice === 'Open File') {
+                                    vscode.window.showTextDocument(vscode.Uri.file(outputPath));
+                                }
+                            });
+                        } else {
+                            vscode.window.showInformationMessage(
+                                `Files copied to clipboard! (${filesCount} files, ${byteCount} bytes, ${projectType} project)`
+                            );
+                        }
                         
                         resolve();
                     });
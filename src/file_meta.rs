@@ -0,0 +1,136 @@
+// src/file_meta.rs
+use chrono::{DateTime, Local};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::languages::format_size;
+use crate::{paths, FileEntry};
+
+// Size, modification date and last commit of a copied file, for --file-metadata
+#[derive(Debug, Clone)]
+pub struct FileMetadata {
+    pub size: u64,
+    pub modified: Option<String>,
+    // Abbreviated hash and commit date
+    pub last_commit: Option<String>,
+}
+
+impl FileMetadata {
+    // The attributes added to the <file> tag
+    pub fn attributes(&self) -> String {
+        let mut attributes = format!(" size=\"{}\"", format_size(self.size as usize));
+        if let Some(ref modified) = self.modified {
+            attributes.push_str(&format!(" modified=\"{}\"", modified));
+        }
+        if let Some(ref last_commit) = self.last_commit {
+            attributes.push_str(&format!(" last_commit=\"{}\"", last_commit));
+        }
+        attributes
+    }
+}
+
+pub fn collect(files: &mut [FileEntry]) {
+    let last_commits = last_commits(files);
+    for file in files.iter_mut() {
+        let metadata = match fs::metadata(&file.source_path) {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .map(|time| DateTime::<Local>::from(time).format("%Y-%m-%d").to_string());
+        file.metadata = Some(FileMetadata {
+            size: metadata.len(),
+            modified,
+            last_commit: last_commits.get(&file.source_path).cloned(),
+        });
+    }
+}
+
+// The newest commit touching each tracked file, keyed by source path. Files
+// are grouped by repository, and each repository's log is read only until
+// all of its files were seen
+fn last_commits(files: &[FileEntry]) -> HashMap<PathBuf, String> {
+    let mut toplevels: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+    let mut repositories: HashMap<PathBuf, HashMap<String, PathBuf>> = HashMap::new();
+    for file in files {
+        let absolute = match paths::canonicalize(&file.source_path) {
+            Ok(absolute) => absolute,
+            Err(_) => continue,
+        };
+        let dir = match absolute.parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => continue,
+        };
+        let toplevel = toplevels
+            .entry(dir.clone())
+            .or_insert_with(|| {
+                git(&dir, &["rev-parse", "--show-toplevel"]).and_then(|top| paths::canonicalize(top.trim()).ok())
+            })
+            .clone();
+        if let Some(toplevel) = toplevel {
+            if let Ok(relative) = absolute.strip_prefix(&toplevel) {
+                let relative = paths::normalize(relative);
+                repositories.entry(toplevel).or_default().insert(relative, file.source_path.clone());
+            }
+        }
+    }
+    
+    let mut commits = HashMap::new();
+    for (toplevel, mut wanted) in repositories {
+        // Untracked files would keep the log running to the first commit
+        let tracked: HashSet<String> = match git(&toplevel, &["ls-files", "-z"]) {
+            Some(listing) => listing.split('\0').map(str::to_string).collect(),
+            None => continue,
+        };
+        wanted.retain(|path, _| tracked.contains(path));
+        if wanted.is_empty() {
+            continue;
+        }
+        
+        let mut child = match Command::new("git")
+            .arg("-C")
+            .arg(&toplevel)
+            .args(["-c", "core.quotepath=off", "log", "--no-renames", "--name-only", "--format=%x01%h %cs"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(_) => return commits,
+        };
+        if let Some(stdout) = child.stdout.take() {
+            let mut current = String::new();
+            for line in BufReader::new(stdout).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if let Some(commit) = line.strip_prefix('\u{1}') {
+                    current = commit.to_string();
+                } else if let Some(source_path) = wanted.remove(&line) {
+                    commits.insert(source_path, current.clone());
+                    if wanted.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    commits
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).stderr(Stdio::null()).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,87 @@
+// src/file_reader.rs
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+// Bytes inspected for NUL bytes before the rest of a file is read
+const SNIFF_SIZE: usize = 8 * 1024;
+
+// Files at least this large are memory-mapped instead of read into a buffer
+const MMAP_THRESHOLD: u64 = 256 * 1024;
+
+// Bytes of file contents the daemon keeps in memory
+const MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+// Contents kept between requests by the daemon, reused while the file's
+// modification time and size are unchanged
+type MemoryCache = crate::cache::MemoryCache<PathBuf, (SystemTime, u64, FileContent)>;
+static MEMORY: Mutex<Option<MemoryCache>> = Mutex::new(None);
+
+#[derive(Clone)]
+pub enum FileContent {
+    Text(String),
+    Binary,
+}
+
+pub fn keep_in_memory() {
+    *MEMORY.lock().unwrap() = Some(MemoryCache::new(MEMORY_LIMIT));
+}
+
+// Reads a file as UTF-8 text, stopping after the first chunk when it looks binary
+pub fn read(path: &Path) -> io::Result<FileContent> {
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let len = metadata.len();
+    
+    let mut memory = MEMORY.lock().unwrap();
+    let (memory, modified) = match (memory.as_mut(), metadata.modified()) {
+        (Some(memory), Ok(modified)) => (memory, modified),
+        _ => return read_file(file, len),
+    };
+    match memory.get(&path.to_path_buf()) {
+        Some((cached_modified, cached_len, content)) if cached_modified == modified && cached_len == len => {
+            return Ok(content);
+        }
+        // A changed file's old content is dropped even if it can no longer be read
+        Some(_) => memory.remove(&path.to_path_buf()),
+        None => {}
+    }
+    let content = read_file(file, len)?;
+    memory.insert(path.to_path_buf(), (modified, len, content.clone()), len as usize);
+    Ok(content)
+}
+
+fn read_file(mut file: File, len: u64) -> io::Result<FileContent> {
+    if len >= MMAP_THRESHOLD {
+        // SAFETY: the map is only read here and dropped before returning; a
+        // file truncated by another process meanwhile fails the read with SIGBUS
+        // at worst, the same risk every mmap-based tool accepts
+        let map = unsafe { Mmap::map(&file)? };
+        if is_binary(&map[..SNIFF_SIZE.min(map.len())]) {
+            return Ok(FileContent::Binary);
+        }
+        let text = std::str::from_utf8(&map).map_err(invalid_utf8)?;
+        return Ok(FileContent::Text(text.to_owned()));
+    }
+    
+    // Read the first chunk on its own, then the rest into the same buffer
+    let mut buffer = Vec::with_capacity(len as usize);
+    (&mut file).take(SNIFF_SIZE as u64).read_to_end(&mut buffer)?;
+    if is_binary(&buffer) {
+        return Ok(FileContent::Binary);
+    }
+    file.read_to_end(&mut buffer)?;
+    String::from_utf8(buffer).map(FileContent::Text).map_err(|e| invalid_utf8(e.utf8_error()))
+}
+
+// Text files practically never contain NUL bytes, while most binary formats do
+fn is_binary(chunk: &[u8]) -> bool {
+    chunk.contains(&0)
+}
+
+fn invalid_utf8(error: std::str::Utf8Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("not valid UTF-8 ({})", error))
+}
@@ -0,0 +1,85 @@
+// src/formatter.rs
+use crate::{chunks, editor, git_diff, html, markdown, ContextSections, FileEntry, FormatOptions};
+
+// Everything a formatter gets to render the collected files
+pub struct RenderContext<'a> {
+    pub files: &'a [FileEntry],
+    pub sections: &'a ContextSections,
+    pub options: &'a FormatOptions,
+    // Name of the project, used as a heading by formats that have one
+    pub title: &'a str,
+    // Anonymization, applied to every text that ends up in the output; the
+    // files, sections and title are already redacted
+    pub post_process: &'a dyn Fn(&str) -> String,
+}
+
+// An output format selectable with --format. Formats are built into the
+// binary, which has no library target to implement this trait from another
+// crate: a new one implements it in its own module and is added to
+// FORMATTERS below
+pub trait Formatter: Sync {
+    fn name(&self) -> &'static str;
+    
+    // Whether the output is the XML project block, which prompt templates and
+    // front matter are built around
+    fn sectioned(&self) -> bool {
+        false
+    }
+    
+    // Whether the output can be wrapped in a prompt template with --prompt,
+    // --question, --task or --reply-format
+    fn prompts(&self) -> bool {
+        self.sectioned()
+    }
+    
+    // Whether the output can start with a YAML front matter block
+    fn front_matter(&self) -> bool {
+        self.sectioned()
+    }
+    
+    // Whether the files are shown as their changes against a git ref, which
+    // --against and --with-new-files choose
+    fn compares_to_ref(&self) -> bool {
+        false
+    }
+    
+    // Whether the files can be written one at a time instead of rendering the
+    // whole output in memory
+    fn streams(&self) -> bool {
+        false
+    }
+    
+    fn render(&self, context: &RenderContext) -> String;
+}
+
+// The default <project> block
+struct Xml;
+
+impl Formatter for Xml {
+    fn name(&self) -> &'static str {
+        "xml"
+    }
+    
+    fn sectioned(&self) -> bool {
+        true
+    }
+    
+    fn streams(&self) -> bool {
+        true
+    }
+    
+    fn render(&self, context: &RenderContext) -> String {
+        (context.post_process)(&context.sections.to_project_block())
+    }
+}
+
+// In the order --help lists them; the first one is the default
+static FORMATTERS: &[&dyn Formatter] = &[&Xml, &markdown::Markdown, &html::Html, &editor::EditorJson, &chunks::Jsonl, &git_diff::Diff];
+
+pub fn by_name(name: &str) -> Option<&'static dyn Formatter> {
+    FORMATTERS.iter().copied().find(|formatter| formatter.name() == name)
+}
+
+pub fn names() -> Vec<&'static str> {
+    FORMATTERS.iter().map(|formatter| formatter.name()).collect()
+}
@@ -0,0 +1,36 @@
+// src/front_matter.rs
+use std::path::Path;
+use std::process::Command;
+
+// YAML front matter for --frontmatter, which prompt managers index contexts by
+pub fn render(project: &str, commit: Option<&str>, files: usize, tokens: usize) -> String {
+    let mut block = String::from("---\n");
+    block.push_str(&format!("project: {}\n", quote(project)));
+    if let Some(commit) = commit {
+        block.push_str(&format!("commit: {}\n", commit));
+    }
+    block.push_str(&format!("date: {}\n", chrono::Local::now().to_rfc3339()));
+    block.push_str(&format!("files: {}\n", files));
+    block.push_str(&format!("tokens: {}\n", tokens));
+    block.push_str("---\n\n");
+    block
+}
+
+// HEAD of the repository containing the project, if it is one
+pub fn git_commit(project_root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_root)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!commit.is_empty()).then_some(commit)
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,25 @@
+// src/generated.rs
+
+// Only the top of a file is scanned, where generators put their headers
+// (after a license comment at most)
+const HEADER_LINES: usize = 10;
+
+// Markers written by protoc, OpenAPI generators, bindgen, go generate and
+// friends, compared in lowercase
+const MARKERS: &[&str] = &[
+    "do not edit",
+    "@generated",
+    "automatically generated",
+    "auto-generated",
+    "autogenerated",
+    "code generated by",
+    "generated by the protocol buffer compiler",
+];
+
+// Whether the file's header says it was produced by a code generator
+pub fn is_generated(content: &str) -> bool {
+    content.lines().take(HEADER_LINES).any(|line| {
+        let line = line.to_lowercase();
+        MARKERS.iter().any(|marker| line.contains(marker))
+    })
+}
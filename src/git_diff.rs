@@ -0,0 +1,163 @@
+// src/git_diff.rs
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::formatter::{Formatter, RenderContext};
+use crate::{iac, paths, FileEntry};
+
+// --format diff: the XML project block with the files section replaced by
+// the diff, which generate_output puts there
+pub struct Diff;
+
+impl Formatter for Diff {
+    fn name(&self) -> &'static str {
+        "diff"
+    }
+    
+    fn sectioned(&self) -> bool {
+        true
+    }
+    
+    fn compares_to_ref(&self) -> bool {
+        true
+    }
+    
+    fn render(&self, context: &RenderContext) -> String {
+        (context.post_process)(&context.sections.to_project_block())
+    }
+}
+
+// The selected files' changes against a git ref for --format diff
+pub struct WorkingTreeDiff {
+    pub diff: String,
+    // Display paths of the files in the diff
+    pub changed: HashSet<String>,
+    // Display paths of files added since the ref or untracked, kept out of the
+    // diff with --with-new-files
+    pub new_files: HashSet<String>,
+    // Selected untracked files, which `git diff` does not show
+    pub untracked: usize,
+}
+
+// Runs `git diff <against>` in the directory each file's display path is
+// relative to, so the paths in the diff match the rest of the output; the
+// values in tfvars files are redacted unless `redact_tfvars` is off
+pub fn collect(files: &[FileEntry], against: &str, with_new_files: bool, redact_tfvars: bool) -> io::Result<WorkingTreeDiff> {
+    let mut groups: BTreeMap<PathBuf, HashMap<String, String>> = BTreeMap::new();
+    for file in files {
+        let (base, relative) = split_base(file)?;
+        groups.entry(base).or_default().insert(relative, file.path.clone());
+    }
+    
+    let mut result = WorkingTreeDiff {
+        diff: String::new(),
+        changed: HashSet::new(),
+        new_files: HashSet::new(),
+        untracked: 0,
+    };
+    for (base, selected) in &groups {
+        if paths::repo_root(base).is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("--format diff requires a git repository, but {} is not in one", base.display()),
+            ));
+        }
+        if git(base, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", against)]).is_err() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown git ref '{}'", against)));
+        }
+        
+        // `--relative` limits both to the base directory; the entries of the
+        // name list and the sections of the diff come in the same order
+        let statuses = git(base, &["diff", "--name-status", "-z", "--no-renames", "--relative", against, "--"])?;
+        let patch = git(base, &["diff", "--no-color", "--no-ext-diff", "--no-renames", "--relative", against, "--"])?;
+        let mut fields = statuses.split('\0');
+        for section in split_sections(&patch) {
+            let (status, path) = match (fields.next(), fields.next()) {
+                (Some(status), Some(path)) => (status, path),
+                _ => break,
+            };
+            let display = match selected.get(path) {
+                Some(display) => display,
+                None => continue,
+            };
+            if with_new_files && status == "A" {
+                result.new_files.insert(display.clone());
+            } else if redact_tfvars && iac::is_tfvars(Path::new(path)) {
+                result.diff.push_str(&iac::redact_tfvars_diff(section));
+                result.changed.insert(display.clone());
+            } else {
+                result.diff.push_str(section);
+                result.changed.insert(display.clone());
+            }
+        }
+        
+        let untracked = git(base, &["ls-files", "-z", "--others", "--exclude-standard"])?;
+        for path in untracked.split('\0').filter(|path| !path.is_empty()) {
+            if let Some(display) = selected.get(path) {
+                if with_new_files {
+                    result.new_files.insert(display.clone());
+                } else {
+                    result.untracked += 1;
+                }
+            }
+        }
+    }
+    
+    Ok(result)
+}
+
+// The directory a file's display path is relative to, and the path itself
+fn split_base(file: &FileEntry) -> io::Result<(PathBuf, String)> {
+    let source = paths::canonicalize(&file.source_path)?;
+    let display = Path::new(&file.path);
+    if source.ends_with(display) {
+        let depth = display.components().count();
+        if let Some(base) = source.ancestors().nth(depth) {
+            return Ok((base.to_path_buf(), file.path.clone()));
+        }
+    }
+    
+    let base = source.parent().unwrap_or(Path::new("/")).to_path_buf();
+    let name = source.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    Ok((base, name))
+}
+
+// Splits a diff at its `diff --git` lines, one section per file
+fn split_sections(patch: &str) -> Vec<&str> {
+    let mut starts: Vec<usize> = Vec::new();
+    let mut offset = 0;
+    for line in patch.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    starts.push(patch.len());
+    starts.windows(2).map(|range| &patch[range[0]..range[1]]).collect()
+}
+
+fn git(dir: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| io::Error::other(format!("Failed to run git (is it installed?): {}", e)))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn format_diff_section(diff: &WorkingTreeDiff, against: &str) -> String {
+    if diff.diff.is_empty() {
+        return format!("<diff against=\"{}\"/>\n", against);
+    }
+    format!("<diff against=\"{}\">\n{}</diff>\n", against, diff.diff)
+}
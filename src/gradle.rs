@@ -0,0 +1,280 @@
+// src/gradle.rs
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+// Kotlin Multiplatform target declarations in a `kotlin { }` block
+const KMP_TARGETS: &[&str] = &[
+    "androidTarget", "jvm", "js", "wasmJs", "wasmWasi",
+    "iosX64", "iosArm64", "iosSimulatorArm64",
+    "macosX64", "macosArm64",
+    "watchosArm32", "watchosArm64", "watchosX64", "watchosSimulatorArm64", "watchosDeviceArm64",
+    "tvosArm64", "tvosX64", "tvosSimulatorArm64",
+    "linuxX64", "linuxArm64", "mingwX64",
+    "androidNativeArm32", "androidNativeArm64", "androidNativeX86", "androidNativeX64",
+];
+
+// Dependency configurations listed in the summary
+const CONFIGURATIONS: &[&str] = &[
+    "implementation", "api", "compileOnly", "runtimeOnly",
+    "testImplementation", "androidTestImplementation", "debugImplementation",
+    "ksp", "kapt", "coreLibraryDesugaring",
+];
+
+// A build script of the project and the module it belongs to
+struct Module {
+    name: String,
+    script: String,
+}
+
+// Summary of the Gradle project using Kotlin whose settings script is at or
+// above `start_dir`: its modules, multiplatform targets and dependencies
+pub fn find_kotlin_info(start_dir: &Path) -> Option<String> {
+    let root = start_dir
+        .ancestors()
+        .find(|dir| dir.join("settings.gradle.kts").is_file() || dir.join("settings.gradle").is_file())?;
+    let settings = read_script(root, "settings.gradle")?;
+    let modules = modules(root, &settings);
+    if !modules.iter().any(|module| uses_kotlin(&module.script)) {
+        return None;
+    }
+    let catalog = VersionCatalog::load(&root.join("gradle").join("libs.versions.toml"));
+    
+    let multiplatform = modules.iter().any(|module| is_multiplatform(&module.script));
+    let mut info = format!(
+        "Project Type: {} (Gradle)\n",
+        if multiplatform { "Kotlin Multiplatform" } else { "Kotlin" }
+    );
+    if let Some(name) = first_capture(r#"rootProject\.name\s*=\s*["']([^"']+)["']"#, &settings) {
+        info.push_str(&format!("Project Name: {}\n", name));
+    }
+    if let Some(version) = kotlin_version(&modules, &catalog) {
+        info.push_str(&format!("Kotlin Version: {}\n", version));
+    }
+    
+    for module in &modules {
+        let targets = targets(&module.script);
+        let android = android_settings(&module.script, &catalog);
+        let dependencies = dependencies(&module.script, &catalog);
+        if targets.is_empty() && android.is_empty() && dependencies.is_empty() {
+            continue;
+        }
+        
+        info.push_str(&format!("\nModule {}:\n", module.name));
+        if !targets.is_empty() {
+            info.push_str(&format!("Targets: {}\n", targets.join(", ")));
+        }
+        if !android.is_empty() {
+            info.push_str(&format!("Android: {}\n", android.join(", ")));
+        }
+        for (group, entries) in dependencies {
+            info.push_str(&format!("Dependencies ({}):\n", group));
+            for entry in entries {
+                info.push_str(&format!("- {}\n", entry));
+            }
+        }
+    }
+    
+    Some(info)
+}
+
+// The root project and the modules `include`d by the settings script
+fn modules(root: &Path, settings: &str) -> Vec<Module> {
+    let mut modules = Vec::new();
+    if let Some(script) = read_script(root, "build.gradle") {
+        modules.push(Module { name: ":".to_string(), script });
+    }
+    let include = Regex::new(r#"include\s*\(?([^)\n]*)"#).expect("valid regex");
+    let name = Regex::new(r#"["']:?([^"']+)["']"#).expect("valid regex");
+    for line in include.captures_iter(settings) {
+        for module in name.captures_iter(&line[1]) {
+            let dir: PathBuf = module[1].split(':').collect();
+            if let Some(script) = read_script(&root.join(dir), "build.gradle") {
+                modules.push(Module { name: format!(":{}", &module[1]), script });
+            }
+        }
+    }
+    modules
+}
+
+// `<name>.kts` or the Groovy `<name>` in `dir`
+fn read_script(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(format!("{}.kts", name)))
+        .or_else(|_| fs::read_to_string(dir.join(name)))
+        .ok()
+}
+
+fn uses_kotlin(script: &str) -> bool {
+    script.contains("kotlin(") || script.contains("org.jetbrains.kotlin") || script.contains("plugins.kotlin")
+}
+
+fn is_multiplatform(script: &str) -> bool {
+    script.contains("kotlin(\"multiplatform\")")
+        || script.contains("org.jetbrains.kotlin.multiplatform")
+        || Regex::new(r"plugins\.kotlin[.\-]?[Mm]ultiplatform").is_ok_and(|regex| regex.is_match(script))
+}
+
+fn kotlin_version(modules: &[Module], catalog: &VersionCatalog) -> Option<String> {
+    modules
+        .iter()
+        .find_map(|module| first_capture(r#"kotlin\("[\w.]+"\)\s+version\s+"([^"]+)""#, &module.script))
+        .or_else(|| catalog.versions.get("kotlin").cloned())
+}
+
+// Multiplatform targets, in the order they are declared
+fn targets(script: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let call = Regex::new(r"\b(\w+)\s*(?:\(|\{)").expect("valid regex");
+    for caps in call.captures_iter(script) {
+        let name = &caps[1];
+        if KMP_TARGETS.contains(&name) && !targets.iter().any(|target| target == name) {
+            targets.push(name.to_string());
+        }
+    }
+    targets
+}
+
+// Android namespace and SDK levels, which may come from the version catalog
+fn android_settings(script: &str, catalog: &VersionCatalog) -> Vec<String> {
+    ["namespace", "compileSdk", "minSdk", "targetSdk"]
+        .iter()
+        .filter_map(|key| {
+            let value = first_capture(&format!(r#"\b{}\b\s*=?\s*["']?([\w.]+)["']?"#, key), script)?;
+            let value = match value.strip_prefix("libs.versions.") {
+                Some(alias) => catalog.version(alias.trim_end_matches(".get"))?,
+                None => value,
+            };
+            Some(format!("{} {}", key, value))
+        })
+        .collect()
+}
+
+// Declared dependencies grouped by source set (`commonMain`, `iosTest`, ...)
+// or, outside multiplatform source sets, by configuration
+fn dependencies(script: &str, catalog: &VersionCatalog) -> BTreeMap<String, Vec<String>> {
+    let declaration = Regex::new(&format!(r#"^\s*({})\s*[( ]\s*(.+?)\)?\s*$"#, CONFIGURATIONS.join("|"))).expect("valid regex");
+    let source_set = Regex::new(r"\b(\w+(?:Main|Test))\b").expect("valid regex");
+    
+    // Headers of the blocks enclosing the current line, innermost last
+    let mut blocks: Vec<String> = Vec::new();
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for line in script.lines() {
+        let code = line.split("//").next().unwrap_or("");
+        if let Some(caps) = declaration.captures(code) {
+            let group = blocks
+                .iter()
+                .rev()
+                .find_map(|header| source_set.captures(header).map(|caps| caps[1].to_string()))
+                .unwrap_or_else(|| caps[1].to_string());
+            if let Some(dependency) = notation(&caps[2], catalog) {
+                groups.entry(group).or_default().push(dependency);
+            }
+        }
+        for (index, c) in code.char_indices() {
+            match c {
+                '{' => blocks.push(code[..index].trim().to_string()),
+                '}' => {
+                    blocks.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    groups
+}
+
+// "io.ktor:ktor-client-core:2.3.7", libs.ktor.client.core or project(":shared")
+fn notation(argument: &str, catalog: &VersionCatalog) -> Option<String> {
+    let argument = argument.trim().trim_end_matches(')');
+    if let Some(quoted) = first_capture(r#"^(?:platform\()?["']([^"']+)["']"#, argument) {
+        return Some(quoted);
+    }
+    if let Some(project) = first_capture(r#"project\(\s*["']([^"']+)["']"#, argument) {
+        return Some(format!("project {}", project));
+    }
+    let reference = argument.trim_start_matches("platform(").trim_end_matches(')');
+    if let Some(alias) = reference.strip_prefix("libs.") {
+        return Some(catalog.library(alias).unwrap_or_else(|| reference.to_string()));
+    }
+    if reference.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '_') && !reference.is_empty() {
+        return Some(reference.to_string());
+    }
+    None
+}
+
+// gradle/libs.versions.toml, for resolving `libs.*` references
+#[derive(Default)]
+struct VersionCatalog {
+    versions: BTreeMap<String, String>,
+    libraries: BTreeMap<String, String>,
+}
+
+impl VersionCatalog {
+    fn load(path: &Path) -> VersionCatalog {
+        let catalog = match fs::read_to_string(path).ok().and_then(|text| text.parse::<Value>().ok()) {
+            Some(catalog) => catalog,
+            None => return VersionCatalog::default(),
+        };
+        let versions: BTreeMap<String, String> = catalog
+            .get("versions")
+            .and_then(Value::as_table)
+            .map(|table| {
+                table.iter().filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string()))).collect()
+            })
+            .unwrap_or_default();
+        let libraries = catalog
+            .get("libraries")
+            .and_then(Value::as_table)
+            .map(|table| {
+                table
+                    .iter()
+                    .filter_map(|(key, value)| Some((normalize_alias(key), library_coordinates(value, &versions)?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        VersionCatalog { versions, libraries }
+    }
+    
+    // `ktor.client.core` for the `ktor-client-core` entry
+    fn library(&self, alias: &str) -> Option<String> {
+        self.libraries.get(&normalize_alias(alias)).cloned()
+    }
+    
+    fn version(&self, alias: &str) -> Option<String> {
+        let alias = normalize_alias(alias);
+        self.versions.iter().find(|(key, _)| normalize_alias(key) == alias).map(|(_, version)| version.clone())
+    }
+}
+
+// Catalog aliases may use `-`, `_` or `.` as separators; references use `.`
+fn normalize_alias(alias: &str) -> String {
+    alias.replace(['-', '_'], ".").to_lowercase()
+}
+
+// "group:name:version" from a string entry or a `{ module, version.ref }` table
+fn library_coordinates(value: &Value, versions: &BTreeMap<String, String>) -> Option<String> {
+    if let Some(notation) = value.as_str() {
+        return Some(notation.to_string());
+    }
+    let table = value.as_table()?;
+    let module = match table.get("module").and_then(Value::as_str) {
+        Some(module) => module.to_string(),
+        None => format!("{}:{}", table.get("group")?.as_str()?, table.get("name")?.as_str()?),
+    };
+    let version = match table.get("version") {
+        Some(Value::String(version)) => Some(version.clone()),
+        Some(Value::Table(version)) => version.get("ref").and_then(Value::as_str).and_then(|key| versions.get(key).cloned()),
+        _ => None,
+    };
+    Some(match version {
+        Some(version) => format!("{}:{}", module, version),
+        None => module,
+    })
+}
+
+fn first_capture(pattern: &str, text: &str) -> Option<String> {
+    let regex = Regex::new(&format!("(?m){}", pattern)).ok()?;
+    regex.captures(text).map(|caps| caps[1].to_string())
+}
@@ -0,0 +1,152 @@
+// src/handlers.rs
+use globset::{Glob, GlobMatcher};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::config::HandlerConfig;
+
+// Number of leading lines kept by the summarize handler
+const SUMMARY_LINES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Handler {
+    Skip,
+    Truncate(usize),
+    Summarize,
+    Command(String),
+}
+
+struct Rule {
+    pattern: String,
+    matcher: GlobMatcher,
+    handler: Handler,
+}
+
+// The compiled [handlers] table
+#[derive(Default)]
+pub struct Handlers {
+    rules: Vec<Rule>,
+}
+
+impl Handlers {
+    pub fn from_config(config: &HashMap<String, HandlerConfig>) -> io::Result<Handlers> {
+        let mut rules = Vec::new();
+        
+        for (pattern, handler_config) in config {
+            let handler = match handler_config {
+                HandlerConfig::Action(action) => match action.as_str() {
+                    "skip" => Handler::Skip,
+                    "summarize" => Handler::Summarize,
+                    other => {
+                        return Err(invalid_data(format!(
+                            "Unknown handler '{}' for '{}'. Expected skip, summarize, {{ truncate = N }} or {{ command = \"...\" }}",
+                            other, pattern
+                        )));
+                    }
+                },
+                HandlerConfig::Truncate { truncate } => Handler::Truncate(*truncate),
+                HandlerConfig::Command { command } => Handler::Command(command.clone()),
+            };
+            
+            // A bare extension like ".ipynb" matches every file with that extension
+            let glob = if pattern.starts_with('.') && !pattern[1..].contains(['.', '*', '?', '[', '/']) {
+                format!("*{}", pattern)
+            } else {
+                pattern.clone()
+            };
+            let matcher = Glob::new(&glob)
+                .map_err(|e| invalid_data(format!("Invalid handler pattern '{}': {}", pattern, e)))?
+                .compile_matcher();
+            
+            rules.push(Rule {
+                pattern: pattern.clone(),
+                matcher,
+                handler,
+            });
+        }
+        
+        // The most specific (longest) pattern wins when several match
+        rules.sort_by(|a, b| b.pattern.len().cmp(&a.pattern.len()).then_with(|| a.pattern.cmp(&b.pattern)));
+        
+        Ok(Handlers { rules })
+    }
+    
+    // Matches against the displayed relative path as well as the bare file name
+    pub fn find(&self, relative_path: &str, file_path: &Path) -> Option<&Handler> {
+        let file_name = file_path.file_name().map(|name| name.to_string_lossy().into_owned());
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.matcher.is_match(relative_path)
+                    || file_name.as_deref().is_some_and(|name| rule.matcher.is_match(name))
+            })
+            .map(|rule| &rule.handler)
+    }
+}
+
+// Pipes the file through a shell command and returns its stdout; `{path}` in
+// the command stands for the file path
+pub fn run_command(command: &str, file_path: &Path) -> io::Result<String> {
+    let input = std::fs::read(file_path)?;
+    
+    let mut child = if cfg!(windows) {
+        // Windows file names cannot contain the double quote that ends the argument
+        let command_line = command.replace("\"{path}\"", "{path}").replace("{path}", &format!("\"{}\"", file_path.display()));
+        Command::new("cmd").args(["/C", &command_line]).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?
+    } else {
+        // The path is passed as $1 rather than spliced into the script, so
+        // spaces, quotes, `;` or `$()` in file names are never interpreted
+        let command_line = command.replace("\"{path}\"", "{path}").replace("'{path}'", "{path}").replace("{path}", "\"$1\"");
+        Command::new("sh")
+            .arg("-c")
+            .arg(&command_line)
+            .arg("sh")
+            .arg(file_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?
+    };
+    
+    // Commands that read the file by path may exit without consuming stdin
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(&input);
+    }
+    
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!("Handler command '{}' failed with {}", command, output.status)));
+    }
+    
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn truncate(content: &str, max_lines: usize) -> String {
+    let total = content.lines().count();
+    if total <= max_lines {
+        return content.to_string();
+    }
+    
+    let mut truncated: String = content.lines().take(max_lines).map(|line| format!("{}\n", line)).collect();
+    truncated.push_str(&format!("... ({} more lines truncated)\n", total - max_lines));
+    truncated
+}
+
+// A size overview followed by the first lines of the file
+pub fn summarize(content: &str) -> String {
+    let total = content.lines().count();
+    let mut summary = format!("[Summary: {} lines, {} bytes]\n", total, content.len());
+    for line in content.lines().take(SUMMARY_LINES) {
+        summary.push_str(line);
+        summary.push('\n');
+    }
+    if total > SUMMARY_LINES {
+        summary.push_str("...\n");
+    }
+    summary
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
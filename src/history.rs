@@ -0,0 +1,274 @@
+// src/history.rs
+use chrono::Local;
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::clipboard;
+use crate::GeneratedOutput;
+
+#[derive(Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub timestamp: String,
+    pub invocation: Vec<String>,
+    pub cwd: String,
+    pub files: Vec<HistoryFile>,
+    pub context: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HistoryFile {
+    pub path: String,
+    pub content: String,
+}
+
+pub fn history_dir() -> io::Result<PathBuf> {
+    dirs::data_dir()
+        .map(|dir| dir.join("codecopier").join("history"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not determine the data directory"))
+}
+
+// Stores a generated context and returns the id it was saved under
+pub fn save(invocation: &[String], output: &GeneratedOutput) -> io::Result<String> {
+    let dir = history_dir()?;
+    fs::create_dir_all(&dir)?;
+    
+    let now = Local::now();
+    let base_id = now.format("%Y%m%d-%H%M%S").to_string();
+    
+    // Several copies within the same second get a numeric suffix
+    let mut id = base_id.clone();
+    let mut suffix = 2;
+    while dir.join(format!("{}.json", id)).exists() {
+        id = format!("{}-{}", base_id, suffix);
+        suffix += 1;
+    }
+    
+    let entry = HistoryEntry {
+        id: id.clone(),
+        timestamp: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+        invocation: invocation.to_vec(),
+        cwd: env::current_dir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        files: output
+            .files
+            .iter()
+            .map(|file| HistoryFile {
+                path: file.path.clone(),
+                content: file.content.clone(),
+            })
+            .collect(),
+        context: output.text().to_string(),
+    };
+    
+    let json = serde_json::to_string(&entry).map_err(io::Error::other)?;
+    fs::write(dir.join(format!("{}.json", id)), json)?;
+    
+    Ok(id)
+}
+
+// Returns the ids of all saved entries, oldest first
+fn list_ids() -> io::Result<Vec<String>> {
+    let dir = history_dir()?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    
+    let mut ids: Vec<String> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".json").map(|id| id.to_string())
+        })
+        .collect();
+    ids.sort();
+    
+    Ok(ids)
+}
+
+pub fn load(id: &str) -> io::Result<HistoryEntry> {
+    let id = resolve_id(id)?;
+    let content = fs::read_to_string(history_dir()?.join(format!("{}.json", id)))?;
+    serde_json::from_str(&content).map_err(|e| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("Corrupt history entry {}: {}", id, e))
+    })
+}
+
+// Accepts a full id, a unique prefix of one, or "latest"
+fn resolve_id(id: &str) -> io::Result<String> {
+    let ids = list_ids()?;
+    
+    if id == "latest" || id == "last" {
+        return ids
+            .last()
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "History is empty"));
+    }
+    
+    if ids.iter().any(|existing| existing == id) {
+        return Ok(id.to_string());
+    }
+    
+    let matches: Vec<&String> = ids.iter().filter(|existing| existing.starts_with(id)).collect();
+    match matches.len() {
+        1 => Ok(matches[0].clone()),
+        0 => Err(io::Error::new(io::ErrorKind::NotFound, format!("No history entry '{}'", id))),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("History id '{}' is ambiguous ({} matches)", id, matches.len()),
+        )),
+    }
+}
+
+// `history` subcommands
+#[derive(Subcommand)]
+pub enum HistoryCommand {
+    /// List saved contexts
+    List,
+    /// Print a saved context
+    Show { id: String },
+    /// Copy a saved context to the clipboard again
+    Recopy { id: String },
+    /// Show what changed between two saved contexts
+    Diff {
+        old: String,
+        new: String,
+        /// Copy the diff to the clipboard instead of printing it
+        #[arg(long)]
+        copy: bool,
+    },
+}
+
+pub fn run(command: &HistoryCommand) -> io::Result<()> {
+    match command {
+        HistoryCommand::List => list(),
+        HistoryCommand::Show { id } => {
+            print!("{}", load(id)?.context);
+            Ok(())
+        }
+        HistoryCommand::Recopy { id } => recopy(id),
+        HistoryCommand::Diff { old, new, copy } => diff(old, new, *copy),
+    }
+}
+
+fn list() -> io::Result<()> {
+    let ids = list_ids()?;
+    if ids.is_empty() {
+        println!("No saved contexts. Use --save-history to record them.");
+        return Ok(());
+    }
+    
+    for id in ids {
+        match load(&id) {
+            Ok(entry) => {
+                println!(
+                    "{:<20} {}  {:>4} files  {:>9} chars  {}",
+                    entry.id,
+                    entry.timestamp,
+                    entry.files.len(),
+                    entry.context.len(),
+                    format_invocation(&entry.invocation)
+                );
+            }
+            Err(e) => eprintln!("{:<20} (unreadable: {})", id, e),
+        }
+    }
+    
+    Ok(())
+}
+
+fn recopy(id: &str) -> io::Result<()> {
+    let entry = load(id)?;
+    match clipboard::copy(&entry.context)? {
+        clipboard::CopyOutcome::Clipboard => println!("Context {} copied to clipboard!", entry.id),
+        clipboard::CopyOutcome::TempFile(path) => println!("Output written to file: {}", path.display()),
+    }
+    println!("Total size: {} characters", entry.context.len());
+    Ok(())
+}
+
+// Per-file unified diff of the project between two saved contexts
+fn diff(old_id: &str, new_id: &str, copy: bool) -> io::Result<()> {
+    let old = load(old_id)?;
+    let new = load(new_id)?;
+    
+    let old_files: BTreeMap<&str, &str> = old.files.iter().map(|f| (f.path.as_str(), f.content.as_str())).collect();
+    let new_files: BTreeMap<&str, &str> = new.files.iter().map(|f| (f.path.as_str(), f.content.as_str())).collect();
+    
+    let mut paths: Vec<&str> = old_files.keys().chain(new_files.keys()).copied().collect();
+    paths.sort();
+    paths.dedup();
+    
+    let mut output = String::new();
+    let (mut changed, mut added, mut removed) = (0, 0, 0);
+    
+    for path in paths {
+        let (old_content, new_content, old_label, new_label) = match (old_files.get(path), new_files.get(path)) {
+            (Some(before), Some(after)) => {
+                if before == after {
+                    continue;
+                }
+                changed += 1;
+                (*before, *after, format!("a/{}", path), format!("b/{}", path))
+            }
+            (Some(before), None) => {
+                removed += 1;
+                (*before, "", format!("a/{}", path), "/dev/null".to_string())
+            }
+            (None, Some(after)) => {
+                added += 1;
+                ("", *after, "/dev/null".to_string(), format!("b/{}", path))
+            }
+            (None, None) => continue,
+        };
+        
+        output.push_str(&format!("diff a/{} b/{}\n", path, path));
+        output.push_str(
+            &TextDiff::from_lines(old_content, new_content)
+                .unified_diff()
+                .context_radius(3)
+                .header(&old_label, &new_label)
+                .to_string(),
+        );
+    }
+    
+    let summary = format!(
+        "{} -> {}: {} changed, {} added, {} removed",
+        old.id, new.id, changed, added, removed
+    );
+    
+    if copy {
+        match clipboard::copy(&output)? {
+            clipboard::CopyOutcome::Clipboard => println!("Diff copied to clipboard!"),
+            clipboard::CopyOutcome::TempFile(path) => println!("Output written to file: {}", path.display()),
+        }
+        println!("{}", summary);
+    } else {
+        print!("{}", output);
+        eprintln!("{}", summary);
+    }
+    
+    Ok(())
+}
+
+fn format_invocation(invocation: &[String]) -> String {
+    invocation
+        .iter()
+        .skip(1)
+        .map(|arg| {
+            if arg.contains(' ') {
+                format!("\"{}\"", arg)
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
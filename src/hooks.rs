@@ -0,0 +1,77 @@
+// src/hooks.rs
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use std::io;
+use std::path::Path;
+
+use crate::config::HooksConfig;
+
+// A loaded hook script; either callback may be missing
+pub struct Hooks {
+    engine: Engine,
+    ast: AST,
+    has_should_include: bool,
+    has_transform: bool,
+}
+
+impl Hooks {
+    pub fn load(config: &HooksConfig) -> io::Result<Option<Hooks>> {
+        let script = match config.script {
+            Some(ref script) => script,
+            None => return Ok(None),
+        };
+        
+        let engine = Engine::new();
+        let ast = engine.compile_file(script.clone()).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to load hook script {}: {}", script.display(), e),
+            )
+        })?;
+        
+        let defines = |name: &str| ast.iter_functions().any(|f| f.name == name && f.params.len() == 2);
+        let has_should_include = defines("should_include");
+        let has_transform = defines("transform");
+        
+        Ok(Some(Hooks {
+            engine,
+            ast,
+            has_should_include,
+            has_transform,
+        }))
+    }
+    
+    // Called before a file is read; metadata holds `name`, `extension` and `size`
+    pub fn should_include(&self, path: &str, file_path: &Path) -> io::Result<bool> {
+        if !self.has_should_include {
+            return Ok(true);
+        }
+        
+        let mut metadata = Map::new();
+        let name = file_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let extension = file_path.extension().map(|e| e.to_string_lossy().into_owned()).unwrap_or_default();
+        let size = std::fs::metadata(file_path).map(|m| m.len()).unwrap_or(0);
+        metadata.insert("name".into(), Dynamic::from(name));
+        metadata.insert("extension".into(), Dynamic::from(extension));
+        metadata.insert("size".into(), Dynamic::from(size as i64));
+        
+        self.engine
+            .call_fn::<bool>(&mut Scope::new(), &self.ast, "should_include", (path.to_string(), metadata))
+            .map_err(|e| hook_error("should_include", path, e))
+    }
+    
+    // Called with the content of every included file; returns the replacement content
+    pub fn transform(&self, path: &str, content: String) -> io::Result<String> {
+        if !self.has_transform {
+            return Ok(content);
+        }
+        
+        self.engine
+            .call_fn::<String>(&mut Scope::new(), &self.ast, "transform", (path.to_string(), content))
+            .map_err(|e| hook_error("transform", path, e))
+    }
+}
+
+// Hook failures abort the run so rules like masking are never silently skipped
+fn hook_error(hook: &str, path: &str, error: Box<rhai::EvalAltResult>) -> io::Error {
+    io::Error::other(format!("Hook {} failed for {}: {}", hook, path, error))
+}
@@ -0,0 +1,195 @@
+// src/html.rs
+use clap::ValueEnum;
+use std::collections::HashMap;
+
+use syntect::highlighting::ThemeSet;
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::SyntaxSet;
+
+use crate::formatter::{Formatter, RenderContext};
+use crate::tree::TreeNode;
+use crate::{ContextSections, FileEntry, FormatOptions};
+
+const THEME: &str = "InspiredGitHub";
+
+// HTML flavor placed on the clipboard with --clipboard-html
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ClipboardStyle {
+    // The whole output in a single <pre> block
+    Pre,
+    // File contents syntax-highlighted, the rest in <pre> blocks
+    Highlight,
+}
+
+const STYLE: &str = "body { margin: 0; display: flex; font-family: -apple-system, 'Segoe UI', sans-serif; font-size: 14px; }
+nav { width: 280px; flex-shrink: 0; height: 100vh; overflow: auto; position: sticky; top: 0; padding: 12px; box-sizing: border-box; background: #f6f8fa; border-right: 1px solid #d0d7de; }
+nav ul { list-style: none; margin: 0; padding-left: 14px; }
+nav > ul { padding-left: 0; }
+nav summary { cursor: pointer; font-weight: 600; }
+nav a { color: #0969da; text-decoration: none; }
+nav a:hover { text-decoration: underline; }
+main { flex-grow: 1; min-width: 0; padding: 16px 24px; }
+section { margin-bottom: 24px; }
+h2 { font-size: 15px; font-family: monospace; padding: 6px 10px; margin: 0; background: #f6f8fa; border: 1px solid #d0d7de; border-bottom: none; }
+pre { margin: 0; padding: 10px; overflow: auto; border: 1px solid #d0d7de; font-size: 13px; }
+.note { color: #57606a; font-style: italic; }";
+
+// --format html
+pub struct Html;
+
+impl Formatter for Html {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+    
+    fn render(&self, context: &RenderContext) -> String {
+        (context.post_process)(&render(context.files, context.sections, context.options, context.title))
+    }
+}
+
+// Renders a self-contained HTML page with a collapsible file tree and highlighted sources
+fn render(files: &[FileEntry], sections: &ContextSections, options: &FormatOptions, title: &str) -> String {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+    let theme = &theme_set.themes[THEME];
+    
+    let tree = TreeNode::build(files.iter().map(|file| file.path.as_str()));
+    
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n", escape(title), STYLE));
+    
+    html.push_str("<nav>\n");
+    render_tree(&tree, &mut html);
+    html.push_str("</nav>\n<main>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+    
+    // The header, project metadata and any extra sections are shown as plain text
+    for text in [&sections.header, &sections.project_info, &sections.extra] {
+        if !text.trim().is_empty() {
+            html.push_str(&format!("<section>\n<pre>{}</pre>\n</section>\n", escape(text.trim_end())));
+        }
+    }
+    
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for (index, file) in files.iter().enumerate() {
+        html.push_str(&format!("<section id=\"file-{}\">\n<h2>{}</h2>\n", index, escape(&file.path)));
+        
+        if options.dedupe && !file.content.trim().is_empty() {
+            if let Some(&original) = seen.get(file.hash.as_str()) {
+                html.push_str(&format!(
+                    "<pre class=\"note\">Same content as <a href=\"#file-{}\">{}</a></pre>\n</section>\n",
+                    original,
+                    escape(&files[original].path)
+                ));
+                continue;
+            }
+            seen.insert(&file.hash, index);
+        }
+        
+        let extension = file.path.rsplit('.').next().unwrap_or("");
+        let syntax = syntax_set
+            .find_syntax_by_extension(extension)
+            .or_else(|| syntax_set.find_syntax_by_first_line(&file.content))
+            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+        match highlighted_html_for_string(&file.content, &syntax_set, syntax, theme) {
+            Ok(highlighted) => html.push_str(&highlighted),
+            Err(_) => html.push_str(&format!("<pre>{}</pre>\n", escape(&file.content))),
+        }
+        html.push_str("</section>\n");
+    }
+    
+    html.push_str("</main>\n</body>\n</html>\n");
+    html
+}
+
+// Wraps the output for the clipboard's HTML flavor, so that chat UIs which
+// reflow pasted text keep the indentation and line breaks
+pub fn clipboard_fragment(text: &str, style: ClipboardStyle) -> String {
+    const PRE: &str = "<pre style=\"font-family: monospace; white-space: pre;\">";
+    if style == ClipboardStyle::Pre {
+        return format!("{}{}</pre>", PRE, escape(text));
+    }
+    
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+    let theme = &theme_set.themes[THEME];
+    
+    let mut html = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("<file path=\"") {
+        let open_end = match rest[start..].find('\n') {
+            Some(end) => start + end + 1,
+            None => break,
+        };
+        // Deduplicated files are a self-closing reference with no content
+        if rest[start..open_end].trim_end().ends_with("/>") {
+            html.push_str(&format!("{}{}</pre>", PRE, escape(&rest[..open_end])));
+            rest = &rest[open_end..];
+            continue;
+        }
+        let close = match rest[open_end..].find("\n</file>") {
+            Some(close) => open_end + close,
+            None => break,
+        };
+        
+        html.push_str(&format!("{}{}</pre>", PRE, escape(&rest[..open_end])));
+        let tag = &rest[start..open_end];
+        let path = tag.trim_start_matches("<file path=\"").split('"').next().unwrap_or("");
+        let content = &rest[open_end..close];
+        let extension = path.rsplit('.').next().unwrap_or("");
+        let syntax = syntax_set
+            .find_syntax_by_extension(extension)
+            .or_else(|| syntax_set.find_syntax_by_first_line(content))
+            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+        match highlighted_html_for_string(content, &syntax_set, syntax, theme) {
+            Ok(highlighted) => html.push_str(&highlighted),
+            Err(_) => html.push_str(&format!("{}{}</pre>", PRE, escape(content))),
+        }
+        rest = &rest[close + 1..];
+    }
+    html.push_str(&format!("{}{}</pre>", PRE, escape(rest)));
+    html
+}
+
+fn render_tree(node: &TreeNode, html: &mut String) {
+    html.push_str("<ul>\n");
+    for (name, child) in &node.dirs {
+        html.push_str(&format!("<li><details open><summary>{}/</summary>\n", escape(name)));
+        render_tree(child, html);
+        html.push_str("</details></li>\n");
+    }
+    for (name, index) in &node.files {
+        html.push_str(&format!("<li><a href=\"#file-{}\">{}</a></li>\n", index, escape(name)));
+    }
+    html.push_str("</ul>\n");
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn clipboard_fragment_keeps_deduplicated_files_apart() {
+        let text = concat!(
+            "<project>\n",
+            "<file path=\"a.rs\">\nfn a() {}\n</file>\n\n",
+            "<file path=\"b.rs\" same-as=\"a.rs\"/>\n\n",
+            "<file path=\"c.py\">\ndef c():\n    pass\n</file>\n\n",
+            "</project>",
+        );
+        let html = clipboard_fragment(text, ClipboardStyle::Highlight);
+        
+        // Each tag stays in the plain text around the highlighted contents
+        assert!(html.contains(&escape("<file path=\"b.rs\" same-as=\"a.rs\"/>\n")));
+        assert!(html.contains(&format!("{}</pre>", escape("<file path=\"c.py\">\n"))));
+        assert_eq!(html.matches("<pre style=\"background-color").count(), 2);
+    }
+}
@@ -0,0 +1,342 @@
+// src/iac.rs
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Terraform variable files, whose values are redacted unless --no-redact-tfvars is set
+const TFVARS_SUFFIXES: &[&str] = &[".tfvars", ".tfvars.json"];
+
+// Placeholder for redacted tfvars values
+const REDACTED: &str = "[REDACTED]";
+
+// Summary of the Terraform, Pulumi and CloudFormation code directly in `dir`;
+// infrastructure directories are recognized by their own files rather than
+// by a project file further up
+pub fn find_info(dir: &Path) -> Option<String> {
+    let files = match fs::read_dir(dir) {
+        Ok(entries) => {
+            let mut files: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+            files.sort();
+            files
+        }
+        Err(_) => return None,
+    };
+    
+    let sections: Vec<String> = [terraform_info(dir, &files), pulumi_info(dir, &files), cloudformation_info(&files)]
+        .into_iter()
+        .flatten()
+        .collect();
+    if sections.is_empty() {
+        None
+    } else {
+        Some(sections.join("\n"))
+    }
+}
+
+fn terraform_info(dir: &Path, files: &[PathBuf]) -> Option<String> {
+    let sources: Vec<String> = files
+        .iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tf"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .collect();
+    if sources.is_empty() {
+        return None;
+    }
+    let source = sources.join("\n");
+    let mut info = String::from("Project Type: Terraform\n");
+    
+    if let Some(version) = first_capture(r#"required_version\s*=\s*"([^"]+)""#, &source) {
+        info.push_str(&format!("Terraform Version: {}\n", version));
+    }
+    if let Some(backend) = first_capture(r#"backend\s+"([^"]+)""#, &source) {
+        info.push_str(&format!("Backend: {}\n", backend));
+    }
+    
+    // Declared constraints, with the versions locked in .terraform.lock.hcl
+    let locked = fs::read_to_string(dir.join(".terraform.lock.hcl"))
+        .map(|lock| {
+            captures(r#"provider\s+"(?:[^"/]+/)?([^"]+)"\s*\{[^}]*?version\s*=\s*"([^"]+)""#, &lock)
+                .into_iter()
+                .map(|caps| (caps[0].clone(), caps[1].clone()))
+                .collect::<BTreeMap<String, String>>()
+        })
+        .unwrap_or_default();
+    let mut providers: BTreeMap<String, String> = BTreeMap::new();
+    if let Some(block) = block(&source, "required_providers") {
+        for caps in captures(r#"(?s)([\w-]+)\s*=\s*\{([^}]*)\}"#, block) {
+            let source = first_capture(r#"source\s*=\s*"([^"]+)""#, &caps[1]).unwrap_or_else(|| format!("hashicorp/{}", caps[0]));
+            let constraint = first_capture(r#"version\s*=\s*"([^"]+)""#, &caps[1]);
+            let mut entry = source.clone();
+            if let Some(constraint) = constraint {
+                entry.push_str(&format!(" {}", constraint));
+            }
+            if let Some(version) = locked.get(&source) {
+                entry.push_str(&format!(" (locked {})", version));
+            }
+            providers.insert(source, entry);
+        }
+    }
+    for (source, version) in &locked {
+        providers.entry(source.clone()).or_insert_with(|| format!("{} (locked {})", source, version));
+    }
+    list(&mut info, "Providers", providers.into_values());
+    
+    let modules = captures(r#"(?s)module\s+"([^"]+)"\s*\{(.*?)\n\}"#, &source).into_iter().map(|caps| {
+        let source = first_capture(r#"source\s*=\s*"([^"]+)""#, &caps[1]).unwrap_or_default();
+        match first_capture(r#"version\s*=\s*"([^"]+)""#, &caps[1]) {
+            Some(version) => format!("{}: {} {}", caps[0], source, version),
+            None => format!("{}: {}", caps[0], source),
+        }
+    });
+    list(&mut info, "Modules", modules);
+    
+    let mut resources: BTreeMap<String, usize> = BTreeMap::new();
+    for caps in captures(r#"(?m)^\s*resource\s+"([^"]+)""#, &source) {
+        *resources.entry(caps[0].clone()).or_insert(0) += 1;
+    }
+    list(&mut info, "Resources", resources.into_iter().map(|(kind, count)| format!("{} x{}", kind, count)));
+    
+    let names = |kind: &str| captures(&format!(r#"(?m)^\s*{}\s+"([^"]+)""#, kind), &source).into_iter().map(|caps| caps[0].clone()).collect::<Vec<_>>();
+    let variables = names("variable");
+    if !variables.is_empty() {
+        info.push_str(&format!("\nVariables: {}\n", variables.join(", ")));
+    }
+    let outputs = names("output");
+    if !outputs.is_empty() {
+        info.push_str(&format!("Outputs: {}\n", outputs.join(", ")));
+    }
+    Some(info)
+}
+
+fn pulumi_info(dir: &Path, files: &[PathBuf]) -> Option<String> {
+    let project = fs::read_to_string(dir.join("Pulumi.yaml")).or_else(|_| fs::read_to_string(dir.join("Pulumi.yml"))).ok()?;
+    let mut info = String::from("Project Type: Pulumi\n");
+    if let Some(name) = first_capture(r"(?m)^name:\s*(.+)$", &project) {
+        info.push_str(&format!("Project Name: {}\n", name.trim()));
+    }
+    // `runtime: nodejs` or `runtime:\n  name: python`
+    if let Some(runtime) = first_capture(r"(?m)^runtime:[ \t]*(?:\n\s+name:\s*)?(\S+)", &project) {
+        info.push_str(&format!("Runtime: {}\n", runtime));
+    }
+    if let Some(description) = first_capture(r"(?m)^description:\s*(.+)$", &project) {
+        info.push_str(&format!("Description: {}\n", description.trim()));
+    }
+    // Stack settings are named Pulumi.<stack>.yaml; their values are not read
+    let stacks: Vec<String> = files
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str())
+        .filter_map(|name| name.strip_prefix("Pulumi.")?.strip_suffix(".yaml").or_else(|| name.strip_prefix("Pulumi.")?.strip_suffix(".yml")))
+        .filter(|stack| !stack.is_empty())
+        .map(str::to_string)
+        .collect();
+    if !stacks.is_empty() {
+        info.push_str(&format!("Stacks: {}\n", stacks.join(", ")));
+    }
+    Some(info)
+}
+
+fn cloudformation_info(files: &[PathBuf]) -> Option<String> {
+    let mut info = String::new();
+    for path in files {
+        let is_template = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| matches!(ext, "yaml" | "yml" | "json" | "template"));
+        if !is_template {
+            continue;
+        }
+        let content = match fs::read_to_string(path) {
+            Ok(content) if content.contains("AWSTemplateFormatVersion") || content.contains("AWS::Serverless") => content,
+            _ => continue,
+        };
+        
+        let mut resources: BTreeMap<String, usize> = BTreeMap::new();
+        for caps in captures(r#"(?m)"?Type"?\s*:\s*["']?((?:AWS|Custom|Alexa)::[\w:]+)"#, &content) {
+            *resources.entry(caps[0].clone()).or_insert(0) += 1;
+        }
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let serverless = if content.contains("AWS::Serverless-") { ", SAM" } else { "" };
+        info.push_str(&format!("\nTemplate {} ({} resources{}):\n", name, resources.values().sum::<usize>(), serverless));
+        if let Some(description) = first_capture(r#"(?m)^\s*"?Description"?\s*:\s*["']?([^"'\n]+)"#, &content) {
+            info.push_str(&format!("Description: {}\n", description.trim()));
+        }
+        for (kind, count) in resources {
+            info.push_str(&format!("- {} x{}\n", kind, count));
+        }
+    }
+    if info.is_empty() {
+        None
+    } else {
+        Some(format!("Project Type: CloudFormation\n{}", info))
+    }
+}
+
+// Whether the file holds Terraform variable values
+pub fn is_tfvars(path: &Path) -> bool {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default();
+    TFVARS_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+// Replaces the string values and heredoc bodies of a tfvars file, which
+// often hold credentials, keeping the variable names and the line numbers;
+// quoted object keys (`"key" = ` or `"key": `) are kept
+pub fn redact_tfvars(content: &str) -> String {
+    let string = Regex::new(r#""(?:[^"\\]|\\.)*""#).expect("valid regex");
+    let heredoc = Regex::new(r"<<-?\s*([A-Za-z_]\w*)\s*$").expect("valid regex");
+    let mut redacted = String::with_capacity(content.len());
+    let mut heredoc_end: Option<String> = None;
+    for line in content.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\n', '\r']);
+        let ending = &line[text.len()..];
+        if let Some(ref end) = heredoc_end {
+            if text.trim() == end {
+                heredoc_end = None;
+                redacted.push_str(line);
+            } else {
+                redacted.push_str(REDACTED);
+                redacted.push_str(ending);
+            }
+            continue;
+        }
+        
+        // Comments may explain a value but not contain one
+        let (code, comment) = match text.find('#').filter(|&index| !string.find_iter(text).any(|m| m.range().contains(&index))) {
+            Some(index) => text.split_at(index),
+            None => (text, ""),
+        };
+        let code = string.replace_all(code, |caps: &regex::Captures| {
+            let end = caps.get(0).map(|m| m.end()).unwrap_or(0);
+            if code[end..].trim_start().starts_with([':', '=']) {
+                caps[0].to_string()
+            } else {
+                format!("\"{}\"", REDACTED)
+            }
+        });
+        if let Some(caps) = heredoc.captures(&code) {
+            heredoc_end = Some(caps[1].to_string());
+        }
+        redacted.push_str(&code);
+        redacted.push_str(comment);
+        redacted.push_str(ending);
+    }
+    redacted
+}
+
+// The `git diff` of a tfvars file with the values on its hunk lines redacted.
+// The old and the new side are redacted as files of their own, so heredocs
+// are followed on both; headers and `\ No newline` markers stay as they are
+pub fn redact_tfvars_diff(diff: &str) -> String {
+    let lines: Vec<&str> = diff.split_inclusive('\n').collect();
+    let first_hunk = lines.iter().position(|line| line.starts_with("@@")).unwrap_or(lines.len());
+    let is_hunk_line = |index: usize, line: &str| index > first_hunk && line.starts_with([' ', '-', '+']);
+    
+    // Line index to redacted text, for one side of the diff
+    let side = |marker: char| -> BTreeMap<usize, String> {
+        let indexes: Vec<usize> = (0..lines.len())
+            .filter(|&index| is_hunk_line(index, lines[index]) && (lines[index].starts_with(' ') || lines[index].starts_with(marker)))
+            .collect();
+        let text: String = indexes.iter().map(|&index| format!("{}\n", lines[index][1..].trim_end_matches(['\n', '\r']))).collect();
+        indexes.into_iter().zip(redact_tfvars(&text).lines().map(str::to_string)).collect()
+    };
+    let old = side('-');
+    let new = side('+');
+    
+    let mut redacted = String::with_capacity(diff.len());
+    for (index, line) in lines.iter().enumerate() {
+        let text = match line.chars().next() {
+            Some('-') if is_hunk_line(index, line) => old.get(&index),
+            Some(' ' | '+') if is_hunk_line(index, line) => new.get(&index),
+            _ => None,
+        };
+        match text {
+            Some(text) => {
+                let ending = &line[line.trim_end_matches(['\n', '\r']).len()..];
+                redacted.push_str(&line[..1]);
+                redacted.push_str(text);
+                redacted.push_str(ending);
+            }
+            None => redacted.push_str(line),
+        }
+    }
+    redacted
+}
+
+// The body of the first `name { ... }` block
+fn block<'a>(source: &'a str, name: &str) -> Option<&'a str> {
+    let start = source.find(name)?;
+    let open = start + source[start..].find('{')?;
+    let mut depth = 0;
+    for (offset, c) in source[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&source[open + 1..open + offset]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn first_capture(pattern: &str, text: &str) -> Option<String> {
+    let regex = Regex::new(pattern).ok()?;
+    regex.captures(text).map(|caps| caps[1].to_string())
+}
+
+// The capture groups of every match, without the whole match
+fn captures(pattern: &str, text: &str) -> Vec<Vec<String>> {
+    match Regex::new(pattern) {
+        Ok(regex) => regex
+            .captures_iter(text)
+            .map(|caps| caps.iter().skip(1).map(|group| group.map(|m| m.as_str().to_string()).unwrap_or_default()).collect())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn list(info: &mut String, title: &str, items: impl Iterator<Item = String>) {
+    let items: Vec<String> = items.collect();
+    if !items.is_empty() {
+        info.push_str(&format!("\n{}:\n", title));
+        for item in items {
+            info.push_str(&format!("- {}\n", item));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn redact_tfvars_diff_hides_added_and_removed_values() {
+        let diff = concat!(
+            "diff --git a/prod.tfvars b/prod.tfvars\n",
+            "index 1111111..2222222 100644\n",
+            "--- a/prod.tfvars\n",
+            "+++ b/prod.tfvars\n",
+            "@@ -1,2 +1,6 @@\n",
+            " region = \"eu-west-1\"\n",
+            "-db_password = \"old-secret\"\n",
+            "+db_password = \"hunter2-secret\"\n",
+            "+tags = { \"team\" = \"payments\" }\n",
+            "+cert = <<EOT\n",
+            "+-----BEGIN CERTIFICATE-----\n",
+            "+EOT\n",
+        );
+        let redacted = redact_tfvars_diff(diff);
+        for secret in ["old-secret", "hunter2-secret", "payments", "eu-west-1", "BEGIN CERTIFICATE"] {
+            assert!(!redacted.contains(secret), "{} leaked:\n{}", secret, redacted);
+        }
+        assert!(redacted.contains("+db_password = \"[REDACTED]\"\n"));
+        assert!(redacted.contains("-db_password = \"[REDACTED]\"\n"));
+        assert!(redacted.contains("+tags = { \"team\" = \"[REDACTED]\" }\n"));
+        assert!(redacted.contains("+[REDACTED]\n+EOT\n"));
+        assert!(redacted.starts_with("diff --git a/prod.tfvars b/prod.tfvars\n"));
+        assert_eq!(redacted.lines().count(), diff.lines().count());
+    }
+}
@@ -0,0 +1,311 @@
+// src/imports.rs
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml::Value;
+
+// A reference from one source file to another module, as written in the source
+#[derive(Debug, Clone, PartialEq)]
+pub enum Import {
+    // `use a::b::c` paths, split into segments
+    RustUse(Vec<String>),
+    // `mod name;` declarations
+    RustMod(String),
+    // Dotted module name and the number of leading dots for relative imports
+    Python { module: String, level: usize },
+    // Relative specifier from `import ... from "./x"` or `require("./x")`
+    Js(String),
+}
+
+fn rust_use_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);").unwrap())
+}
+
+fn alias_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\s+as\s+[A-Za-z_][A-Za-z0-9_]*").unwrap())
+}
+
+fn rust_mod_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_][A-Za-z0-9_]*)\s*;").unwrap())
+}
+
+fn python_import_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?m)^\s*import\s+([A-Za-z0-9_.,\s]+?)\s*(?:#.*)?$").unwrap())
+}
+
+fn python_from_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?m)^\s*from\s+(\.*)([A-Za-z0-9_.]*)\s+import\s+\(?([A-Za-z0-9_,\s*]+)").unwrap())
+}
+
+fn js_import_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(?:import|export)\s[^'"]*?from\s*['"]([^'"]+)['"]|import\s*\(?\s*['"]([^'"]+)['"]|require\s*\(\s*['"]([^'"]+)['"]\s*\)"#)
+            .unwrap()
+    })
+}
+
+pub fn extract_imports(path: &Path, content: &str) -> Vec<Import> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match ext {
+        "rs" => extract_rust_imports(content),
+        "py" => extract_python_imports(content),
+        "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" => extract_js_imports(content),
+        _ => Vec::new(),
+    }
+}
+
+fn extract_rust_imports(content: &str) -> Vec<Import> {
+    let mut imports = Vec::new();
+    
+    for captures in rust_use_regex().captures_iter(content) {
+        let tree = alias_regex().replace_all(&captures[1], "");
+        let tree: String = tree.split_whitespace().collect();
+        for path in expand_use_tree(&tree) {
+            let segments: Vec<String> = path
+                .split("::")
+                .map(|s| s.to_string())
+                .filter(|s| !s.is_empty() && s != "*")
+                .collect();
+            if !segments.is_empty() {
+                imports.push(Import::RustUse(segments));
+            }
+        }
+    }
+    
+    for captures in rust_mod_regex().captures_iter(content) {
+        imports.push(Import::RustMod(captures[1].to_string()));
+    }
+    
+    imports
+}
+
+// Expands `a::{b, c::{d, e}}` into `a::b`, `a::c::d`, `a::c::e`
+fn expand_use_tree(tree: &str) -> Vec<String> {
+    let open = match tree.find('{') {
+        Some(open) => open,
+        None => return vec![tree.to_string()],
+    };
+    let close = match tree.rfind('}') {
+        Some(close) if close > open => close,
+        _ => return vec![tree[..open].trim_end_matches("::").to_string()],
+    };
+    
+    let prefix = &tree[..open];
+    let inner = &tree[open + 1..close];
+    
+    // Split the inner list on top-level commas only
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for ch in inner.chars() {
+        match ch {
+            '{' => {
+                depth += 1;
+                current.push(ch);
+            }
+            '}' => {
+                depth -= 1;
+                current.push(ch);
+            }
+            ',' if depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+            }
+            _ => current.push(ch),
+        }
+    }
+    parts.push(current);
+    
+    let mut paths = Vec::new();
+    for part in parts {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        if part == "self" {
+            paths.push(prefix.trim_end_matches("::").to_string());
+            continue;
+        }
+        for expanded in expand_use_tree(part) {
+            paths.push(format!("{}{}", prefix, expanded));
+        }
+    }
+    paths
+}
+
+fn extract_python_imports(content: &str) -> Vec<Import> {
+    let mut imports = Vec::new();
+    
+    for captures in python_import_regex().captures_iter(content) {
+        for module in captures[1].split(',') {
+            let module = module.split_whitespace().next().unwrap_or("");
+            if !module.is_empty() {
+                imports.push(Import::Python { module: module.to_string(), level: 0 });
+            }
+        }
+    }
+    
+    for captures in python_from_regex().captures_iter(content) {
+        let level = captures[1].len();
+        let module = captures[2].to_string();
+        
+        // `from pkg import name` may import a submodule, so try each name as one
+        for name in captures[3].split(',') {
+            let name = name.split_whitespace().next().unwrap_or("");
+            if name.is_empty() || name == "*" {
+                continue;
+            }
+            let submodule = if module.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", module, name)
+            };
+            imports.push(Import::Python { module: submodule, level });
+        }
+        imports.push(Import::Python { module, level });
+    }
+    
+    imports
+}
+
+fn extract_js_imports(content: &str) -> Vec<Import> {
+    js_import_regex()
+        .captures_iter(content)
+        .filter_map(|captures| {
+            captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .or_else(|| captures.get(3))
+                .map(|m| m.as_str().to_string())
+        })
+        .filter(|spec| spec.starts_with('.'))
+        .map(Import::Js)
+        .collect()
+}
+
+// Rust crate names (with `-` replaced by `_`) declared in the project's Cargo.toml
+pub fn rust_crate_names(project_root: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Ok(content) = fs::read_to_string(project_root.join("Cargo.toml")) {
+        if let Ok(cargo_toml) = content.parse::<Value>() {
+            if let Some(name) = cargo_toml.get("package").and_then(|p| p.get("name")).and_then(|v| v.as_str()) {
+                names.push(name.replace('-', "_"));
+            }
+            if let Some(name) = cargo_toml.get("lib").and_then(|l| l.get("name")).and_then(|v| v.as_str()) {
+                names.push(name.replace('-', "_"));
+            }
+        }
+    }
+    names
+}
+
+// Resolves an import to the existing files it most likely refers to
+pub fn resolve(import: &Import, from_file: &Path, project_root: &Path, crate_names: &[String]) -> Vec<PathBuf> {
+    let from_dir = from_file.parent().unwrap_or(project_root);
+    
+    match import {
+        Import::RustUse(segments) => resolve_rust_use(segments, from_file, project_root, crate_names),
+        Import::RustMod(name) => {
+            // `mod x;` in lib.rs/main.rs/mod.rs lives next to the file, otherwise in a
+            // directory named after the declaring file
+            let stem = from_file.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            let base = if ["lib", "main", "mod"].contains(&stem) {
+                from_dir.to_path_buf()
+            } else {
+                from_dir.join(stem)
+            };
+            first_existing(&[base.join(format!("{}.rs", name)), base.join(name).join("mod.rs")])
+                .into_iter()
+                .collect()
+        }
+        Import::Python { module, level } => {
+            let parts: Vec<&str> = module.split('.').filter(|s| !s.is_empty()).collect();
+            let bases: Vec<PathBuf> = if *level > 0 {
+                let mut base = from_dir.to_path_buf();
+                for _ in 1..*level {
+                    base.pop();
+                }
+                vec![base]
+            } else {
+                vec![project_root.to_path_buf(), project_root.join("src"), from_dir.to_path_buf()]
+            };
+            
+            for base in bases {
+                let module_path = parts.iter().fold(base.clone(), |path, part| path.join(part));
+                let candidates = if parts.is_empty() {
+                    vec![base.join("__init__.py")]
+                } else {
+                    vec![module_path.with_extension("py"), module_path.join("__init__.py")]
+                };
+                if let Some(found) = first_existing(&candidates) {
+                    return vec![found];
+                }
+            }
+            Vec::new()
+        }
+        Import::Js(spec) => {
+            let target = from_dir.join(spec);
+            let mut candidates = vec![target.clone()];
+            for ext in ["ts", "tsx", "js", "jsx", "mjs", "cjs"] {
+                candidates.push(PathBuf::from(format!("{}.{}", target.to_string_lossy(), ext)));
+            }
+            for ext in ["ts", "tsx", "js", "jsx"] {
+                candidates.push(target.join(format!("index.{}", ext)));
+            }
+            first_existing(&candidates).into_iter().collect()
+        }
+    }
+}
+
+fn resolve_rust_use(segments: &[String], from_file: &Path, project_root: &Path, crate_names: &[String]) -> Vec<PathBuf> {
+    let src_dir = project_root.join("src");
+    let first = match segments.first() {
+        Some(first) => first.as_str(),
+        None => return Vec::new(),
+    };
+    
+    let (base, rest, crate_root) = if first == "crate" || crate_names.iter().any(|name| name == first) {
+        (src_dir.clone(), &segments[1..], true)
+    } else if first == "super" || first == "self" {
+        let mut base = from_file.parent().unwrap_or(&src_dir).to_path_buf();
+        let mut rest = segments;
+        while rest.first().map(|s| s.as_str()) == Some("super") {
+            base.pop();
+            rest = &rest[1..];
+        }
+        if rest.first().map(|s| s.as_str()) == Some("self") {
+            rest = &rest[1..];
+        }
+        (base, rest, false)
+    } else {
+        // Could be an external crate; only resolve if it names a local module
+        (src_dir.clone(), segments, false)
+    };
+    
+    // Prefer the longest module path that exists; trailing segments are items
+    for len in (1..=rest.len()).rev() {
+        let module_path = rest[..len].iter().fold(base.clone(), |path, part| path.join(part));
+        let candidates = [module_path.with_extension("rs"), module_path.join("mod.rs")];
+        if let Some(found) = first_existing(&candidates) {
+            return vec![found];
+        }
+    }
+    
+    if crate_root {
+        return first_existing(&[src_dir.join("lib.rs"), src_dir.join("main.rs")])
+            .into_iter()
+            .collect();
+    }
+    
+    Vec::new()
+}
+
+fn first_existing(candidates: &[PathBuf]) -> Option<PathBuf> {
+    candidates.iter().find(|path| path.is_file()).cloned()
+}
@@ -0,0 +1,122 @@
+// src/incremental.rs
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{content_hash, paths, FileEntry};
+
+// Unchanged files beyond this many are summarized as a count
+const MAX_LISTED_UNCHANGED: usize = 50;
+
+// Content hashes of the files copied in previous runs, keyed by absolute path
+#[derive(Default, Serialize, Deserialize)]
+struct State {
+    files: BTreeMap<String, String>,
+}
+
+// Files split by whether they changed since the previous run in this project
+pub struct ChangeSet {
+    pub changed: Vec<FileEntry>,
+    // Display and source paths
+    pub unchanged: Vec<(String, PathBuf)>,
+    pub first_run: bool,
+    pub pending: Pending,
+}
+
+// The hashes of the changed files, recorded once the copy has been delivered
+// so a failed or cancelled run does not mark them as sent
+pub struct Pending {
+    path: PathBuf,
+    state: State,
+    // Display path, state key and content hash as read, before any reduction
+    hashes: Vec<(String, String, String)>,
+}
+
+impl Pending {
+    // Records the files that made it into the delivered output
+    pub fn save(&self, delivered: &[FileEntry]) -> io::Result<()> {
+        let mut state = State { files: self.state.files.clone() };
+        for (path, key, hash) in &self.hashes {
+            if delivered.iter().any(|file| &file.path == path) {
+                state.files.insert(key.clone(), hash.clone());
+            }
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(&state).map_err(io::Error::other)?;
+        fs::write(&self.path, json)
+    }
+}
+
+fn state_path(project_root: &Path) -> io::Result<PathBuf> {
+    let dir = dirs::data_dir()
+        .map(|dir| dir.join("codecopier").join("state"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not determine the data directory"))?;
+    
+    // One state file per project, named after a hash of the project root
+    let root_hash = content_hash(&project_root.to_string_lossy());
+    Ok(dir.join(format!("{}.json", &root_hash[..16])))
+}
+
+fn load_state(path: &Path) -> Option<State> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn state_key(file: &FileEntry) -> String {
+    paths::canonicalize(&file.source_path)
+        .unwrap_or_else(|_| file.source_path.clone())
+        .to_string_lossy()
+        .to_string()
+}
+
+// Compares the files against the previous run; their current hashes are
+// recorded by `Pending::save`
+pub fn split_changed(project_root: &Path, files: Vec<FileEntry>) -> io::Result<ChangeSet> {
+    let path = state_path(project_root)?;
+    let previous = load_state(&path);
+    let first_run = previous.is_none();
+    let state = previous.unwrap_or_default();
+    
+    let mut changed = Vec::new();
+    let mut unchanged = Vec::new();
+    let mut hashes = Vec::new();
+    
+    for file in files {
+        let key = state_key(&file);
+        if state.files.get(&key) == Some(&file.hash) {
+            unchanged.push((file.path, file.source_path));
+        } else {
+            hashes.push((file.path.clone(), key, file.hash.clone()));
+            changed.push(file);
+        }
+    }
+    
+    Ok(ChangeSet {
+        changed,
+        unchanged,
+        first_run,
+        pending: Pending { path, state, hashes },
+    })
+}
+
+pub fn format_unchanged_section(unchanged: &[String]) -> String {
+    let mut section = String::from("<unchanged_files>\n");
+    section.push_str("These files are unchanged since the last copy and are not included:\n");
+    
+    let mut sorted: Vec<&String> = unchanged.iter().collect();
+    sorted.sort();
+    
+    for path in sorted.iter().take(MAX_LISTED_UNCHANGED) {
+        section.push_str(&format!("- {}\n", path));
+    }
+    if sorted.len() > MAX_LISTED_UNCHANGED {
+        section.push_str(&format!("- ... and {} more\n", sorted.len() - MAX_LISTED_UNCHANGED));
+    }
+    
+    section.push_str("</unchanged_files>\n\n");
+    section
+}
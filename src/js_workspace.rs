@@ -0,0 +1,244 @@
+// src/js_workspace.rs
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Dependency tables whose entries may point at other workspace packages
+const DEPENDENCY_KEYS: &[&str] = &["dependencies", "devDependencies", "peerDependencies", "optionalDependencies"];
+
+// Where Nx looks for projects when the package manager declares no workspaces
+const NX_DEFAULT_PATTERNS: &[&str] = &["apps/*", "libs/*", "packages/*"];
+
+// A pnpm, Yarn, npm or Nx monorepo and its packages
+pub struct Workspace {
+    pub root: PathBuf,
+    pub tool: String,
+    packages: Vec<Package>,
+}
+
+struct Package {
+    name: String,
+    dir: PathBuf,
+    // Names of the other workspace packages this one depends on
+    dependencies: Vec<String>,
+}
+
+// The nearest workspace at or above `start`: a pnpm-workspace.yaml, a
+// package.json with `workspaces`, a lerna.json or an nx.json
+pub fn find(start: &Path) -> Option<Workspace> {
+    for dir in start.ancestors() {
+        let package_json = read_json(&dir.join("package.json"));
+        let pnpm = fs::read_to_string(dir.join("pnpm-workspace.yaml")).ok();
+        let mut patterns = match pnpm {
+            Some(ref manifest) => pnpm_patterns(manifest),
+            None => package_json.as_ref().map(workspace_patterns).unwrap_or_default(),
+        };
+        if patterns.is_empty() {
+            if let Some(lerna) = read_json(&dir.join("lerna.json")) {
+                patterns = string_list(lerna.get("packages"));
+            }
+        }
+        let nx = dir.join("nx.json").is_file();
+        if patterns.is_empty() && nx {
+            patterns = NX_DEFAULT_PATTERNS.iter().map(|pattern| pattern.to_string()).collect();
+        }
+        if patterns.is_empty() {
+            continue;
+        }
+        
+        let mut tool = if pnpm.is_some() {
+            "pnpm".to_string()
+        } else if dir.join("yarn.lock").is_file() {
+            "Yarn".to_string()
+        } else {
+            "npm".to_string()
+        };
+        if nx {
+            tool.push_str(" + Nx");
+        } else if dir.join("turbo.json").is_file() {
+            tool.push_str(" + Turborepo");
+        }
+        // pnpm only links packages referenced with the workspace: protocol;
+        // npm and Yarn classic link any dependency named like a workspace package
+        let packages = packages(dir, &patterns, pnpm.is_none());
+        return Some(Workspace { root: dir.to_path_buf(), tool, packages });
+    }
+    None
+}
+
+impl Workspace {
+    // The directories of the named packages and of every workspace package
+    // they depend on, directly or not, requested packages first
+    pub fn resolve(&self, names: &[String]) -> io::Result<Vec<(String, PathBuf)>> {
+        let mut queue = Vec::new();
+        for name in names {
+            queue.push(self.lookup(name)?);
+        }
+        let mut resolved: Vec<&Package> = Vec::new();
+        while !queue.is_empty() {
+            let package = queue.remove(0);
+            if resolved.iter().any(|done| done.name == package.name) {
+                continue;
+            }
+            resolved.push(package);
+            queue.extend(package.dependencies.iter().filter_map(|name| self.packages.iter().find(|p| &p.name == name)));
+        }
+        Ok(resolved.into_iter().map(|package| (package.name.clone(), package.dir.clone())).collect())
+    }
+    
+    // By its full name, or else by the unscoped name (`ui` for `@acme/ui`)
+    // or directory name when only one package has it
+    fn lookup(&self, name: &str) -> io::Result<&Package> {
+        if let Some(package) = self.packages.iter().find(|package| package.name == name) {
+            return Ok(package);
+        }
+        let matches: Vec<&Package> = self
+            .packages
+            .iter()
+            .filter(|package| {
+                package.name.rsplit('/').next() == Some(name)
+                    || package.dir.file_name().is_some_and(|dir| dir.to_string_lossy() == name)
+            })
+            .collect();
+        match matches.as_slice() {
+            [package] => Ok(package),
+            [] => {
+                let available: Vec<&str> = self.packages.iter().map(|package| package.name.as_str()).collect();
+                Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No package named {} in the {} workspace at {}. Available packages: {}", name, self.tool, self.root.display(), available.join(", ")),
+                ))
+            }
+            _ => {
+                let names: Vec<&str> = matches.iter().map(|package| package.name.as_str()).collect();
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} matches several packages: {}; use the full name", name, names.join(", ")),
+                ))
+            }
+        }
+    }
+}
+
+// The `name` of the package.json in `dir`, used to label its component
+pub fn package_name(dir: &Path) -> Option<String> {
+    let manifest = read_json(&dir.join("package.json"))?;
+    manifest.get("name")?.as_str().map(str::to_string)
+}
+
+// The `packages:` list of pnpm-workspace.yaml
+fn pnpm_patterns(manifest: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+    let mut in_packages = false;
+    for line in manifest.lines() {
+        let text = line.split(" #").next().unwrap_or("").trim_end();
+        if !text.starts_with([' ', '\t', '-']) && !text.is_empty() {
+            in_packages = text.trim() == "packages:";
+            continue;
+        }
+        if let Some(pattern) = text.trim().strip_prefix('-').filter(|_| in_packages) {
+            patterns.push(pattern.trim().trim_matches(['"', '\'']).to_string());
+        }
+    }
+    patterns
+}
+
+// `"workspaces": [...]` or Yarn's `"workspaces": { "packages": [...] }`
+fn workspace_patterns(manifest: &Value) -> Vec<String> {
+    match manifest.get("workspaces") {
+        Some(Value::Object(workspaces)) => string_list(workspaces.get("packages")),
+        workspaces => string_list(workspaces),
+    }
+}
+
+// Directories below `root` matching the patterns, leaving out the ones
+// matching a `!pattern`, that hold a package.json (or an Nx project.json)
+fn packages(root: &Path, patterns: &[String], link_by_name: bool) -> Vec<Package> {
+    let (included, excluded) = match (glob_set(patterns, false), glob_set(patterns, true)) {
+        (Some(included), Some(excluded)) => (included, excluded),
+        _ => return Vec::new(),
+    };
+    let mut manifests: Vec<(String, PathBuf, Value)> = Vec::new();
+    for entry in WalkBuilder::new(root)
+        .filter_entry(|entry| entry.file_name() != "node_modules")
+        .build()
+        .filter_map(|entry| entry.ok())
+    {
+        let dir = entry.path();
+        if entry.depth() == 0 || !entry.file_type().is_some_and(|file_type| file_type.is_dir()) {
+            continue;
+        }
+        let relative = dir.strip_prefix(root).unwrap_or(dir).to_string_lossy().replace('\\', "/");
+        if !included.is_match(&relative) || excluded.is_match(&relative) {
+            continue;
+        }
+        let manifest = match read_json(&dir.join("package.json")).or_else(|| read_json(&dir.join("project.json"))) {
+            Some(manifest) => manifest,
+            None => continue,
+        };
+        let name = manifest
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| entry.file_name().to_string_lossy().into_owned());
+        manifests.push((name, dir.to_path_buf(), manifest));
+    }
+    
+    let names: BTreeSet<String> = manifests.iter().map(|(name, _, _)| name.clone()).collect();
+    manifests
+        .into_iter()
+        .map(|(name, dir, manifest)| {
+            let mut dependencies: Vec<String> = Vec::new();
+            for key in DEPENDENCY_KEYS {
+                let table = match manifest.get(*key).and_then(Value::as_object) {
+                    Some(table) => table,
+                    None => continue,
+                };
+                for (dependency, version) in table {
+                    let version = version.as_str().unwrap_or("");
+                    let local = version.starts_with("workspace:") || (link_by_name && !version.starts_with("npm:"));
+                    if local && names.contains(dependency) && !dependencies.contains(dependency) {
+                        dependencies.push(dependency.clone());
+                    }
+                }
+            }
+            // Nx project.json files list dependencies without package.json entries
+            for dependency in string_list(manifest.get("implicitDependencies")) {
+                if names.contains(&dependency) && !dependencies.contains(&dependency) {
+                    dependencies.push(dependency);
+                }
+            }
+            Package { name, dir, dependencies }
+        })
+        .collect()
+}
+
+// The patterns, or with `negated` the ones starting with `!`, as a set
+fn glob_set(patterns: &[String], negated: bool) -> Option<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = match (pattern.strip_prefix('!'), negated) {
+            (Some(pattern), true) => pattern,
+            (None, false) => pattern.as_str(),
+            _ => continue,
+        };
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        builder.add(GlobBuilder::new(pattern).literal_separator(true).build().ok()?);
+    }
+    builder.build().ok()
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(|item| item.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
@@ -0,0 +1,336 @@
+// src/languages.rs
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::FileEntry;
+
+// Language names by file extension, for the statistics in the file structure
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("py", "Python"),
+    ("pyi", "Python"),
+    ("js", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("go", "Go"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("kts", "Kotlin"),
+    ("swift", "Swift"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cc", "C++"),
+    ("cpp", "C++"),
+    ("cxx", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("scala", "Scala"),
+    ("lua", "Lua"),
+    ("sh", "Shell"),
+    ("pl", "Perl"),
+    ("bash", "Shell"),
+    ("zsh", "Shell"),
+    ("ps1", "PowerShell"),
+    ("sql", "SQL"),
+    ("html", "HTML"),
+    ("htm", "HTML"),
+    ("css", "CSS"),
+    ("scss", "SCSS"),
+    ("vue", "Vue"),
+    ("svelte", "Svelte"),
+    ("md", "Markdown"),
+    ("rst", "reStructuredText"),
+    ("txt", "Text"),
+    ("toml", "TOML"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("json", "JSON"),
+    ("jsonl", "JSON"),
+    ("xml", "XML"),
+    ("csv", "CSV"),
+    ("tsv", "TSV"),
+    ("proto", "Protobuf"),
+    ("tf", "Terraform"),
+    ("rhai", "Rhai"),
+];
+
+// Files recognized by their full name
+const FILE_NAMES: &[(&str, &str)] = &[
+    ("Dockerfile", "Dockerfile"),
+    ("Makefile", "Makefile"),
+    ("Justfile", "Just"),
+    ("justfile", "Just"),
+    ("Gemfile", "Ruby"),
+    ("Rakefile", "Ruby"),
+    ("Vagrantfile", "Ruby"),
+    ("Jenkinsfile", "Groovy"),
+    ("CMakeLists.txt", "CMake"),
+    ("Cargo.lock", "TOML"),
+];
+
+// Language identifiers as used by VS Code and in the `lang` attribute, by file extension
+const LANGUAGE_IDS: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("py", "python"),
+    ("pyi", "python"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "javascriptreact"),
+    ("ts", "typescript"),
+    ("tsx", "typescriptreact"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("swift", "swift"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("scala", "scala"),
+    ("lua", "lua"),
+    ("sh", "shellscript"),
+    ("pl", "perl"),
+    ("bash", "shellscript"),
+    ("zsh", "shellscript"),
+    ("ps1", "powershell"),
+    ("sql", "sql"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("md", "markdown"),
+    ("rst", "restructuredtext"),
+    ("toml", "toml"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("json", "json"),
+    ("jsonl", "jsonl"),
+    ("xml", "xml"),
+    ("csv", "csv"),
+    ("proto", "proto3"),
+    ("tf", "terraform"),
+];
+
+// Identifiers of files recognized by their full name
+const FILE_NAME_IDS: &[(&str, &str)] = &[
+    ("Dockerfile", "dockerfile"),
+    ("Makefile", "makefile"),
+    ("Justfile", "just"),
+    ("justfile", "just"),
+    ("Gemfile", "ruby"),
+    ("Rakefile", "ruby"),
+    ("Vagrantfile", "ruby"),
+    ("Jenkinsfile", "groovy"),
+    ("CMakeLists.txt", "cmake"),
+    ("Cargo.lock", "toml"),
+];
+
+pub fn language_for(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    if let Some((_, language)) = FILE_NAMES.iter().find(|(file_name, _)| *file_name == name) {
+        return Some(language);
+    }
+    
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|(_, language)| *language)
+}
+
+// Lowercase identifier of the file's language, like "rust" or "typescriptreact"
+pub fn language_id(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    if let Some((_, id)) = FILE_NAME_IDS.iter().find(|(file_name, _)| *file_name == name) {
+        return Some(id);
+    }
+    
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    LANGUAGE_IDS.iter().find(|(known, _)| *known == extension).map(|(_, id)| *id)
+}
+
+// Interpreters named in shebang lines, with their language identifier
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("python", "python"),
+    ("sh", "shellscript"),
+    ("bash", "shellscript"),
+    ("zsh", "shellscript"),
+    ("dash", "shellscript"),
+    ("ksh", "shellscript"),
+    ("node", "javascript"),
+    ("nodejs", "javascript"),
+    ("deno", "typescript"),
+    ("bun", "typescript"),
+    ("ts-node", "typescript"),
+    ("tsx", "typescript"),
+    ("ruby", "ruby"),
+    ("perl", "perl"),
+    ("php", "php"),
+    ("lua", "lua"),
+    ("pwsh", "powershell"),
+    ("rust-script", "rust"),
+    ("make", "makefile"),
+    ("just", "just"),
+];
+
+// Modeline file types that differ from our identifiers
+const MODELINE_ALIASES: &[(&str, &str)] = &[
+    ("sh", "shellscript"),
+    ("bash", "shellscript"),
+    ("zsh", "shellscript"),
+    ("shell-script", "shellscript"),
+    ("js", "javascript"),
+    ("ts", "typescript"),
+    ("make", "makefile"),
+    ("c++", "cpp"),
+    ("yml", "yaml"),
+];
+
+// Lines at the start and end of a file searched for a modeline, as in vim
+const MODELINE_LINES: usize = 5;
+
+// Language identifier from the file name, or for files without a known
+// extension from a shebang line or a vim/emacs modeline
+pub fn detect_language_id(path: &Path, content: &str) -> Option<&'static str> {
+    language_id(path).or_else(|| shebang_language(content)).or_else(|| modeline_language(content))
+}
+
+// Display name counterpart of detect_language_id, like "Python"
+pub fn detect_language(path: &Path, content: &str) -> Option<&'static str> {
+    if let Some(language) = language_for(path) {
+        return Some(language);
+    }
+    let id = shebang_language(content).or_else(|| modeline_language(content))?;
+    name_for_id(id)
+}
+
+fn name_for_id(id: &str) -> Option<&'static str> {
+    let named_by_extension = LANGUAGE_IDS
+        .iter()
+        .find(|(_, known)| *known == id)
+        .and_then(|(extension, _)| EXTENSIONS.iter().find(|(known, _)| known == extension));
+    let named_by_file = FILE_NAME_IDS
+        .iter()
+        .find(|(_, known)| *known == id)
+        .and_then(|(file_name, _)| FILE_NAMES.iter().find(|(known, _)| known == file_name));
+    named_by_extension.or(named_by_file).map(|(_, name)| *name)
+}
+
+// `#!/usr/bin/env -S python3 -u` → python
+fn shebang_language(content: &str) -> Option<&'static str> {
+    let line = content.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    
+    // python3.12 → python
+    let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    INTERPRETERS.iter().find(|(known, _)| *known == program).map(|(_, id)| *id)
+}
+
+fn modeline_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?:\b(?:vim?|ex):.*?\b(?:ft|filetype|syntax)=([A-Za-z0-9_+-]+))|(?:-\*-\s*(?:.*?\bmode:\s*([A-Za-z0-9_+-]+)|([A-Za-z0-9_+-]+)\s*-\*-))")
+            .unwrap()
+    })
+}
+
+// `# vim: set ft=python:` or `# -*- mode: ruby -*-` near the start or end of the file
+fn modeline_language(content: &str) -> Option<&'static str> {
+    let lines: Vec<&str> = content.lines().collect();
+    let tail_start = lines.len().saturating_sub(MODELINE_LINES).max(MODELINE_LINES.min(lines.len()));
+    let candidates = lines.iter().take(MODELINE_LINES).chain(lines[tail_start..].iter());
+    
+    for line in candidates {
+        let captures = match modeline_regex().captures(line) {
+            Some(captures) => captures,
+            None => continue,
+        };
+        let name = captures.iter().skip(1).flatten().next()?.as_str().to_lowercase();
+        let name = name.strip_suffix("-mode").unwrap_or(&name);
+        if let Some((_, id)) = MODELINE_ALIASES.iter().find(|(alias, _)| *alias == name) {
+            return Some(id);
+        }
+        if let Some((_, id)) = LANGUAGE_IDS.iter().chain(FILE_NAME_IDS).find(|(_, id)| *id == name) {
+            return Some(id);
+        }
+    }
+    None
+}
+
+// Note shown after a file in the file structure with --stats
+pub fn file_stats(file: &FileEntry) -> String {
+    let lines = file.content.lines().count();
+    let counts = format!("{} {}, {}", lines, if lines == 1 { "line" } else { "lines" }, format_size(file.content.len()));
+    match detect_language(&file.source_path, &file.content) {
+        Some(language) => format!("{}, {}", language, counts),
+        None => counts,
+    }
+}
+
+// Totals footer for the file structure: overall, then per language by line count
+pub fn totals(files: &[FileEntry]) -> String {
+    let mut by_language: BTreeMap<&str, (usize, usize, usize)> = BTreeMap::new();
+    for file in files {
+        let language = detect_language(&file.source_path, &file.content).unwrap_or("Other");
+        let entry = by_language.entry(language).or_insert((0, 0, 0));
+        entry.0 += 1;
+        entry.1 += file.content.lines().count();
+        entry.2 += file.content.len();
+    }
+    
+    let total_lines: usize = by_language.values().map(|(_, lines, _)| lines).sum();
+    let total_bytes: usize = by_language.values().map(|(_, _, bytes)| bytes).sum();
+    let mut output = format!(
+        "Total: {} {}, {} lines, {}\n",
+        files.len(),
+        if files.len() == 1 { "file" } else { "files" },
+        total_lines,
+        format_size(total_bytes)
+    );
+    
+    let mut languages: Vec<(&str, (usize, usize, usize))> = by_language.into_iter().collect();
+    languages.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(b.0)));
+    let width = languages.iter().map(|(language, _)| language.len()).max().unwrap_or(0);
+    for (language, (count, lines, bytes)) in languages {
+        output.push_str(&format!(
+            "  {:<width$}  {:>4} {:<5}  {:>7} lines  {:>9}\n",
+            language,
+            count,
+            if count == 1 { "file" } else { "files" },
+            lines,
+            format_size(bytes),
+            width = width
+        ));
+    }
+    output
+}
+
+pub fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
@@ -0,0 +1,424 @@
+// src/llm.rs
+use clap::Args;
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use std::env;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crate::config::{self, ProviderConfig};
+use crate::{tokens, Options};
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const ANTHROPIC_MAX_TOKENS: u32 = 4096;
+
+// Tokens of a local model's context window left for the answer, at most a
+// quarter of the window
+const OLLAMA_ANSWER_TOKENS: usize = 2048;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Provider {
+    OpenAi,
+    Anthropic,
+    Ollama,
+}
+
+impl Provider {
+    fn from_name(name: &str) -> Option<Provider> {
+        match name.to_lowercase().as_str() {
+            "openai" => Some(Provider::OpenAi),
+            "anthropic" => Some(Provider::Anthropic),
+            "ollama" => Some(Provider::Ollama),
+            _ => None,
+        }
+    }
+    
+    fn default_model(self) -> &'static str {
+        match self {
+            Provider::OpenAi => "gpt-4o",
+            Provider::Anthropic => "claude-3-5-sonnet-latest",
+            Provider::Ollama => "llama3",
+        }
+    }
+    
+    fn default_base_url(self) -> &'static str {
+        match self {
+            Provider::OpenAi => "https://api.openai.com/v1",
+            Provider::Anthropic => "https://api.anthropic.com/v1",
+            Provider::Ollama => "http://localhost:11434",
+        }
+    }
+    
+    fn api_key_env(self) -> Option<&'static str> {
+        match self {
+            Provider::OpenAi => Some("OPENAI_API_KEY"),
+            Provider::Anthropic => Some("ANTHROPIC_API_KEY"),
+            Provider::Ollama => None,
+        }
+    }
+}
+
+// Arguments of the `ask` subcommand
+#[derive(Args)]
+pub struct AskArgs {
+    /// Chat API to send the context to (defaults to [ask] provider in the config, then openai)
+    #[arg(long, value_parser = ["openai", "anthropic", "ollama"])]
+    provider: Option<String>,
+    
+    /// Model to use instead of the provider's default
+    #[arg(long)]
+    model: Option<String>,
+    
+    #[command(flatten)]
+    pub options: Options,
+}
+
+// `ask` subcommand: builds the context like a normal copy, then sends it to a
+// chat API and streams the answer to stdout instead of using the clipboard
+pub fn run_ask(args: AskArgs) -> io::Result<()> {
+    let AskArgs { provider: provider_name, model, mut options } = args;
+    options.configure_logging();
+    let _scratch_dirs = crate::resolve_inputs(&mut options)?;
+    if options.question.is_none() {
+        return Err(invalid_input("The ask command requires --question \"...\"".to_string()));
+    }
+    if options.paths.is_empty() {
+        return Err(invalid_input("No files or directories given".to_string()));
+    }
+    
+    let config = config::load(&options.paths, options.config_path.as_deref())?;
+    
+    let provider_name = provider_name
+        .or_else(|| config.ask.provider.clone())
+        .unwrap_or_else(|| "openai".to_string());
+    let provider = Provider::from_name(&provider_name).ok_or_else(|| {
+        invalid_input(format!(
+            "Unknown provider '{}'. Expected openai, anthropic or ollama",
+            provider_name
+        ))
+    })?;
+    
+    let provider_config = match provider {
+        Provider::OpenAi => config.ask.openai.clone(),
+        Provider::Anthropic => config.ask.anthropic.clone(),
+        Provider::Ollama => config.ask.ollama.clone(),
+    };
+    
+    let model = model
+        .or_else(|| provider_config.model.clone())
+        .unwrap_or_else(|| provider.default_model().to_string());
+    
+    // Without --max-tokens, a local model's context window sets the budget
+    let mut context_length = None;
+    if provider == Provider::Ollama {
+        match ollama_context_length(&provider_config, &model) {
+            Ok(Some(length)) => {
+                context_length = Some(length);
+                if options.max_tokens.is_none() {
+                    let budget = length - answer_tokens(length);
+                    info!("{} has a context window of {} tokens, limiting the context to {} tokens", model, length, budget);
+                    options.max_tokens = Some(budget);
+                }
+            }
+            Ok(None) => debug!("Ollama did not report a context length for {}", model),
+            Err(e) => warn!("Could not query the context window of {}: {}", model, e),
+        }
+    }
+    
+    let output = crate::generate_output(&options, &config)?;
+    let prompt = output.text().to_string();
+    
+    // Ollama cuts prompts to its default window unless num_ctx asks for more
+    let mut num_ctx = None;
+    if let Some(length) = context_length {
+        let prompt_tokens = tokens::estimate(&prompt);
+        let needed = prompt_tokens + answer_tokens(length);
+        if needed > length {
+            warn!(
+                "The context (~{} tokens) plus room for the answer does not fit in the {}-token context window of {}; it will be truncated",
+                prompt_tokens,
+                length,
+                model
+            );
+        }
+        num_ctx = Some(needed.min(length));
+    }
+    
+    info!("Asking {} ({}) with {} characters of context...", provider_name, model, prompt.len());
+    
+    let mut stdout = io::stdout();
+    let mut on_text = |text: &str| {
+        let _ = stdout.write_all(text.as_bytes());
+        let _ = stdout.flush();
+    };
+    
+    match provider {
+        Provider::OpenAi => stream_openai(&provider_config, &model, &prompt, &mut on_text)?,
+        Provider::Anthropic => stream_anthropic(&provider_config, &model, &prompt, &mut on_text)?,
+        Provider::Ollama => stream_ollama(&provider_config, &model, &prompt, num_ctx, &mut on_text)?,
+    }
+    
+    println!();
+    if let Some(ref pending) = output.incremental {
+        pending.save(&output.files)?;
+    }
+    Ok(())
+}
+
+fn api_key(provider: Provider, provider_config: &ProviderConfig) -> io::Result<String> {
+    // Environment variables take precedence over keys stored in the config file
+    if let Some(var) = provider.api_key_env() {
+        if let Ok(key) = env::var(var) {
+            if !key.is_empty() {
+                return Ok(key);
+            }
+        }
+    }
+    
+    provider_config.api_key.clone().ok_or_else(|| {
+        invalid_input(format!(
+            "No API key found. Set {} or api_key in the config file",
+            provider.api_key_env().unwrap_or("an API key")
+        ))
+    })
+}
+
+fn base_url(provider: Provider, provider_config: &ProviderConfig) -> String {
+    let url = match provider {
+        Provider::Ollama => provider_config
+            .base_url
+            .clone()
+            .or_else(|| env::var("OLLAMA_HOST").ok())
+            .unwrap_or_else(|| provider.default_base_url().to_string()),
+        _ => provider_config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| provider.default_base_url().to_string()),
+    };
+    
+    let url = if url.starts_with("http://") || url.starts_with("https://") {
+        url
+    } else {
+        format!("http://{}", url)
+    };
+    
+    url.trim_end_matches('/').to_string()
+}
+
+fn stream_openai(
+    provider_config: &ProviderConfig,
+    model: &str,
+    prompt: &str,
+    on_text: &mut dyn FnMut(&str),
+) -> io::Result<()> {
+    let key = api_key(Provider::OpenAi, provider_config)?;
+    let url = format!("{}/chat/completions", base_url(Provider::OpenAi, provider_config));
+    
+    let body = json!({
+        "model": model,
+        "stream": true,
+        "messages": [{ "role": "user", "content": prompt }],
+    });
+    
+    let response = ureq::post(&url)
+        .set("Authorization", &format!("Bearer {}", key))
+        .send_json(body)
+        .map_err(request_error)?;
+    
+    read_server_sent_events(response.into_reader(), |data| {
+        if data == "[DONE]" {
+            return false;
+        }
+        if let Ok(event) = serde_json::from_str::<Value>(data) {
+            if let Some(text) = event["choices"][0]["delta"]["content"].as_str() {
+                on_text(text);
+            }
+        }
+        true
+    })
+}
+
+fn stream_anthropic(
+    provider_config: &ProviderConfig,
+    model: &str,
+    prompt: &str,
+    on_text: &mut dyn FnMut(&str),
+) -> io::Result<()> {
+    let key = api_key(Provider::Anthropic, provider_config)?;
+    let url = format!("{}/messages", base_url(Provider::Anthropic, provider_config));
+    
+    let body = json!({
+        "model": model,
+        "max_tokens": ANTHROPIC_MAX_TOKENS,
+        "stream": true,
+        "messages": [{ "role": "user", "content": prompt }],
+    });
+    
+    let response = ureq::post(&url)
+        .set("x-api-key", &key)
+        .set("anthropic-version", ANTHROPIC_VERSION)
+        .send_json(body)
+        .map_err(request_error)?;
+    
+    read_server_sent_events(response.into_reader(), |data| {
+        if let Ok(event) = serde_json::from_str::<Value>(data) {
+            match event["type"].as_str() {
+                Some("content_block_delta") => {
+                    if let Some(text) = event["delta"]["text"].as_str() {
+                        on_text(text);
+                    }
+                }
+                Some("message_stop") => return false,
+                _ => {}
+            }
+        }
+        true
+    })
+}
+
+fn answer_tokens(context_length: usize) -> usize {
+    OLLAMA_ANSWER_TOKENS.min(context_length / 4)
+}
+
+// The model's maximum context length from /api/show, reported as
+// "<architecture>.context_length" in its model info
+fn ollama_context_length(provider_config: &ProviderConfig, model: &str) -> io::Result<Option<usize>> {
+    let url = format!("{}/api/show", base_url(Provider::Ollama, provider_config));
+    let response: Value = ureq::post(&url)
+        .send_json(json!({ "model": model }))
+        .map_err(request_error)?
+        .into_json()?;
+    
+    let length = response["model_info"].as_object().and_then(|info| {
+        info.iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, value)| value.as_u64())
+    });
+    Ok(length.map(|length| length as usize))
+}
+
+fn stream_ollama(
+    provider_config: &ProviderConfig,
+    model: &str,
+    prompt: &str,
+    num_ctx: Option<usize>,
+    on_text: &mut dyn FnMut(&str),
+) -> io::Result<()> {
+    let url = format!("{}/api/chat", base_url(Provider::Ollama, provider_config));
+    
+    let mut body = json!({
+        "model": model,
+        "stream": true,
+        "messages": [{ "role": "user", "content": prompt }],
+    });
+    if let Some(num_ctx) = num_ctx {
+        body["options"] = json!({ "num_ctx": num_ctx });
+    }
+    
+    let response = ureq::post(&url).send_json(body).map_err(request_error)?;
+    
+    // Ollama streams newline-delimited JSON objects rather than SSE
+    let reader = BufReader::new(response.into_reader());
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Ok(chunk) = serde_json::from_str::<Value>(&line) {
+            if let Some(error) = chunk["error"].as_str() {
+                return Err(io::Error::other(format!("Ollama error: {}", error)));
+            }
+            if let Some(text) = chunk["message"]["content"].as_str() {
+                on_text(text);
+            }
+            if chunk["done"].as_bool() == Some(true) {
+                break;
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+// Embedding vectors for each of `texts`, from OpenAI's /embeddings or
+// Ollama's /api/embed endpoint
+pub fn embed(provider_name: &str, provider_config: &ProviderConfig, model: &str, texts: &[String]) -> io::Result<Vec<Vec<f32>>> {
+    let provider = Provider::from_name(provider_name)
+        .filter(|provider| *provider != Provider::Anthropic)
+        .ok_or_else(|| invalid_input(format!("Unknown embedding provider '{}'. Expected openai or ollama", provider_name)))?;
+    
+    let response: Value = match provider {
+        Provider::OpenAi => {
+            let key = api_key(provider, provider_config)?;
+            let url = format!("{}/embeddings", base_url(provider, provider_config));
+            ureq::post(&url)
+                .set("Authorization", &format!("Bearer {}", key))
+                .send_json(json!({ "model": model, "input": texts }))
+                .map_err(request_error)?
+                .into_json()?
+        }
+        _ => {
+            let url = format!("{}/api/embed", base_url(provider, provider_config));
+            ureq::post(&url)
+                .send_json(json!({ "model": model, "input": texts }))
+                .map_err(request_error)?
+                .into_json()?
+        }
+    };
+    
+    let vectors: Option<Vec<&Value>> = match provider {
+        Provider::OpenAi => response["data"].as_array().map(|data| data.iter().map(|item| &item["embedding"]).collect()),
+        _ => response["embeddings"].as_array().map(|embeddings| embeddings.iter().collect()),
+    };
+    let vectors: Vec<Vec<f32>> = vectors
+        .unwrap_or_default()
+        .into_iter()
+        .map(|vector| {
+            vector
+                .as_array()
+                .map(|values| values.iter().filter_map(Value::as_f64).map(|value| value as f32).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+    if vectors.len() != texts.len() {
+        return Err(io::Error::other(format!(
+            "Expected {} embeddings from {}, got {}",
+            texts.len(),
+            provider_name,
+            vectors.len()
+        )));
+    }
+    Ok(vectors)
+}
+
+// Calls `on_data` with the payload of each `data:` line; returning false stops reading
+fn read_server_sent_events<F>(reader: Box<dyn Read + Send + Sync>, mut on_data: F) -> io::Result<()>
+where
+    F: FnMut(&str) -> bool,
+{
+    let reader = BufReader::new(reader);
+    for line in reader.lines() {
+        let line = line?;
+        if let Some(data) = line.strip_prefix("data:") {
+            if !on_data(data.trim()) {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn request_error(error: ureq::Error) -> io::Error {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            io::Error::other(format!("API request failed with status {}: {}", code, body.trim()))
+        }
+        ureq::Error::Transport(transport) => {
+            io::Error::other(format!("API request failed: {}", transport))
+        }
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
@@ -0,0 +1,73 @@
+// src/logging.rs
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::json;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::progress;
+
+// Log records go to stderr so stdout stays free for results and manifests
+struct Logger;
+
+static LOGGER: Logger = Logger;
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+
+impl Log for Logger {
+    // Besides our own records, only the ignore crate's are kept, since they
+    // explain which .gitignore/.copierignore rule excluded a path
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+            && (metadata.target().starts_with(env!("CARGO_CRATE_NAME")) || metadata.target().starts_with("ignore"))
+    }
+    
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        
+        let line = if JSON_FORMAT.load(Ordering::Relaxed) {
+            json!({
+                "timestamp": chrono::Local::now().to_rfc3339(),
+                "level": record.level().as_str().to_lowercase(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            })
+            .to_string()
+        } else {
+            match record.level() {
+                Level::Error => format!("Error: {}", record.args()),
+                Level::Warn => format!("Warning: {}", record.args()),
+                Level::Info => record.args().to_string(),
+                Level::Debug => format!("[debug] {}", record.args()),
+                Level::Trace => format!("[trace] {}", record.args()),
+            }
+        };
+        
+        progress::suspend(|| {
+            let _ = writeln!(io::stderr(), "{}", line);
+        });
+    }
+    
+    fn flush(&self) {
+        let _ = io::stderr().flush();
+    }
+}
+
+// Installs the logger at the default (info) level
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
+
+// Applies -q/-v/-vv (verbosity -1 to 2) and --log-format
+pub fn configure(verbosity: i8, json: bool) {
+    let level = match verbosity {
+        i8::MIN..=-1 => LevelFilter::Error,
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    log::set_max_level(level);
+    JSON_FORMAT.store(json, Ordering::Relaxed);
+}
@@ -1,244 +1,2315 @@
 // src/main.rs
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::{self, File};
-use std::io::{self, Read, Write, BufRead, BufReader};
+use std::borrow::Cow;
+use std::cell::{Cell, OnceCell};
+use std::io::{self, Read, BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::ExitCode;
+use std::time::Instant;
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use log::{debug, error, info, trace, warn};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use ignore::WalkBuilder;
 use toml::Value;
-use walkdir::WalkDir;
 
-#[cfg(not(windows))]
-use clipboard::{ClipboardContext, ClipboardProvider};
-
-#[cfg(windows)]
-use clipboard_win::{Clipboard, formats, Getter, Setter};
+mod anonymize;
+mod apply;
+mod architecture;
+mod archive;
+mod backup;
+mod bazel;
+mod bench;
+mod budget;
+mod cache;
+mod cancel;
+mod chunks;
+mod clipboard;
+mod comments;
+mod config;
+mod confirm;
+mod contracts;
+mod coverage;
+mod daemon;
+mod data_sample;
+mod dep_source;
+mod dep_tree;
+mod diagnostics;
+mod docker;
+mod docs;
+mod editor;
+mod excerpt;
+mod expand;
+mod explain;
+mod file_meta;
+mod file_reader;
+mod formatter;
+mod front_matter;
+mod generated;
+mod git_diff;
+mod gradle;
+mod handlers;
+mod history;
+mod hooks;
+mod html;
+mod iac;
+mod imports;
+mod incremental;
+mod js_workspace;
+mod languages;
+mod llm;
+mod logging;
+mod manifest;
+mod markdown;
+mod migrations;
+mod omitted;
+mod outline;
+mod patch;
+mod path_spec;
+mod paths;
+mod presets;
+mod progress;
+mod prompt;
+mod provenance;
+mod public_api;
+mod redact;
+mod remote;
+mod review;
+mod sanitize;
+mod scratch;
+mod search;
+mod semantic;
+mod sensitive;
+mod session;
+mod sink;
+mod sql_schema;
+mod summary;
+mod swift;
+mod symbols;
+mod test_code;
+mod test_results;
+mod todos;
+mod tokens;
+mod transform;
+mod tree;
+mod truncation;
 
+#[derive(Clone)]
 struct FileEntry {
     path: String,
+    source_path: PathBuf,
     content: String,
+    hash: String,
+    // Lines of the original file kept by references, empty for whole files
+    line_ranges: Vec<(usize, usize)>,
+    // Line coverage from --with-coverage
+    coverage: Option<coverage::LineCoverage>,
+    // Size, modification date and last commit from --file-metadata
+    metadata: Option<file_meta::FileMetadata>,
+}
+
+
+#[derive(Args, Clone)]
+struct FormatOptions {
+    /// Include every file's content even when it is identical to another file
+    #[arg(long = "no-dedupe", action = ArgAction::SetFalse)]
+    dedupe: bool,
+    
+    /// Prefix each line of file content with its line number
+    #[arg(long)]
+    line_numbers: bool,
+    
+    /// Annotate the file structure with each file's language, line count and size
+    #[arg(long)]
+    stats: bool,
+    
+    /// Add each file's size, modification date and last commit to its <file> tag
+    #[arg(long)]
+    file_metadata: bool,
+    
+    /// Largest chunk --format jsonl writes, in tokens
+    #[arg(long, value_name = "TOKENS", default_value_t = 512)]
+    chunk_tokens: usize,
+    
+    /// Tokens of the previous chunk repeated at the start of each --format jsonl chunk
+    #[arg(long, value_name = "TOKENS", default_value_t = 64)]
+    overlap: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ProjectType {
+    Rust,
+    Python,
+    Swift,
+    Kotlin,
+    Infrastructure,
+    Unknown,
+}
+
+// Where deliver_output put the output
+enum Delivery {
+    Clipboard,
+    Stdout,
+    File(PathBuf),
+    // The clipboard rejected the output, so it was written to a temporary file
+    ClipboardFallback(PathBuf),
+    // POSTed to this URL
+    Http(String),
+    // Piped into this command
+    Command(String),
+}
+
+// The rendered output along with the files that went into it
+struct GeneratedOutput {
+    // Written before the rendered output, with --frontmatter
+    front_matter: Option<String>,
+    rendered: Rendered,
+    project_type: ProjectType,
+    files: Vec<FileEntry>,
+    skipped: Vec<summary::SkippedFile>,
+    // Hashes to record for --changed-since-last once the output is delivered
+    incremental: Option<incremental::Pending>,
+    // Applied while writing streamed output, and to the front matter
+    post_processing: PostProcessing,
+    // The whole text once a destination needed it, and the size once known,
+    // so the output is rendered at most once more after being streamed
+    text: OnceCell<String>,
+    size: Cell<Option<OutputSize>>,
+}
+
+// The complete output text, or for plain XML output the sections around the
+// files, which are rendered one at a time while writing to keep memory flat
+enum Rendered {
+    Text(String),
+    Streamed {
+        sections: ContextSections,
+        format: FormatOptions,
+    },
+}
+
+// Anonymization of the whole output, metadata included. The [redact] rules
+// are not run here: each text is redacted once, before it is rendered
+struct PostProcessing {
+    anonymizer: Option<anonymize::Anonymizer>,
+}
+
+impl PostProcessing {
+    fn apply(&self, text: &str) -> String {
+        match self.anonymizer {
+            Some(ref anonymizer) => anonymizer.apply(text),
+            None => text.to_string(),
+        }
+    }
+}
+
+// Size of the written output, for the summary
+#[derive(Debug, Default, Clone, Copy)]
+struct OutputSize {
+    bytes: usize,
+    chars: usize,
+}
+
+impl OutputSize {
+    fn add(&mut self, text: &str) {
+        self.bytes += text.len();
+        self.chars += text.chars().count();
+    }
+    
+    fn tokens(&self) -> usize {
+        tokens::estimate_chars(self.chars)
+    }
+}
+
+impl GeneratedOutput {
+    // Writes the output, rendering streamed files one at a time unless the
+    // whole text was already rendered
+    fn write_to(&self, writer: &mut dyn Write) -> io::Result<OutputSize> {
+        if let Some(text) = self.text.get() {
+            writer.write_all(text.as_bytes())?;
+            return Ok(self.size());
+        }
+        
+        let mut size = OutputSize::default();
+        if let Some(ref front_matter) = self.front_matter {
+            size.add(front_matter);
+            writer.write_all(front_matter.as_bytes())?;
+        }
+        match self.rendered {
+            Rendered::Text(ref text) => {
+                size.add(text);
+                writer.write_all(text.as_bytes())?;
+            }
+            Rendered::Streamed { ref sections, ref format } => {
+                let mut emit = |chunk: &str| -> io::Result<()> {
+                    let chunk = self.post_processing.apply(chunk);
+                    size.add(&chunk);
+                    writer.write_all(chunk.as_bytes())
+                };
+                emit("<project>\n")?;
+                emit(&sections.header)?;
+                emit(&sections.project_info)?;
+                emit(&sections.file_structure)?;
+                emit(&sections.extra)?;
+                write_file_entries(&self.files, format, &mut emit)?;
+                emit("</project>")?;
+            }
+        }
+        self.size.set(Some(size));
+        Ok(size)
+    }
+    
+    // The complete output, for the clipboard and other destinations that need it at once
+    fn text(&self) -> &str {
+        match self.rendered {
+            Rendered::Text(ref text) if self.front_matter.is_none() => text,
+            _ => self.text.get_or_init(|| {
+                let mut buffer = Vec::new();
+                let _ = self.write_to(&mut buffer);
+                String::from_utf8_lossy(&buffer).into_owned()
+            }),
+        }
+    }
+    
+    fn size(&self) -> OutputSize {
+        if let Some(size) = self.size.get() {
+            return size;
+        }
+        let size = match self.rendered {
+            Rendered::Text(ref text) if self.front_matter.is_none() => {
+                let mut size = OutputSize::default();
+                size.add(text);
+                size
+            }
+            _ => self.write_to(&mut io::sink()).unwrap_or_default(),
+        };
+        self.size.set(Some(size));
+        size
+    }
+    
+    // Puts the front matter before the output, growing the known size by it
+    fn set_front_matter(&mut self, front_matter: String) {
+        let mut size = self.size();
+        size.add(&front_matter);
+        self.size.set(Some(size));
+        self.text = OnceCell::new();
+        self.front_matter = Some(front_matter);
+    }
+}
+
+#[derive(Parser)]
+#[command(name = "llm-cocop-rs", version, about = "Copy code files to the clipboard in an LLM-friendly format")]
+#[command(args_conflicts_with_subcommands = true, args_override_self = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    
+    #[command(flatten)]
+    options: Options,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Build the context and send it to an LLM, streaming the answer to stdout
+    Ask(Box<llm::AskArgs>),
+    
+    /// List, show, re-copy or diff saved contexts
+    #[command(subcommand)]
+    History(history::HistoryCommand),
+    
+    /// Copy an overview of the project, then the files the model asks for
+    Session(Box<Options>),
+    
+    /// Show why a file is or is not copied: explain <FILE> [PATHS]... [OPTIONS]
+    Explain(Box<Options>),
+    
+    /// Split the selected files into chunks at function and class boundaries, as JSON lines for embedding and RAG pipelines
+    Chunks(Box<Options>),
+    
+    /// Rank the project's files by how well they match a query and show the matching lines
+    Search(Box<search::SearchArgs>),
+    
+    /// Record which files and content hashes went into a context, or verify the working tree against such a record
+    #[command(subcommand)]
+    Manifest(manifest::ManifestCommand),
+    
+    /// Write a model's reply or an editor-json document back to disk
+    Apply(apply::ApplyArgs),
+    
+    /// Restore the files changed by the last apply
+    Undo(backup::UndoArgs),
+    
+    /// Print the text on the clipboard, e.g. to pipe a model's reply into apply
+    Paste(clipboard::PasteArgs),
+    
+    /// Serve copy, stats and tree requests over a local socket with warm caches (Unix only)
+    Daemon(daemon::DaemonArgs),
+    
+    /// Print a shell completion script
+    Completions {
+        shell: Shell,
+    },
+}
+
+// Command-line options shared by the copy and ask modes
+#[derive(Args)]
+struct Options {
+    /// Files and directories to copy
+    #[arg(value_name = "PATHS")]
+    paths: Vec<String>,
+    
+    /// Use this Cargo.toml for project metadata
+    #[arg(long = "cargo-toml", value_name = "PATH")]
+    cargo_toml_path: Option<String>,
+    
+    /// Use this pyproject.toml/setup.py/requirements.txt for project metadata
+    #[arg(long = "pyproject", value_name = "PATH")]
+    pyproject_path: Option<String>,
+    
+    #[command(flatten)]
+    format: FormatOptions,
+    
+    /// Wrap the output in a prompt template (a file path or the template text itself)
+    #[arg(long = "prompt", value_name = "FILE_OR_TEMPLATE")]
+    prompt_template: Option<String>,
+    
+    /// Question to include in the prompt
+    #[arg(long)]
+    question: Option<String>,
+    
+    /// Append instructions for a task: review, explain, refactor, write-tests, find-bugs or one from the config
+    #[arg(long)]
+    task: Option<String>,
+    
+    /// Append instructions for the format of the model's reply, readable by the apply subcommand
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    reply_format: Option<apply::ReplyFormat>,
+    
+    /// Use this config file instead of the user and project config files
+    #[arg(long = "config", value_name = "PATH")]
+    config_path: Option<String>,
+    
+    /// Prepend the arguments of a [profiles.<name>] entry from the config
+    #[arg(long)]
+    profile: Option<String>,
+    
+    /// Replace the project name, root directory names and the identifiers listed in FILE with placeholders
+    #[arg(long, value_name = "FILE", num_args = 0..=1, require_equals = true)]
+    anonymize: Option<Option<String>>,
+    
+    /// Add a section listing the files that were left out, with their size, language and first doc comment
+    #[arg(long)]
+    list_omitted: bool,
+    
+    /// Save the generated context to the history
+    #[arg(long)]
+    save_history: bool,
+    
+    /// Include only files that changed since the previous run with this flag
+    #[arg(long)]
+    changed_since_last: bool,
+    
+    /// Skip test directories, test files and #[cfg(test)] items
+    #[arg(long, conflicts_with = "tests_only")]
+    no_tests: bool,
+    
+    /// Include only tests and the source files they import
+    #[arg(long)]
+    tests_only: bool,
+    
+    /// Include only Protobuf definitions and OpenAPI/Swagger specs
+    #[arg(long, conflicts_with = "tests_only")]
+    contracts_only: bool,
+    
+    /// Copy only the files that best match this search query (see the search subcommand)
+    #[arg(long, value_name = "QUERY")]
+    select_from_search: Option<String>,
+    
+    /// Copy the files whose contents are most similar to this question, using embeddings from the [embeddings] provider
+    #[arg(long, value_name = "QUESTION", conflicts_with = "select_from_search")]
+    semantic: Option<String>,
+    
+    /// How many files --select-from-search and --semantic (without --max-tokens) copy and search lists
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    search_results: usize,
+    
+    /// Framework preset whose rules skip noise and keep its key files first with --max-tokens (axum, django, react, bevy or a [presets] entry)
+    #[arg(long, value_name = "NAME")]
+    preset: Option<String>,
+    
+    /// Add a Mermaid graph of the dependencies between the included files
+    #[arg(long)]
+    architecture: bool,
+    
+    /// Reduce Rust sources to their public API
+    #[arg(long)]
+    public_api: bool,
+    
+    /// Reduce Rust and Python sources to their doc comments and docstrings with the signatures they document
+    #[arg(long, conflicts_with = "public_api")]
+    docs_only: bool,
+    
+    /// Outline the definitions of each file with their line numbers, in place of (replace) or alongside its contents
+    #[arg(long, value_name = "MODE", num_args = 0..=1, require_equals = true, default_missing_value = "replace", conflicts_with_all = ["public_api", "docs_only"])]
+    outline: Option<outline::Mode>,
+    
+    /// Add the `cargo expand` output of the module defined in this Rust file (repeatable)
+    #[arg(long, value_name = "FILE")]
+    expand: Vec<PathBuf>,
+    
+    /// Whether --expand output is added alongside the files or replaces their contents
+    #[arg(long, value_enum, value_name = "MODE", default_value = "alongside", requires = "expand")]
+    expand_mode: expand::Mode,
+    
+    /// Copy this package of the pnpm, Yarn, npm or Nx workspace around the paths, and the workspace packages it depends on (repeatable)
+    #[arg(long = "package", value_name = "NAME")]
+    packages: Vec<String>,
+    
+    /// Copy the sources of this Bazel or Buck target (//path:label) and of its direct dependencies (repeatable)
+    #[arg(long = "target", value_name = "LABEL", conflicts_with = "packages")]
+    targets: Vec<String>,
+    
+    /// Also copy the source of this dependency from the Cargo registry (repeatable)
+    #[arg(long = "with-dep-source", value_name = "CRATE[@VERSION]")]
+    dep_sources: Vec<String>,
+    
+    /// Add the resolved dependency tree (cargo tree, pip freeze or npm ls) to the project info
+    #[arg(long = "with-dep-tree", value_name = "DEPTH", num_args = 0..=1, require_equals = true, default_missing_value = "1")]
+    dep_tree_depth: Option<usize>,
+    
+    /// Add cargo check (or clippy) diagnostics
+    #[arg(long = "with-diagnostics", value_name = "TOOL", num_args = 0..=1, require_equals = true, default_missing_value = "check")]
+    diagnostics: Option<diagnostics::Tool>,
+    
+    /// Add the output of failing tests, optionally only those matching a filter
+    #[arg(long, value_name = "FILTER", num_args = 0..=1, require_equals = true)]
+    with_test_output: Option<Option<String>>,
+    
+    /// Add criterion benchmark results, saved ones (target/criterion or DIR) or from running cargo bench
+    #[arg(long, value_name = "DIR|run[:FILTER]", num_args = 0..=1, require_equals = true, default_missing_value = "", value_parser = bench::parse_source)]
+    with_bench: Option<bench::BenchSource>,
+    
+    /// Annotate the file structure and file headers with line coverage from an lcov or Cobertura report
+    #[arg(long, value_name = "FILE")]
+    with_coverage: Option<PathBuf>,
+    
+    /// Add a list of the TODO, FIXME and HACK comments in the copied files with their locations
+    #[arg(long)]
+    with_todos: bool,
+    
+    /// Read additional paths from a file, or stdin with -
+    #[arg(long, value_name = "FILE")]
+    files_from: Option<String>,
+    
+    /// Paths in --files-from and --list-files are NUL-delimited
+    #[arg(short = '0', long = "null")]
+    null_delimited: bool,
+    
+    /// Print the paths of the files that would be included instead of copying them
+    #[arg(long)]
+    list_files: bool,
+    
+    /// Output format
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        default_value = "xml",
+        value_parser = clap::builder::PossibleValuesParser::new(formatter::names())
+    )]
+    output_format: String,
+    
+    /// Git ref that --format diff compares the working tree against
+    #[arg(long, value_name = "REF")]
+    against: Option<String>,
+    
+    /// With --format diff, include files added since the ref or untracked in full instead of in the diff
+    #[arg(long)]
+    with_new_files: bool,
+    
+    /// Also place an HTML flavor on the clipboard: the output in <pre>, or with highlighted file contents
+    #[arg(long, value_enum, value_name = "STYLE", num_args = 0..=1, require_equals = true, default_missing_value = "pre")]
+    clipboard_html: Option<html::ClipboardStyle>,
+    
+    /// How <file path> values are computed (by default relative to each copied directory, or as given for files)
+    #[arg(long, value_enum, value_name = "BASE")]
+    path_base: Option<paths::PathBase>,
+    
+    /// Write the output to this file (or stdout for -) instead of the clipboard
+    #[arg(long = "output", value_name = "FILE")]
+    output_path: Option<String>,
+    
+    /// Write the output to a temporary file instead of trying the clipboard
+    #[arg(long)]
+    no_clipboard: bool,
+    
+    /// Pipe the output into this shell command instead of copying it, e.g. 'llm -m gpt-4o'
+    #[arg(long, value_name = "COMMAND", conflicts_with_all = ["output_path", "no_clipboard", "post_to"])]
+    pipe_to: Option<String>,
+    
+    /// POST the output to this URL instead of copying it
+    #[arg(long, value_name = "URL", conflicts_with_all = ["output_path", "no_clipboard"])]
+    post_to: Option<String>,
+    
+    /// Also deliver the output to clipboard, - (stdout), an http(s) URL, pipe:<COMMAND> or a file (repeatable)
+    #[arg(long, value_name = "DEST", value_parser = sink::parse_destination)]
+    tee: Vec<sink::Destination>,
+    
+    /// Start the output with a YAML front matter block (project, commit, date, file count, tokens)
+    #[arg(long)]
+    frontmatter: bool,
+    
+    /// Add a <provenance> section with the repository URL, commit, licenses and a timestamp
+    #[arg(long)]
+    provenance: bool,
+    
+    /// Show a summary and a preview of every file, and ask before copying
+    #[arg(long)]
+    confirm: bool,
+    
+    /// When stopped with Ctrl-C, still copy the files collected so far, marked as incomplete
+    #[arg(long)]
+    partial_on_cancel: bool,
+    
+    /// Copy files matching the sensitive path list (.env, keys, credentials, ...) instead of refusing
+    #[arg(long)]
+    force: bool,
+    
+    /// Copy the values in Terraform *.tfvars files instead of replacing them with [REDACTED]
+    #[arg(long)]
+    no_redact_tfvars: bool,
+    
+    /// How to copy diesel, sqlx, Flyway and Alembic migration directories: as the schema they build, as the last N migrations, or all of them
+    #[arg(long, value_name = "schema|last[:N]|all", default_value = "schema", value_parser = migrations::parse_mode)]
+    migrations: migrations::Mode,
+    
+    /// Write a JSON report of the run (files, sizes, tokens, skipped files, duration, exit code) to this file
+    #[arg(long, value_name = "FILE")]
+    summary_json: Option<String>,
+    
+    /// Run every file through these stages in order: redact, strip-comments, truncate[:LINES], normalize
+    #[arg(long, value_name = "STAGES", value_delimiter = ',', value_parser = transform::parse_stage)]
+    transforms: Vec<transform::Stage>,
+    
+    /// Include files over 100KB truncated instead of skipping them
+    #[arg(long, value_enum, value_name = "STRATEGY")]
+    truncate_large: Option<truncation::Strategy>,
+    
+    /// Drop files until the file contents fit in about this many tokens
+    #[arg(long, value_name = "TOKENS")]
+    max_tokens: Option<usize>,
+    
+    /// Which files to keep first with --max-tokens
+    #[arg(long, value_enum, value_name = "HEURISTIC", default_value = "path", requires = "max_tokens")]
+    rank: budget::Rank,
+    
+    /// Fail instead of leaving files out: exit code 4 when files do not fit in --max-tokens, 6 when files or directories cannot be read
+    #[arg(long)]
+    strict: bool,
+    
+    /// Leave out files and directories that cannot be read without warning about each of them
+    #[arg(long, conflicts_with = "strict")]
+    skip_errors: bool,
+    
+    /// Sample CSV/TSV/JSON data files, keeping this many rows (10 by default)
+    #[arg(long = "sample-data", value_name = "ROWS", num_args = 0..=1, require_equals = true)]
+    sample_data: Option<Option<usize>>,
+    
+    /// Include files marked as generated ("DO NOT EDIT", "@generated", ...), which are skipped by default
+    #[arg(long)]
+    include_generated: bool,
+    
+    /// Keep ANSI escape sequences and control characters in file contents instead of removing them
+    #[arg(long)]
+    keep_control_chars: bool,
+    
+    /// Recompute per-file passes (--public-api, --docs-only, --outline, --no-tests) instead of using the on-disk cache
+    #[arg(long)]
+    no_cache: bool,
+    
+    /// Leave out the Cargo.toml/pyproject.toml metadata
+    #[arg(long)]
+    no_project_info: bool,
+    
+    /// Put the contents of this file at the top of the project block
+    #[arg(long = "header", value_name = "FILE")]
+    header_path: Option<String>,
+    
+    /// Include dotfiles and dot-directories
+    #[arg(long)]
+    hidden: bool,
+    
+    /// Ignore the .codecopier-paths file that otherwise selects which files of a directory are copied
+    #[arg(long)]
+    no_path_spec: bool,
+    
+    /// Only descend this many levels into each directory
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+    
+    /// Skip files fewer than this many levels below each directory
+    #[arg(long, value_name = "N")]
+    min_depth: Option<usize>,
+    
+    /// Stop with an error when more than this many files are selected, instead of asking past 5000 files
+    #[arg(long, value_name = "N")]
+    max_files: Option<usize>,
+    
+    /// Print nothing except errors
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+    
+    /// Log included files (-v) and exclusion decisions (-vv)
+    #[arg(short, long, action = ArgAction::Count)]
+    verbose: u8,
+    
+    /// Format of log messages on stderr
+    #[arg(long, value_name = "FORMAT", value_parser = ["text", "json"], default_value = "text")]
+    log_format: String,
+    
+    /// Do not show a progress bar, even when the copy takes more than a second
+    #[arg(long)]
+    no_progress: bool,
+    
+    // Set by `session`: list every file without its contents
+    #[arg(skip)]
+    overview_only: bool,
+    
+    // Set by `session`: include only these relative paths
+    #[arg(skip)]
+    only_paths: Option<Vec<String>>,
+    
+    // `file:line[:column]` and `file::symbol` arguments, resolved from the paths
+    #[arg(skip)]
+    references: Vec<excerpt::Reference>,
+}
+
+impl Options {
+    // -1 with -q, 1 with -v, 2 with -vv
+    fn verbosity(&self) -> i8 {
+        if self.quiet {
+            -1
+        } else {
+            self.verbose.min(2) as i8
+        }
+    }
+    
+    fn configure_logging(&self) {
+        logging::configure(self.verbosity(), self.log_format == "json");
+    }
+    
+    fn sample_rows(&self) -> Option<usize> {
+        self.sample_data.map(|rows| rows.unwrap_or(data_sample::DEFAULT_ROWS))
+    }
+}
+
+// Gitignore-syntax file for excluding paths from copies only, read at any directory level
+const IGNORE_FILE: &str = ".copierignore";
+
+// Selecting more files than this without --max-files asks before going on,
+// in case the tool was pointed at a home directory or a whole disk
+const FILE_COUNT_PROMPT: usize = 5000;
+
+// Files larger than this are skipped, or truncated with --truncate-large
+const MAX_FILE_SIZE: usize = 100 * 1024;
+
+// Exit codes scripts can check for; errors exit with 1
+const EXIT_NOTHING_MATCHED: u8 = 2;
+const EXIT_CLIPBOARD_FAILED: u8 = 3;
+const EXIT_BUDGET_EXCEEDED: u8 = 4;
+const EXIT_UNREADABLE: u8 = 6;
+
+// Set by the cargo-copier wrapper when running as `cargo copier`
+const CARGO_SUBCOMMAND_ENV: &str = "CODECOPIER_CARGO_SUBCOMMAND";
+
+fn main() -> io::Result<ExitCode> {
+    let started = Instant::now();
+    let args: Vec<String> = env::args().collect();
+    logging::init();
+    let cargo_subcommand = env::var_os(CARGO_SUBCOMMAND_ENV).is_some();
+    
+    if args.len() < 2 && !cargo_subcommand {
+        let _ = Cli::command().print_help();
+        return Ok(ExitCode::SUCCESS);
+    }
+    
+    let cli = parse_cli(&args)?;
+    let mut options = match cli.command {
+        Some(Command::Ask(ask)) => return llm::run_ask(*ask).map(|()| ExitCode::SUCCESS),
+        Some(Command::History(ref command)) => return history::run(command).map(|()| ExitCode::SUCCESS),
+        Some(Command::Session(options)) => return session::run(*options).map(|()| ExitCode::SUCCESS),
+        Some(Command::Explain(options)) => return explain::run(*options).map(|()| ExitCode::SUCCESS),
+        Some(Command::Search(args)) => return search::run(*args).map(|()| ExitCode::SUCCESS),
+        Some(Command::Manifest(command)) => return manifest::run(command),
+        Some(Command::Apply(args)) => return apply::run_apply(args).map(|()| ExitCode::SUCCESS),
+        Some(Command::Undo(args)) => return backup::run_undo(args).map(|()| ExitCode::SUCCESS),
+        Some(Command::Paste(args)) => return clipboard::run_paste(args).map(|()| ExitCode::SUCCESS),
+        Some(Command::Daemon(args)) => return daemon::run(args).map(|()| ExitCode::SUCCESS),
+        Some(Command::Completions { shell }) => return print_completions(shell).map(|()| ExitCode::SUCCESS),
+        Some(Command::Chunks(options)) => chunks::copy_options(*options)?,
+        None => cli.options,
+    };
+    options.configure_logging();
+    let _scratch_dirs = resolve_inputs(&mut options)?;
+    
+    info!("Processing paths...");
+    
+    // `cargo copier` without paths copies the whole workspace
+    if options.paths.is_empty() && cargo_subcommand {
+        match cargo_workspace_root() {
+            Some(root) => options.paths.push(root.to_string_lossy().to_string()),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "Could not locate a Cargo workspace; pass the paths to copy explicitly",
+                ));
+            }
+        }
+    }
+    let config = config::load(&options.paths, options.config_path.as_deref())?;
+    
+    let _progress = progress::start(!options.no_progress && !options.quiet && options.log_format == "text");
+    cancel::install();
+    let output = generate_output(&options, &config)?;
+    // Ctrl-C during delivery does not stop it, so the destination is never half-written
+    let cancelled = cancel::requested();
+    let mut exit_code = if cancelled {
+        cancel::EXIT_CANCELLED
+    } else if output.files.is_empty() && !output.skipped.iter().any(|file| file.reason == summary::SkipReason::Folded) {
+        // Migrations folded into a summary still matched
+        EXIT_NOTHING_MATCHED
+    } else {
+        0
+    };
+    let mut delivery = None;
+    let mut size = None;
+    
+    let dropped = output.skipped.iter().filter(|file| file.reason == summary::SkipReason::OverBudget).count();
+    let unreadable = output.skipped.iter().filter(|file| file.reason == summary::SkipReason::ReadError).count();
+    // Only delivering the output reports progress from here on
+    let delivers = !options.list_files
+        && !(options.strict && (unreadable > 0 || dropped > 0))
+        && (!cancelled || options.partial_on_cancel);
+    if !delivers {
+        progress::finish();
+    }
+    if cancelled && !options.partial_on_cancel {
+        eprintln!(
+            "Cancelled after collecting {} files, nothing was copied (--partial-on-cancel copies them)",
+            output.files.len()
+        );
+    } else if options.strict && unreadable > 0 {
+        error!("{} files or directories could not be read and --strict is set", unreadable);
+        exit_code = EXIT_UNREADABLE;
+    } else if options.strict && dropped > 0 {
+        error!("{} files do not fit in --max-tokens and --strict is set", dropped);
+        exit_code = EXIT_BUDGET_EXCEEDED;
+    } else if options.list_files {
+        let separator = if options.null_delimited { '\0' } else { '\n' };
+        let mut manifest = String::new();
+        for file in &output.files {
+            manifest.push_str(&file.source_path.to_string_lossy());
+            manifest.push(separator);
+        }
+        print!("{}", manifest);
+    } else if options.confirm && !progress::suspend(|| confirm_delivery(&options, &output))? {
+        progress::finish();
+        eprintln!("Cancelled, nothing was copied");
+    } else {
+        let (delivered, written) = deliver_output(&options, &output)?;
+        if delivered.iter().any(|delivery| matches!(delivery, Delivery::ClipboardFallback(_))) {
+            exit_code = EXIT_CLIPBOARD_FAILED;
+        }
+        // --tee destinations are not part of the summary
+        delivery = delivered.into_iter().next();
+        size = Some(written);
+        if let Some(ref pending) = output.incremental {
+            pending.save(&output.files)?;
+        }
+        
+        if options.save_history {
+            let id = history::save(&args, &output)?;
+            info!("Saved to history as {}", id);
+        }
+    }
+    
+    if let Some(ref path) = options.summary_json {
+        let destination = match delivery {
+            Some(Delivery::Clipboard) => json!({ "type": "clipboard" }),
+            Some(Delivery::Stdout) => json!({ "type": "stdout" }),
+            Some(Delivery::File(ref path)) => json!({ "type": "file", "path": path }),
+            Some(Delivery::ClipboardFallback(ref path)) => json!({ "type": "temp_file", "path": path }),
+            Some(Delivery::Http(ref url)) => json!({ "type": "http", "url": url }),
+            Some(Delivery::Command(ref command)) => json!({ "type": "command", "command": command }),
+            None => serde_json::Value::Null,
+        };
+        let size = size.unwrap_or_else(|| output.size());
+        summary::write_json(
+            Path::new(path),
+            &output.files,
+            &output.skipped,
+            (size.bytes, size.tokens()),
+            exit_code,
+            started.elapsed(),
+            destination,
+        )?;
+    }
+    
+    Ok(ExitCode::from(exit_code))
+}
+
+// Asks whether to go ahead with the copy after previewing it
+fn confirm_delivery(options: &Options, output: &GeneratedOutput) -> io::Result<bool> {
+    let destination = sink::from_options(options).iter().map(|sink| sink.describe()).collect::<Vec<_>>().join(" and ");
+    let preview = confirm::preview(&output.files, &output.skipped, output.size().tokens(), &destination);
+    confirm::ask(&preview, "Continue?")
+}
+
+fn deliver_output(options: &Options, output: &GeneratedOutput) -> io::Result<(Vec<Delivery>, OutputSize)> {
+    let sinks = sink::from_options(options);
+    let quiet = options.quiet || sinks.iter().any(|sink| sink.uses_stdout());
+    let mut deliveries = Vec::new();
+    let mut size = None;
+    let streamed = matches!(output.rendered, Rendered::Streamed { .. });
+    for sink in &sinks {
+        progress::stage("Writing", streamed.then_some(output.files.len()));
+        let (delivery, written) = sink.deliver(output, quiet)?;
+        deliveries.push(delivery);
+        // Every sink gets the same output; the first one measures it
+        size.get_or_insert(written);
+    }
+    let size = size.unwrap_or_default();
+    progress::finish();
+    
+    if !quiet {
+        let project_type = match output.project_type {
+            ProjectType::Rust => "Rust",
+            ProjectType::Python => "Python",
+            ProjectType::Swift => "Swift",
+            ProjectType::Kotlin => "Kotlin",
+            ProjectType::Infrastructure => "Infrastructure as code",
+            ProjectType::Unknown => "Unknown",
+        };
+        summary::print_summary(&output.files, &output.skipped, project_type, size.bytes, size.tokens());
+    }
+    
+    Ok((deliveries, size))
+}
+
+// Collects the files and renders the full output text
+fn generate_output(options: &Options, config: &config::Config) -> io::Result<GeneratedOutput> {
+    let formatter = formatter::by_name(&options.output_format).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown output format {}", options.output_format))
+    })?;
+    let uses_prompt = options.prompt_template.is_some()
+        || options.question.is_some()
+        || options.task.is_some()
+        || options.reply_format.is_some();
+    if !formatter.prompts() && uses_prompt {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "--format {} cannot be combined with --prompt, --question, --task or --reply-format",
+                formatter.name()
+            ),
+        ));
+    }
+    
+    if options.frontmatter && !formatter.front_matter() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("--format {} cannot be combined with --frontmatter", formatter.name()),
+        ));
+    }
+    if (options.against.is_some() || options.with_new_files) && !formatter.compares_to_ref() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--against and --with-new-files require --format diff",
+        ));
+    }
+    
+    cache::set_disabled(options.no_cache);
+    
+    // Resolve the task preset before doing any work
+    let task_text = match options.task {
+        Some(ref name) => match prompt::task_text(name, config) {
+            Some(text) => Some(text),
+            None => {
+                let mut available: Vec<String> = prompt::task_names().iter().map(|n| n.to_string()).collect();
+                for custom in config.tasks.keys() {
+                    if !available.contains(custom) {
+                        available.push(custom.clone());
+                    }
+                }
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown task '{}'. Available tasks: {}", name, available.join(", ")),
+                ));
+            }
+        },
+        None => None,
+    };
+    
+    // Collect all files from specified paths
+    let handlers = handlers::Handlers::from_config(&config.handlers)?;
+    let hooks = hooks::Hooks::load(&config.hooks)?;
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    for path_str in &options.paths {
+        if cancel::requested() {
+            break;
+        }
+        collect_files_from_path(path_str, &mut files, &mut skipped, options, &config.hidden.allow, &handlers, hooks.as_ref())?;
+    }
+    let interrupted = cancel::requested();
+    if interrupted && options.partial_on_cancel {
+        warn!("Interrupted after {} files, copying them as an incomplete context", files.len());
+    }
+    progress::stage("Processing", None);
+    let unreadable = skipped.iter().filter(|file| file.reason == summary::SkipReason::ReadError).count();
+    if unreadable > 0 && !options.skip_errors {
+        warn!(
+            "{} files or directories could not be read and were left out (--skip-errors hides these warnings, --strict fails instead)",
+            unreadable
+        );
+    }
+    
+    // Files named by `file:line` or `file::symbol` references are cut down to those items
+    excerpt::apply(&mut files, &options.references)?;
+    
+    // Drop what the framework preset considers noise
+    let preset = match options.preset {
+        Some(ref name) => Some(presets::load(name, config)?),
+        None => None,
+    };
+    if let Some(ref preset) = preset {
+        let (dropped, kept): (Vec<FileEntry>, Vec<FileEntry>) = files.into_iter().partition(|file| preset.skips(&file.path));
+        files = kept;
+        for file in dropped {
+            skipped.push(summary::SkippedFile {
+                path: file.path,
+                source_path: file.source_path,
+                reason: summary::SkipReason::Filtered,
+            });
+        }
+        let important = files.iter().filter(|file| preset.is_important(&file.path)).count();
+        info!("{} of {} files are key files for the {} preset", important, files.len(), preset.name);
+    }
+    
+    // Narrow the selection down to the best matches of the query
+    if let Some(ref query) = options.select_from_search {
+        let hits = search::rank(&files, query);
+        let mut selected = vec![false; files.len()];
+        for hit in hits.iter().take(options.search_results) {
+            selected[hit.index] = true;
+        }
+        info!("{} files match '{}', copying {}", hits.len(), query, hits.len().min(options.search_results));
+        keep_selected(&mut files, &mut skipped, &selected);
+    }
+    
+    // Or to the files most similar to the question, as many as fit in the budget.
+    // The chunks are sent to the embedding provider, so they are redacted first
+    // and sensitive files are not embedded at all
+    if let Some(ref question) = options.semantic {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        let redaction = transform::Pipeline::new(&[], redact::Redactor::from_config(&config.redact)?, !options.no_redact_tfvars);
+        let sensitive = sensitive::SensitivePaths::from_config(&config.sensitive)?.matching(&files);
+        let mut embedded = Vec::new();
+        let mut indexes = Vec::new();
+        for (index, file) in files.iter().enumerate() {
+            if sensitive.contains(&file.path) {
+                continue;
+            }
+            let mut file = file.clone();
+            redaction.apply(&mut file);
+            embedded.push(file);
+            indexes.push(index);
+        }
+        let hits = semantic::rank(&embedded, question, config, &project_root)?;
+        let mut selected = vec![false; files.len()];
+        match options.max_tokens {
+            Some(max_tokens) => {
+                let mut used = 0;
+                for hit in &hits {
+                    let size = budget::file_tokens(&files[indexes[hit.index]]);
+                    if used + size <= max_tokens {
+                        used += size;
+                        selected[indexes[hit.index]] = true;
+                    }
+                }
+            }
+            None => {
+                for hit in hits.iter().take(options.search_results) {
+                    selected[indexes[hit.index]] = true;
+                }
+            }
+        }
+        info!("Copying the {} files most relevant to the question", selected.iter().filter(|&&s| s).count());
+        keep_selected(&mut files, &mut skipped, &selected);
+    }
+    
+    // Detect project type and extract metadata
+    let (project_type, project_info) = detect_project_type_and_extract_info(
+        &options.paths,
+        options.cargo_toml_path.clone(),
+        options.pyproject_path.clone(),
+    );
+    let project_name = project_info
+        .as_deref()
+        .and_then(|info| info.lines().find_map(|line| line.strip_prefix("Project Name: ")))
+        .map(|name| name.trim().to_string());
+    
+    // A `session` round includes only the files the model asked for
+    if let Some(ref requested) = options.only_paths {
+        files.retain(|file| requested.contains(&file.path));
+    }
+    
+    // Narrow the selection down to tests and the sources they reference
+    if options.tests_only {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        let candidates: Vec<(String, PathBuf)> = files.iter().map(|f| (f.path.clone(), f.source_path.clone())).collect();
+        files = test_code::select_tests_with_references(files, &project_root);
+        let selected: HashSet<&PathBuf> = files.iter().map(|file| &file.source_path).collect();
+        for (path, source_path) in candidates {
+            if !selected.contains(&source_path) {
+                skipped.push(summary::SkippedFile { path, source_path, reason: summary::SkipReason::Filtered });
+            }
+        }
+    }
+    
+    // Dockerfiles and Compose files in the copied directories are small and
+    // tell how the project runs, so they are copied whatever the filters say
+    let mut deployment_files = Vec::new();
+    for dir in options.paths.iter().filter(|path| Path::new(path).is_dir()) {
+        let base = display_base(dir, options);
+        for path in docker::find_files(Path::new(dir)) {
+            if options.only_paths.is_none() && !files.iter().any(|file| file.source_path == path) {
+                debug!("Including deployment file {}", path.display());
+                skipped.retain(|file| file.source_path != path);
+                process_file(&path, base.as_deref(), &mut files, &mut skipped, options, &handlers, hooks.as_ref())?;
+            }
+            deployment_files.push((display_path(&path, base.as_deref(), options), path));
+        }
+    }
+    
+    // Protobuf and OpenAPI contracts define the interfaces most questions
+    // hinge on, so they are copied whatever the filters say and come first
+    for dir in options.paths.iter().filter(|path| Path::new(path).is_dir() && options.only_paths.is_none()) {
+        let base = display_base(dir, options);
+        for path in contracts::find_files(Path::new(dir)) {
+            if !files.iter().any(|file| file.source_path == path) {
+                debug!("Including contract {}", path.display());
+                skipped.retain(|file| file.source_path != path);
+                process_file(&path, base.as_deref(), &mut files, &mut skipped, options, &handlers, hooks.as_ref())?;
+            }
+        }
+    }
+    let contract_paths: Vec<String> = files
+        .iter()
+        .filter(|file| contracts::is_contract(&file.source_path, &file.content))
+        .map(|file| file.path.clone())
+        .collect();
+    if options.contracts_only {
+        let (kept, dropped): (Vec<FileEntry>, Vec<FileEntry>) = files.into_iter().partition(|file| contract_paths.contains(&file.path));
+        files = kept;
+        for file in dropped {
+            skipped.push(summary::SkippedFile { path: file.path, source_path: file.source_path, reason: summary::SkipReason::Filtered });
+        }
+        if files.is_empty() {
+            warn!("No .proto files or OpenAPI/Swagger specs found");
+        }
+    }
+    files.sort_by_key(|file| !contract_paths.contains(&file.path));
+    
+    // Fold runs of migrations into the schema they build or the latest few
+    let folded = migrations::fold(&mut files, options.migrations);
+    for file in folded.files {
+        skipped.push(summary::SkippedFile { path: file.path, source_path: file.source_path, reason: summary::SkipReason::Folded });
+    }
+    
+    // Keep only the files that changed since the previous run in this project
+    let mut unchanged_files = Vec::new();
+    let mut incremental_state = None;
+    if options.changed_since_last {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        let change_set = incremental::split_changed(&project_root, files)?;
+        if change_set.first_run {
+            info!("No previous run recorded for {}; including all files", project_root.display());
+        } else {
+            info!("{} changed files, {} unchanged", change_set.changed.len(), change_set.unchanged.len());
+        }
+        files = change_set.changed;
+        for (path, source_path) in change_set.unchanged {
+            unchanged_files.push(path.clone());
+            skipped.push(summary::SkippedFile { path, source_path, reason: summary::SkipReason::Unchanged });
+        }
+        incremental_state = Some(change_set.pending);
+    }
+    
+    // Keep only the files that differ from the ref, which the diff stands in for
+    let against = options.against.as_deref().unwrap_or("HEAD");
+    let mut working_tree_diff = None;
+    if formatter.compares_to_ref() {
+        let diff = git_diff::collect(&files, against, options.with_new_files, !options.no_redact_tfvars)?;
+        let selected = files.len();
+        files.retain(|file| diff.changed.contains(&file.path) || diff.new_files.contains(&file.path));
+        info!("{} of {} files differ from {}", files.len(), selected, against);
+        if diff.untracked > 0 {
+            info!("{} untracked files are not in the diff; use --with-new-files to include them", diff.untracked);
+        }
+        working_tree_diff = Some(diff);
+    }
+    
+    if let Some(ref report_path) = options.with_coverage {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        let report = coverage::load(report_path, &project_root)?;
+        for file in &mut files {
+            file.coverage = report.get(&file.source_path);
+        }
+        let matched = files.iter().filter(|file| file.coverage.is_some()).count();
+        if matched == 0 {
+            warn!("None of the copied files appear in {}", report_path.display());
+        } else {
+            info!("Coverage found for {} of {} files", matched, files.len());
+        }
+    }
+    
+    if options.format.file_metadata {
+        file_meta::collect(&mut files);
+    }
+    
+    // Comments are collected before sources are reduced, with their real line numbers
+    let todo_comments = if options.with_todos { Some(todos::collect(&files)) } else { None };
+    
+    // The dependency graph is built from the full sources
+    let mut architecture_graph = None;
+    if options.architecture {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        architecture_graph = architecture::dependency_graph(&files, &project_root);
+    }
+    
+    // Reduce Rust sources to their public items
+    if options.public_api {
+        files = public_api::extract(files);
+    }
+    
+    // Or to their documentation
+    if options.docs_only {
+        files = docs::extract(files);
+    }
+    
+    // Or to an outline of their definitions
+    let mut outline_section = None;
+    match options.outline {
+        Some(outline::Mode::Replace) => files = outline::extract(files),
+        Some(outline::Mode::Alongside) => outline_section = Some(outline::format_outline_section(&files)),
+        None => {}
+    }
+    
+    // Macro-heavy modules expanded by cargo expand
+    let mut expanded_section = None;
+    if !options.expand.is_empty() {
+        let mut expansions = Vec::new();
+        for path in &options.expand {
+            info!("Running cargo expand for {}...", path.display());
+            expansions.push(expand::expand(path)?);
+        }
+        if options.expand_mode == expand::Mode::Replace {
+            expansions = expand::replace(&mut files, expansions);
+        }
+        if !expansions.is_empty() {
+            expanded_section = Some(expand::format_expanded_section(&expansions));
+        }
+    }
+    
+    // The --transforms stages, with the [redact] rules applied to file contents
+    // so saved history is redacted too. Nothing before this point may send
+    // file contents out of the process without redacting them the same way.
+    // Every other text in the output is redacted once as it is added, since
+    // the rules need not give the same result when run twice
+    let redactor = redact::Redactor::from_config(&config.redact)?;
+    let pipeline = transform::Pipeline::new(&options.transforms, redactor.clone(), !options.no_redact_tfvars);
+    if !pipeline.is_empty() {
+        for file in &mut files {
+            pipeline.apply(file);
+        }
+    }
+    
+    // Trim to the token budget once the contents are final
+    if let Some(max_tokens) = options.max_tokens {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        let areas = budget::areas(&config.budget)?;
+        for file in budget::trim(&mut files, max_tokens, options.rank, &areas, &project_root, &|path| {
+            preset.as_ref().is_some_and(|preset| preset.is_important(path)) || contract_paths.iter().any(|contract| contract == path)
+        }) {
+            skipped.push(summary::SkippedFile {
+                path: file.path,
+                source_path: file.source_path,
+                reason: summary::SkipReason::OverBudget,
+            });
+        }
+    }
+    
+    // Refuse to copy likely secrets unless forced, whatever the redaction rules
+    if !options.overview_only {
+        let sensitive = sensitive::SensitivePaths::from_config(&config.sensitive)?.matching(&files);
+        if !sensitive.is_empty() {
+            if !options.force {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!(
+                        "The selection includes sensitive files: {}. Pass --force to copy them anyway",
+                        sensitive.join(", ")
+                    ),
+                ));
+            }
+            for path in &sensitive {
+                warn!("Copying sensitive file {}", path);
+            }
+        }
+    }
+    
+    // The paths are redacted once the sensitive files are found by their real names
+    if !redactor.is_empty() {
+        for file in &mut files {
+            file.path = redactor.apply(&file.path);
+        }
+        for file in &mut skipped {
+            file.path = redactor.apply(&file.path);
+        }
+        for path in &mut unchanged_files {
+            *path = redactor.apply(path);
+        }
+        if let Some(ref mut diff) = working_tree_diff {
+            diff.diff = redactor.apply(&diff.diff);
+            diff.changed = diff.changed.iter().map(|path| redactor.apply(path)).collect();
+            diff.new_files = diff.new_files.iter().map(|path| redactor.apply(path)).collect();
+        }
+    }
+    
+    // Files from several projects are grouped into one component per project
+    let roots = component_roots(&options.paths);
+    // Plain XML output is written file by file instead of being built in memory
+    let streamed = formatter.streams() && !uses_prompt && roots.len() <= 1;
+    progress::stage("Formatting", (!streamed).then_some(files.len()));
+    let mut sections = if roots.len() > 1 {
+        format_components(&files, &roots, options, &redactor)
+    } else {
+        let mut sections = format_sections(&files, project_type.clone(), project_info, &options.format);
+        if options.no_project_info {
+            sections.project_info.clear();
+        } else {
+            sections.project_info.push_str(&infrastructure_section(&options.paths, &project_type));
+            sections.project_info.push_str(&docker::format_deployment_section(&deployment_files));
+        }
+        if !options.overview_only && !streamed {
+            sections.files = format_files(&files, &options.format);
+        }
+        sections
+    };
+    if let Some(ref diff) = working_tree_diff {
+        if !options.overview_only {
+            let new_files: Vec<FileEntry> =
+                files.iter().filter(|file| diff.new_files.contains(&file.path)).cloned().collect();
+            sections.files = git_diff::format_diff_section(diff, against);
+            sections.files.push_str(&format_files(&new_files, &options.format));
+        }
+    }
+    if options.overview_only {
+        for file in files.drain(..) {
+            skipped.push(summary::SkippedFile {
+                path: file.path,
+                source_path: file.source_path,
+                reason: summary::SkipReason::NotRequested,
+            });
+        }
+    }
+    // The dependency tree is part of the project info
+    if let Some(depth) = options.dep_tree_depth.filter(|_| !options.no_project_info) {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        info!("Collecting the dependency tree...");
+        match dep_tree::collect(&project_root, &project_type, depth)? {
+            Some(tree) => sections.project_info.push_str(&dep_tree::format_dependency_tree_section(&tree)),
+            None => warn!("--with-dep-tree requires a Rust, Python or npm project, skipping"),
+        }
+    }
+    if let Some(ref path) = options.header_path {
+        let header = fs::read_to_string(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("Failed to read header file {}: {}", path, e)))?;
+        sections.header = format!("<header>\n{}\n</header>\n\n", header.trim_end());
+    }
+    // The repository URL and commit would undo --anonymize
+    if options.provenance && options.anonymize.is_some() {
+        warn!("--provenance is ignored with --anonymize, skipping");
+    } else if options.provenance {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        sections.header.push_str(&provenance::format_provenance_section(&project_root));
+    }
+    sections.extra.push_str(&folded.section);
+    if let Some(ref graph) = architecture_graph {
+        sections.extra.push_str(&architecture::format_architecture_section(graph));
+    }
+    if let Some(ref outline) = outline_section {
+        sections.extra.push_str(outline);
+    }
+    if let Some(ref expanded) = expanded_section {
+        sections.extra.push_str(expanded);
+    }
+    if let Some(tool) = options.diagnostics {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        if project_root.join("Cargo.toml").is_file() {
+            info!("Running cargo {} for diagnostics...", tool.subcommand());
+            let found = diagnostics::collect(&project_root, tool)?;
+            sections.extra.push_str(&diagnostics::format_diagnostics_section(&found));
+        } else {
+            warn!("--with-diagnostics requires a Cargo project, skipping");
+        }
+    }
+    if let Some(ref filter) = options.with_test_output {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        info!("Running tests...");
+        match test_results::run(&project_root, &project_type, filter.as_deref())? {
+            Some(run) => sections.extra.push_str(&test_results::format_test_results_section(&run)),
+            None => warn!("--with-test-output requires a Rust or Python project, skipping"),
+        }
+    }
+    if let Some(ref source) = options.with_bench {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        if project_root.join("Cargo.toml").is_file() {
+            if let bench::BenchSource::Run(_) = source {
+                info!("Running benchmarks...");
+            }
+            let results = bench::collect(&project_root, source)?;
+            sections.extra.push_str(&bench::format_bench_section(&results));
+        } else {
+            warn!("--with-bench requires a Cargo project, skipping");
+        }
+    }
+    if let Some(ref found) = todo_comments {
+        sections.extra.push_str(&todos::format_todos_section(found));
+    }
+    // The file structure and the sections below list paths that are already redacted
+    sections.header = redactor.apply(&sections.header);
+    sections.project_info = redactor.apply(&sections.project_info);
+    sections.extra = redactor.apply(&sections.extra);
+    if !unchanged_files.is_empty() {
+        sections.extra.push_str(&incremental::format_unchanged_section(&unchanged_files));
+    }
+    if (options.list_omitted || options.overview_only) && !skipped.is_empty() {
+        sections.extra.push_str(&omitted::format_omitted_section(&skipped));
+    }
+    if interrupted {
+        sections.extra.push_str(&cancel::format_truncated_section(files.len()));
+    }
+    
+    let anonymizer = match options.anonymize {
+        Some(ref identifiers_path) => {
+            let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+            let anonymizer = anonymize::Anonymizer::new(
+                project_name.as_deref(),
+                &anonymized_roots(&options.paths, &project_root),
+                identifiers_path.as_deref(),
+            )?;
+            let mapping_path = anonymizer.save_mapping(&project_root)?;
+            info!("Anonymization mapping written to {}", mapping_path.display());
+            Some(anonymizer)
+        }
+        None => None,
+    };
+    let post_processing = PostProcessing { anonymizer };
+    
+    // Format the output, wrapping it in a prompt template when requested
+    let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+    let title = project_root
+        .file_name()
+        .map(|name| redactor.apply(&name.to_string_lossy()))
+        .unwrap_or_else(|| "Code context".to_string());
+    let rendered = if streamed {
+        Rendered::Streamed { sections, format: options.format.clone() }
+    } else if uses_prompt {
+        let template = match options.prompt_template {
+            Some(ref prompt) => redactor.apply(&prompt::load_template(prompt)?),
+            None => prompt::DEFAULT_TEMPLATE.to_string(),
+        };
+        // The whole prompt is post-processed at once
+        let context = formatter.render(&formatter::RenderContext {
+            files: &files,
+            sections: &sections,
+            options: &options.format,
+            title: &title,
+            post_process: &|text| text.to_string(),
+        });
+        let text = prompt::render(
+            &template,
+            &context,
+            &sections,
+            options.question.as_deref().map(|question| redactor.apply(question)).as_deref(),
+            task_text.as_deref().map(|task| redactor.apply(task)).as_deref(),
+            options.reply_format.map(|format| format.instructions()),
+        );
+        Rendered::Text(post_processing.apply(&text))
+    } else {
+        Rendered::Text(formatter.render(&formatter::RenderContext {
+            files: &files,
+            sections: &sections,
+            options: &options.format,
+            title: &title,
+            post_process: &|text| post_processing.apply(text),
+        }))
+    };
+    
+    let mut output = GeneratedOutput {
+        front_matter: None,
+        rendered,
+        project_type,
+        files,
+        skipped,
+        incremental: incremental_state,
+        post_processing,
+        text: OnceCell::new(),
+        size: Cell::new(None),
+    };
+    
+    // The token count describes the context itself, so the block is added last
+    if options.frontmatter {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        let project = project_name.unwrap_or_else(|| {
+            paths::canonicalize(&project_root)
+                .ok()
+                .and_then(|root| root.file_name().map(|name| name.to_string_lossy().to_string()))
+                .unwrap_or_default()
+        });
+        // A commit hash would identify the repository, so --anonymize leaves it out
+        let commit = front_matter::git_commit(&project_root).filter(|_| options.anonymize.is_none());
+        let tokens = output.size().tokens();
+        // The project name and commit are redacted and anonymized like the rest
+        let block = redactor.apply(&front_matter::render(&project, commit.as_deref(), output.files.len(), tokens));
+        output.set_front_matter(output.post_processing.apply(&block));
+    }
+    
+    Ok(output)
+}
+
+fn detect_project_type_and_extract_info(
+    paths: &[String], 
+    cargo_toml_path: Option<String>, 
+    pyproject_path: Option<String>
+) -> (ProjectType, Option<String>) {
+    // If cargo_toml_path is explicitly provided, it's a Rust project
+    if let Some(ref path) = cargo_toml_path {
+        if let Some(info) = extract_cargo_info(path) {
+            return (ProjectType::Rust, Some(info));
+        }
+    }
+    
+    // If pyproject_path is explicitly provided, it's a Python project
+    if let Some(ref path) = pyproject_path {
+        if Path::new(path).exists() {
+            if let Some(info) = extract_python_project_info(path) {
+                return (ProjectType::Python, Some(info));
+            }
+        }
+    }
+    
+    // Try to find project files in the given paths
+    if let Some(dir) = first_dir(paths) {
+        // Check for Rust project first
+        if let Some(info) = find_and_extract_cargo_info(&dir) {
+            return (ProjectType::Rust, Some(info));
+        }
+        
+        // Then check for Python project
+        if let Some(info) = find_and_extract_python_info(&dir) {
+            return (ProjectType::Python, Some(info));
+        }
+        
+        // Mobile projects: Swift packages and Kotlin (Multiplatform) Gradle builds
+        if let Some(info) = swift::find_package_info(&dir) {
+            return (ProjectType::Swift, Some(info));
+        }
+        if let Some(info) = gradle::find_kotlin_info(&dir) {
+            return (ProjectType::Kotlin, Some(info));
+        }
+        
+        // Terraform, Pulumi and CloudFormation code on its own; next to a
+        // project file it gets an infrastructure section of its own
+        if let Some(info) = iac::find_info(&dir) {
+            return (ProjectType::Infrastructure, Some(info));
+        }
+    }
+    
+    // If no specific project info was found
+    (ProjectType::Unknown, None)
+}
+
+// The first path's directory, where project files are looked for
+fn first_dir(paths: &[String]) -> Option<PathBuf> {
+    let path = Path::new(paths.first()?);
+    if path.is_file() {
+        path.parent().map(|p| p.to_path_buf())
+    } else {
+        Some(path.to_path_buf())
+    }
+}
+
+// <infrastructure_info> for Terraform, Pulumi or CloudFormation code in a
+// directory that also has a language project file
+fn infrastructure_section(paths: &[String], project_type: &ProjectType) -> String {
+    if *project_type == ProjectType::Infrastructure {
+        return String::new();
+    }
+    match first_dir(paths).and_then(|dir| iac::find_info(&dir)) {
+        Some(info) => format!("<infrastructure_info>\n{}</infrastructure_info>\n\n", info),
+        None => String::new(),
+    }
+}
+
+// The nearest directory above the first path that looks like a project root
+fn find_project_root(paths: &[String]) -> Option<PathBuf> {
+    let first = paths::canonicalize(paths.first()?).ok()?;
+    let start_dir = if first.is_file() {
+        first.parent()?.to_path_buf()
+    } else {
+        first
+    };
+    
+    let markers = [
+        "Cargo.toml", "pyproject.toml", "setup.py", "package.json", "Package.swift",
+        "settings.gradle.kts", "settings.gradle", ".git",
+    ];
+    let mut current_dir = start_dir.clone();
+    loop {
+        if markers.iter().any(|marker| current_dir.join(marker).exists()) {
+            return Some(current_dir);
+        }
+        
+        // Go up one directory
+        if !current_dir.pop() {
+            break;
+        }
+    }
+    
+    Some(start_dir)
+}
+
+// The project root plus any copied directories outside of it; directories
+// inside the project (like src/) keep their names
+fn anonymized_roots(paths: &[String], project_root: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![project_root.to_path_buf()];
+    for path in paths {
+        if let Ok(path) = paths::canonicalize(path) {
+            let dir = if path.is_file() { path.parent().map(Path::to_path_buf) } else { Some(path) };
+            if let Some(dir) = dir {
+                if !roots.iter().any(|root| dir.starts_with(root)) {
+                    roots.push(dir);
+                }
+            }
+        }
+    }
+    roots
+}
+
+// Parses the command line, then again with the arguments of the --profile
+// (if any) inserted before the ones given explicitly so those take precedence
+fn parse_cli(args: &[String]) -> io::Result<Cli> {
+    let cli = Cli::try_parse_from(args).unwrap_or_else(|e| e.exit());
+    let subcommand: &[&str] = match cli.command {
+        None => &[],
+        Some(Command::Ask(_)) => &["ask"],
+        Some(Command::Session(_)) => &["session"],
+        Some(Command::Explain(_)) => &["explain"],
+        Some(Command::Search(_)) => &["search"],
+        Some(Command::Chunks(_)) => &["chunks"],
+        Some(Command::Manifest(manifest::ManifestCommand::Write(_))) => &["manifest", "write"],
+        Some(_) => return Ok(cli),
+    };
+    let position = subcommand.len() + 1;
+    
+    // CODECOPIER_* variables go before the command-line arguments, which win
+    let mut command = Cli::command();
+    let matches = Cli::command().try_get_matches_from(args).unwrap_or_else(|e| e.exit());
+    let mut matches = &matches;
+    for name in subcommand {
+        command = command.find_subcommand(name).cloned().unwrap_or(command);
+        matches = matches.subcommand_matches(name).unwrap_or(matches);
+    }
+    let env_args = config::env_args(&command, matches);
+    let mut expanded = args.to_vec();
+    let cli = if env_args.is_empty() {
+        cli
+    } else {
+        let listed = env_args.join(" ");
+        expanded.splice(position..position, env_args);
+        Cli::try_parse_from(&expanded).map_err(|e| {
+            let message = e.to_string();
+            let message = message.lines().next().unwrap_or("").trim_start_matches("error: ");
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid CODECOPIER_* environment variable ({}): {}", listed, message),
+            )
+        })?
+    };
+    
+    let options = match cli.command {
+        Some(Command::Ask(ref ask)) => &ask.options,
+        Some(Command::Search(ref search)) => &search.options,
+        Some(Command::Session(ref options))
+        | Some(Command::Explain(ref options))
+        | Some(Command::Chunks(ref options))
+        | Some(Command::Manifest(manifest::ManifestCommand::Write(ref options))) => options.as_ref(),
+        _ => &cli.options,
+    };
+    let name = match options.profile {
+        Some(ref name) => name.clone(),
+        None => return Ok(cli),
+    };
+    
+    let paths = if options.paths.is_empty() { vec![".".to_string()] } else { options.paths.clone() };
+    let config = config::load(&paths, options.config_path.as_deref())?;
+    let profile = config.profiles.get(&name).ok_or_else(|| {
+        let available: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown profile '{}'. Available profiles: {}", name, available.join(", ")),
+        )
+    })?;
+    debug!("Using profile {}: {}", name, profile.args.join(" "));
+    
+    // Profile arguments go first, so the environment and command line override them
+    expanded.splice(position..position, profile.args.iter().cloned());
+    Ok(Cli::try_parse_from(&expanded).unwrap_or_else(|e| e.exit()))
+}
+
+// Prints a completion script; profile names from the config files visible
+// from the current directory are offered as values for --profile
+fn print_completions(shell: Shell) -> io::Result<()> {
+    let config = config::load(&[".".to_string()], None)?;
+    let profiles: Vec<String> = config.profiles.keys().cloned().collect();
+    let with_profiles = |arg: clap::Arg| arg.value_parser(clap::builder::PossibleValuesParser::new(profiles.clone()));
+    
+    let mut command = Cli::command()
+        .mut_arg("profile", with_profiles)
+        .mut_subcommand("ask", |ask| ask.mut_arg("profile", with_profiles));
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+    Ok(())
+}
+
+// Turns everything that names input into local paths: --files-from lists,
+// repository URLs, archives, `file:line` and `file::symbol` references and
+// --with-dep-source crates. The returned clones and extracted archives must
+// be kept alive until the output is generated
+fn resolve_inputs(options: &mut Options) -> io::Result<Vec<scratch::ScratchDir>> {
+    add_paths_from_list(options)?;
+    let mut scratch_dirs = remote::fetch_remote_paths(&mut options.paths)?;
+    scratch_dirs.extend(archive::extract_archive_paths(&mut options.paths)?);
+    options.references = excerpt::split_references(&mut options.paths);
+    
+    // The paths only locate the workspace; the packages are copied instead
+    if !options.packages.is_empty() {
+        let start = paths::canonicalize(options.paths.first().map(String::as_str).unwrap_or("."))?;
+        let workspace = js_workspace::find(&start).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("--package requires a pnpm, Yarn, npm or Nx workspace, and none was found at or above {}", start.display()),
+            )
+        })?;
+        let packages = workspace.resolve(&options.packages)?;
+        let names: Vec<&str> = packages.iter().map(|(name, _)| name.as_str()).collect();
+        info!("Copying {} from the {} workspace at {}", names.join(", "), workspace.tool, workspace.root.display());
+        let current_dir = paths::canonicalize(env::current_dir()?)?;
+        options.paths = packages.iter().map(|(_, dir)| cwd_relative(dir, &current_dir)).collect();
+    }
+    
+    // Bazel targets are copied as the files the build graph gives them
+    if !options.targets.is_empty() {
+        let start = paths::canonicalize(options.paths.first().map(String::as_str).unwrap_or("."))?;
+        let dir = if start.is_file() { start.parent().unwrap_or(&start).to_path_buf() } else { start };
+        let current_dir = paths::canonicalize(env::current_dir()?)?;
+        let mut files: Vec<String> = Vec::new();
+        for target in &options.targets {
+            let sources = bazel::target_files(target, &dir)?;
+            info!("{} has {} files with its direct dependencies", target, sources.len());
+            for source in sources {
+                let path = cwd_relative(&source, &current_dir);
+                if !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        options.paths = files;
+    }
+    
+    if !options.dep_sources.is_empty() {
+        let project_root = find_project_root(&options.paths).unwrap_or_else(|| PathBuf::from("."));
+        for spec in &options.dep_sources {
+            let dir = dep_source::locate(spec, &project_root)?;
+            info!("Including the source of {} from {}", spec, dir.display());
+            options.paths.push(dir.to_string_lossy().to_string());
+        }
+    }
+    
+    Ok(scratch_dirs)
+}
+
+// `path` relative to the current directory when it is inside it
+fn cwd_relative(path: &Path, current_dir: &Path) -> String {
+    match path.strip_prefix(current_dir) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => paths::normalize(relative),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+// Adds the paths listed in the --files-from file (or stdin for `-`), one per line
+// or NUL-delimited with -0
+fn add_paths_from_list(options: &mut Options) -> io::Result<()> {
+    let source = match options.files_from {
+        Some(ref source) => source.clone(),
+        None => return Ok(()),
+    };
+    
+    let mut list = String::new();
+    if source == "-" {
+        io::stdin().read_to_string(&mut list)?;
+    } else {
+        list = fs::read_to_string(&source).map_err(|e| {
+            io::Error::new(e.kind(), format!("Failed to read file list {}: {}", source, e))
+        })?;
+    }
+    
+    // With -0 the list is NUL-delimited, so paths may contain newlines
+    let entries: Vec<&str> = if options.null_delimited {
+        list.split('\0').collect()
+    } else {
+        list.lines().map(|line| line.trim_end_matches('\r')).collect()
+    };
+    
+    for path in entries {
+        if path.trim().is_empty() {
+            continue;
+        }
+        if Path::new(path).exists() {
+            options.paths.push(path.to_string());
+        } else {
+            warn!("Skipping missing path from file list: {}", path);
+        }
+    }
+    
+    Ok(())
+}
+
+// Finds the workspace root from CARGO_MANIFEST_DIR or `cargo locate-project`
+fn cargo_workspace_root() -> Option<PathBuf> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = std::process::Command::new(cargo)
+        .args(["locate-project", "--workspace", "--message-format", "plain"])
+        .current_dir(env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string()))
+        .output()
+        .ok()?;
+    
+    if !output.status.success() {
+        return None;
+    }
+    
+    let manifest = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    manifest.parent().map(Path::to_path_buf)
+}
+
+fn collect_files_from_path(
+    path_str: &str,
+    files: &mut Vec<FileEntry>,
+    skipped: &mut Vec<summary::SkippedFile>,
+    options: &Options,
+    hidden_allow: &[String],
+    handlers: &handlers::Handlers,
+    hooks: Option<&hooks::Hooks>,
+) -> io::Result<()> {
+    let path = Path::new(path_str);
+    let display_base = display_base(path_str, options);
+    let display_base = display_base.as_deref();
+    
+    if path.is_file() {
+        // If path is a file, just add it under the path it was given as
+        process_file(path, display_base, files, skipped, options, handlers, hooks)?;
+        check_file_count(files.len(), path, options)?;
+    } else if path.is_dir() {
+        // If path is a directory, walk through it, honouring .gitignore and .copierignore files
+        let no_tests = options.no_tests;
+        let include_hidden = options.hidden;
+        let root = path.to_path_buf();
+        let allow = hidden_allow.to_vec();
+        let path_spec = if options.no_path_spec { None } else { path_spec::PathSpec::find(path)? };
+        let canonical_root = paths::canonicalize(path)?;
+        if let Some(ref spec) = path_spec {
+            debug!("Selecting files in {} with {}", path.display(), spec.path.display());
+        }
+        // The files the walk reaches, to tell which ones ignore files left out
+        let list_omitted = options.list_omitted || options.overview_only;
+        let mut walked = HashSet::new();
+        for entry in WalkBuilder::new(path)
+            .max_depth(options.max_depth)
+            .hidden(false)
+            .add_custom_ignore_filename(IGNORE_FILE)
+            .filter_entry(move |e| {
+                if !is_walked_entry(e, &root, &allow, include_hidden) {
+                    return false;
+                }
+                if no_tests && is_test_entry(e) {
+                    trace!("Excluding {}: test code", e.path().display());
+                    return false;
+                }
+                true
+            })
+            .build() {
+            
+            if cancel::requested() {
+                break;
+            }
+            progress::walked();
+            // Unreadable directories are left out without ending the walk
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    report_walk_error(&e, path, display_base, skipped, options);
+                    continue;
+                }
+            };
+            let entry_path = entry.path();
+            if list_omitted && entry_path.is_file() {
+                walked.insert(entry_path.to_path_buf());
+            }
+            let mut omit = |reason: summary::SkipReason| {
+                if list_omitted && entry_path.is_file() {
+                    skipped.push(summary::SkippedFile {
+                        path: display_path(entry_path, display_base, options),
+                        source_path: entry_path.to_path_buf(),
+                        reason,
+                    });
+                }
+            };
+            
+            // Files above --min-depth are skipped, but their directories are still walked
+            if entry.depth() < options.min_depth.unwrap_or(0) {
+                trace!("Excluding {}: above --min-depth", entry_path.display());
+                omit(summary::SkipReason::Filtered);
+                continue;
+            }
+            
+            if entry_path.is_file() {
+                if let Some(ref spec) = path_spec {
+                    let absolute = canonical_root.join(entry_path.strip_prefix(path).unwrap_or(entry_path));
+                    if !spec.includes(&absolute) {
+                        trace!("Excluding {}: not selected by {}", entry_path.display(), path_spec::PATH_SPEC_FILE);
+                        omit(summary::SkipReason::Filtered);
+                        continue;
+                    }
+                }
+                process_file(entry_path, display_base, files, skipped, options, handlers, hooks)?;
+                check_file_count(files.len(), path, options)?;
+            }
+        }
+        if list_omitted && !cancel::requested() {
+            record_unwalked(path, display_base, &walked, skipped, options, hidden_allow);
+        }
+    }
+    
+    Ok(())
+}
+
+// Ignore files and --no-tests leave files out before the walk reaches them,
+// so for --list-omitted a second walk without them finds what they dropped
+fn record_unwalked(
+    path: &Path,
+    display_base: Option<&Path>,
+    walked: &HashSet<PathBuf>,
+    skipped: &mut Vec<summary::SkippedFile>,
+    options: &Options,
+    hidden_allow: &[String],
+) {
+    let root = path.to_path_buf();
+    let allow = hidden_allow.to_vec();
+    let include_hidden = options.hidden;
+    for entry in WalkBuilder::new(path)
+        .max_depth(options.max_depth)
+        .standard_filters(false)
+        .filter_entry(move |e| is_walked_entry(e, &root, &allow, include_hidden))
+        .build()
+        .filter_map(|entry| entry.ok())
+    {
+        let entry_path = entry.path();
+        if !entry.file_type().is_some_and(|file_type| file_type.is_file())
+            || entry.depth() < options.min_depth.unwrap_or(0)
+            || walked.contains(entry_path)
+        {
+            continue;
+        }
+        let reason = if options.no_tests && is_test_path(entry_path, path) {
+            summary::SkipReason::Test
+        } else {
+            summary::SkipReason::Ignored
+        };
+        skipped.push(summary::SkippedFile {
+            path: display_path(entry_path, display_base, options),
+            source_path: entry_path.to_path_buf(),
+            reason,
+        });
+    }
+}
+
+// Whether the walk enters the entry: hidden paths only when allowlisted or
+// with --hidden, and never the default excluded directories
+fn is_walked_entry(entry: &ignore::DirEntry, root: &Path, allow: &[String], include_hidden: bool) -> bool {
+    // Allowlisted hidden paths are exempt from the default exclusions too
+    match hidden_allowance(entry, root, allow) {
+        Some(true) => return true,
+        Some(false) if !include_hidden => {
+            trace!("Excluding {}: hidden", entry.path().display());
+            return false;
+        }
+        _ => {}
+    }
+    if should_exclude_entry(entry) {
+        trace!("Excluding {}: excluded directory", entry.path().display());
+        return false;
+    }
+    true
 }
 
-
-#[derive(Debug, Clone, PartialEq)]
-enum ProjectType {
-    Rust,
-    Python,
-    Unknown,
+// Directory the paths of the files under `path_str` are shown relative to
+fn display_base(path_str: &str, options: &Options) -> Option<PathBuf> {
+    let path = Path::new(path_str);
+    match options.path_base {
+        None if path.is_dir() => Some(path.to_path_buf()),
+        Some(paths::PathBase::Repo) => {
+            paths::repo_root(path).or_else(|| find_project_root(&[path_str.to_string()]))
+        }
+        Some(paths::PathBase::Cwd) => env::current_dir().ok(),
+        _ => None,
+    }
 }
 
-fn main() -> io::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    
-    if args.len() < 2 {
-        eprintln!("Usage: code-copier <file_or_directory_paths> [--cargo-toml <cargo_toml_path>] [--pyproject <pyproject_path>]");
+// Enforces --max-files, or asks once whether to go on when the selection
+// grows past FILE_COUNT_PROMPT; without a terminal to ask on it only warns
+fn check_file_count(count: usize, path: &Path, options: &Options) -> io::Result<()> {
+    if let Some(max_files) = options.max_files {
+        if count > max_files {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("More than {} files selected in {}; narrow the paths or raise --max-files", max_files, path.display()),
+            ));
+        }
         return Ok(());
     }
-    
-    println!("Processing paths...");
-    
-    // Parse arguments
-    let mut paths: Vec<String> = Vec::new();
-    let mut cargo_toml_path: Option<String> = None;
-    let mut pyproject_path: Option<String> = None;
-    
-    let mut i = 1;
-    while i < args.len() {
-        if args[i] == "--cargo-toml" && i + 1 < args.len() {
-            cargo_toml_path = Some(args[i + 1].clone());
-            i += 2;
-        } else if args[i] == "--pyproject" && i + 1 < args.len() {
-            pyproject_path = Some(args[i + 1].clone());
-            i += 2;
-        } else {
-            paths.push(args[i].clone());
-            i += 1;
-        }
+    if count != FILE_COUNT_PROMPT + 1 {
+        return Ok(());
     }
     
-    // Collect all files from specified paths
-    let mut files = Vec::new();
-    for path_str in &paths {
-        collect_files_from_path(path_str, &mut files)?;
+    let question = format!("More than {} files selected in {} so far. Continue?", FILE_COUNT_PROMPT, path.display());
+    if !confirm::is_interactive() {
+        warn!("More than {} files selected in {}; pass --max-files to set a limit", FILE_COUNT_PROMPT, path.display());
+    } else if !progress::suspend(|| confirm::yes_no(&question))? {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "Cancelled; narrow the paths or pass --max-files to allow more files",
+        ));
     }
-    
-    // Detect project type and extract metadata
-    let (project_type, project_info) = detect_project_type_and_extract_info(&paths, cargo_toml_path, pyproject_path);
-    
-    // Format the output
-    let formatted_output = format_for_llm(files, project_type.clone(), project_info);
-    
-    // Copy to clipboard
-    copy_to_clipboard(&formatted_output)?;
-    
-    println!("Files successfully copied to clipboard!");
-    println!("Files processed: {}", formatted_output.matches("<file ").count());
-    println!("Total size: {} characters", formatted_output.len());
-    println!("Project type: {}", match project_type {
-        ProjectType::Rust => "Rust",
-        ProjectType::Python => "Python",
-        ProjectType::Unknown => "Unknown",
-    });
-    
     Ok(())
 }
 
-fn detect_project_type_and_extract_info(
-    paths: &[String], 
-    cargo_toml_path: Option<String>, 
-    pyproject_path: Option<String>
-) -> (ProjectType, Option<String>) {
-    // If cargo_toml_path is explicitly provided, it's a Rust project
-    if let Some(ref path) = cargo_toml_path {
-        if let Some(info) = extract_cargo_info(path) {
-            return (ProjectType::Rust, Some(info));
+// Records a file or directory the walk could not read as skipped; errors
+// without a path, like invalid .gitignore lines, are only logged
+fn report_walk_error(
+    error: &ignore::Error,
+    root: &Path,
+    display_base: Option<&Path>,
+    skipped: &mut Vec<summary::SkippedFile>,
+    options: &Options,
+) {
+    let source_path = match walk_error_path(error) {
+        Some(path) => path.to_path_buf(),
+        None => {
+            warn!("Error walking {}: {}", root.display(), error);
+            return;
         }
+    };
+    let reason = error.io_error().map(|e| e.kind().to_string()).unwrap_or_else(|| error.to_string());
+    if options.skip_errors {
+        debug!("Could not read {}: {}", source_path.display(), reason);
+    } else {
+        warn!("Could not read {}: {}", source_path.display(), reason);
     }
-    
-    // If pyproject_path is explicitly provided, it's a Python project
-    if let Some(ref path) = pyproject_path {
-        if Path::new(path).exists() {
-            if let Some(info) = extract_python_project_info(path) {
-                return (ProjectType::Python, Some(info));
-            }
+    let path = match display_base {
+        Some(base) => paths::relative_to(&source_path, base),
+        None => paths::normalize(&source_path),
+    };
+    skipped.push(summary::SkippedFile { path, source_path, reason: summary::SkipReason::ReadError });
+}
+
+fn walk_error_path(error: &ignore::Error) -> Option<&Path> {
+    match error {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::Loop { child, .. } => Some(child),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => walk_error_path(err),
+        _ => None,
+    }
+}
+
+// Moves the files not marked in `selected` to the skipped files, as filtered
+fn keep_selected(files: &mut Vec<FileEntry>, skipped: &mut Vec<summary::SkippedFile>, selected: &[bool]) {
+    for (file, &selected) in std::mem::take(files).into_iter().zip(selected) {
+        if selected {
+            files.push(file);
+        } else {
+            skipped.push(summary::SkippedFile {
+                path: file.path,
+                source_path: file.source_path,
+                reason: summary::SkipReason::Filtered,
+            });
         }
     }
-    
-    // Try to find project files in the given paths
-    if !paths.is_empty() {
-        let path = Path::new(&paths[0]);
-        let dir_path = if path.is_file() {
-            path.parent().map(|p| p.to_path_buf())
+}
+
+const EXCLUDED_DIRS: &[&str] = &[
+    ".git", "target", "node_modules", ".vscode", ".idea", 
+    ".github", "dist", "build", "out", "__pycache__", 
+    ".pytest_cache", ".mypy_cache", ".tox", ".eggs", 
+    "*.egg-info", ".ipynb_checkpoints", "venv", "env", ".env"
+];
+
+fn should_exclude_entry(entry: &ignore::DirEntry) -> bool {
+    let path = entry.path();
+    path.is_dir() && path.file_name().and_then(|name| name.to_str()).is_some_and(is_excluded_dir_name)
+}
+
+fn is_excluded_dir_name(name: &str) -> bool {
+    EXCLUDED_DIRS.iter().any(|excluded| {
+        let excluded = excluded.trim_start_matches('*');
+        if excluded.starts_with('.') {
+            name == excluded
         } else {
-            Some(path.to_path_buf())
-        };
-        
-        if let Some(dir) = dir_path {
-            // Check for Rust project first
-            if let Some(info) = find_and_extract_cargo_info(&dir) {
-                return (ProjectType::Rust, Some(info));
-            }
-            
-            // Then check for Python project
-            if let Some(info) = find_and_extract_python_info(&dir) {
-                return (ProjectType::Python, Some(info));
-            }
+            name == excluded || name.ends_with(excluded)
         }
+    })
+}
+
+// For entries with a dotfile or dot-directory below the copied directory in
+// their path: whether the config allowlist covers them (or leads to an allowed path)
+fn hidden_allowance(entry: &ignore::DirEntry, root: &Path, allow: &[String]) -> Option<bool> {
+    if entry.depth() == 0 {
+        return None;
     }
     
-    // If no specific project info was found
-    (ProjectType::Unknown, None)
+    let relative = paths::relative_to(entry.path(), root);
+    hidden_allowed(&relative, entry.file_type().is_some_and(|file_type| file_type.is_dir()), allow)
 }
 
-fn collect_files_from_path(path_str: &str, files: &mut Vec<FileEntry>) -> io::Result<()> {
-    let path = Path::new(path_str);
+// Like `hidden_allowance`, for a path relative to the copied directory
+fn hidden_allowed(relative: &str, is_dir: bool, allow: &[String]) -> Option<bool> {
+    if !relative.split('/').any(|part| part.starts_with('.')) {
+        return None;
+    }
     
-    if path.is_file() {
-        // If path is a file, just add it
-        process_file(path, path.to_string_lossy().to_string(), files)?;
-    } else if path.is_dir() {
-        // If path is a directory, walk through it
-        let base_dir = path.to_string_lossy().to_string();
-        for entry in WalkDir::new(path)
-            .into_iter()
-            .filter_entry(|e| !should_exclude_entry(e))
-            .filter_map(|e| e.ok()) {
-                
-            let entry_path = entry.path();
-            
-            if entry_path.is_file() {
-                process_file(entry_path, base_dir.clone(), files)?;
-            }
+    Some(allow.iter().any(|allowed| {
+        let allowed = allowed.trim_matches('/');
+        relative == allowed
+            || relative.starts_with(&format!("{}/", allowed))
+            || (is_dir && allowed.starts_with(&format!("{}/", relative)))
+    }))
+}
+
+// Create a relative path that shows the structure well; files given
+// directly keep the path they were given as
+fn display_path(file_path: &Path, base_dir: Option<&Path>, options: &Options) -> String {
+    match (options.path_base, base_dir) {
+        (Some(paths::PathBase::Absolute), _) => {
+            paths::normalize(&paths::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf()))
         }
+        (_, Some(base_dir)) => paths::relative_to(file_path, base_dir),
+        (_, None) => paths::normalize(file_path),
     }
-    
-    Ok(())
 }
 
-fn should_exclude_entry(entry: &walkdir::DirEntry) -> bool {
-    let excluded_dirs = vec![
-        ".git", "target", "node_modules", ".vscode", ".idea", 
-        ".github", "dist", "build", "out", "__pycache__", 
-        ".pytest_cache", ".mypy_cache", ".tox", ".eggs", 
-        "*.egg-info", ".ipynb_checkpoints", "venv", "env", ".env"
-    ];
-    
-    let path = entry.path();
-    if path.is_dir() {
-        return excluded_dirs.iter().any(|excluded| {
-            let excluded = excluded.trim_start_matches('*');
-            path.file_name()
-                .and_then(|name| name.to_str())
-                .map_or(false, |name| {
-                    if excluded.starts_with('.') {
-                        name == excluded
-                    } else {
-                        name == excluded || name.ends_with(excluded)
-                    }
-                })
-        });
+// Whether --no-tests leaves out the file: a test file, or one in a test
+// directory below `root`
+fn is_test_path(path: &Path, root: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    test_code::is_test_file(path)
+        || relative.parent().is_some_and(|dir| dir.components().any(|c| c.as_os_str().to_str().is_some_and(test_code::is_test_dir)))
+}
+
+fn is_test_entry(entry: &ignore::DirEntry) -> bool {
+    if entry.depth() == 0 {
+        return false;
     }
     
-    false
+    if entry.file_type().is_some_and(|file_type| file_type.is_dir()) {
+        entry.file_name().to_str().is_some_and(test_code::is_test_dir)
+    } else {
+        test_code::is_test_file(entry.path())
+    }
 }
 
-fn process_file(file_path: &Path, base_dir: String, files: &mut Vec<FileEntry>) -> io::Result<()> {
-    let excluded_ext = vec![
-        ".exe", ".dll", ".so", ".dylib", ".o", ".obj", ".a", 
-        ".lib", ".bin", ".png", ".jpg", ".jpeg", ".gif", 
-        ".svg", ".ico", ".woff", ".woff2", ".ttf", ".eot",
-        ".pyc", ".pyd", ".pyo", ".class", ".jar"
-    ];
-    
-    // Skip binary or image files
-    if let Some(ext) = file_path.extension().and_then(|ext| ext.to_str()) {
-        if excluded_ext.iter().any(|excluded| excluded.trim_start_matches(".") == ext) {
+const EXCLUDED_EXTENSIONS: &[&str] = &[
+    ".exe", ".dll", ".so", ".dylib", ".o", ".obj", ".a", 
+    ".lib", ".bin", ".png", ".jpg", ".jpeg", ".gif", 
+    ".svg", ".ico", ".woff", ".woff2", ".ttf", ".eot",
+    ".pyc", ".pyd", ".pyo", ".class", ".jar"
+];
+
+fn has_excluded_extension(ext: &str) -> bool {
+    EXCLUDED_EXTENSIONS.iter().any(|excluded| excluded.trim_start_matches(".") == ext)
+}
+
+fn process_file(
+    file_path: &Path,
+    base_dir: Option<&Path>,
+    files: &mut Vec<FileEntry>,
+    skipped: &mut Vec<summary::SkippedFile>,
+    options: &Options,
+    handlers: &handlers::Handlers,
+    hooks: Option<&hooks::Hooks>,
+) -> io::Result<()> {
+    let clean_path = display_path(file_path, base_dir, options);
+    
+    let truncate_strategy = options.truncate_large;
+    let sample_rows = options.sample_rows().filter(|_| data_sample::is_data_file(file_path));
+    
+    // A configured handler overrides the default filters
+    let handler = handlers.find(&clean_path, file_path);
+    let mut skip = |reason: summary::SkipReason| {
+        trace!("Skipping {}: {}", file_path.display(), reason.label());
+        skipped.push(summary::SkippedFile {
+            path: clean_path.clone(),
+            source_path: file_path.to_path_buf(),
+            reason,
+        });
+    };
+    if handler == Some(&handlers::Handler::Skip) {
+        skip(summary::SkipReason::Handler);
+        return Ok(());
+    }
+    if let Some(hooks) = hooks {
+        if !hooks.should_include(&clean_path, file_path)? {
+            skip(summary::SkipReason::Hook);
             return Ok(());
         }
     }
     
-    // Skip large files (> 100KB)
-    if let Ok(metadata) = fs::metadata(file_path) {
-        if metadata.len() > 100 * 1024 {
-            println!("Skipping large file: {}", file_path.display());
-            return Ok(());
+    if handler.is_none() {
+        // Skip binary or image files
+        if let Some(ext) = file_path.extension().and_then(|ext| ext.to_str()) {
+            if has_excluded_extension(ext) {
+                skip(summary::SkipReason::Binary);
+                return Ok(());
+            }
+        }
+        
+        // Skip large files (> 100KB) unless they should be truncated instead
+        if let Ok(metadata) = fs::metadata(file_path) {
+            if metadata.len() > MAX_FILE_SIZE as u64 {
+                if truncate_strategy.is_none() && sample_rows.is_none() {
+                    info!("Skipping large file: {}", file_path.display());
+                    skip(summary::SkipReason::TooLarge);
+                    return Ok(());
+                }
+                if sample_rows.is_none() {
+                    info!("Truncating large file: {}", file_path.display());
+                }
+            }
         }
     }
     
     // Read file content
-    match read_file(file_path) {
-        Ok(content) => {
-            // Create a relative path that shows the structure well
-            let relative_path = if file_path.starts_with(&base_dir) {
-                if let Ok(rel_path) = file_path.strip_prefix(&base_dir) {
-                    rel_path.to_string_lossy().to_string()
-                } else {
-                    file_path.to_string_lossy().to_string()
+    let content = match handler {
+        Some(handlers::Handler::Command(command)) => handlers::run_command(command, file_path),
+        _ => match file_reader::read(file_path) {
+            Ok(file_reader::FileContent::Text(content)) => Ok(content),
+            // Handlers bypass the binary filter, so their files are read anyway
+            Ok(file_reader::FileContent::Binary) if handler.is_some() => fs::read_to_string(file_path),
+            Ok(file_reader::FileContent::Binary) => {
+                skip(summary::SkipReason::Binary);
+                return Ok(());
+            }
+            Err(e) => Err(e),
+        },
+    };
+    
+    match content {
+        Ok(mut content) => {
+            // Colored logs and captured terminal output carry escape sequences
+            if !options.keep_control_chars {
+                if let Cow::Owned(sanitized) = sanitize::strip_control_chars(&content) {
+                    trace!("Removed control characters from {}", file_path.display());
+                    content = sanitized;
                 }
-            } else {
-                file_path.to_string_lossy().to_string()
-            };
+            }
+            
+            // Skip generated code, which is rarely worth reading
+            if handler.is_none() && !options.include_generated && generated::is_generated(&content) {
+                skip(summary::SkipReason::Generated);
+                return Ok(());
+            }
+            
+            // Drop #[cfg(test)] modules from Rust sources
+            if options.no_tests && file_path.extension().is_some_and(|ext| ext == "rs") {
+                content = cache::get_or_compute("strip-tests", &content, || {
+                    Some(test_code::strip_rust_test_code(&content))
+                })
+                .unwrap_or(content);
+            }
+            
+            if handler.is_none() {
+                // Data files are sampled before any size-based truncation
+                if let Some(rows) = sample_rows {
+                    content = data_sample::sample(file_path, &content, rows);
+                }
+                if let Some(strategy) = truncate_strategy {
+                    content = truncation::truncate_to_size(&content, strategy, MAX_FILE_SIZE);
+                }
+            }
+            
+            match handler {
+                Some(handlers::Handler::Truncate(max_lines)) => content = handlers::truncate(&content, *max_lines),
+                Some(handlers::Handler::Summarize) => content = handlers::summarize(&content),
+                _ => {}
+            }
+            
+            if let Some(hooks) = hooks {
+                content = hooks.transform(&clean_path, content)?;
+            }
             
-            // Clean up path (remove leading / or \)
-            let clean_path = relative_path.trim_start_matches('/').trim_start_matches('\\').to_string();
+            let hash = content_hash(&content);
+            debug!("Including {} ({} bytes)", clean_path, content.len());
+            progress::read(content.len());
             
             files.push(FileEntry {
                 path: clean_path,
+                source_path: file_path.to_path_buf(),
                 content,
+                hash,
+                line_ranges: Vec::new(),
+                coverage: None,
+                metadata: None,
             });
         }
         Err(e) => {
-            eprintln!("Error reading file {}: {}", file_path.display(), e);
+            if options.skip_errors {
+                debug!("Error reading file {}: {}", file_path.display(), e);
+            } else {
+                warn!("Error reading file {}: {}", file_path.display(), e);
+            }
+            skip(summary::SkipReason::ReadError);
         }
     }
     
     Ok(())
 }
 
-fn read_file(path: &Path) -> io::Result<String> {
-    let mut file = File::open(path)?;
-    let mut content = String::new();
-    file.read_to_string(&mut content)?;
-    Ok(content)
+fn content_hash(content: &str) -> String {
+    let digest = Sha256::digest(content.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 // Functions for Rust project detection and metadata extraction
@@ -294,55 +2365,47 @@ fn extract_cargo_info(cargo_path: &str) -> Option<String> {
                 if let Some(description) = package.get("description").and_then(|v| v.as_str()) {
                     info.push_str(&format!("Description: {}\n", description));
                 }
+                
+                if let Some(edition) = package.get("edition").and_then(|v| v.as_str()) {
+                    info.push_str(&format!("Edition: {}\n", edition));
+                }
+                
+                if let Some(rust_version) = package.get("rust-version").and_then(|v| v.as_str()) {
+                    info.push_str(&format!("Rust Version: {}\n", rust_version));
+                }
             }
             
             // Extract dependencies
-            if let Some(deps) = cargo_toml.get("dependencies") {
-                if let Some(deps_table) = deps.as_table() {
-                    info.push_str("\nDependencies:\n");
-                    
-                    for (name, value) in deps_table {
-                        match value {
-                            Value::String(version) => {
-                                info.push_str(&format!("- {} = \"{}\"\n", name, version));
-                            }
-                            Value::Table(table) => {
-                                if let Some(version) = table.get("version").and_then(|v| v.as_str()) {
-                                    info.push_str(&format!("- {} = \"{}\"\n", name, version));
-                                } else {
-                                    info.push_str(&format!("- {}\n", name));
-                                }
-                            }
-                            _ => {
-                                info.push_str(&format!("- {}\n", name));
-                            }
-                        }
-                    }
+            for (key, title) in [
+                ("dependencies", "Dependencies"),
+                ("dev-dependencies", "Dev Dependencies"),
+                ("build-dependencies", "Build Dependencies"),
+            ] {
+                if let Some(deps_table) = cargo_toml.get(key).and_then(|deps| deps.as_table()) {
+                    info.push_str(&format!("\n{}:\n", title));
+                    info.push_str(&format_cargo_dependencies(deps_table));
                 }
             }
             
-            // Extract dev-dependencies
-            if let Some(deps) = cargo_toml.get("dev-dependencies") {
-                if let Some(deps_table) = deps.as_table() {
-                    info.push_str("\nDev Dependencies:\n");
-                    
-                    for (name, value) in deps_table {
-                        match value {
-                            Value::String(version) => {
-                                info.push_str(&format!("- {} = \"{}\"\n", name, version));
-                            }
-                            Value::Table(table) => {
-                                if let Some(version) = table.get("version").and_then(|v| v.as_str()) {
-                                    info.push_str(&format!("- {} = \"{}\"\n", name, version));
-                                } else {
-                                    info.push_str(&format!("- {}\n", name));
-                                }
-                            }
-                            _ => {
-                                info.push_str(&format!("- {}\n", name));
-                            }
-                        }
-                    }
+            // Feature flags, with the default set first
+            if let Some(features) = cargo_toml.get("features").and_then(|features| features.as_table()) {
+                info.push_str("\nFeatures:\n");
+                let mut names: Vec<&String> = features.keys().collect();
+                names.sort_by_key(|name| *name != "default");
+                for name in names {
+                    let enables: Vec<&str> = features[name]
+                        .as_array()
+                        .map(|values| values.iter().filter_map(|v| v.as_str()).collect())
+                        .unwrap_or_default();
+                    info.push_str(&format!("- {} = [{}]\n", name, enables.join(", ")));
+                }
+            }
+            
+            let targets = cargo_targets(&cargo_toml, path.parent().unwrap_or(Path::new(".")));
+            if !targets.is_empty() {
+                info.push_str("\nTargets:\n");
+                for target in targets {
+                    info.push_str(&format!("- {}\n", target));
                 }
             }
             
@@ -354,6 +2417,93 @@ fn extract_cargo_info(cargo_path: &str) -> Option<String> {
 
 // Functions for Python project detection and metadata extraction
 
+// One line per dependency with its version, marking optional ones since they
+// are usually enabled by a feature
+fn format_cargo_dependencies(deps_table: &toml::map::Map<String, Value>) -> String {
+    let mut lines = String::new();
+    for (name, value) in deps_table {
+        match value {
+            Value::String(version) => {
+                lines.push_str(&format!("- {} = \"{}\"\n", name, version));
+            }
+            Value::Table(table) => {
+                let optional = table.get("optional").and_then(|v| v.as_bool()) == Some(true);
+                let marker = if optional { " (optional)" } else { "" };
+                if let Some(version) = table.get("version").and_then(|v| v.as_str()) {
+                    lines.push_str(&format!("- {} = \"{}\"{}\n", name, version, marker));
+                } else {
+                    lines.push_str(&format!("- {}{}\n", name, marker));
+                }
+            }
+            _ => {
+                lines.push_str(&format!("- {}\n", name));
+            }
+        }
+    }
+    lines
+}
+
+// Library and binary targets, declared or found in the default locations,
+// plus the build script
+fn cargo_targets(cargo_toml: &Value, manifest_dir: &Path) -> Vec<String> {
+    let mut targets = Vec::new();
+    
+    match cargo_toml.get("lib") {
+        Some(lib) => {
+            let path = lib.get("path").and_then(|v| v.as_str()).unwrap_or("src/lib.rs");
+            let crate_types: Vec<&str> = lib
+                .get("crate-type")
+                .and_then(|v| v.as_array())
+                .map(|types| types.iter().filter_map(|t| t.as_str()).collect())
+                .unwrap_or_default();
+            if crate_types.is_empty() {
+                targets.push(format!("lib: {}", path));
+            } else {
+                targets.push(format!("lib: {} ({})", path, crate_types.join(", ")));
+            }
+        }
+        None if manifest_dir.join("src/lib.rs").is_file() => targets.push("lib: src/lib.rs".to_string()),
+        None => {}
+    }
+    
+    let bins = cargo_toml.get("bin").and_then(|v| v.as_array());
+    match bins {
+        Some(bins) => {
+            for bin in bins {
+                let name = bin.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+                match bin.get("path").and_then(|v| v.as_str()) {
+                    Some(path) => targets.push(format!("bin {}: {}", name, path)),
+                    None => targets.push(format!("bin {}", name)),
+                }
+            }
+        }
+        None if manifest_dir.join("src/main.rs").is_file() => targets.push("bin: src/main.rs".to_string()),
+        None => {}
+    }
+    if let Ok(entries) = fs::read_dir(manifest_dir.join("src/bin")) {
+        let mut extra: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".rs"))
+            .map(|name| format!("bin: src/bin/{}", name))
+            .filter(|target| !targets.iter().any(|t| t.ends_with(&target["bin: ".len()..])))
+            .collect();
+        extra.sort();
+        targets.extend(extra);
+    }
+    
+    // `package.build` can name another script, or be false to disable it
+    let build = cargo_toml.get("package").and_then(|package| package.get("build"));
+    match build {
+        Some(Value::String(script)) => targets.push(format!("build script: {}", script)),
+        Some(Value::Boolean(false)) => {}
+        _ if manifest_dir.join("build.rs").is_file() => targets.push("build script: build.rs".to_string()),
+        _ => {}
+    }
+    
+    targets
+}
+
 fn find_and_extract_python_info(start_dir: &Path) -> Option<String> {
     let mut current_dir = start_dir.to_path_buf();
     
@@ -688,7 +2838,7 @@ fn extract_setup_list_param(content: &str, param: &str) -> Option<Vec<String>> {
         let mut in_string = false;
         let mut string_delim = ' ';
         
-        for (i, ch) in content[start_idx..].chars().enumerate() {
+        for ch in content[start_idx..].chars() {
             match ch {
                 '[' if !in_string => depth += 1,
                 ']' if !in_string => {
@@ -744,7 +2894,7 @@ fn extract_setup_dict_param(content: &str, param: &str) -> Option<Vec<(String, V
         let mut string_delim = ' ';
         let mut in_key = true;
         
-        for (i, ch) in content[start_idx..].chars().enumerate() {
+        for ch in content[start_idx..].chars() {
             match ch {
                 '{' if !in_string => depth += 1,
                 '}' if !in_string => {
@@ -810,7 +2960,7 @@ fn extract_list_from_str(list_str: &str) -> Option<Vec<String>> {
             let list_content = &list_str[start_idx + 1..end_idx];
             let items: Vec<String> = list_content
                 .split(',')
-                .map(|s| cleanup_string(s))
+                .map(cleanup_string)
                 .filter(|s| !s.is_empty())
                 .collect();
             return Some(items);
@@ -843,19 +2993,17 @@ fn extract_requirements_info(requirements_path: &str) -> Option<String> {
             let reader = BufReader::new(file);
             let mut dependencies = Vec::new();
             
-            for line_result in reader.lines() {
-                if let Ok(line) = line_result {
-                    let trimmed = line.trim();
-                    if !trimmed.is_empty() && !trimmed.starts_with('#') {
-                        // Remove any comments at the end of the line
-                        let dep = match trimmed.find('#') {
-                            Some(idx) => trimmed[..idx].trim(),
-                            None => trimmed
-                        };
-                        
-                        if !dep.is_empty() {
-                            dependencies.push(dep.to_string());
-                        }
+            for line in reader.lines().map_while(Result::ok) {
+                let trimmed = line.trim();
+                if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                    // Remove any comments at the end of the line
+                    let dep = match trimmed.find('#') {
+                        Some(idx) => trimmed[..idx].trim(),
+                        None => trimmed
+                    };
+                    
+                    if !dep.is_empty() {
+                        dependencies.push(dep.to_string());
                     }
                 }
             }
@@ -888,9 +3036,7 @@ fn regex_extract(text: &str, pattern: &str) -> Option<Vec<String>> {
         pos += start_pos + parts[0].len();
         
         // For each middle part, extract the text in between
-        for i in 1..parts.len() {
-            let part = parts[i];
-            
+        for part in parts.iter().skip(1) {
             if let Some(end_pos) = text[pos..].find(part) {
                 // Extract the captured text
                 let captured = &text[pos..pos + end_pos];
@@ -910,119 +3056,300 @@ fn regex_extract(text: &str, pattern: &str) -> Option<Vec<String>> {
     }
 }
 
-fn format_for_llm(files: Vec<FileEntry>, project_type: ProjectType, project_info: Option<String>) -> String {
-    let mut output = String::new();
+struct ContextSections {
+    // Hand-written context from --header, placed first
+    header: String,
+    project_info: String,
+    file_structure: String,
+    // Additional sections placed between the file structure and the files
+    extra: String,
+    files: String,
+}
+
+impl ContextSections {
+    fn to_project_block(&self) -> String {
+        format!(
+            "<project>\n{}{}{}{}{}</project>",
+            self.header, self.project_info, self.file_structure, self.extra, self.files
+        )
+    }
+}
+
+// The distinct project roots of the copied paths, in the order given
+fn component_roots(paths: &[String]) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        if let Some(root) = find_project_root(std::slice::from_ref(path)) {
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+    }
+    roots
+}
+
+// Wraps each project's metadata, file structure and files in a <component>
+// section, so that files with the same relative path in different projects
+// stay distinguishable
+fn format_components(files: &[FileEntry], roots: &[PathBuf], options: &Options, redactor: &redact::Redactor) -> ContextSections {
+    let mut groups: Vec<Vec<FileEntry>> = vec![Vec::new(); roots.len()];
+    for file in files {
+        let source = paths::canonicalize(&file.source_path).unwrap_or_else(|_| file.source_path.clone());
+        let index = roots
+            .iter()
+            .enumerate()
+            .filter(|(_, root)| source.starts_with(root))
+            .max_by_key(|(_, root)| root.components().count())
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+        groups[index].push(file.clone());
+    }
+    
+    let current_dir = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let mut names: Vec<String> = Vec::new();
+    let mut components = String::new();
+    for (root, group) in roots.iter().zip(&groups) {
+        // JS packages are labeled with their package.json name
+        let base_name = js_workspace::package_name(root).unwrap_or_else(|| {
+            root.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "root".to_string())
+        });
+        let mut name = base_name.clone();
+        let mut suffix = 2;
+        while names.contains(&name) {
+            name = format!("{}-{}", base_name, suffix);
+            suffix += 1;
+        }
+        names.push(name.clone());
+        
+        let root_paths = [root.to_string_lossy().to_string()];
+        let (project_type, project_info) = detect_project_type_and_extract_info(
+            &root_paths,
+            manifest_override(options.cargo_toml_path.as_deref(), root, roots),
+            manifest_override(options.pyproject_path.as_deref(), root, roots),
+        );
+        let infrastructure = infrastructure_section(&root_paths, &project_type);
+        let mut sections = format_sections(group, project_type, project_info, &options.format);
+        if options.no_project_info {
+            sections.project_info.clear();
+        } else {
+            sections.project_info.push_str(&infrastructure);
+            let deployment_files: Vec<(String, PathBuf)> =
+                docker::find_files(root).into_iter().map(|path| (paths::relative_to(&path, root), path)).collect();
+            sections.project_info.push_str(&docker::format_deployment_section(&deployment_files));
+        }
+        if !options.overview_only {
+            sections.files = format_files(group, &options.format);
+        }
+        
+        // The file structure and files are built from redacted paths and contents
+        let heading = format!("<component name=\"{}\" path=\"{}\">\n{}", name, paths::relative_to(root, &current_dir), sections.project_info);
+        components.push_str(&format!(
+            "{}{}{}</component>\n\n",
+            redactor.apply(&heading),
+            sections.file_structure,
+            sections.files
+        ));
+    }
     
-    // Add project metadata
-    output.push_str("<project>\n");
+    ContextSections {
+        header: String::new(),
+        project_info: String::new(),
+        file_structure: String::new(),
+        extra: String::new(),
+        files: components,
+    }
+}
+
+// --cargo-toml or --pyproject when it belongs to the component at `root`: the
+// one whose directory holds the manifest, or the first one when none does
+fn manifest_override(manifest: Option<&str>, root: &Path, roots: &[PathBuf]) -> Option<String> {
+    let manifest = manifest?;
+    let dir = Path::new(manifest).parent().and_then(|dir| paths::canonicalize(if dir.as_os_str().is_empty() { Path::new(".") } else { dir }).ok());
+    let owner = dir
+        .and_then(|dir| roots.iter().filter(|candidate| dir.starts_with(candidate)).max_by_key(|candidate| candidate.components().count()))
+        .or_else(|| roots.first());
+    (owner.map(PathBuf::as_path) == Some(root)).then(|| manifest.to_string())
+}
+
+fn format_sections(files: &[FileEntry], project_type: ProjectType, project_info: Option<String>, options: &FormatOptions) -> ContextSections {
+    let mut info_section = String::new();
     
     // Add project information based on type
     match project_type {
         ProjectType::Rust => {
             if let Some(info) = project_info {
-                output.push_str("<cargo_info>\n");
-                output.push_str(&info);
-                output.push_str("</cargo_info>\n\n");
+                info_section.push_str("<cargo_info>\n");
+                info_section.push_str(&info);
+                info_section.push_str("</cargo_info>\n\n");
             }
         },
         ProjectType::Python => {
             if let Some(info) = project_info {
-                output.push_str("<python_info>\n");
-                output.push_str(&info);
-                output.push_str("</python_info>\n\n");
+                info_section.push_str("<python_info>\n");
+                info_section.push_str(&info);
+                info_section.push_str("</python_info>\n\n");
+            }
+        },
+        ProjectType::Swift => {
+            if let Some(info) = project_info {
+                info_section.push_str("<swift_package_info>\n");
+                info_section.push_str(&info);
+                info_section.push_str("</swift_package_info>\n\n");
+            }
+        },
+        ProjectType::Kotlin => {
+            if let Some(info) = project_info {
+                info_section.push_str("<gradle_info>\n");
+                info_section.push_str(&info);
+                info_section.push_str("</gradle_info>\n\n");
+            }
+        },
+        ProjectType::Infrastructure => {
+            if let Some(info) = project_info {
+                info_section.push_str("<infrastructure_info>\n");
+                info_section.push_str(&info);
+                info_section.push_str("</infrastructure_info>\n\n");
             }
         },
         ProjectType::Unknown => {
-            output.push_str("<project_info>\n");
-            output.push_str("Project type could not be determined.\n");
-            output.push_str("</project_info>\n\n");
+            info_section.push_str("<project_info>\n");
+            info_section.push_str("Project type could not be determined.\n");
+            info_section.push_str("</project_info>\n\n");
         }
     }
     
     // Add file structure information
-    output.push_str("<file_structure>\n");
+    let mut structure_section = String::new();
+    structure_section.push_str("<file_structure>\n");
     
     // Get directory structure and format it nicely
-    let dir_structure = get_directory_structure(files.iter().map(|f| &f.path).collect());
-    output.push_str(&dir_structure);
-    
-    output.push_str("</file_structure>\n\n");
-    
-    // Add each file with content
-    for file in files {
-        output.push_str(&format!("<file path=\"{}\">\n", file.path));
-        output.push_str(&file.content);
-        output.push_str("\n</file>\n\n");
+    let tree = tree::TreeNode::build(files.iter().map(|f| f.path.as_str()));
+    let has_coverage = files.iter().any(|file| file.coverage.is_some());
+    if options.stats || has_coverage {
+        let annotate = |index: usize| {
+            let file = &files[index];
+            let mut notes = Vec::new();
+            if options.stats {
+                notes.push(languages::file_stats(file));
+            }
+            if let Some(coverage) = file.coverage {
+                notes.push(format!("{} covered", coverage.percent()));
+            }
+            (!notes.is_empty()).then(|| notes.join(", "))
+        };
+        // Directories show the coverage of all files below them
+        let annotate_dir = |indices: &[usize]| {
+            let mut total = coverage::LineCoverage::default();
+            let mut found = false;
+            for coverage in indices.iter().filter_map(|&index| files[index].coverage) {
+                total.add(coverage);
+                found = true;
+            }
+            found.then(|| format!("{} covered", total.percent()))
+        };
+        structure_section.push_str(&tree.render_annotated_dirs(&annotate, &annotate_dir));
+    } else {
+        structure_section.push_str(&tree.render());
+    }
+    if options.stats {
+        structure_section.push('\n');
+        structure_section.push_str(&languages::totals(files));
     }
     
-    output.push_str("</project>");
+    structure_section.push_str("</file_structure>\n\n");
     
-    output
+    ContextSections {
+        header: String::new(),
+        project_info: info_section,
+        file_structure: structure_section,
+        extra: String::new(),
+        files: String::new(),
+    }
 }
 
-fn get_directory_structure(paths: Vec<&String>) -> String {
-    let mut structure = String::new();
-    let mut current_indent = 0;
-    let mut current_dirs: Vec<String> = Vec::new();
-    
-    // Sort paths to ensure directories are processed in order
-    let mut sorted_paths = paths.clone();
-    sorted_paths.sort();
-    
-    for path in sorted_paths {
-        let parts: Vec<&str> = path.split('/').collect();
-        
-        // Handle directory structure
-        for (i, part) in parts.iter().enumerate().take(parts.len() - 1) {
-            let dir_path = parts[0..=i].join("/");
-            
-            if i >= current_dirs.len() {
-                // New directory level
-                structure.push_str(&format!("{:indent$}└── {}/\n", "", part, indent = i * 2));
-                current_dirs.push(dir_path);
-            } else if current_dirs[i] != dir_path {
-                // New directory at existing level
-                structure.push_str(&format!("{:indent$}└── {}/\n", "", part, indent = i * 2));
-                current_dirs[i] = dir_path;
-                
-                // Clear deeper levels
-                current_dirs.truncate(i + 1);
+fn format_files(files: &[FileEntry], options: &FormatOptions) -> String {
+    let mut files_section = String::new();
+    let _ = write_file_entries(files, options, &mut |entry| {
+        files_section.push_str(entry);
+        Ok(())
+    });
+    files_section
+}
+
+// Renders each file with its content, referencing earlier copies of identical
+// files, and passes the entries to `emit` one at a time
+fn write_file_entries(files: &[FileEntry], options: &FormatOptions, emit: &mut dyn FnMut(&str) -> io::Result<()>) -> io::Result<()> {
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    for file in files {
+        if options.dedupe && !file.content.trim().is_empty() {
+            if let Some(original) = seen.get(file.hash.as_str()) {
+                emit(&format!("<file path=\"{}\" same-as=\"{}\"/>\n\n", file.path, original))?;
+                continue;
             }
+            seen.insert(&file.hash, &file.path);
         }
         
-        // Add file with appropriate indentation
-        let file_name = parts.last().unwrap_or(&"");
-        let indent = (parts.len() - 1) * 2;
-        structure.push_str(&format!("{:indent$}├── {}\n", "", file_name, indent = indent));
-    }
-    
-    structure
-}
-
-#[cfg(not(windows))]
-fn copy_to_clipboard(text: &str) -> io::Result<()> {
-    match ClipboardProvider::new() {
-        Ok(mut ctx) => {
-            ctx.set_contents(text.to_owned()).map_err(|e| {
-                io::Error::new(io::ErrorKind::Other, format!("Clipboard error: {}", e))
-            })
+        let mut entry = String::with_capacity(file.content.len() + file.path.len() + 64);
+        entry.push_str(&format!("<file path=\"{}\"", file.path));
+        if let Some(lang) = languages::detect_language_id(&file.source_path, &file.content) {
+            entry.push_str(&format!(" lang=\"{}\"", lang));
         }
-        Err(e) => {
-            Err(io::Error::new(io::ErrorKind::Other, format!("Clipboard error: {}", e)))
+        if let Some(coverage) = file.coverage {
+            entry.push_str(&format!(" coverage=\"{}\"", coverage.percent()));
+        }
+        if let Some(ref metadata) = file.metadata {
+            entry.push_str(&metadata.attributes());
+        }
+        if file.line_ranges.is_empty() {
+            entry.push_str(&format!(" lines=\"{}\">\n", file.content.lines().count()));
+        } else {
+            let ranges: Vec<String> = file.line_ranges.iter().map(|(start, end)| format!("{}-{}", start, end)).collect();
+            let lines: usize = file.line_ranges.iter().map(|(start, end)| end - start + 1).sum();
+            entry.push_str(&format!(" lines=\"{}\" ranges=\"{}\">\n", lines, ranges.join(",")));
+        }
+        if options.line_numbers && !file.line_ranges.is_empty() {
+            entry.push_str(&number_excerpt_lines(&file.content, &file.line_ranges));
+        } else if options.line_numbers {
+            entry.push_str(&number_lines(&file.content));
+        } else {
+            entry.push_str(&file.content);
         }
+        entry.push_str("\n</file>\n\n");
+        emit(&entry)?;
+        progress::formatted(entry.len());
     }
+    Ok(())
 }
 
-#[cfg(windows)]
-fn copy_to_clipboard(text: &str) -> io::Result<()> {
-    match Clipboard::new_attempts(10) {
-        Ok(_clip) => {
-            formats::Unicode.write_clipboard(&text).map_err(|e| {
-                io::Error::new(io::ErrorKind::Other, format!("Clipboard error: {:?}", e))
-            })
+fn number_lines(content: &str) -> String {
+    let line_count = content.lines().count();
+    let width = line_count.to_string().len();
+    
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| format!("{:>width$} │ {}", i + 1, line, width = width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Numbers excerpts with their line numbers in the original file
+fn number_excerpt_lines(content: &str, ranges: &[(usize, usize)]) -> String {
+    let width = ranges.last().map(|(_, end)| end.to_string().len()).unwrap_or(1);
+    let mut lines = content.lines();
+    let mut numbered = Vec::new();
+    
+    for (index, &(start, end)) in ranges.iter().enumerate() {
+        if index > 0 {
+            lines.next();
+            numbered.push(format!("{:>width$} │ {}", "", excerpt::SEPARATOR, width = width));
         }
-        Err(e) => {
-            Err(io::Error::new(io::ErrorKind::Other, format!("Clipboard error: {:?}", e)))
+        for number in start..=end {
+            numbered.push(format!("{:>width$} │ {}", number, lines.next().unwrap_or(""), width = width));
         }
     }
-}
\ No newline at end of file
+    
+    numbered.join("\n")
+}
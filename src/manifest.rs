@@ -0,0 +1,153 @@
+// src/manifest.rs
+use chrono::Local;
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crate::{config, paths, Options};
+
+// Written next to where the command runs unless --output says otherwise
+pub const MANIFEST_FILE: &str = "codecopier.lock";
+
+// Exit code of `manifest check` when the working tree no longer matches
+const EXIT_MISMATCH: u8 = 5;
+
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Subcommand)]
+pub enum ManifestCommand {
+    /// Record the files the paths and options select, with their hashes: manifest write [PATHS]... [OPTIONS]
+    Write(Box<Options>),
+    /// Check that the files recorded in a manifest are unchanged
+    Check {
+        #[arg(default_value = MANIFEST_FILE)]
+        manifest: PathBuf,
+    },
+}
+
+// Exactly which files went into a context, like a lockfile
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    created: String,
+    // The arguments the context was built with
+    args: Vec<String>,
+    // Hash of the whole generated context
+    context_sha256: String,
+    #[serde(rename = "file", default)]
+    files: Vec<ManifestFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestFile {
+    // Relative to the manifest's directory
+    path: String,
+    // Hash of the file on disk, not of the possibly reduced copied content
+    sha256: String,
+}
+
+pub fn run(command: ManifestCommand) -> io::Result<ExitCode> {
+    match command {
+        ManifestCommand::Write(options) => write(*options).map(|()| ExitCode::SUCCESS),
+        ManifestCommand::Check { manifest } => check(&manifest),
+    }
+}
+
+fn write(mut options: Options) -> io::Result<()> {
+    options.configure_logging();
+    let _scratch_dirs = crate::resolve_inputs(&mut options)?;
+    if options.paths.is_empty() {
+        options.paths.push(".".to_string());
+    }
+    let config = config::load(&options.paths, options.config_path.as_deref())?;
+    let output = crate::generate_output(&options, &config)?;
+    
+    let destination = options.output_path.clone().unwrap_or_else(|| MANIFEST_FILE.to_string());
+    let base = match destination.as_str() {
+        "-" => env::current_dir()?,
+        path => manifest_dir(Path::new(path))?,
+    };
+    let mut files = Vec::new();
+    for file in &output.files {
+        let absolute = paths::canonicalize(&file.source_path)?;
+        files.push(ManifestFile { path: paths::relative_to(&absolute, &base), sha256: file_hash(&absolute)? });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        created: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        args: env::args().skip(3).collect(),
+        context_sha256: crate::content_hash(output.text()),
+        files,
+    };
+    let text = toml::to_string(&manifest).map_err(io::Error::other)?;
+    let text = format!("# Generated by `codecopier manifest write`; verify with `codecopier manifest check`\n{}", text);
+    if destination == "-" {
+        print!("{}", text);
+    } else {
+        fs::write(&destination, text)
+            .map_err(|e| io::Error::new(e.kind(), format!("Failed to write {}: {}", destination, e)))?;
+        if !options.quiet {
+            println!("Recorded {} files in {}", manifest.files.len(), destination);
+        }
+    }
+    Ok(())
+}
+
+fn check(path: &Path) -> io::Result<ExitCode> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to read {}: {}", path.display(), e)))?;
+    let manifest: Manifest = toml::from_str(&text).map_err(|e| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("Invalid manifest {}: {}", path.display(), e))
+    })?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} was written by a newer version (manifest version {})", path.display(), manifest.version),
+        ));
+    }
+    
+    let base = manifest_dir(path)?;
+    let mut mismatches = 0;
+    for file in &manifest.files {
+        let status = match file_hash(&base.join(&file.path)) {
+            Ok(hash) if hash == file.sha256 => continue,
+            Ok(_) => "modified",
+            Err(e) if e.kind() == io::ErrorKind::NotFound => "missing",
+            Err(e) => return Err(io::Error::new(e.kind(), format!("Failed to read {}: {}", file.path, e))),
+        };
+        println!("{:<9} {}", status, file.path);
+        mismatches += 1;
+    }
+    
+    if mismatches == 0 {
+        println!("All {} files match {} (created {})", manifest.files.len(), path.display(), manifest.created);
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!("{} of {} files differ from {}", mismatches, manifest.files.len(), path.display());
+        if !manifest.args.is_empty() {
+            println!("Recorded with: {}", manifest.args.join(" "));
+        }
+        Ok(ExitCode::from(EXIT_MISMATCH))
+    }
+}
+
+// The canonical directory a manifest's paths are relative to
+fn manifest_dir(path: &Path) -> io::Result<PathBuf> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    paths::canonicalize(&dir).map_err(|e| io::Error::new(e.kind(), format!("Failed to open {}: {}", dir.display(), e)))
+}
+
+fn file_hash(path: &Path) -> io::Result<String> {
+    let digest = Sha256::digest(fs::read(path)?);
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
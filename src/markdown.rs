@@ -0,0 +1,90 @@
+// src/markdown.rs
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::formatter::{Formatter, RenderContext};
+use crate::languages;
+use crate::tree::TreeNode;
+
+// --format markdown: headings and fenced code blocks, for chat UIs and
+// documents that render Markdown
+pub struct Markdown;
+
+impl Formatter for Markdown {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+    
+    fn prompts(&self) -> bool {
+        true
+    }
+    
+    fn front_matter(&self) -> bool {
+        true
+    }
+    
+    fn render(&self, context: &RenderContext) -> String {
+        let post_process = context.post_process;
+        let mut markdown = format!("# {}\n\n", post_process(context.title));
+        
+        // The header, project metadata and any extra sections keep their XML tags
+        for text in [&context.sections.header, &context.sections.project_info, &context.sections.extra] {
+            if !text.trim().is_empty() {
+                markdown.push_str(&fenced(&post_process(text.trim_end()), "xml"));
+            }
+        }
+        
+        // The file structure section carries the --stats and coverage notes;
+        // grouped components have none, so their tree is drawn here
+        let structure = context
+            .sections
+            .file_structure
+            .trim()
+            .trim_start_matches("<file_structure>")
+            .trim_end_matches("</file_structure>")
+            .trim_matches('\n');
+        let tree = match structure {
+            "" => TreeNode::build(context.files.iter().map(|file| file.path.as_str())).render(),
+            structure => structure.to_string(),
+        };
+        markdown.push_str("## File structure\n\n");
+        markdown.push_str(&fenced(&post_process(tree.trim_end()), "text"));
+        
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+        for file in context.files {
+            markdown.push_str(&format!("## {}\n\n", post_process(&file.path)));
+            
+            if context.options.dedupe && !file.content.trim().is_empty() {
+                if let Some(original) = seen.get(file.hash.as_str()) {
+                    markdown.push_str(&format!("Same content as {}\n\n", post_process(original)));
+                    continue;
+                }
+                seen.insert(&file.hash, &file.path);
+            }
+            
+            let language = languages::language_id(Path::new(&file.path)).unwrap_or("");
+            let content = if context.options.line_numbers && !file.line_ranges.is_empty() {
+                crate::number_excerpt_lines(&file.content, &file.line_ranges)
+            } else if context.options.line_numbers {
+                crate::number_lines(&file.content)
+            } else {
+                file.content.clone()
+            };
+            markdown.push_str(&fenced(&post_process(&content), language));
+        }
+        
+        markdown
+    }
+}
+
+// A code block whose fence is longer than any backtick run in the text
+fn fenced(text: &str, language: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    let newline = if text.ends_with('\n') { "" } else { "\n" };
+    format!("{}{}\n{}{}{}\n\n", fence, language, text, newline, fence)
+}
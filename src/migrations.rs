@@ -0,0 +1,300 @@
+// src/migrations.rs
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::sql_schema::Schema;
+use crate::FileEntry;
+
+// Migrations kept by `--migrations last`, and for Alembic in schema mode
+const DEFAULT_LAST: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    // Replace the migrations with the schema they build
+    Schema,
+    // Keep only the latest migrations
+    Last(usize),
+    // Copy every migration
+    All,
+}
+
+pub fn parse_mode(value: &str) -> Result<Mode, String> {
+    match value {
+        "schema" => Ok(Mode::Schema),
+        "all" => Ok(Mode::All),
+        "last" => Ok(Mode::Last(DEFAULT_LAST)),
+        _ => match value.strip_prefix("last:").map(str::parse::<usize>) {
+            Some(Ok(count)) if count > 0 => Ok(Mode::Last(count)),
+            Some(_) => Err("expected a positive number after last:".to_string()),
+            None => Err("expected schema, last, last:N or all".to_string()),
+        },
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tool {
+    Diesel,
+    Sqlx,
+    Flyway,
+    Alembic,
+}
+
+impl Tool {
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Diesel => "diesel",
+            Tool::Sqlx => "sqlx",
+            Tool::Flyway => "flyway",
+            Tool::Alembic => "alembic",
+        }
+    }
+}
+
+// What a file is to its migration
+#[derive(Clone, Copy, PartialEq)]
+enum Role {
+    // Applied when migrating forward: up.sql, V1__x.sql, R__x.sql
+    Up,
+    // down.sql, .down.sql, U1__x.sql and diesel's metadata.toml
+    Other,
+}
+
+// One migration: a diesel directory, a sqlx file or up/down pair, a
+// Flyway script or an Alembic revision
+struct Migration {
+    name: String,
+    files: Vec<usize>,
+    up: Option<usize>,
+    // Repeatable Flyway scripts run after the versioned ones
+    repeatable: bool,
+    // Alembic revision ids
+    revision: Option<String>,
+    down_revision: Option<String>,
+}
+
+struct MigrationSet {
+    tool: Tool,
+    dir: String,
+    migrations: Vec<Migration>,
+}
+
+// Migration files taken out of the copy, and the <migrations> sections
+// standing in for them
+pub struct Folded {
+    pub files: Vec<FileEntry>,
+    pub section: String,
+}
+
+// Replaces each run of two or more migrations among `files` with the schema
+// they build, or with the latest ones, depending on `mode`
+pub fn fold(files: &mut Vec<FileEntry>, mode: Mode) -> Folded {
+    let mut folded = Folded { files: Vec::new(), section: String::new() };
+    if mode == Mode::All {
+        return folded;
+    }
+    
+    let mut remove = vec![false; files.len()];
+    for set in migration_sets(files) {
+        if set.migrations.len() < 2 {
+            continue;
+        }
+        let (kept, section) = match (mode, set.tool) {
+            (Mode::Schema, Tool::Diesel | Tool::Sqlx | Tool::Flyway) => match schema_section(&set, files) {
+                Some(section) => (0, section),
+                None => last_section(&set, DEFAULT_LAST, "no schema statements were found"),
+            },
+            (Mode::Schema, Tool::Alembic) => {
+                last_section(&set, DEFAULT_LAST, "Alembic migrations are Python, so the schema is not synthesized")
+            }
+            (Mode::Last(count), _) => last_section(&set, count, ""),
+            (Mode::All, _) => continue,
+        };
+        for migration in &set.migrations[..set.migrations.len().saturating_sub(kept)] {
+            for &index in &migration.files {
+                remove[index] = true;
+            }
+        }
+        folded.section.push_str(&section);
+    }
+    
+    let mut kept = Vec::with_capacity(files.len());
+    for (index, file) in files.drain(..).enumerate() {
+        if remove[index] {
+            folded.files.push(file);
+        } else {
+            kept.push(file);
+        }
+    }
+    *files = kept;
+    folded
+}
+
+// <migrations> section with the schema left by all the up migrations
+fn schema_section(set: &MigrationSet, files: &[FileEntry]) -> Option<String> {
+    let mut schema = Schema::default();
+    for migration in &set.migrations {
+        if let Some(up) = migration.up {
+            schema.apply(&files[up].content);
+        }
+    }
+    if schema.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "<migrations dir=\"{}\" tool=\"{}\" count=\"{}\" latest=\"{}\">\n-- Schema after all {} migrations, replayed from their up migrations\n{}</migrations>\n\n",
+        set.dir,
+        set.tool.name(),
+        set.migrations.len(),
+        set.migrations.last().map(|migration| migration.name.as_str()).unwrap_or(""),
+        set.migrations.len(),
+        schema.render()
+    ))
+}
+
+// The number of migrations kept and a <migrations> section naming the
+// folded ones; `note` says why the schema was not synthesized
+fn last_section(set: &MigrationSet, count: usize, note: &str) -> (usize, String) {
+    let total = set.migrations.len();
+    if count >= total {
+        return (total, String::new());
+    }
+    let mut section = format!(
+        "<migrations dir=\"{}\" tool=\"{}\" count=\"{}\" latest=\"{}\">\n",
+        set.dir,
+        set.tool.name(),
+        total,
+        set.migrations.last().map(|migration| migration.name.as_str()).unwrap_or("")
+    );
+    if !note.is_empty() {
+        section.push_str(&format!("{}. ", note));
+    }
+    section.push_str(&format!("The last {} of {} migrations are copied; these earlier ones are left out:\n", count, total));
+    for migration in &set.migrations[..total - count] {
+        section.push_str(&format!("- {}\n", migration.name));
+    }
+    section.push_str("</migrations>\n\n");
+    (count, section)
+}
+
+// Files grouped into migration sets by directory, in the order they run
+fn migration_sets(files: &[FileEntry]) -> Vec<MigrationSet> {
+    let diesel_dir = Regex::new(r"^(?:\d{4}-\d{2}-\d{2}-\d{6}|\d{14})_\w+$").expect("valid regex");
+    let flyway = Regex::new(r"^(?:([VU])(\d+(?:[._]\d+)*)|(R))__(.+)\.sql$").expect("valid regex");
+    let sqlx = Regex::new(r"^(\d+)_(.+?)(?:\.(up|down))?\.sql$").expect("valid regex");
+    let revision = Regex::new(r#"(?m)^revision\s*(?::\s*str\s*)?=\s*['"]([^'"]+)['"]"#).expect("valid regex");
+    let down_revision = Regex::new(r#"(?m)^down_revision\s*(?::[^=]*)?=\s*['"]([^'"]+)['"]"#).expect("valid regex");
+    
+    let mut sets: Vec<MigrationSet> = Vec::new();
+    let mut set_indexes: HashMap<PathBuf, usize> = HashMap::new();
+    for (index, file) in files.iter().enumerate() {
+        let source = &file.source_path;
+        let name = file_name(source);
+        let parent = source.parent().map(Path::to_path_buf).unwrap_or_default();
+        let parent_name = file_name(&parent);
+        
+        // The tool, the set's directory, the migration name and the file's role
+        let (tool, set_dir, migration, role, repeatable) = if diesel_dir.is_match(&parent_name) {
+            let role = if name == "up.sql" { Role::Up } else { Role::Other };
+            (Tool::Diesel, parent.parent().map(Path::to_path_buf).unwrap_or_default(), parent_name, role, false)
+        } else if let Some(caps) = flyway.captures(&name) {
+            let role = if caps.get(1).is_some_and(|kind| kind.as_str() == "U") { Role::Other } else { Role::Up };
+            let migration = match caps.get(2) {
+                Some(version) => format!("V{}__{}", version.as_str(), &caps[4]),
+                None => format!("R__{}", &caps[4]),
+            };
+            (Tool::Flyway, parent, migration, role, caps.get(3).is_some())
+        } else if let Some(caps) = sqlx.captures(&name).filter(|_| parent_name.contains("migration")) {
+            let role = if caps.get(3).is_some_and(|direction| direction.as_str() == "down") { Role::Other } else { Role::Up };
+            (Tool::Sqlx, parent, format!("{}_{}", &caps[1], &caps[2]), role, false)
+        } else if name.ends_with(".py") && parent_name == "versions" && revision.is_match(&file.content) {
+            (Tool::Alembic, parent, name.trim_end_matches(".py").to_string(), Role::Up, false)
+        } else {
+            continue;
+        };
+        
+        let set_index = *set_indexes.entry(set_dir.clone()).or_insert_with(|| {
+            sets.push(MigrationSet { tool, dir: display_dir(file, &set_dir), migrations: Vec::new() });
+            sets.len() - 1
+        });
+        let set = &mut sets[set_index];
+        if set.tool != tool {
+            continue;
+        }
+        let entry = match set.migrations.iter().position(|existing| existing.name == migration) {
+            Some(position) => &mut set.migrations[position],
+            None => {
+                set.migrations.push(Migration {
+                    name: migration,
+                    files: Vec::new(),
+                    up: None,
+                    repeatable,
+                    revision: revision.captures(&file.content).map(|caps| caps[1].to_string()).filter(|_| tool == Tool::Alembic),
+                    down_revision: down_revision.captures(&file.content).map(|caps| caps[1].to_string()).filter(|_| tool == Tool::Alembic),
+                });
+                set.migrations.last_mut().expect("just pushed")
+            }
+        };
+        entry.files.push(index);
+        if role == Role::Up {
+            entry.up = Some(index);
+        }
+    }
+    
+    for set in &mut sets {
+        if set.tool == Tool::Alembic {
+            order_revisions(&mut set.migrations);
+        } else {
+            set.migrations.sort_by(|a, b| {
+                a.repeatable
+                    .cmp(&b.repeatable)
+                    .then_with(|| version_key(&a.name).cmp(&version_key(&b.name)))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+        }
+    }
+    sets
+}
+
+// Alembic revisions follow their down_revision chain rather than their file names
+fn order_revisions(migrations: &mut Vec<Migration>) {
+    migrations.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut ordered: Vec<Migration> = Vec::new();
+    let mut previous: Option<String> = None;
+    while !migrations.is_empty() {
+        let next = migrations
+            .iter()
+            .position(|migration| migration.down_revision == previous)
+            // Branches and merges are appended in file name order
+            .unwrap_or(0);
+        let migration = migrations.remove(next);
+        previous = migration.revision.clone();
+        ordered.push(migration);
+    }
+    *migrations = ordered;
+}
+
+// The numbers in a migration name, so `V10` sorts after `V9`
+fn version_key(name: &str) -> Vec<u128> {
+    let version: String = name.chars().take_while(|c| !c.is_alphabetic() || *c == 'V').collect();
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().unwrap_or(u128::MAX))
+        .collect()
+}
+
+// The set's directory as shown in the copy, from the displayed path of one of its files
+fn display_dir(file: &FileEntry, set_dir: &Path) -> String {
+    let depth = file.source_path.strip_prefix(set_dir).map(|relative| relative.components().count()).unwrap_or(1);
+    let parts: Vec<&str> = file.path.split('/').collect();
+    if parts.len() > depth {
+        parts[..parts.len() - depth].join("/")
+    } else {
+        file_name(set_dir)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
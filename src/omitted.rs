@@ -0,0 +1,101 @@
+// src/omitted.rs
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::languages;
+use crate::summary::SkippedFile;
+
+// Only the start of a file is read to find its language and doc comment
+const DOC_SCAN_BYTES: u64 = 8 * 1024;
+// Doc comments are cut to this many characters
+const MAX_DOC_CHARS: usize = 120;
+
+// Lists the files that were left out, so the model can ask for them by name
+pub fn format_omitted_section(skipped: &[SkippedFile]) -> String {
+    let mut sorted: Vec<&SkippedFile> = skipped.iter().collect();
+    sorted.sort_by(|a, b| a.path.cmp(&b.path));
+    
+    let mut section = format!("<omitted_files count=\"{}\">\n", sorted.len());
+    for file in sorted {
+        let mut details = Vec::new();
+        let head = read_head(&file.source_path);
+        if let Some(language) = languages::detect_language(&file.source_path, head.as_deref().unwrap_or("")) {
+            details.push(language.to_string());
+        }
+        if let Ok(metadata) = file.source_path.metadata() {
+            details.push(languages::format_size(metadata.len() as usize));
+        }
+        details.push(file.reason.label().to_string());
+        
+        section.push_str(&format!("{}  [{}]", file.path, details.join(", ")));
+        if let Some(doc) = head.as_deref().and_then(first_doc_comment) {
+            section.push_str(&format!(" {}", doc));
+        }
+        section.push('\n');
+    }
+    section.push_str("</omitted_files>\n\n");
+    section
+}
+
+// The start of a text file, where doc comments, shebangs and modelines are
+fn read_head(path: &Path) -> Option<String> {
+    let mut bytes = Vec::new();
+    File::open(path).ok()?.take(DOC_SCAN_BYTES).read_to_end(&mut bytes).ok()?;
+    if bytes.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+// The first line of the file's leading doc comment: `//!`/`///` in Rust,
+// a module docstring in Python, or a `/** ... */` block elsewhere
+fn first_doc_comment(content: &str) -> Option<String> {
+    let mut in_block = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if in_block {
+            let text = line.trim_start_matches('*').trim_end_matches("*/").trim_end_matches("\"\"\"").trim();
+            if !text.is_empty() {
+                return Some(shorten(text));
+            }
+            if line.ends_with("*/") {
+                in_block = false;
+            }
+            continue;
+        }
+        
+        if let Some(text) = line.strip_prefix("//!").or_else(|| line.strip_prefix("///")) {
+            if !text.trim().is_empty() {
+                return Some(shorten(text.trim()));
+            }
+        } else if let Some(rest) = line.strip_prefix("\"\"\"").or_else(|| line.strip_prefix("'''")) {
+            let text = rest.trim_end_matches("\"\"\"").trim_end_matches("'''").trim();
+            if !text.is_empty() {
+                return Some(shorten(text));
+            }
+            in_block = true;
+        } else if let Some(rest) = line.strip_prefix("/**") {
+            let text = rest.trim_end_matches("*/").trim();
+            if !text.is_empty() {
+                return Some(shorten(text));
+            }
+            in_block = !rest.ends_with("*/");
+        } else if line.is_empty() || line.starts_with("#!") || line.starts_with("//") || line.starts_with('#') {
+            // Shebangs, blank lines and ordinary comments may precede the doc comment
+            continue;
+        } else {
+            break;
+        }
+    }
+    None
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() <= MAX_DOC_CHARS {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(MAX_DOC_CHARS).collect();
+        format!("{}...", cut.trim_end())
+    }
+}
@@ -0,0 +1,277 @@
+// src/outline.rs
+use std::path::Path;
+
+use clap::ValueEnum;
+use tree_sitter::{Language, Node, Parser};
+
+use crate::{languages, FileEntry};
+
+// Longest signature shown in an outline before it is cut off
+const MAX_SIGNATURE_LENGTH: usize = 120;
+
+// Whether --outline replaces file contents or is added next to them
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Mode {
+    Replace,
+    Alongside,
+}
+
+// Syntax node kinds treated as definitions, per language. Containers are
+// definitions whose bodies are outlined as well (classes, impls, modules)
+struct Grammar {
+    language: &'static str,
+    definitions: &'static [&'static str],
+    containers: &'static [&'static str],
+}
+
+const GRAMMARS: &[Grammar] = &[
+    Grammar {
+        language: "Rust",
+        definitions: &[
+            "function_item", "function_signature_item", "struct_item", "enum_item", "union_item", "trait_item",
+            "impl_item", "mod_item", "type_item", "const_item", "static_item", "macro_definition",
+        ],
+        containers: &["trait_item", "impl_item", "mod_item"],
+    },
+    Grammar {
+        language: "Python",
+        definitions: &["function_definition", "class_definition"],
+        containers: &["class_definition"],
+    },
+    Grammar {
+        language: "JavaScript",
+        definitions: &[
+            "function_declaration", "generator_function_declaration", "class_declaration", "method_definition",
+            "lexical_declaration", "variable_declaration",
+        ],
+        containers: &["class_declaration"],
+    },
+    Grammar {
+        language: "TypeScript",
+        definitions: &[
+            "function_declaration", "generator_function_declaration", "class_declaration",
+            "abstract_class_declaration", "method_definition", "method_signature", "interface_declaration",
+            "type_alias_declaration", "enum_declaration", "module", "internal_module", "lexical_declaration",
+            "variable_declaration",
+        ],
+        containers: &["class_declaration", "abstract_class_declaration", "interface_declaration", "module", "internal_module"],
+    },
+    Grammar {
+        language: "Go",
+        definitions: &["function_declaration", "method_declaration", "type_declaration"],
+        containers: &[],
+    },
+    Grammar {
+        language: "Java",
+        definitions: &[
+            "class_declaration", "interface_declaration", "enum_declaration", "record_declaration",
+            "annotation_type_declaration", "method_declaration", "constructor_declaration",
+        ],
+        containers: &["class_declaration", "interface_declaration", "enum_declaration", "record_declaration"],
+    },
+    Grammar {
+        language: "C",
+        definitions: &["function_definition", "struct_specifier", "union_specifier", "enum_specifier", "type_definition"],
+        containers: &[],
+    },
+    Grammar {
+        language: "C++",
+        definitions: &[
+            "function_definition", "struct_specifier", "union_specifier", "enum_specifier", "class_specifier",
+            "type_definition", "namespace_definition",
+        ],
+        containers: &["class_specifier", "struct_specifier", "namespace_definition"],
+    },
+    Grammar {
+        language: "C#",
+        definitions: &[
+            "class_declaration", "struct_declaration", "interface_declaration", "enum_declaration",
+            "record_declaration", "method_declaration", "constructor_declaration", "property_declaration",
+            "namespace_declaration", "file_scoped_namespace_declaration",
+        ],
+        containers: &[
+            "class_declaration", "struct_declaration", "interface_declaration", "record_declaration",
+            "namespace_declaration", "file_scoped_namespace_declaration",
+        ],
+    },
+    Grammar {
+        language: "Ruby",
+        definitions: &["method", "singleton_method", "class", "module"],
+        containers: &["class", "module"],
+    },
+];
+
+// A definition found in a source file; lines are 1-based and inclusive
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub name: String,
+    pub signature: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    // Number of enclosing definitions
+    pub depth: usize,
+}
+
+fn language(path: &Path, name: &str) -> Option<Language> {
+    let language = match name {
+        "Rust" => tree_sitter_rust::LANGUAGE,
+        "Python" => tree_sitter_python::LANGUAGE,
+        "JavaScript" => tree_sitter_javascript::LANGUAGE,
+        "TypeScript" if path.extension().and_then(|ext| ext.to_str()) == Some("tsx") => {
+            tree_sitter_typescript::LANGUAGE_TSX
+        }
+        "TypeScript" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+        "Go" => tree_sitter_go::LANGUAGE,
+        "Java" => tree_sitter_java::LANGUAGE,
+        "C" => tree_sitter_c::LANGUAGE,
+        "C++" => tree_sitter_cpp::LANGUAGE,
+        "C#" => tree_sitter_c_sharp::LANGUAGE,
+        "Ruby" => tree_sitter_ruby::LANGUAGE,
+        _ => return None,
+    };
+    Some(language.into())
+}
+
+// Definitions in the order they appear, or None for languages without a grammar
+pub fn definitions(path: &Path, content: &str) -> Option<Vec<Definition>> {
+    let name = languages::detect_language(path, content)?;
+    let grammar = GRAMMARS.iter().find(|grammar| grammar.language == name)?;
+    let mut parser = Parser::new();
+    parser.set_language(&language(path, name)?).ok()?;
+    let tree = parser.parse(content, None)?;
+    
+    let mut definitions = Vec::new();
+    collect(tree.root_node(), content, grammar, 0, &mut definitions);
+    Some(definitions)
+}
+
+fn collect(node: Node, source: &str, grammar: &Grammar, depth: usize, definitions: &mut Vec<Definition>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let kind = child.kind();
+        // `struct point *p` mentions a struct without defining it
+        let is_definition = grammar.definitions.contains(&kind)
+            && !(kind.ends_with("_specifier") && child.child_by_field_name("body").is_none());
+        
+        // Wrappers such as decorators, `export` statements and class bodies are
+        // looked through; definitions other than containers are not descended into
+        if !is_definition {
+            collect(child, source, grammar, depth, definitions);
+            continue;
+        }
+        
+        definitions.push(Definition {
+            name: definition_name(child, source).unwrap_or_default(),
+            signature: signature(child, source),
+            start_line: child.start_position().row + 1,
+            end_line: child.end_position().row + 1,
+            depth,
+        });
+        if grammar.containers.contains(&kind) {
+            collect(child, source, grammar, depth + 1, definitions);
+        }
+    }
+}
+
+fn definition_name(node: Node, source: &str) -> Option<String> {
+    // Rust impls are named after their type
+    for field in ["name", "type"] {
+        if let Some(name) = node.child_by_field_name(field) {
+            return Some(text(name, source).to_string());
+        }
+    }
+    
+    // C function names sit inside nested declarators
+    if let Some(mut declarator) = node.child_by_field_name("declarator") {
+        while let Some(inner) = declarator.child_by_field_name("declarator") {
+            declarator = inner;
+        }
+        return Some(text(declarator, source).to_string());
+    }
+    
+    // Go `type` and JavaScript `const` declarations wrap named specs
+    let mut cursor = node.walk();
+    let names = node.named_children(&mut cursor).find_map(|child| child.child_by_field_name("name"));
+    names.map(|name| text(name, source).to_string())
+}
+
+// The definition's text up to its body, on one line
+fn signature(node: Node, source: &str) -> String {
+    let end = node.child_by_field_name("body").map(|body| body.start_byte()).unwrap_or_else(|| node.end_byte());
+    // `export` is a separate node wrapping JavaScript and TypeScript definitions
+    let start = match node.parent() {
+        Some(parent) if parent.kind() == "export_statement" => parent.start_byte(),
+        _ => node.start_byte(),
+    };
+    let head = &source[start..end];
+    let head = match node.child_by_field_name("body") {
+        Some(_) => head,
+        None => head.lines().next().unwrap_or(""),
+    };
+    
+    let mut signature = head.split_whitespace().collect::<Vec<_>>().join(" ");
+    signature = signature.trim_end_matches(['{', ':', '=']).trim_end().to_string();
+    if signature.chars().count() > MAX_SIGNATURE_LENGTH {
+        signature = signature.chars().take(MAX_SIGNATURE_LENGTH).collect::<String>() + "...";
+    }
+    signature
+}
+
+fn text<'a>(node: Node, source: &'a str) -> &'a str {
+    &source[node.byte_range()]
+}
+
+// One line per definition: line number, then the signature indented by nesting
+pub fn format_outline(definitions: &[Definition]) -> String {
+    let width = definitions.iter().map(|definition| definition.start_line.to_string().len()).max().unwrap_or(1);
+    definitions
+        .iter()
+        .map(|definition| {
+            format!(
+                "{:>width$}: {}{}",
+                definition.start_line,
+                "    ".repeat(definition.depth),
+                definition.signature,
+                width = width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Replaces every file that has a grammar with its outline and drops the rest
+pub fn extract(files: Vec<FileEntry>) -> Vec<FileEntry> {
+    files
+        .into_iter()
+        .filter_map(|mut file| {
+            file.content = cached_outline(&file)?;
+            file.hash = crate::content_hash(&file.content);
+            file.line_ranges = Vec::new();
+            Some(file)
+        })
+        .collect()
+}
+
+// The formatted outline of a file, through the pass cache since parsing
+// every file of a large repository is slow
+fn cached_outline(file: &FileEntry) -> Option<String> {
+    let language = languages::detect_language(&file.source_path, &file.content)?;
+    // TSX and TypeScript share a language name but not a grammar
+    let extension = file.source_path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    let pass = format!("outline-{}-{}", language, extension);
+    crate::cache::get_or_compute(&pass, &file.content, || {
+        definitions(&file.source_path, &file.content).map(|definitions| format_outline(&definitions))
+    })
+}
+
+// `<outline>` section listing the outline of every file that has a grammar
+pub fn format_outline_section(files: &[FileEntry]) -> String {
+    let mut section = String::from("<outline>\n");
+    for file in files {
+        if let Some(outline) = cached_outline(file) {
+            section.push_str(&format!("<file path=\"{}\">\n{}\n</file>\n", file.path, outline));
+        }
+    }
+    section.push_str("</outline>\n\n");
+    section
+}
@@ -0,0 +1,289 @@
+// src/patch.rs
+
+// One file's section of a unified diff; a missing path is /dev/null
+pub struct FilePatch {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+pub struct Hunk {
+    // First line of the hunk in the old file, 1-based
+    pub old_start: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+pub enum HunkLine {
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+impl Hunk {
+    fn old_lines(&self) -> Vec<&str> {
+        old_side(&self.lines)
+    }
+    
+    // The hunk as it appears in a unified diff
+    pub fn render(&self) -> String {
+        let old_count = self.old_lines().len();
+        let new_count = self.new_lines().len();
+        // Empty sides start before the first line, as in `diff -u`
+        let new_start = match (old_count, new_count) {
+            (0, _) => self.old_start + 1,
+            (_, 0) => self.old_start.saturating_sub(1),
+            _ => self.old_start,
+        };
+        let mut text = format!("@@ -{},{} +{},{} @@\n", self.old_start, old_count, new_start, new_count);
+        for line in &self.lines {
+            let (prefix, content) = match line {
+                HunkLine::Context(content) => (' ', content),
+                HunkLine::Removed(content) => ('-', content),
+                HunkLine::Added(content) => ('+', content),
+            };
+            text.push(prefix);
+            text.push_str(content);
+            text.push('\n');
+        }
+        text
+    }
+    
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Added(text) => Some(text.as_str()),
+                HunkLine::Removed(_) => None,
+            })
+            .collect()
+    }
+}
+
+// Parses the unified diffs in a text, ignoring anything around them (prose,
+// code fences, `diff --git` and `index` lines)
+pub fn parse(text: &str) -> Vec<FilePatch> {
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut lines = text.lines().peekable();
+    
+    while let Some(line) = lines.next() {
+        if let Some(old) = line.strip_prefix("--- ") {
+            if let Some(new) = lines.peek().and_then(|next| next.strip_prefix("+++ ")) {
+                patches.push(FilePatch {
+                    old_path: diff_path(old, "a/"),
+                    new_path: diff_path(new, "b/"),
+                    hunks: Vec::new(),
+                });
+                lines.next();
+            }
+            continue;
+        }
+        
+        let (patch, old_start) = match (patches.last_mut(), hunk_header(line)) {
+            (Some(patch), Some(old_start)) => (patch, old_start),
+            _ => continue,
+        };
+        let mut hunk = Hunk { old_start, lines: Vec::new() };
+        while let Some(next) = lines.peek() {
+            let parsed = match next.chars().next() {
+                Some(' ') => HunkLine::Context(next[1..].to_string()),
+                Some('-') if !next.starts_with("--- ") => HunkLine::Removed(next[1..].to_string()),
+                Some('+') if !next.starts_with("+++ ") => HunkLine::Added(next[1..].to_string()),
+                // Models often drop the space of empty context lines
+                None => HunkLine::Context(String::new()),
+                Some('\\') => {
+                    lines.next();
+                    continue;
+                }
+                _ => break,
+            };
+            hunk.lines.push(parsed);
+            lines.next();
+        }
+        // Trailing empty lines belong to the text around the diff
+        while matches!(hunk.lines.last(), Some(HunkLine::Context(text)) if text.is_empty()) {
+            hunk.lines.pop();
+        }
+        patch.hunks.push(hunk);
+    }
+    
+    patches
+}
+
+// `a/src/lib.rs\t2024-01-01 ...` → src/lib.rs
+fn diff_path(header: &str, prefix: &str) -> Option<String> {
+    let path = header.split('\t').next().unwrap_or("").trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+}
+
+// `@@ -12,7 +12,8 @@ fn main` → 12
+fn hunk_header(line: &str) -> Option<usize> {
+    let range = line.strip_prefix("@@ -")?.split_whitespace().next()?;
+    range.split(',').next()?.parse().ok()
+}
+
+// Context lines that may be dropped from each end of a hunk that does not
+// match otherwise, like the fuzz factor of `patch`
+const MAX_FUZZ: usize = 2;
+
+// What happened to one hunk
+pub enum Outcome {
+    // Applied at this 1-based line, `fuzzy` when whitespace or context differed
+    Applied { line: usize, fuzzy: bool },
+    // The file already contains the hunk's result
+    AlreadyApplied,
+    // The hunk matches nowhere; with conflict markers they start at this line
+    Conflict { line: usize },
+}
+
+pub struct Patched {
+    pub content: String,
+    pub outcomes: Vec<Outcome>,
+}
+
+impl Patched {
+    pub fn conflicts(&self) -> usize {
+        self.outcomes.iter().filter(|outcome| matches!(outcome, Outcome::Conflict { .. })).count()
+    }
+}
+
+// Where a hunk, possibly with context dropped, matches the file
+struct Match<'a> {
+    start: usize,
+    // Lines between where the hunk was expected and where it matched
+    drift: isize,
+    lines: &'a [HunkLine],
+    fuzzy: bool,
+}
+
+// Applies the hunks in order. A hunk whose lines are not at the stated
+// position is looked for nearby, since models often get line numbers wrong,
+// then with whitespace ignored and with less context. A hunk that still does
+// not match is a conflict: it is left out, or with `markers` written between
+// diff3-style conflict markers showing the current lines, the lines the hunk
+// expected and its replacement
+pub fn apply(content: &str, hunks: &[Hunk], markers: bool) -> Patched {
+    let trailing_newline = content.is_empty() || content.ends_with('\n');
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut delta: isize = 0;
+    let mut outcomes = Vec::new();
+    
+    for hunk in hunks {
+        // An insertion without context goes after the stated line
+        let expected = if hunk.old_lines().is_empty() { hunk.old_start } else { hunk.old_start.saturating_sub(1) };
+        let expected = (expected as isize + delta).clamp(0, lines.len() as isize) as usize;
+        
+        let (replaced, replacement, drift, outcome) = if let Some(found) = locate(&lines, &hunk.lines, expected) {
+            let old_len = found.lines.iter().filter(|line| !matches!(line, HunkLine::Added(_))).count();
+            let replacement = merge(&lines[found.start..found.start + old_len], found.lines);
+            let outcome = Outcome::Applied { line: found.start + 1, fuzzy: found.fuzzy };
+            (found.start..found.start + old_len, replacement, found.drift, outcome)
+        } else if is_applied(&lines, hunk, expected) {
+            outcomes.push(Outcome::AlreadyApplied);
+            continue;
+        } else if markers {
+            let old = hunk.old_lines();
+            let end = (expected + old.len()).min(lines.len());
+            let mut block = vec!["<<<<<<< current".to_string()];
+            block.extend(lines[expected..end].iter().cloned());
+            block.push("||||||| expected by the patch".to_string());
+            block.extend(old.iter().map(|line| line.to_string()));
+            block.push("=======".to_string());
+            block.extend(hunk.new_lines().iter().map(|line| line.to_string()));
+            block.push(">>>>>>> patch".to_string());
+            (expected..end, block, 0, Outcome::Conflict { line: expected + 1 })
+        } else {
+            outcomes.push(Outcome::Conflict { line: expected + 1 });
+            continue;
+        };
+        
+        delta += drift + replacement.len() as isize - replaced.len() as isize;
+        lines.splice(replaced, replacement);
+        outcomes.push(outcome);
+    }
+    
+    let mut updated = lines.join("\n");
+    if trailing_newline && !updated.is_empty() {
+        updated.push('\n');
+    }
+    Patched { content: updated, outcomes }
+}
+
+fn locate<'a>(file: &[String], hunk: &'a [HunkLine], expected: usize) -> Option<Match<'a>> {
+    for fuzz in 0..=MAX_FUZZ {
+        let leading = hunk.iter().take(fuzz).take_while(|line| matches!(line, HunkLine::Context(_))).count();
+        let rest = &hunk[leading..];
+        let trailing = rest.iter().rev().take(fuzz).take_while(|line| matches!(line, HunkLine::Context(_))).count();
+        let lines = &rest[..rest.len() - trailing];
+        if fuzz > 0 && leading + trailing == 0 {
+            break;
+        }
+        
+        let old: Vec<&str> = old_side(lines);
+        // Without context, an insertion could go anywhere
+        if old.is_empty() && fuzz > 0 {
+            continue;
+        }
+        let expected = expected + leading;
+        if let Some(start) = find(file, &old, expected, |line, wanted| line == wanted) {
+            return Some(Match { start, drift: start as isize - expected as isize, lines, fuzzy: fuzz > 0 });
+        }
+        if let Some(start) = find(file, &old, expected, |line, wanted| line.trim() == wanted.trim()) {
+            return Some(Match { start, drift: start as isize - expected as isize, lines, fuzzy: true });
+        }
+    }
+    None
+}
+
+fn old_side(lines: &[HunkLine]) -> Vec<&str> {
+    lines
+        .iter()
+        .filter_map(|line| match line {
+            HunkLine::Context(text) | HunkLine::Removed(text) => Some(text.as_str()),
+            HunkLine::Added(_) => None,
+        })
+        .collect()
+}
+
+// The replacement for the matched lines, keeping the file's own version of
+// context lines that only matched with whitespace ignored
+fn merge(matched: &[String], lines: &[HunkLine]) -> Vec<String> {
+    let mut current = matched.iter();
+    let mut merged = Vec::new();
+    for line in lines {
+        match line {
+            HunkLine::Context(_) => merged.extend(current.next().cloned()),
+            HunkLine::Removed(_) => {
+                current.next();
+            }
+            HunkLine::Added(text) => merged.push(text.clone()),
+        }
+    }
+    merged
+}
+
+// A hunk that adds lines is already applied when its result is in the file
+fn is_applied(file: &[String], hunk: &Hunk, expected: usize) -> bool {
+    if !hunk.lines.iter().any(|line| matches!(line, HunkLine::Added(_))) {
+        return false;
+    }
+    find(file, &hunk.new_lines(), expected, |line, wanted| line.trim() == wanted.trim()).is_some()
+}
+
+// The position of `needle` closest to `expected`
+fn find(lines: &[String], needle: &[&str], expected: usize, equal: impl Fn(&str, &str) -> bool) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(expected.min(lines.len()));
+    }
+    let last_start = lines.len().checked_sub(needle.len())?;
+    let expected = expected.min(last_start);
+    let matches_at = |start: usize| lines[start..start + needle.len()].iter().zip(needle).all(|(line, wanted)| equal(line, wanted));
+    
+    (0..=last_start)
+        .flat_map(|distance| [expected.checked_sub(distance), Some(expected + distance).filter(|_| distance > 0)])
+        .flatten()
+        .filter(|&start| start <= last_start)
+        .find(|&start| matches_at(start))
+}
@@ -0,0 +1,64 @@
+// src/path_spec.rs
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+
+// Checked-in list of what a project sends to the LLM, searched for upwards
+// from each copied directory
+pub const PATH_SPEC_FILE: &str = ".codecopier-paths";
+
+// The patterns of a .codecopier-paths file, in git sparse-checkout syntax: a
+// file is copied when the last pattern matching it or one of its directories
+// is not negated with `!`
+pub struct PathSpec {
+    pub path: PathBuf,
+    root: PathBuf,
+    matcher: Gitignore,
+}
+
+impl PathSpec {
+    // The spec file governing `dir`, if any
+    pub fn find(dir: &Path) -> io::Result<Option<PathSpec>> {
+        let dir = match paths::canonicalize(dir) {
+            Ok(dir) => dir,
+            Err(_) => return Ok(None),
+        };
+        let path = match dir.ancestors().map(|ancestor| ancestor.join(PATH_SPEC_FILE)).find(|path| path.is_file()) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        
+        let mut builder = GitignoreBuilder::new(&root);
+        if let Some(e) = builder.add(&path) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid {}: {}", path.display(), e)));
+        }
+        let matcher = builder
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid {}: {}", path.display(), e)))?;
+        Ok(Some(PathSpec { path, root, matcher }))
+    }
+    
+    // Whether the file at the absolute path `file` is selected; files outside
+    // the spec file's directory are not affected by it
+    pub fn includes(&self, file: &Path) -> bool {
+        if !file.starts_with(&self.root) {
+            return true;
+        }
+        matches!(self.matcher.matched_path_or_any_parents(file, false), Match::Ignore(_))
+    }
+    
+    // The pattern deciding about `file`, for `explain`
+    pub fn deciding_pattern(&self, file: &Path) -> Option<String> {
+        if !file.starts_with(&self.root) {
+            return None;
+        }
+        match self.matcher.matched_path_or_any_parents(file, false) {
+            Match::Ignore(glob) | Match::Whitelist(glob) => Some(glob.original().to_string()),
+            Match::None => None,
+        }
+    }
+}
@@ -0,0 +1,266 @@
+// src/paths.rs
+use clap::ValueEnum;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf, Prefix};
+
+// Longest path most Windows APIs accept without the `\\?\` prefix
+const MAX_PATH: usize = 260;
+
+// How `<file path>` values are computed with --path-base
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum PathBase {
+    // Relative to the repository (or project) root of each copied path
+    Repo,
+    // Relative to the current directory
+    Cwd,
+    // The copied path as given, followed by the path below it
+    Given,
+    // Absolute paths
+    Absolute,
+}
+
+// Renders a path with forward slashes on every platform, turning verbatim
+// prefixes (`\\?\C:\`, `\\?\UNC\server\share`) back into their usual form
+pub fn normalize(path: &Path) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut root = String::new();
+    
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                root = match prefix.kind() {
+                    Prefix::Verbatim(name) => format!("//{}", name.to_string_lossy()),
+                    Prefix::VerbatimUNC(server, share) | Prefix::UNC(server, share) => {
+                        format!("//{}/{}", server.to_string_lossy(), share.to_string_lossy())
+                    }
+                    Prefix::VerbatimDisk(disk) | Prefix::Disk(disk) => format!("{}:", disk as char),
+                    Prefix::DeviceNS(name) => format!("//./{}", name.to_string_lossy()),
+                };
+            }
+            Component::RootDir => root.push('/'),
+            Component::CurDir => {}
+            Component::ParentDir => parts.push("..".to_string()),
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+        }
+    }
+    
+    // Also treat backslashes as separators for paths written on Windows but read elsewhere
+    let joined = parts.join("/").replace('\\', "/");
+    if root.is_empty() {
+        joined
+    } else if root.ends_with('/') {
+        format!("{}{}", root, joined)
+    } else {
+        format!("{}/{}", root, joined)
+    }
+}
+
+// The path of `file_path` relative to `base`, normalized; falls back to
+// comparing canonicalized paths, using `..` for files outside of `base`, and
+// finally to the normalized path itself
+pub fn relative_to(file_path: &Path, base: &Path) -> String {
+    if let Some(relative) = relative_path(file_path, base, false) {
+        return relative;
+    }
+    
+    if let (Ok(file), Ok(base)) = (canonicalize(file_path), canonicalize(base)) {
+        if let Some(relative) = relative_path(&file, &base, true) {
+            return relative;
+        }
+    }
+    
+    normalize(file_path)
+}
+
+// Like fs::canonicalize, without the `\\?\` prefix Windows puts on every
+// result unless the path needs it: paths longer than MAX_PATH and names that
+// are only valid in verbatim form keep it, so they can still be opened. Other
+// verbatim paths would reject `/` and `..` in paths joined to them
+pub fn canonicalize(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let canonical = fs::canonicalize(path)?;
+    Ok(simplify_verbatim(&canonical).unwrap_or(canonical))
+}
+
+fn simplify_verbatim(path: &Path) -> Option<PathBuf> {
+    let mut components = path.components();
+    let mut simple = match components.next()? {
+        Component::Prefix(prefix) => match prefix.kind() {
+            Prefix::VerbatimDisk(disk) => format!("{}:", disk as char),
+            Prefix::VerbatimUNC(server, share) => format!(r"\\{}\{}", server.to_str()?, share.to_str()?),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    for component in components {
+        match component {
+            Component::RootDir => {}
+            Component::Normal(name) if is_plain_name(name.to_str()?) => {
+                simple.push('\\');
+                simple.push_str(name.to_str()?);
+            }
+            _ => return None,
+        }
+    }
+    if simple.ends_with(':') {
+        simple.push('\\');
+    }
+    (simple.len() < MAX_PATH).then(|| PathBuf::from(simple))
+}
+
+// Whether Windows resolves the name to the same file without `\\?\`: no
+// trailing dots or spaces, which it strips, and no device names like `nul`
+fn is_plain_name(name: &str) -> bool {
+    const DEVICES: [&str; 4] = ["con", "prn", "aux", "nul"];
+    let stem = name.split('.').next().unwrap_or(name).trim_end().to_ascii_lowercase();
+    let device = DEVICES.contains(&stem.as_str())
+        || ((stem.starts_with("com") || stem.starts_with("lpt"))
+            && stem.len() == 4
+            && stem.as_bytes()[3].is_ascii_digit());
+    !name.is_empty()
+        && !name.ends_with(['.', ' '])
+        && !name.contains(|c: char| c.is_control() || "<>:\"/\\|?*".contains(c))
+        && !device
+}
+
+// `file` relative to `base`, climbing out of `base` with `..` when `climb`
+// is set; None when they have no common root
+fn relative_path(file: &Path, base: &Path, climb: bool) -> Option<String> {
+    if file.is_absolute() != base.is_absolute() {
+        return None;
+    }
+    let file_components: Vec<Component> = file.components().filter(|c| *c != Component::CurDir).collect();
+    let base_components: Vec<Component> = base.components().filter(|c| *c != Component::CurDir).collect();
+    let file_keys: Vec<String> = file_components.iter().map(component_key).collect();
+    let base_keys: Vec<String> = base_components.iter().map(component_key).collect();
+    let common = file_keys.iter().zip(&base_keys).take_while(|(a, b)| a == b).count();
+    
+    // Paths on different drives or shares have no common prefix to climb up to
+    if common < base_keys.len() && (!climb || common == 0) {
+        return None;
+    }
+    let mut relative = PathBuf::new();
+    for _ in common..base_keys.len() {
+        relative.push("..");
+    }
+    for component in &file_components[common..] {
+        relative.push(component.as_os_str());
+    }
+    Some(normalize(&relative))
+}
+
+// Components as Windows resolves them: `\\?\C:\` is the same root as `c:\`
+// and `\\?\UNC\server\share` the same as `\\server\share`
+fn component_key(component: &Component) -> String {
+    match component {
+        Component::Prefix(prefix) => match prefix.kind() {
+            Prefix::VerbatimDisk(disk) | Prefix::Disk(disk) => format!("{}:", disk.to_ascii_uppercase() as char),
+            Prefix::VerbatimUNC(server, share) | Prefix::UNC(server, share) => {
+                format!("//{}/{}", server.to_string_lossy(), share.to_string_lossy()).to_lowercase()
+            }
+            _ => component.as_os_str().to_string_lossy().into_owned(),
+        },
+        _ => component.as_os_str().to_string_lossy().into_owned(),
+    }
+}
+
+// The nearest directory above `path` containing `.git`
+pub fn repo_root(path: &Path) -> Option<PathBuf> {
+    let mut current = canonicalize(path).ok()?;
+    if current.is_file() {
+        current.pop();
+    }
+    loop {
+        if current.join(".git").exists() {
+            return Some(current);
+        }
+        if !current.pop() {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    
+    // A scratch directory nested deep enough to exceed MAX_PATH
+    fn long_dir(name: &str) -> (PathBuf, PathBuf) {
+        let root = env::temp_dir().join(format!("codecopier-paths-{}-{}", name, std::process::id()));
+        let mut dir = root.clone();
+        while dir.as_os_str().len() <= MAX_PATH + 40 {
+            dir.push("a-rather-long-directory-name");
+        }
+        fs::create_dir_all(&dir).unwrap();
+        (root, dir)
+    }
+    
+    #[test]
+    fn relative_to_long_paths() {
+        let (root, dir) = long_dir("relative");
+        let file = dir.join("main.rs");
+        fs::write(&file, "fn main() {}").unwrap();
+        
+        let expected = normalize(file.strip_prefix(&root).unwrap());
+        assert!(expected.len() > MAX_PATH);
+        assert_eq!(relative_to(&file, &root), expected);
+        assert_eq!(relative_to(&canonicalize(&file).unwrap(), &root), expected);
+        assert_eq!(relative_to(&file, &dir.join("..")), "a-rather-long-directory-name/main.rs");
+        fs::remove_dir_all(&root).unwrap();
+    }
+    
+    #[test]
+    fn relative_to_climbs_out_of_long_base() {
+        let (root, dir) = long_dir("climb");
+        let file = root.join("top.rs");
+        fs::write(&file, "").unwrap();
+        
+        let depth = dir.strip_prefix(&root).unwrap().components().count();
+        assert_eq!(relative_to(&file, &dir), format!("{}top.rs", "../".repeat(depth)));
+        fs::remove_dir_all(&root).unwrap();
+    }
+    
+    #[test]
+    fn canonicalize_opens_long_paths() {
+        let (root, dir) = long_dir("open");
+        let file = dir.join("lib.rs");
+        fs::write(&file, "pub fn f() {}").unwrap();
+        
+        let canonical = canonicalize(&file).unwrap();
+        assert_eq!(fs::read_to_string(&canonical).unwrap(), "pub fn f() {}");
+        fs::remove_dir_all(&root).unwrap();
+    }
+    
+    #[test]
+    fn relative_paths_stay_relative() {
+        assert_eq!(relative_to(Path::new("src/main.rs"), Path::new("src")), "main.rs");
+        assert_eq!(relative_to(Path::new("./src/main.rs"), Path::new(".")), "src/main.rs");
+    }
+    
+    #[cfg(windows)]
+    #[test]
+    fn verbatim_prefixes_are_dropped_when_not_needed() {
+        assert_eq!(simplify_verbatim(Path::new(r"\\?\C:\work\src")), Some(PathBuf::from(r"C:\work\src")));
+        assert_eq!(simplify_verbatim(Path::new(r"\\?\C:\")), Some(PathBuf::from(r"C:\")));
+        assert_eq!(
+            simplify_verbatim(Path::new(r"\\?\UNC\server\share\src")),
+            Some(PathBuf::from(r"\\server\share\src"))
+        );
+        assert_eq!(simplify_verbatim(Path::new(r"\\?\C:\work\nul.txt")), None);
+        assert_eq!(simplify_verbatim(Path::new(r"\\?\C:\work\trailing.")), None);
+        let long = format!(r"\\?\C:\{}", "directory\\".repeat(30));
+        assert_eq!(simplify_verbatim(Path::new(&long)), None);
+    }
+    
+    #[cfg(windows)]
+    #[test]
+    fn relative_to_matches_verbatim_and_plain_roots() {
+        assert_eq!(relative_to(Path::new(r"\\?\C:\work\src\main.rs"), Path::new(r"c:\work")), "src/main.rs");
+        assert_eq!(
+            relative_to(Path::new(r"\\?\UNC\Server\share\src\main.rs"), Path::new(r"\\server\share")),
+            "src/main.rs"
+        );
+        assert_eq!(normalize(Path::new(r"\\?\UNC\server\share\src")), "//server/share/src");
+    }
+}
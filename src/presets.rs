@@ -0,0 +1,211 @@
+// src/presets.rs
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::io;
+
+use crate::config::{Config, PresetConfig};
+
+// Rules of a built-in --preset. A trailing slash matches a directory
+// anywhere in the path, patterns with a slash match the end of the path and
+// other patterns match the file name
+struct BuiltinPreset {
+    name: &'static str,
+    // Routes, models, components and configs, kept first with --max-tokens
+    important: &'static [&'static str],
+    // Files that are noise for the framework, dropped from the selection
+    skip: &'static [&'static str],
+}
+
+const BUILTIN_PRESETS: &[BuiltinPreset] = &[
+    BuiltinPreset {
+        name: "axum",
+        important: &[
+            "Cargo.toml",
+            "src/main.rs",
+            "src/lib.rs",
+            "routes/",
+            "routes.rs",
+            "router.rs",
+            "handlers/",
+            "handlers.rs",
+            "api/",
+            "extractors/",
+            "extractors.rs",
+            "middleware/",
+            "middleware.rs",
+            "state.rs",
+            "error.rs",
+            "errors.rs",
+            "models/",
+            "models.rs",
+            "config.rs",
+            "migrations/",
+        ],
+        skip: &["*.snap", "sqlx-data.json", ".sqlx/"],
+    },
+    BuiltinPreset {
+        name: "django",
+        important: &[
+            "manage.py",
+            "settings.py",
+            "settings/",
+            "urls.py",
+            "models.py",
+            "models/",
+            "views.py",
+            "views/",
+            "serializers.py",
+            "forms.py",
+            "admin.py",
+            "apps.py",
+            "signals.py",
+            "tasks.py",
+            "middleware.py",
+            "templates/",
+            "requirements*.txt",
+            "pyproject.toml",
+        ],
+        skip: &["static/", "staticfiles/", "media/", "*.po", "*.mo", "__pycache__/"],
+    },
+    BuiltinPreset {
+        name: "react",
+        important: &[
+            "package.json",
+            "tsconfig.json",
+            "vite.config.*",
+            "next.config.*",
+            "src/index.*",
+            "src/main.*",
+            "src/App.*",
+            "components/",
+            "pages/",
+            "app/",
+            "routes/",
+            "hooks/",
+            "context/",
+            "store/",
+        ],
+        skip: &[
+            "__snapshots__/",
+            "*.snap",
+            "*.min.js",
+            "*.map",
+            "package-lock.json",
+            "yarn.lock",
+            "pnpm-lock.yaml",
+            "public/",
+            "coverage/",
+        ],
+    },
+    BuiltinPreset {
+        name: "bevy",
+        important: &[
+            "Cargo.toml",
+            "src/main.rs",
+            "src/lib.rs",
+            "plugins/",
+            "plugins.rs",
+            "systems/",
+            "systems.rs",
+            "components/",
+            "components.rs",
+            "resources/",
+            "resources.rs",
+            "states.rs",
+            "events.rs",
+        ],
+        skip: &["assets/"],
+    },
+];
+
+// The compiled rules of the chosen preset
+pub struct Preset {
+    pub name: String,
+    important: Rules,
+    skip: Rules,
+}
+
+impl Preset {
+    pub fn is_important(&self, path: &str) -> bool {
+        self.important.is_match(path)
+    }
+    
+    pub fn skips(&self, path: &str) -> bool {
+        self.skip.is_match(path)
+    }
+}
+
+struct Rules {
+    names: GlobSet,
+    paths: GlobSet,
+}
+
+impl Rules {
+    fn compile(preset: &str, patterns: &[String]) -> io::Result<Rules> {
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+        for pattern in patterns {
+            let (builder, glob) = match pattern.strip_suffix('/') {
+                Some(dir) => (&mut paths, format!("{{{dir},**/{dir}}}/**")),
+                None if pattern.contains('/') => {
+                    let path = pattern.trim_start_matches('/');
+                    (&mut paths, format!("{{{path},**/{path}}}"))
+                }
+                None => (&mut names, pattern.clone()),
+            };
+            let glob = Glob::new(&glob).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid pattern '{}' in preset {}: {}", pattern, preset, e),
+                )
+            })?;
+            builder.add(glob);
+        }
+        
+        let build = |builder: GlobSetBuilder| {
+            builder.build().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+        };
+        Ok(Rules { names: build(names)?, paths: build(paths)? })
+    }
+    
+    fn is_match(&self, path: &str) -> bool {
+        let path = path.replace('\\', "/");
+        let path = path.trim_start_matches("./");
+        let name = path.rsplit('/').next().unwrap_or(path);
+        self.names.is_match(name) || self.paths.is_match(path)
+    }
+}
+
+// Names of the built-in presets and those from the [presets] table
+fn names(config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_PRESETS.iter().map(|preset| preset.name.to_string()).collect();
+    for name in config.presets.keys() {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    names
+}
+
+// Resolves a preset name, preferring the config file's [presets] table
+pub fn load(name: &str, config: &Config) -> io::Result<Preset> {
+    let rules = match config.presets.get(name) {
+        Some(preset) => preset.clone(),
+        None => match BUILTIN_PRESETS.iter().find(|preset| preset.name == name) {
+            Some(preset) => PresetConfig {
+                important: preset.important.iter().map(|pattern| pattern.to_string()).collect(),
+                skip: preset.skip.iter().map(|pattern| pattern.to_string()).collect(),
+            },
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown preset '{}'. Available presets: {}", name, names(config).join(", ")),
+                ));
+            }
+        },
+    };
+    Ok(Preset {
+        name: name.to_string(),
+        important: Rules::compile(name, &rules.important)?,
+        skip: Rules::compile(name, &rules.skip)?,
+    })
+}
@@ -0,0 +1,146 @@
+// src/progress.rs
+use indicatif::{HumanBytes, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::{self, IsTerminal};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Runs finishing sooner than this never show the bar
+const SHOW_AFTER: Duration = Duration::from_secs(1);
+
+const TICK: Duration = Duration::from_millis(100);
+
+// The progress of the current copy; None when disabled or not started, so
+// the other commands' calls into this module do nothing
+static PROGRESS: Mutex<Option<Progress>> = Mutex::new(None);
+
+struct Progress {
+    bar: ProgressBar,
+    started: Instant,
+    shown: bool,
+    walked: u64,
+    read: u64,
+    bytes: u64,
+}
+
+impl Progress {
+    // Attaches the bar to stderr once the run has taken long enough to look hung
+    fn update(&mut self) {
+        if !self.shown && self.started.elapsed() >= SHOW_AFTER {
+            self.shown = true;
+            self.bar.set_draw_target(ProgressDrawTarget::stderr());
+            self.bar.enable_steady_tick(TICK);
+        }
+    }
+}
+
+// Clears the bar when the copy ends, including on errors
+pub struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        finish();
+    }
+}
+
+// Starts reporting progress on stderr if it is a terminal
+pub fn start(enabled: bool) -> Guard {
+    if !enabled || !io::stderr().is_terminal() {
+        return Guard;
+    }
+    let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::hidden());
+    bar.set_style(spinner_style());
+    bar.set_prefix("Walking");
+    let progress = Progress { bar, started: Instant::now(), shown: false, walked: 0, read: 0, bytes: 0 };
+    *lock() = Some(progress);
+    Guard
+}
+
+// A directory entry was visited
+pub fn walked() {
+    with(|progress| {
+        progress.walked += 1;
+        progress.bar.set_message(walk_message(progress));
+    });
+}
+
+// A file was read and included
+pub fn read(bytes: usize) {
+    with(|progress| {
+        progress.read += 1;
+        progress.bytes += bytes as u64;
+        progress.bar.set_message(walk_message(progress));
+    });
+}
+
+// Moves on to the next stage; stages that go through the files one by one
+// pass their number to get a bar with an ETA
+pub fn stage(name: &str, files: Option<usize>) {
+    with(|progress| {
+        progress.bar.set_prefix(name.to_string());
+        progress.bar.set_position(0);
+        progress.bytes = 0;
+        match files {
+            Some(files) => {
+                progress.bar.set_length(files as u64);
+                progress.bar.set_style(bar_style());
+                progress.bar.set_message(String::new());
+                progress.bar.reset_eta();
+            }
+            None => {
+                progress.bar.unset_length();
+                progress.bar.set_style(spinner_style());
+                progress.bar.set_message(String::new());
+            }
+        }
+    });
+}
+
+// A file was formatted or written
+pub fn formatted(bytes: usize) {
+    with(|progress| {
+        progress.bytes += bytes as u64;
+        progress.bar.inc(1);
+        progress.bar.set_message(format!("({})", HumanBytes(progress.bytes)));
+    });
+}
+
+// Removes the bar before the summary is printed
+pub fn finish() {
+    if let Some(progress) = lock().take() {
+        progress.bar.finish_and_clear();
+    }
+}
+
+// Hides the bar while `f` writes to the terminal, for log lines and prompts
+pub fn suspend<R>(f: impl FnOnce() -> R) -> R {
+    let bar = lock().as_ref().filter(|progress| progress.shown).map(|progress| progress.bar.clone());
+    match bar {
+        Some(bar) => bar.suspend(f),
+        None => f(),
+    }
+}
+
+fn with(f: impl FnOnce(&mut Progress)) {
+    if let Some(progress) = lock().as_mut() {
+        f(progress);
+        progress.update();
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<Progress>> {
+    PROGRESS.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn walk_message(progress: &Progress) -> String {
+    format!("{} entries, {} files read ({})", progress.walked, progress.read, HumanBytes(progress.bytes))
+}
+
+fn spinner_style() -> ProgressStyle {
+    ProgressStyle::with_template("{spinner} {prefix}: {msg} [{elapsed}]").unwrap_or_else(|_| ProgressStyle::default_spinner())
+}
+
+fn bar_style() -> ProgressStyle {
+    ProgressStyle::with_template("{prefix}: [{bar:25}] {pos}/{len} files {msg} ETA {eta}")
+        .map(|style| style.progress_chars("=> "))
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+}
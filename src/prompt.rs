@@ -0,0 +1,164 @@
+// src/prompt.rs
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::config::{Config, TaskConfig};
+use crate::ContextSections;
+
+// Used when a question or task is given without a custom template
+pub const DEFAULT_TEMPLATE: &str = "{{context}}";
+
+// Built-in task presets: (name, instructions, answer format)
+const TASK_PRESETS: &[(&str, &str, &str)] = &[
+    (
+        "review",
+        "Review the code above for correctness, readability, and maintainability. Point out bugs, unclear code, and deviations from idiomatic style, and suggest concrete improvements.",
+        "A list of findings ordered by severity. For each finding give the file path, the relevant lines, a short explanation, and a suggested fix.",
+    ),
+    (
+        "explain",
+        "Explain how the code above works. Start with the overall architecture and purpose, then walk through the main components and how data flows between them.",
+        "A high-level summary paragraph followed by one section per major component, referencing file paths where relevant.",
+    ),
+    (
+        "refactor",
+        "Suggest refactorings that improve the structure of the code above without changing its behavior. Focus on duplication, long functions, unclear naming, and missing abstractions.",
+        "A list of proposed refactorings, each with the motivation, the affected files, and the complete rewritten code in <file path=\"...\"> blocks.",
+    ),
+    (
+        "write-tests",
+        "Write tests for the code above. Cover the main behavior, edge cases, and error handling, following the testing conventions already used in the project.",
+        "Complete test files in <file path=\"...\"> blocks, followed by a short note on what each test covers.",
+    ),
+    (
+        "find-bugs",
+        "Find bugs in the code above, including logic errors, unhandled edge cases, resource leaks, and incorrect error handling. Only report issues you are confident about.",
+        "A list of bugs, each with the file path and lines, a description of the faulty behavior, an input or scenario that triggers it, and a fix.",
+    ),
+];
+
+pub fn task_names() -> Vec<&'static str> {
+    TASK_PRESETS.iter().map(|(name, _, _)| *name).collect()
+}
+
+// Resolves a task name to its text, preferring the config file's [tasks] table
+pub fn task_text(name: &str, config: &Config) -> Option<String> {
+    if let Some(task) = config.tasks.get(name) {
+        return Some(match task {
+            TaskConfig::Text(text) => text.trim().to_string(),
+            TaskConfig::Detailed { instructions, answer_format } => {
+                format_task(instructions.trim(), answer_format.as_deref().map(str::trim))
+            }
+        });
+    }
+    
+    TASK_PRESETS
+        .iter()
+        .find(|(preset, _, _)| *preset == name)
+        .map(|(_, instructions, answer_format)| format_task(instructions, Some(answer_format)))
+}
+
+fn format_task(instructions: &str, answer_format: Option<&str>) -> String {
+    match answer_format {
+        Some(answer_format) if !answer_format.is_empty() => {
+            format!("{}\n\nAnswer format: {}", instructions, answer_format)
+        }
+        _ => instructions.to_string(),
+    }
+}
+
+// The --prompt argument is either a path to a template file or the template itself
+pub fn load_template(prompt: &str) -> io::Result<String> {
+    let path = Path::new(prompt);
+    if path.is_file() {
+        fs::read_to_string(path)
+    } else {
+        Ok(prompt.to_string())
+    }
+}
+
+// Fills in the template, with `context` standing for the whole rendered
+// output; a question, task or reply format that the template does not
+// reference is appended after it in its own section
+pub fn render(
+    template: &str,
+    context: &str,
+    sections: &ContextSections,
+    question: Option<&str>,
+    task: Option<&str>,
+    reply_format: Option<&str>,
+) -> String {
+    let mut referenced = Vec::new();
+    let mut output = substitute(template, &mut referenced, |name| match name {
+        "context" => Some(context.to_string()),
+        "header" => Some(sections.header.trim_end().to_string()),
+        "project_info" => Some(sections.project_info.trim_end().to_string()),
+        "file_structure" => Some(sections.file_structure.trim_end().to_string()),
+        "files" => Some(sections.files.trim_end().to_string()),
+        "question" => Some(question.unwrap_or("").to_string()),
+        "task" => Some(task.unwrap_or("").to_string()),
+        "reply_format" => Some(reply_format.unwrap_or("").to_string()),
+        _ => None,
+    });
+    
+    if let Some(task) = task {
+        if !referenced.iter().any(|name| name == "task") {
+            output.push_str(&format!("\n\n<task>\n{}\n</task>", task));
+        }
+    }
+    
+    if let Some(question) = question {
+        if !referenced.iter().any(|name| name == "question") {
+            output.push_str(&format!("\n\n<question>\n{}\n</question>", question));
+        }
+    }
+    
+    if let Some(reply_format) = reply_format {
+        if !referenced.iter().any(|name| name == "reply_format") {
+            output.push_str(&format!("\n\n<reply_format>\n{}\n</reply_format>", reply_format));
+        }
+    }
+    
+    output
+}
+
+// Substitute placeholders in a single pass so that placeholder-like text
+// inside the inserted file contents is never expanded
+fn substitute<F>(template: &str, referenced: &mut Vec<String>, lookup: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        
+        let end = match after_open.find("}}") {
+            Some(end) => end,
+            None => {
+                output.push_str(&rest[start..]);
+                return output;
+            }
+        };
+        
+        let name = after_open[..end].trim();
+        match lookup(name) {
+            Some(value) => {
+                output.push_str(&value);
+                referenced.push(name.to_string());
+            }
+            None => {
+                // Leave unknown placeholders untouched
+                output.push_str(&rest[start..start + 2 + end + 2]);
+            }
+        }
+        
+        rest = &after_open[end + 2..];
+    }
+    
+    output.push_str(rest);
+    output
+}
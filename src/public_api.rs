@@ -0,0 +1,181 @@
+// src/public_api.rs
+use std::path::{Component, Path};
+use std::sync::OnceLock;
+
+use log::warn;
+use regex::Regex;
+use syn::{Block, Fields, ImplItem, Item, TraitItem, Visibility};
+
+use crate::FileEntry;
+use crate::paths;
+
+// Marker placed in stripped function bodies, replaced with `;` after printing
+const BODY_MARKER: &str = "__public_api_body__";
+
+// Replaces every Rust file with only its public items and drops everything else
+pub fn extract(files: Vec<FileEntry>) -> Vec<FileEntry> {
+    let mut api_files: Vec<(String, FileEntry)> = Vec::new();
+    
+    for mut file in files {
+        if file.source_path.extension().and_then(|ext| ext.to_str()) != Some("rs") {
+            continue;
+        }
+        
+        let api = match crate::cache::get_or_compute("public-api", &file.content, || public_items(&file.content)) {
+            Some(api) => api,
+            None => {
+                warn!("Could not parse {} for --public-api, skipping", file.path);
+                continue;
+            }
+        };
+        if api.trim().is_empty() {
+            continue;
+        }
+        
+        let source_path = paths::canonicalize(&file.source_path).unwrap_or_else(|_| file.source_path.clone());
+        let module = module_path(&source_path, &file.path);
+        file.content = format!("// module {}\n\n{}", module, api);
+        file.hash = crate::content_hash(&file.content);
+        api_files.push((module, file));
+    }
+    
+    // Present the crate module by module rather than in directory order
+    api_files.sort_by(|a, b| a.0.cmp(&b.0));
+    api_files.into_iter().map(|(_, file)| file).collect()
+}
+
+// Prints the public items of a source file with function bodies removed
+fn public_items(content: &str) -> Option<String> {
+    let mut file = syn::parse_file(content).ok()?;
+    file.shebang = None;
+    file.attrs.retain(|attr| attr.path().is_ident("doc"));
+    file.items = filter_items(file.items);
+    
+    if file.attrs.is_empty() && file.items.is_empty() {
+        return Some(String::new());
+    }
+    
+    Some(print_without_bodies(&file))
+}
+
+// Pretty-prints the file, turning bodies replaced by `stripped_body` into `;`
+pub fn print_without_bodies(file: &syn::File) -> String {
+    let printed = prettyplease::unparse(file);
+    body_regex().replace_all(&printed, ";").into_owned()
+}
+
+fn filter_items(items: Vec<Item>) -> Vec<Item> {
+    items.into_iter().filter_map(filter_item).collect()
+}
+
+fn filter_item(item: Item) -> Option<Item> {
+    match item {
+        Item::Fn(mut item) if is_public(&item.vis) => {
+            item.block = Box::new(stripped_body());
+            Some(Item::Fn(item))
+        }
+        Item::Struct(mut item) if is_public(&item.vis) => {
+            // Private fields are not part of the API
+            if let Fields::Named(ref mut fields) = item.fields {
+                fields.named = fields.named.clone().into_iter().filter(|field| is_public(&field.vis)).collect();
+            }
+            Some(Item::Struct(item))
+        }
+        Item::Enum(ref inner) if is_public(&inner.vis) => Some(item),
+        Item::Union(ref inner) if is_public(&inner.vis) => Some(item),
+        Item::Type(ref inner) if is_public(&inner.vis) => Some(item),
+        Item::Const(ref inner) if is_public(&inner.vis) => Some(item),
+        Item::Static(ref inner) if is_public(&inner.vis) => Some(item),
+        Item::Use(ref inner) if is_public(&inner.vis) => Some(item),
+        Item::Trait(mut item) if is_public(&item.vis) => {
+            for trait_item in &mut item.items {
+                if let TraitItem::Fn(method) = trait_item {
+                    method.default = None;
+                    method.semi_token = Some(Default::default());
+                }
+            }
+            Some(Item::Trait(item))
+        }
+        Item::Mod(mut item) if is_public(&item.vis) => {
+            if let Some((brace, content)) = item.content.take() {
+                item.content = Some((brace, filter_items(content)));
+            }
+            Some(Item::Mod(item))
+        }
+        Item::Macro(ref inner) if inner.attrs.iter().any(|attr| attr.path().is_ident("macro_export")) => Some(item),
+        Item::Impl(mut item) => {
+            if item.trait_.is_some() {
+                // Trait impls are listed by their header only
+                item.items.clear();
+                return Some(Item::Impl(item));
+            }
+            
+            item.items.retain(|impl_item| match impl_item {
+                ImplItem::Fn(method) => is_public(&method.vis),
+                ImplItem::Const(constant) => is_public(&constant.vis),
+                ImplItem::Type(alias) => is_public(&alias.vis),
+                _ => false,
+            });
+            if item.items.is_empty() {
+                return None;
+            }
+            for impl_item in &mut item.items {
+                if let ImplItem::Fn(method) = impl_item {
+                    method.block = stripped_body();
+                }
+            }
+            Some(Item::Impl(item))
+        }
+        _ => None,
+    }
+}
+
+fn is_public(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+pub fn stripped_body() -> Block {
+    syn::parse_str(&format!("{{ {}!(); }}", BODY_MARKER)).unwrap()
+}
+
+// A stripped body as printed by prettyplease
+fn body_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(&format!(r"\s*\{{\s*{}!\(\);\s*\}}", BODY_MARKER)).unwrap())
+}
+
+// Derives the module path from the file's location below `src`, or from its
+// path in the copy when it is not below one
+fn module_path(path: &Path, display_path: &str) -> String {
+    let components = |path: &Path| -> Vec<String> {
+        path.components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect()
+    };
+    let absolute = components(path);
+    let after_src = match absolute.iter().rposition(|part| part == "src") {
+        Some(index) => absolute[index + 1..].to_vec(),
+        None => components(Path::new(display_path)),
+    };
+    
+    let mut segments: Vec<String> = Vec::new();
+    for (i, part) in after_src.iter().enumerate() {
+        if i + 1 < after_src.len() {
+            segments.push(part.clone());
+            continue;
+        }
+        let stem = part.strip_suffix(".rs").unwrap_or(part);
+        if !matches!(stem, "lib" | "main" | "mod") {
+            segments.push(stem.to_string());
+        }
+    }
+    
+    if segments.is_empty() {
+        "crate".to_string()
+    } else {
+        format!("crate::{}", segments.join("::"))
+    }
+}
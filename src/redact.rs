@@ -0,0 +1,41 @@
+// src/redact.rs
+use indexmap::IndexMap;
+use regex::Regex;
+use std::io;
+
+// Compiled [redact] rules, applied in the order the config lists them
+#[derive(Default, Clone)]
+pub struct Redactor {
+    rules: Vec<(Regex, String)>,
+}
+
+impl Redactor {
+    pub fn from_config(config: &IndexMap<String, String>) -> io::Result<Redactor> {
+        let mut rules = Vec::new();
+        for (pattern, replacement) in config {
+            let regex = Regex::new(pattern).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid redact pattern '{}': {}", pattern, e),
+                )
+            })?;
+            rules.push((regex, replacement.clone()));
+        }
+        Ok(Redactor { rules })
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+    
+    // Replacements may refer to capture groups as $1 or ${name}
+    pub fn apply(&self, text: &str) -> String {
+        let mut output = text.to_string();
+        for (regex, replacement) in &self.rules {
+            if regex.is_match(&output) {
+                output = regex.replace_all(&output, replacement.as_str()).into_owned();
+            }
+        }
+        output
+    }
+}
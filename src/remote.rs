@@ -0,0 +1,132 @@
+// src/remote.rs
+use log::info;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::scratch::ScratchDir;
+
+// Hosts whose repository URLs can be passed as paths
+const HOSTS: &[&str] = &["github.com", "gitlab.com"];
+
+// A repository URL split into what to clone and which part of it to copy
+#[derive(Debug, PartialEq)]
+struct RemoteSpec {
+    clone_url: String,
+    name: String,
+    reference: Option<String>,
+    subdir: Option<String>,
+}
+
+// Replaces repository URLs among the paths with local shallow clones; the
+// returned directories must be kept alive until the output is generated
+pub fn fetch_remote_paths(paths: &mut [String]) -> io::Result<Vec<ScratchDir>> {
+    let mut checkouts = Vec::new();
+    for path in paths.iter_mut() {
+        let spec = match parse(path) {
+            Some(spec) => spec,
+            None => continue,
+        };
+        
+        let checkout = ScratchDir::create("remote")?;
+        // Cloning into a directory named after the repository keeps its name in the output
+        let repo_dir = checkout.path().join(&spec.name);
+        fs::create_dir_all(&repo_dir)?;
+        
+        info!(
+            "Cloning {}{}...",
+            spec.clone_url,
+            spec.reference.as_deref().map(|r| format!(" at {}", r)).unwrap_or_default()
+        );
+        shallow_clone(&spec, &repo_dir)?;
+        
+        let local = match spec.subdir {
+            Some(ref subdir) => repo_dir.join(subdir),
+            None => repo_dir,
+        };
+        if !local.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not exist in {}", spec.subdir.unwrap_or_default(), spec.clone_url),
+            ));
+        }
+        *path = local.to_string_lossy().to_string();
+        checkouts.push(checkout);
+    }
+    Ok(checkouts)
+}
+
+// Accepts `https://host/owner/repo[@ref][/subdir]` as well as the web UI's
+// `/tree/<ref>/<subdir>` (GitHub) and `/-/tree/<ref>/<subdir>` (GitLab) forms
+fn parse(path: &str) -> Option<RemoteSpec> {
+    let rest = path.strip_prefix("https://").or_else(|| path.strip_prefix("http://"))?;
+    let (host, rest) = rest.split_once('/')?;
+    let host = host.trim_start_matches("www.");
+    if !HOSTS.contains(&host) {
+        return None;
+    }
+    
+    let segments: Vec<&str> = rest.trim_end_matches('/').split('/').filter(|s| !s.is_empty()).collect();
+    
+    // The repository path ends at the segment carrying `@ref`, at GitLab's `-`
+    // separator, or after owner/repo
+    let (mut project, mut remainder): (Vec<&str>, Vec<&str>) =
+        match segments.iter().position(|segment| segment.contains('@') || *segment == "-") {
+            Some(index) if segments[index] == "-" => (segments[..index].to_vec(), segments[index + 1..].to_vec()),
+            Some(index) => (segments[..=index].to_vec(), segments[index + 1..].to_vec()),
+            None if segments.len() >= 2 => (segments[..2].to_vec(), segments[2..].to_vec()),
+            None => return None,
+        };
+    if project.len() < 2 {
+        return None;
+    }
+    
+    let mut reference = None;
+    if let Some(last) = project.pop() {
+        let (repo, git_ref) = match last.split_once('@') {
+            Some((repo, git_ref)) => (repo, Some(git_ref.to_string())),
+            None => (last, None),
+        };
+        project.push(repo.trim_end_matches(".git"));
+        reference = git_ref;
+    }
+    if reference.is_none() && remainder.len() >= 2 && matches!(remainder[0], "tree" | "blob") {
+        reference = Some(remainder[1].to_string());
+        remainder = remainder[2..].to_vec();
+    }
+    
+    Some(RemoteSpec {
+        clone_url: format!("https://{}/{}.git", host, project.join("/")),
+        name: project.last()?.to_string(),
+        reference,
+        subdir: if remainder.is_empty() { None } else { Some(remainder.join("/")) },
+    })
+}
+
+// Fetches a single commit, which works for branches, tags and commit hashes alike
+fn shallow_clone(spec: &RemoteSpec, dir: &Path) -> io::Result<()> {
+    let reference = spec.reference.as_deref().unwrap_or("HEAD");
+    git(dir, &["init", "--quiet"])?;
+    git(dir, &["remote", "add", "origin", &spec.clone_url])?;
+    git(dir, &["fetch", "--quiet", "--depth", "1", "origin", reference])?;
+    git(dir, &["checkout", "--quiet", "FETCH_HEAD"])
+}
+
+fn git(dir: &Path, args: &[&str]) -> io::Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| io::Error::other(format!("Failed to run git (is it installed?): {}", e)))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
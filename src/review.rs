@@ -0,0 +1,200 @@
+// src/review.rs
+use similar::TextDiff;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::process::Command;
+
+use crate::apply::{Edit, FileChange};
+use crate::patch::{self, Hunk, HunkLine};
+
+const HELP: &str = "y - apply this hunk
+n - do not apply this hunk
+e - edit this hunk in $EDITOR
+a - apply this hunk and all later hunks in the file
+d - do not apply this hunk or any later hunks in the file
+q - quit; do not apply this hunk or any remaining ones
+? - print help
+";
+
+enum Answer {
+    Yes,
+    No,
+    Edit,
+    All,
+    Done,
+    Quit,
+}
+
+// Steps through every hunk of the changes like `git add -p` and returns the
+// changes cut down to the accepted hunks
+pub fn review(changes: Vec<FileChange>) -> io::Result<Vec<FileChange>> {
+    let mut terminal = terminal()?;
+    let mut accepted = Vec::new();
+    
+    for change in changes {
+        let current = fs::read_to_string(&change.path).ok();
+        let label = change.path.display().to_string();
+        
+        if let Edit::Delete = change.edit {
+            if current.is_none() {
+                continue;
+            }
+            eprintln!("\n── {} (deleted)", label);
+            match ask(&mut terminal, &format!("Delete {}", label), false)? {
+                Answer::Yes | Answer::All => accepted.push(change),
+                Answer::Quit => break,
+                _ => {}
+            }
+            continue;
+        }
+        
+        let (hunks, replacement) = match change.edit {
+            Edit::Patch(hunks) => (hunks, None),
+            Edit::Replace(content) => {
+                let old = current.as_deref().unwrap_or("");
+                if old == content {
+                    continue;
+                }
+                let diff = TextDiff::from_lines(old, content.as_str())
+                    .unified_diff()
+                    .context_radius(3)
+                    .header(&label, &label)
+                    .to_string();
+                let hunks = patch::parse(&diff).into_iter().flat_map(|file| file.hunks).collect();
+                (hunks, Some(content))
+            }
+            Edit::Delete => unreachable!(),
+        };
+        
+        eprintln!("\n── {}{}", label, if current.is_none() { " (new file)" } else { "" });
+        let total = hunks.len();
+        let mut kept = Vec::new();
+        let mut edited = false;
+        let mut rest: Option<bool> = None;
+        let mut quit = false;
+        for (index, hunk) in hunks.into_iter().enumerate() {
+            if let Some(apply_rest) = rest {
+                if apply_rest {
+                    kept.push(hunk);
+                }
+                continue;
+            }
+            eprint!("{}", hunk.render());
+            let question = format!("Apply this hunk to {} ({}/{})", label, index + 1, total);
+            match ask(&mut terminal, &question, true)? {
+                Answer::Yes => kept.push(hunk),
+                Answer::No => {}
+                Answer::Edit => match edit(&hunk)? {
+                    Some(hunk) => {
+                        kept.push(hunk);
+                        edited = true;
+                    }
+                    None => eprintln!("The edited hunk is empty, skipping it"),
+                },
+                Answer::All => {
+                    kept.push(hunk);
+                    rest = Some(true);
+                }
+                Answer::Done => rest = Some(false),
+                Answer::Quit => {
+                    quit = true;
+                    break;
+                }
+            }
+        }
+        
+        if !kept.is_empty() {
+            // A full file accepted as it is stays a replacement
+            let edit = match replacement {
+                Some(content) if kept.len() == total && !edited => Edit::Replace(content),
+                _ => Edit::Patch(kept),
+            };
+            accepted.push(FileChange { path: change.path, edit });
+        }
+        if quit {
+            break;
+        }
+    }
+    
+    Ok(accepted)
+}
+
+// Answers are read from the terminal even when the reply comes from stdin
+fn terminal() -> io::Result<Box<dyn BufRead>> {
+    if io::stdin().is_terminal() {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+    #[cfg(unix)]
+    if let Ok(tty) = fs::File::open("/dev/tty") {
+        return Ok(Box::new(BufReader::new(tty)));
+    }
+    #[cfg(windows)]
+    if let Ok(console) = fs::File::open("CONIN$") {
+        return Ok(Box::new(BufReader::new(console)));
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidInput, "--interactive needs a terminal to ask on"))
+}
+
+fn ask(terminal: &mut Box<dyn BufRead>, question: &str, hunk: bool) -> io::Result<Answer> {
+    let choices = if hunk { "y,n,e,a,d,q,?" } else { "y,n,q,?" };
+    loop {
+        eprint!("{} [{}]? ", question, choices);
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        // End of input quits like q
+        if terminal.read_line(&mut answer)? == 0 {
+            eprintln!();
+            return Ok(Answer::Quit);
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "y" => return Ok(Answer::Yes),
+            "n" => return Ok(Answer::No),
+            "q" => return Ok(Answer::Quit),
+            "e" if hunk => return Ok(Answer::Edit),
+            "a" if hunk => return Ok(Answer::All),
+            "d" if hunk => return Ok(Answer::Done),
+            _ => eprint!("{}", HELP),
+        }
+    }
+}
+
+// Opens the hunk in $VISUAL or $EDITOR and reads it back, None when all of
+// its lines were removed
+fn edit(hunk: &Hunk) -> io::Result<Option<Hunk>> {
+    let path = env::temp_dir().join(format!("codecopier-hunk-{}.diff", std::process::id()));
+    let text = format!(
+        "# Edit the hunk below. Lines starting with '-' are removed, lines starting\n\
+         # with '+' are added and lines starting with ' ' stay as they are. To keep\n\
+         # a '-' line, make it a ' ' line; to drop a '+' line, delete it. Lines\n\
+         # starting with '#' are ignored.\n{}",
+        hunk.render()
+    );
+    fs::write(&path, text)?;
+    
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = Command::new(program).args(words).arg(&path).status();
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+    if !status?.success() {
+        return Err(io::Error::other(format!("{} exited with an error", editor)));
+    }
+    
+    let body: String = edited?
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let mut hunks = patch::parse(&format!("--- a/hunk\n+++ b/hunk\n{}", body)).into_iter().flat_map(|file| file.hunks);
+    let edited = hunks.next().filter(|edited| {
+        edited.lines.iter().any(|line| !matches!(line, HunkLine::Context(_)))
+    });
+    // Keep the original position even if the header was changed
+    Ok(edited.map(|edited| Hunk { old_start: hunk.old_start, lines: edited.lines }))
+}
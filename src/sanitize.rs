@@ -0,0 +1,62 @@
+// src/sanitize.rs
+use std::borrow::Cow;
+
+// Removes ANSI escape sequences and control characters other than tabs and
+// newlines, which corrupt the output and confuse models. A carriage return
+// not followed by a newline, as in progress bars, becomes a line break
+pub fn strip_control_chars(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(is_control) {
+        return Cow::Borrowed(text);
+    }
+    
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.peek().copied() {
+                // CSI: parameters and intermediates up to a final byte in @..~
+                Some('[') => {
+                    chars.next();
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC, DCS and similar strings end with BEL or ESC \
+                Some(']' | 'P' | 'X' | '^' | '_') => {
+                    chars.next();
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' {
+                            break;
+                        }
+                        if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Two-character sequences like ESC ( B
+                Some('(' | ')' | '#' | '%') => {
+                    chars.next();
+                    chars.next();
+                }
+                // Other escapes are a single printable character
+                Some(c) if ('0'..='~').contains(&c) => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\r' if chars.peek() == Some(&'\n') => output.push(c),
+            '\r' => output.push('\n'),
+            c if is_control(c) => {}
+            c => output.push(c),
+        }
+    }
+    
+    Cow::Owned(output)
+}
+
+fn is_control(c: char) -> bool {
+    c.is_control() && c != '\n' && c != '\t'
+}
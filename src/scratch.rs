@@ -0,0 +1,39 @@
+// src/scratch.rs
+use log::debug;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static SCRATCH_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// A directory in the temp dir holding cloned or extracted input, removed when dropped
+pub struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    pub fn create(kind: &str) -> io::Result<ScratchDir> {
+        let path = env::temp_dir().join(format!(
+            "codecopier-{}-{}-{}",
+            kind,
+            std::process::id(),
+            SCRATCH_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&path)?;
+        Ok(ScratchDir { path })
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.path) {
+            debug!("Could not remove {}: {}", self.path.display(), e);
+        }
+    }
+}
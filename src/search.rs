@@ -0,0 +1,204 @@
+// src/search.rs
+use clap::Args;
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+use crate::{cache, config, handlers, hooks, summary, FileEntry, Options};
+
+// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+// Matching lines shown per result
+const SNIPPET_LINES: usize = 3;
+const SNIPPET_WIDTH: usize = 120;
+
+// Arguments of the `search` subcommand
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Words to look for; identifiers also match their camelCase and snake_case parts
+    query: String,
+    
+    #[command(flatten)]
+    pub options: Options,
+}
+
+// A file and how well it matches the query
+pub struct Hit {
+    pub index: usize,
+    pub score: f64,
+}
+
+// `search` subcommand: ranks the files the paths and options select by how
+// well they match the query and prints the best ones with matching lines
+pub fn run(args: SearchArgs) -> io::Result<()> {
+    let SearchArgs { query, mut options } = args;
+    options.configure_logging();
+    let _scratch_dirs = crate::resolve_inputs(&mut options)?;
+    if options.paths.is_empty() {
+        options.paths.push(".".to_string());
+    }
+    cache::set_disabled(options.no_cache);
+    let config = config::load(&options.paths, options.config_path.as_deref())?;
+    
+    let handlers = handlers::Handlers::from_config(&config.handlers)?;
+    let hooks = hooks::Hooks::load(&config.hooks)?;
+    let mut files = Vec::new();
+    let mut skipped: Vec<summary::SkippedFile> = Vec::new();
+    for path in &options.paths {
+        crate::collect_files_from_path(path, &mut files, &mut skipped, &options, &config.hidden.allow, &handlers, hooks.as_ref())?;
+    }
+    
+    let hits = rank(&files, &query);
+    if hits.is_empty() {
+        println!("No files match '{}'", query);
+        return Ok(());
+    }
+    let query_terms = query_terms(&query);
+    for (position, hit) in hits.iter().take(options.search_results).enumerate() {
+        let file = &files[hit.index];
+        println!("{}. {} (score {:.2})", position + 1, file.path, hit.score);
+        for (line_number, line) in snippets(file, &query_terms) {
+            println!("  {:>5}: {}", line_number, line);
+        }
+    }
+    if hits.len() > options.search_results {
+        println!("... and {} more matching files", hits.len() - options.search_results);
+    }
+    Ok(())
+}
+
+// Files matching at least one query term, best first, scored with BM25 over
+// an inverted index of their contents; terms in the file path count extra
+pub fn rank(files: &[FileEntry], query: &str) -> Vec<Hit> {
+    let query_terms = query_terms(query);
+    if query_terms.is_empty() || files.is_empty() {
+        return Vec::new();
+    }
+    
+    // Postings of the query terms: file index and term frequency
+    let mut postings: HashMap<&str, Vec<(usize, u32)>> = HashMap::new();
+    let mut lengths = Vec::with_capacity(files.len());
+    for (index, file) in files.iter().enumerate() {
+        let counts = term_counts(&file.content);
+        lengths.push(counts.values().sum::<u32>() as f64);
+        for term in &query_terms {
+            if let Some(&count) = counts.get(term.as_str()) {
+                postings.entry(term.as_str()).or_default().push((index, count));
+            }
+        }
+    }
+    
+    let documents = files.len() as f64;
+    let average_length = (lengths.iter().sum::<f64>() / documents).max(1.0);
+    let mut scores = vec![0.0; files.len()];
+    for term in &query_terms {
+        let matches = postings.get(term.as_str()).map(Vec::as_slice).unwrap_or(&[]);
+        let frequency = matches.len() as f64;
+        let idf = (1.0 + (documents - frequency + 0.5) / (frequency + 0.5)).ln();
+        for &(index, count) in matches {
+            let count = count as f64;
+            let norm = K1 * (1.0 - B + B * lengths[index] / average_length);
+            scores[index] += idf * count * (K1 + 1.0) / (count + norm);
+        }
+        for (index, file) in files.iter().enumerate() {
+            if terms(&file.path).any(|path_term| path_term == *term) {
+                scores[index] += idf;
+            }
+        }
+    }
+    
+    let mut hits: Vec<Hit> = scores
+        .into_iter()
+        .enumerate()
+        .filter(|(_, score)| *score > 0.0)
+        .map(|(index, score)| Hit { index, score })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.index.cmp(&b.index)));
+    hits
+}
+
+fn query_terms(query: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    terms(query).filter(|term| seen.insert(term.clone())).collect()
+}
+
+// How often each term occurs in the content, cached by content like the
+// other per-file passes
+fn term_counts(content: &str) -> HashMap<String, u32> {
+    let index = cache::get_or_compute("search-index", content, || {
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for term in terms(content) {
+            *counts.entry(term).or_insert(0) += 1;
+        }
+        let mut lines: Vec<String> = counts.iter().map(|(term, count)| format!("{} {}", term, count)).collect();
+        lines.sort();
+        Some(lines.join("\n"))
+    })
+    .unwrap_or_default();
+    index
+        .lines()
+        .filter_map(|line| {
+            let (term, count) = line.rsplit_once(' ')?;
+            Some((term.to_string(), count.parse().ok()?))
+        })
+        .collect()
+}
+
+// Lowercased words of two or more characters; identifiers also yield their
+// parts, so `parse_config` and `parseConfig` both match "config"
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| {
+            let mut terms = vec![word.to_lowercase()];
+            let parts = identifier_parts(word);
+            if parts.len() > 1 {
+                terms.extend(parts);
+            }
+            terms
+        })
+        .filter(|term| term.chars().count() >= 2)
+}
+
+fn identifier_parts(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in word.chars() {
+        if c == '_' {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_numeric();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+// Numbered lines containing a query term, shortened to fit one line
+fn snippets(file: &FileEntry, query_terms: &[String]) -> Vec<(usize, String)> {
+    file.content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| terms(line).any(|term| query_terms.contains(&term)))
+        .take(SNIPPET_LINES)
+        .map(|(index, line)| {
+            let line = line.trim();
+            let shortened = match line.char_indices().nth(SNIPPET_WIDTH) {
+                Some((end, _)) => format!("{}...", &line[..end]),
+                None => line.to_string(),
+            };
+            (index + 1, shortened)
+        })
+        .collect()
+}
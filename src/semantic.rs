@@ -0,0 +1,156 @@
+// src/semantic.rs
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::search::Hit;
+use crate::{content_hash, llm, paths, tokens, FileEntry};
+
+// Files are embedded in pieces of about this many tokens
+const CHUNK_TOKENS: usize = 400;
+
+// Chunks sent per embedding request
+const BATCH_SIZE: usize = 64;
+
+const DEFAULT_OPENAI_MODEL: &str = "text-embedding-3-small";
+const DEFAULT_OLLAMA_MODEL: &str = "nomic-embed-text";
+
+// Embeddings of the chunks of one project, keyed by a hash of the chunk, so
+// only new or changed chunks are sent to the model
+#[derive(Default, Serialize, Deserialize)]
+struct VectorIndex {
+    model: String,
+    vectors: HashMap<String, Vec<f32>>,
+}
+
+struct Chunk {
+    file: usize,
+    text: String,
+    hash: String,
+}
+
+// Files ranked by the similarity of their best-matching chunk to the question
+pub fn rank(files: &[FileEntry], question: &str, config: &Config, project_root: &Path) -> io::Result<Vec<Hit>> {
+    let provider = config
+        .embeddings
+        .provider
+        .clone()
+        .or_else(|| config.ask.provider.clone().filter(|provider| provider != "anthropic"))
+        .unwrap_or_else(|| "openai".to_string());
+    let provider_config = match provider.as_str() {
+        "ollama" => config.ask.ollama.clone(),
+        _ => config.ask.openai.clone(),
+    };
+    let model = config.embeddings.model.clone().unwrap_or_else(|| {
+        let default = if provider == "ollama" { DEFAULT_OLLAMA_MODEL } else { DEFAULT_OPENAI_MODEL };
+        default.to_string()
+    });
+    let model_key = format!("{}/{}", provider, model);
+    
+    let path = index_path(project_root, &model_key)?;
+    let mut index = load_index(&path).filter(|index| index.model == model_key).unwrap_or_default();
+    index.model = model_key;
+    
+    let chunks = chunks(files);
+    let missing: Vec<&Chunk> = chunks.iter().filter(|chunk| !index.vectors.contains_key(&chunk.hash)).collect();
+    if !missing.is_empty() {
+        info!("Embedding {} of {} chunks with {} ({})...", missing.len(), chunks.len(), model, provider);
+    }
+    for batch in missing.chunks(BATCH_SIZE) {
+        let texts: Vec<String> = batch.iter().map(|chunk| chunk.text.clone()).collect();
+        let vectors = llm::embed(&provider, &provider_config, &model, &texts)?;
+        for (chunk, vector) in batch.iter().zip(vectors) {
+            index.vectors.insert(chunk.hash.clone(), vector);
+        }
+    }
+    
+    // Only the chunks of this run are kept, so the index does not grow forever
+    let current: HashSet<&String> = chunks.iter().map(|chunk| &chunk.hash).collect();
+    index.vectors.retain(|hash, _| current.contains(hash));
+    if let Err(e) = save_index(&path, &index) {
+        debug!("Could not write the vector index {}: {}", path.display(), e);
+    }
+    
+    let question_vector = llm::embed(&provider, &provider_config, &model, &[question.to_string()])?.remove(0);
+    let mut scores = vec![f64::NEG_INFINITY; files.len()];
+    for chunk in &chunks {
+        if let Some(vector) = index.vectors.get(&chunk.hash) {
+            let similarity = cosine(&question_vector, vector);
+            scores[chunk.file] = scores[chunk.file].max(similarity);
+        }
+    }
+    
+    let mut hits: Vec<Hit> = scores
+        .into_iter()
+        .enumerate()
+        .filter(|(_, score)| score.is_finite())
+        .map(|(index, score)| Hit { index, score })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.index.cmp(&b.index)));
+    Ok(hits)
+}
+
+// Consecutive lines of each file of about CHUNK_TOKENS tokens, prefixed with
+// the file path so the model knows where they come from
+fn chunks(files: &[FileEntry]) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    for (index, file) in files.iter().enumerate() {
+        let mut text = String::new();
+        let mut size = 0;
+        let lines: Vec<&str> = file.content.lines().collect();
+        for (position, line) in lines.iter().enumerate() {
+            text.push_str(line);
+            text.push('\n');
+            size += tokens::estimate(line) + 1;
+            if size >= CHUNK_TOKENS || position + 1 == lines.len() {
+                if !text.trim().is_empty() {
+                    let text = format!("{}\n{}", file.path, std::mem::take(&mut text));
+                    let hash = content_hash(&text);
+                    chunks.push(Chunk { file: index, text, hash });
+                }
+                text.clear();
+                size = 0;
+            }
+        }
+    }
+    chunks
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f64 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        dot += *x as f64 * *y as f64;
+        norm_a += *x as f64 * *x as f64;
+        norm_b += *y as f64 * *y as f64;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+// One index per project and model, named after a hash of both
+fn index_path(project_root: &Path, model_key: &str) -> io::Result<PathBuf> {
+    let dir = dirs::cache_dir()
+        .map(|dir| dir.join("codecopier").join("vectors"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not determine the cache directory"))?;
+    let root = paths::canonicalize(project_root).unwrap_or_else(|_| project_root.to_path_buf());
+    let key = content_hash(&format!("{}\n{}", root.to_string_lossy(), model_key));
+    Ok(dir.join(format!("{}.json", &key[..16])))
+}
+
+fn load_index(path: &Path) -> Option<VectorIndex> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_index(path: &Path, index: &VectorIndex) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string(index)?)
+}
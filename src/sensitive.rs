@@ -0,0 +1,75 @@
+// src/sensitive.rs
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::io;
+
+use crate::config::SensitiveConfig;
+use crate::FileEntry;
+
+// Paths that usually hold secrets. A trailing slash matches a directory
+// anywhere in the path; other patterns match the file name, or the relative
+// path when they contain a slash
+const DEFAULT_PATTERNS: &[&str] = &[
+    ".env",
+    ".env.*",
+    "id_rsa",
+    "id_dsa",
+    "id_ecdsa",
+    "id_ed25519",
+    "*.pem",
+    "*.key",
+    "*.p12",
+    "*.pfx",
+    "*.kdbx",
+    ".netrc",
+    ".pgpass",
+    ".npmrc",
+    ".pypirc",
+    "credentials*",
+    "secrets/",
+    ".ssh/",
+    ".aws/",
+];
+
+// Compiled default and [sensitive] patterns
+pub struct SensitivePaths {
+    names: GlobSet,
+    paths: GlobSet,
+}
+
+impl SensitivePaths {
+    pub fn from_config(config: &SensitiveConfig) -> io::Result<SensitivePaths> {
+        let defaults = DEFAULT_PATTERNS.iter().filter(|_| config.defaults).map(|pattern| pattern.to_string());
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+        
+        for pattern in defaults.chain(config.paths.iter().cloned()) {
+            let (builder, glob) = match pattern.strip_suffix('/') {
+                Some(dir) => (&mut paths, format!("{{{dir},**/{dir}}}/**")),
+                None if pattern.contains('/') => (&mut paths, pattern.trim_start_matches('/').to_string()),
+                None => (&mut names, pattern.clone()),
+            };
+            let glob = Glob::new(&glob).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("Invalid sensitive path pattern '{}': {}", pattern, e))
+            })?;
+            builder.add(glob);
+        }
+        
+        let build = |builder: GlobSetBuilder| {
+            builder.build().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+        };
+        Ok(SensitivePaths { names: build(names)?, paths: build(paths)? })
+    }
+    
+    // Displayed paths of the files that match
+    pub fn matching(&self, files: &[FileEntry]) -> Vec<String> {
+        files
+            .iter()
+            .filter(|file| {
+                let path = file.path.replace('\\', "/");
+                let name = path.rsplit('/').next().unwrap_or(&path);
+                self.names.is_match(name) || self.paths.is_match(path.trim_start_matches("./"))
+            })
+            .map(|file| file.path.clone())
+            .collect()
+    }
+}
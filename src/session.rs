@@ -0,0 +1,78 @@
+// src/session.rs
+use log::info;
+use std::io::{self, BufRead};
+
+use crate::{config, Options};
+
+// `session` subcommand: copies the project metadata, tree and a description of
+// every file, then reads the model's reply from stdin and copies exactly the
+// files it asked for, repeating until stdin is closed
+pub fn run(mut options: Options) -> io::Result<()> {
+    options.configure_logging();
+    let _scratch_dirs = crate::resolve_inputs(&mut options)?;
+    if options.paths.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "No files or directories given"));
+    }
+    
+    let config = config::load(&options.paths, options.config_path.as_deref())?;
+    
+    options.overview_only = true;
+    let overview = crate::generate_output(&options, &config)?;
+    let available: Vec<String> = overview.skipped.iter().map(|file| file.path.clone()).collect();
+    crate::deliver_output(&options, &overview)?;
+    options.overview_only = false;
+    
+    let stdin = io::stdin();
+    loop {
+        println!();
+        println!("Paste the model's reply naming the files it needs, then a line with a single '.' (Ctrl-D to finish):");
+        
+        let mut reply = String::new();
+        let mut closed = true;
+        for line in stdin.lock().lines() {
+            let line = line?;
+            if line.trim() == "." {
+                closed = false;
+                break;
+            }
+            reply.push_str(&line);
+            reply.push('\n');
+        }
+        
+        let requested = requested_paths(&reply, &available);
+        if requested.is_empty() {
+            if !reply.trim().is_empty() {
+                println!("No known file paths found in the reply.");
+            }
+        } else {
+            info!("Copying {} requested files: {}", requested.len(), requested.join(", "));
+            options.only_paths = Some(requested);
+            let output = crate::generate_output(&options, &config)?;
+            crate::deliver_output(&options, &output)?;
+        }
+        
+        if closed {
+            return Ok(());
+        }
+    }
+}
+
+// The known paths mentioned anywhere in the reply, in the order given in the
+// overview; a path only counts when it is not part of a longer path or name
+fn requested_paths(reply: &str, available: &[String]) -> Vec<String> {
+    available
+        .iter()
+        .filter(|path| {
+            reply.match_indices(path.as_str()).any(|(start, found)| {
+                let before = reply[..start].chars().next_back();
+                let after = reply[start + found.len()..].chars().next();
+                !before.is_some_and(is_path_char) && !after.is_some_and(|c| is_path_char(c) && c != '.')
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '\\')
+}
@@ -0,0 +1,242 @@
+// src/sink.rs
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::{clipboard, html, Delivery, GeneratedOutput, Options, OutputSize};
+
+// A destination named with --tee
+#[derive(Debug, Clone)]
+pub enum Destination {
+    Clipboard,
+    Stdout,
+    File(PathBuf),
+    Http(String),
+    Command(String),
+}
+
+// `clipboard`, `-` for stdout, an http(s) URL, `pipe:<command>`, or a file path
+pub fn parse_destination(value: &str) -> Result<Destination, String> {
+    match value {
+        "" => Err("expected a destination".to_string()),
+        "clipboard" => Ok(Destination::Clipboard),
+        "-" | "stdout" => Ok(Destination::Stdout),
+        _ if value.starts_with("http://") || value.starts_with("https://") => Ok(Destination::Http(value.to_string())),
+        _ => match value.strip_prefix("pipe:") {
+            Some(command) if !command.trim().is_empty() => Ok(Destination::Command(command.to_string())),
+            Some(_) => Err("expected a command after pipe:".to_string()),
+            None => Ok(Destination::File(PathBuf::from(value))),
+        },
+    }
+}
+
+// Somewhere the finished output can be delivered
+pub trait Sink {
+    // Completes "The output will be ...", for --confirm
+    fn describe(&self) -> String;
+    
+    // Sinks writing to stdout silence the status lines, which would end up
+    // in the output
+    fn uses_stdout(&self) -> bool {
+        false
+    }
+    
+    fn deliver(&self, output: &GeneratedOutput, quiet: bool) -> io::Result<(Delivery, OutputSize)>;
+}
+
+// The destination chosen with --output, --pipe-to, --post-to or
+// --no-clipboard (the clipboard otherwise), followed by every --tee
+pub fn from_options(options: &Options) -> Vec<Box<dyn Sink>> {
+    let primary = if let Some(ref command) = options.pipe_to {
+        open(Destination::Command(command.clone()), options)
+    } else if let Some(ref url) = options.post_to {
+        open(Destination::Http(url.clone()), options)
+    } else {
+        match options.output_path.as_deref() {
+            Some("-") => open(Destination::Stdout, options),
+            Some(path) => open(Destination::File(PathBuf::from(path)), options),
+            None if options.no_clipboard => Box::new(FileSink { path: clipboard::temp_file_path(), temporary: true }),
+            None => open(Destination::Clipboard, options),
+        }
+    };
+    
+    let mut sinks = vec![primary];
+    sinks.extend(options.tee.iter().map(|destination| open(destination.clone(), options)));
+    sinks
+}
+
+fn open(destination: Destination, options: &Options) -> Box<dyn Sink> {
+    match destination {
+        Destination::Clipboard => Box::new(ClipboardSink { html: options.clipboard_html }),
+        Destination::Stdout => Box::new(StdoutSink),
+        Destination::File(path) => Box::new(FileSink { path, temporary: false }),
+        Destination::Http(url) => Box::new(HttpSink { url }),
+        Destination::Command(command) => Box::new(CommandSink { command }),
+    }
+}
+
+// The system clipboard, falling back to a temporary file
+struct ClipboardSink {
+    html: Option<html::ClipboardStyle>,
+}
+
+impl Sink for ClipboardSink {
+    fn describe(&self) -> String {
+        "copied to the clipboard".to_string()
+    }
+    
+    // Only the clipboard needs the whole text at once
+    fn deliver(&self, output: &GeneratedOutput, quiet: bool) -> io::Result<(Delivery, OutputSize)> {
+        let text = output.text();
+        let size = output.size();
+        let outcome = match self.html {
+            Some(style) => clipboard::copy_with_html(text, &html::clipboard_fragment(text, style))?,
+            None => clipboard::copy(text)?,
+        };
+        match outcome {
+            clipboard::CopyOutcome::Clipboard => {
+                if !quiet {
+                    println!("Files successfully copied to clipboard!");
+                }
+                Ok((Delivery::Clipboard, size))
+            }
+            // Printed even with -q, since the output would otherwise be lost
+            clipboard::CopyOutcome::TempFile(path) => {
+                println!("Output written to file: {}", path.display());
+                Ok((Delivery::ClipboardFallback(path), size))
+            }
+        }
+    }
+}
+
+struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn describe(&self) -> String {
+        "written to stdout".to_string()
+    }
+    
+    fn uses_stdout(&self) -> bool {
+        true
+    }
+    
+    fn deliver(&self, output: &GeneratedOutput, _quiet: bool) -> io::Result<(Delivery, OutputSize)> {
+        let mut writer = BufWriter::new(io::stdout().lock());
+        let size = match output.write_to(&mut writer).and_then(|size| writer.flush().map(|()| size)) {
+            Ok(size) => size,
+            // The reader (e.g. `head`) stopped early, which is not an error
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => OutputSize::default(),
+            Err(e) => return Err(e),
+        };
+        Ok((Delivery::Stdout, size))
+    }
+}
+
+struct FileSink {
+    path: PathBuf,
+    // The temporary file of --no-clipboard, reported even with -q
+    temporary: bool,
+}
+
+impl Sink for FileSink {
+    fn describe(&self) -> String {
+        if self.temporary {
+            "written to a temporary file".to_string()
+        } else {
+            format!("written to {}", self.path.display())
+        }
+    }
+    
+    fn deliver(&self, output: &GeneratedOutput, quiet: bool) -> io::Result<(Delivery, OutputSize)> {
+        let size = write_file(output, &self.path)?;
+        if !quiet || self.temporary {
+            println!("Output written to file: {}", self.path.display());
+        }
+        Ok((Delivery::File(self.path.clone()), size))
+    }
+}
+
+fn write_file(output: &GeneratedOutput, path: &Path) -> io::Result<OutputSize> {
+    let file = File::create(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to create {}: {}", path.display(), e)))?;
+    let mut writer = BufWriter::new(file);
+    let size = output.write_to(&mut writer)?;
+    writer.flush()?;
+    Ok(size)
+}
+
+// POSTs the output as the request body
+struct HttpSink {
+    url: String,
+}
+
+impl Sink for HttpSink {
+    fn describe(&self) -> String {
+        format!("sent to {}", self.url)
+    }
+    
+    fn deliver(&self, output: &GeneratedOutput, quiet: bool) -> io::Result<(Delivery, OutputSize)> {
+        let text = output.text();
+        let size = output.size();
+        match ureq::post(&self.url).set("Content-Type", "text/plain; charset=utf-8").send_string(text) {
+            Ok(_) => {}
+            Err(ureq::Error::Status(code, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                return Err(io::Error::other(format!(
+                    "{} rejected the output with status {}: {}",
+                    self.url,
+                    code,
+                    body.trim()
+                )));
+            }
+            Err(e) => return Err(io::Error::other(format!("Failed to send the output: {}", e))),
+        }
+        if !quiet {
+            println!("Output sent to {}", self.url);
+        }
+        Ok((Delivery::Http(self.url.clone()), size))
+    }
+}
+
+// Runs a shell command with the output on its stdin; what the command prints
+// goes straight to our stdout
+struct CommandSink {
+    command: String,
+}
+
+impl Sink for CommandSink {
+    fn describe(&self) -> String {
+        format!("piped into `{}`", self.command)
+    }
+    
+    fn uses_stdout(&self) -> bool {
+        true
+    }
+    
+    fn deliver(&self, output: &GeneratedOutput, _quiet: bool) -> io::Result<(Delivery, OutputSize)> {
+        let mut child = if cfg!(windows) {
+            Command::new("cmd").args(["/C", &self.command]).stdin(Stdio::piped()).spawn()
+        } else {
+            Command::new("sh").args(["-c", &self.command]).stdin(Stdio::piped()).spawn()
+        }
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to run '{}': {}", self.command, e)))?;
+        
+        let mut size = OutputSize::default();
+        if let Some(stdin) = child.stdin.take() {
+            let mut writer = BufWriter::new(stdin);
+            match output.write_to(&mut writer).and_then(|written| writer.flush().map(|()| written)) {
+                Ok(written) => size = written,
+                // The command may stop reading early, its exit status tells whether that was a failure
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+                Err(e) => return Err(e),
+            }
+        }
+        
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("Command '{}' failed with {}", self.command, status)));
+        }
+        Ok((Delivery::Command(self.command.clone()), size))
+    }
+}
@@ -0,0 +1,426 @@
+// src/sql_schema.rs
+use regex::{Captures, Regex};
+
+// A table, view or index name, optionally schema-qualified and quoted
+const IDENT: &str = r#"(?:"[^"]+"|`[^`]+`|\[[^\]]+\]|[\w$]+)(?:\.(?:"[^"]+"|`[^`]+`|\[[^\]]+\]|[\w$]+))?"#;
+
+// Words that end a column's type and start its constraints
+const COLUMN_CONSTRAINTS: &[&str] = &[
+    "NOT", "NULL", "DEFAULT", "PRIMARY", "REFERENCES", "UNIQUE", "CHECK", "CONSTRAINT",
+    "GENERATED", "COLLATE", "AUTO_INCREMENT", "AUTOINCREMENT", "IDENTITY", "ON", "COMMENT",
+];
+
+// Words starting a table constraint rather than a column in CREATE TABLE
+const TABLE_CONSTRAINTS: &[&str] = &["CONSTRAINT", "PRIMARY", "FOREIGN", "UNIQUE", "CHECK", "EXCLUDE", "FULLTEXT", "SPATIAL"];
+
+// The schema SQL migrations leave behind, built by replaying their DDL in
+// order; data changes are left out and statements that cannot be replayed
+// are kept as they are
+#[derive(Default)]
+pub struct Schema {
+    tables: Vec<Table>,
+    // CREATE INDEX statements, keyed by index name
+    indexes: Vec<(String, String)>,
+    // Views, types, functions and the like, keyed by kind and name
+    objects: Vec<(String, String)>,
+    // COMMENT ON, ALTER TYPE and ALTER TABLE actions that are not understood
+    others: Vec<String>,
+}
+
+struct Table {
+    key: String,
+    name: String,
+    // Column name keys with their full definitions
+    columns: Vec<(String, String)>,
+    constraints: Vec<String>,
+}
+
+impl Schema {
+    pub fn apply(&mut self, sql: &str) {
+        for statement in statements(sql) {
+            let flat = collapse(&statement);
+            if !self.apply_statement(&flat, &statement) {
+                let keyword = flat.split(' ').next().unwrap_or("").to_uppercase();
+                if matches!(keyword.as_str(), "CREATE" | "ALTER" | "COMMENT" | "RENAME") {
+                    self.others.push(format!("{};", statement.trim()));
+                }
+            }
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty() && self.indexes.is_empty() && self.objects.is_empty() && self.others.is_empty()
+    }
+    
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        for table in &self.tables {
+            let mut lines: Vec<String> = table.columns.iter().map(|(_, definition)| definition.clone()).collect();
+            lines.extend(table.constraints.iter().cloned());
+            output.push_str(&format!("CREATE TABLE {} (\n    {}\n);\n\n", table.name, lines.join(",\n    ")));
+        }
+        for (_, statement) in self.indexes.iter().chain(&self.objects) {
+            output.push_str(&format!("{};\n\n", statement.trim()));
+        }
+        for statement in &self.others {
+            output.push_str(statement);
+            output.push_str("\n\n");
+        }
+        output.trim_end().to_string() + "\n"
+    }
+    
+    // Whether the statement was understood; `flat` is it on one line
+    fn apply_statement(&mut self, flat: &str, statement: &str) -> bool {
+        if let Some(caps) = regex(&format!(r"^CREATE\s+(?:(?:GLOBAL|LOCAL)\s+)?(?:TEMP(?:ORARY)?\s+)?(?:UNLOGGED\s+)?TABLE\s+(?:IF\s+NOT\s+EXISTS\s+)?({})\s*\(", IDENT)).captures(flat) {
+            return self.create_table(&caps[1], &flat[caps.get(0).map(|m| m.end() - 1).unwrap_or(0)..]);
+        }
+        if let Some(caps) = regex(&format!(r"^ALTER\s+TABLE\s+(?:IF\s+EXISTS\s+)?(?:ONLY\s+)?({})\s+(.+)$", IDENT)).captures(flat) {
+            return self.alter_table(&caps[1], &caps[2]);
+        }
+        if let Some(caps) = regex(r"^DROP\s+TABLE\s+(?:IF\s+EXISTS\s+)?(.+?)(?:\s+(?:CASCADE|RESTRICT))?$").captures(flat) {
+            for name in split_top_level(&caps[1], ',') {
+                let key = key(&name);
+                self.tables.retain(|table| table.key != key);
+                self.indexes.retain(|(_, statement)| index_table(statement).as_deref() != Some(key.as_str()));
+            }
+            return true;
+        }
+        if let Some(caps) = regex(&format!(r"^RENAME\s+TABLE\s+({})\s+TO\s+({})$", IDENT, IDENT)).captures(flat) {
+            return self.rename_table(&caps[1], &caps[2]);
+        }
+        if let Some(caps) = regex(&format!(r"^CREATE\s+(?:UNIQUE\s+)?INDEX\s+(?:CONCURRENTLY\s+)?(?:IF\s+NOT\s+EXISTS\s+)?({})?\s*ON\s", IDENT)).captures(flat) {
+            let name = caps.get(1).map(|m| key(m.as_str())).unwrap_or_else(|| key(flat));
+            upsert(&mut self.indexes, name, flat.to_string());
+            return true;
+        }
+        if let Some(caps) = regex(&format!(r"^DROP\s+INDEX\s+(?:CONCURRENTLY\s+)?(?:IF\s+EXISTS\s+)?({})", IDENT)).captures(flat) {
+            let name = key(&caps[1]);
+            self.indexes.retain(|(key, _)| key != &name && key.rsplit('.').next() != Some(name.as_str()));
+            return true;
+        }
+        let kinds = "VIEW|FUNCTION|PROCEDURE|TYPE|DOMAIN|EXTENSION|TRIGGER|SEQUENCE|SCHEMA|POLICY";
+        if let Some(caps) = regex(&format!(r"^CREATE\s+(?:OR\s+REPLACE\s+)?(?:(?:TEMP|TEMPORARY|MATERIALIZED|RECURSIVE|CONSTRAINT)\s+)*({})\s+(?:IF\s+NOT\s+EXISTS\s+)?({})", kinds, IDENT)).captures(flat) {
+            upsert(&mut self.objects, object_key(&caps), statement.trim().to_string());
+            return true;
+        }
+        if let Some(caps) = regex(&format!(r"^DROP\s+(?:MATERIALIZED\s+)?({})\s+(?:IF\s+EXISTS\s+)?({})", kinds, IDENT)).captures(flat) {
+            let object = object_key(&caps);
+            self.objects.retain(|(key, _)| key != &object);
+            return true;
+        }
+        false
+    }
+    
+    fn create_table(&mut self, name: &str, rest: &str) -> bool {
+        let body = match parenthesized(rest) {
+            Some(body) => body,
+            None => return false,
+        };
+        let mut table = Table { key: key(name), name: name.to_string(), columns: Vec::new(), constraints: Vec::new() };
+        for definition in split_top_level(body, ',') {
+            if is_table_constraint(&definition) {
+                table.constraints.push(definition);
+            } else {
+                table.columns.push((column_key(&definition), definition));
+            }
+        }
+        self.tables.retain(|existing| existing.key != table.key);
+        self.tables.push(table);
+        true
+    }
+    
+    fn rename_table(&mut self, from: &str, to: &str) -> bool {
+        let from = key(from);
+        match self.tables.iter_mut().find(|table| table.key == from) {
+            Some(table) => {
+                table.key = key(to);
+                table.name = to.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+    
+    fn alter_table(&mut self, name: &str, actions: &str) -> bool {
+        let table_key = key(name);
+        if let Some(caps) = regex(&format!(r"^RENAME\s+TO\s+({})$", IDENT)).captures(actions) {
+            return self.rename_table(name, &caps[1]);
+        }
+        let table = match self.tables.iter_mut().find(|table| table.key == table_key) {
+            Some(table) => table,
+            None => return false,
+        };
+        // Actions that are not understood are kept on their own
+        for action in split_top_level(actions, ',') {
+            let columns: Vec<String> = table.columns.iter().map(|(key, _)| key.clone()).collect();
+            if !alter_action(table, &action) {
+                self.others.push(format!("ALTER TABLE {} {};", name, action));
+            } else if regex(r"^DROP\s").is_match(&action) {
+                // Indexes go with a dropped column
+                let dropped: Vec<String> = columns.into_iter().filter(|column| !table.columns.iter().any(|(key, _)| key == column)).collect();
+                self.indexes.retain(|(_, statement)| {
+                    index_table(statement).as_deref() != Some(table_key.as_str())
+                        || !index_columns(statement).iter().any(|column| dropped.contains(column))
+                });
+            }
+        }
+        true
+    }
+}
+
+// One action of ALTER TABLE, applied to the table; false when not understood
+fn alter_action(table: &mut Table, action: &str) -> bool {
+    if let Some(caps) = regex(r"^ADD\s+((?:CONSTRAINT|PRIMARY|FOREIGN|UNIQUE|CHECK|EXCLUDE|INDEX|KEY)\b.*)$").captures(action) {
+        table.constraints.push(caps[1].to_string());
+        return true;
+    }
+    if let Some(caps) = regex(r"^ADD\s+(?:COLUMN\s+)?(?:IF\s+NOT\s+EXISTS\s+)?(.+)$").captures(action) {
+        let definition = caps[1].to_string();
+        let column = column_key(&definition);
+        table.columns.retain(|(key, _)| key != &column);
+        table.columns.push((column, definition));
+        return true;
+    }
+    if let Some(caps) = regex(&format!(r"^DROP\s+(?:CONSTRAINT|INDEX|KEY|FOREIGN\s+KEY)\s+(?:IF\s+EXISTS\s+)?({})", IDENT)).captures(action) {
+        let name = key(&caps[1]);
+        table.constraints.retain(|constraint| !constraint.split_whitespace().any(|word| key(word) == name));
+        return true;
+    }
+    if regex(r"^DROP\s+PRIMARY\s+KEY$").is_match(action) {
+        table.constraints.retain(|constraint| !constraint.to_uppercase().starts_with("PRIMARY"));
+        return true;
+    }
+    if let Some(caps) = regex(&format!(r"^DROP\s+(?:COLUMN\s+)?(?:IF\s+EXISTS\s+)?({})", IDENT)).captures(action) {
+        let column = key(&caps[1]);
+        table.columns.retain(|(key, _)| key != &column);
+        return true;
+    }
+    if let Some(caps) = regex(&format!(r"^RENAME\s+(?:COLUMN\s+)?({})\s+TO\s+({})$", IDENT, IDENT)).captures(action) {
+        let column = key(&caps[1]);
+        if let Some(entry) = table.columns.iter_mut().find(|(key, _)| key == &column) {
+            let (_, column_type, rest) = column_parts(&entry.1);
+            *entry = (key(&caps[2]), join_column(&caps[2], &column_type, &rest));
+            return true;
+        }
+        return false;
+    }
+    // MySQL: CHANGE old new definition, MODIFY col definition
+    let replaced = match regex(&format!(r"^CHANGE\s+(?:COLUMN\s+)?({})\s+({}\s+.+)$", IDENT, IDENT)).captures(action) {
+        Some(caps) => Some((key(&caps[1]), caps[2].to_string())),
+        None => regex(&format!(r"^MODIFY\s+(?:COLUMN\s+)?({}\s+.+)$", IDENT))
+            .captures(action)
+            .map(|caps| (column_key(&caps[1]), caps[1].to_string())),
+    };
+    if let Some((old, definition)) = replaced {
+        return match table.columns.iter_mut().find(|(key, _)| key == &old) {
+            Some(entry) => {
+                *entry = (column_key(&definition), definition);
+                true
+            }
+            None => false,
+        };
+    }
+    if let Some(caps) = regex(&format!(r"^ALTER\s+(?:COLUMN\s+)?({})\s+(.+)$", IDENT)).captures(action) {
+        let column = key(&caps[1]);
+        let change = caps[2].to_string();
+        let entry = match table.columns.iter_mut().find(|(key, _)| key == &column) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        let (name, column_type, rest) = column_parts(&entry.1);
+        let default = regex(r"(?:^|\s)DEFAULT\s+(?:'(?:[^']|'')*'|\([^)]*\)|[^\s,]+(?:\([^)]*\))?)");
+        let not_null = regex(r"(?:^|\s)NOT\s+NULL\b");
+        let updated = if let Some(caps) = regex(r"^(?:SET\s+DATA\s+)?TYPE\s+(.+?)(?:\s+USING\s+.*)?$").captures(&change) {
+            join_column(&name, &caps[1], &rest)
+        } else if let Some(caps) = regex(r"^SET\s+DEFAULT\s+(.+)$").captures(&change) {
+            join_column(&name, &column_type, &format!("{} DEFAULT {}", default.replace_all(&rest, ""), &caps[1]))
+        } else if regex(r"^DROP\s+DEFAULT$").is_match(&change) {
+            join_column(&name, &column_type, &default.replace_all(&rest, ""))
+        } else if regex(r"^SET\s+NOT\s+NULL$").is_match(&change) {
+            join_column(&name, &column_type, &format!("{} NOT NULL", not_null.replace_all(&rest, "")))
+        } else if regex(r"^DROP\s+NOT\s+NULL$").is_match(&change) {
+            join_column(&name, &column_type, &not_null.replace_all(&rest, ""))
+        } else {
+            return false;
+        };
+        entry.1 = updated;
+        return true;
+    }
+    false
+}
+
+fn is_table_constraint(definition: &str) -> bool {
+    let first = definition.split_whitespace().next().unwrap_or("").to_uppercase();
+    TABLE_CONSTRAINTS.contains(&first.as_str())
+        // MySQL `KEY idx (col)` and `INDEX idx (col)`, not a column named key
+        || (matches!(first.as_str(), "KEY" | "INDEX") && regex(r"^\w+\s+(?:\S+\s*)?\(").is_match(definition))
+}
+
+// A column definition split into its name, type and constraints
+fn column_parts(definition: &str) -> (String, String, String) {
+    let mut words = definition.split_whitespace();
+    let name = words.next().unwrap_or("").to_string();
+    let mut column_type = Vec::new();
+    let mut rest = Vec::new();
+    for word in words {
+        if rest.is_empty() && !COLUMN_CONSTRAINTS.contains(&word.to_uppercase().as_str()) {
+            column_type.push(word);
+        } else {
+            rest.push(word);
+        }
+    }
+    (name, column_type.join(" "), rest.join(" "))
+}
+
+fn join_column(name: &str, column_type: &str, rest: &str) -> String {
+    [name, column_type, rest.trim()].iter().filter(|part| !part.is_empty()).copied().collect::<Vec<_>>().join(" ")
+}
+
+fn column_key(definition: &str) -> String {
+    key(definition.split_whitespace().next().unwrap_or(""))
+}
+
+// Identifiers compare without quotes and case
+fn key(name: &str) -> String {
+    name.trim().replace(['"', '`', '[', ']'], "").to_lowercase()
+}
+
+fn object_key(caps: &Captures) -> String {
+    format!("{} {}", caps[1].to_lowercase(), key(&caps[2]))
+}
+
+// The table a CREATE INDEX statement is on
+fn index_table(statement: &str) -> Option<String> {
+    regex(&format!(r"\sON\s+(?:ONLY\s+)?({})", IDENT)).captures(statement).map(|caps| key(&caps[1]))
+}
+
+// The columns a CREATE INDEX statement lists
+fn index_columns(statement: &str) -> Vec<String> {
+    let start = match regex(&format!(r"\sON\s+(?:ONLY\s+)?{}(?:\s+USING\s+\w+)?\s*\(", IDENT)).find(statement) {
+        Some(found) => found.end() - 1,
+        None => return Vec::new(),
+    };
+    parenthesized(&statement[start..]).map(|body| split_top_level(body, ',').iter().map(|column| column_key(column)).collect()).unwrap_or_default()
+}
+
+fn upsert(entries: &mut Vec<(String, String)>, key: String, statement: String) {
+    match entries.iter_mut().find(|(existing, _)| existing == &key) {
+        Some(entry) => entry.1 = statement,
+        None => entries.push((key, statement)),
+    }
+}
+
+// Case-insensitive patterns matched against whole statements
+fn regex(pattern: &str) -> Regex {
+    Regex::new(&format!("(?is){}", pattern)).expect("valid regex")
+}
+
+// The text inside the parenthesis `text` starts with
+fn parenthesized(text: &str) -> Option<&str> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(open), _) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[1..index]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Parts of `text` separated by `separator` outside parentheses and quotes
+fn split_top_level(text: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(open), _) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, _) if c == separator && depth == 0 => {
+                parts.push(text[start..index].trim().to_string());
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim().to_string());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+fn collapse(statement: &str) -> String {
+    statement.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// The statements of a migration without comments, split at semicolons
+// outside quotes and dollar-quoted function bodies
+fn statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut chars = sql.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                current.push(c);
+                for (_, next) in chars.by_ref() {
+                    current.push(next);
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            '-' if sql[index..].starts_with("--") => {
+                while chars.peek().is_some_and(|(_, next)| *next != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if sql[index..].starts_with("/*") => {
+                let end = sql[index + 2..].find("*/").map(|end| index + 2 + end + 2).unwrap_or(sql.len());
+                while chars.peek().is_some_and(|(next, _)| *next < end) {
+                    chars.next();
+                }
+                current.push(' ');
+            }
+            '$' => {
+                // $$ ... $$ or $tag$ ... $tag$
+                let tag_end = sql[index + 1..].find(|c: char| !(c.is_alphanumeric() || c == '_')).map(|end| index + 1 + end);
+                match tag_end.filter(|&end| sql[end..].starts_with('$')) {
+                    Some(end) => {
+                        let tag = &sql[index..=end];
+                        let close = sql[end + 1..].find(tag).map(|close| end + 1 + close + tag.len()).unwrap_or(sql.len());
+                        current.push_str(&sql[index..close]);
+                        while chars.peek().is_some_and(|(next, _)| *next < close) {
+                            chars.next();
+                        }
+                    }
+                    None => current.push(c),
+                }
+            }
+            ';' => {
+                if !current.trim().is_empty() {
+                    statements.push(std::mem::take(&mut current));
+                }
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.trim().is_empty() {
+        statements.push(current);
+    }
+    statements
+}
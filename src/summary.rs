@@ -0,0 +1,142 @@
+// src/summary.rs
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::cancel;
+use crate::tokens;
+use crate::FileEntry;
+
+// Number of largest files listed in the summary
+const LARGEST_FILES: usize = 5;
+
+// Why a file found during collection was left out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SkipReason {
+    Binary,
+    TooLarge,
+    Handler,
+    Hook,
+    ReadError,
+    Generated,
+    Filtered,
+    NotRequested,
+    OverBudget,
+    Folded,
+    Ignored,
+    Test,
+    Unchanged,
+}
+
+impl SkipReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            SkipReason::Binary => "binary",
+            SkipReason::TooLarge => "too large",
+            SkipReason::Handler => "skip handler",
+            SkipReason::Hook => "hook",
+            SkipReason::ReadError => "unreadable",
+            SkipReason::Generated => "generated",
+            SkipReason::Filtered => "filtered",
+            SkipReason::NotRequested => "not included",
+            SkipReason::OverBudget => "over token budget",
+            SkipReason::Folded => "folded migration",
+            SkipReason::Ignored => "ignore file",
+            SkipReason::Test => "test code",
+            SkipReason::Unchanged => "unchanged since last copy",
+        }
+    }
+}
+
+pub struct SkippedFile {
+    pub path: String,
+    pub source_path: PathBuf,
+    pub reason: SkipReason,
+}
+
+pub fn print_summary(files: &[FileEntry], skipped: &[SkippedFile], project_type: &str, output_bytes: usize, output_tokens: usize) {
+    println!();
+    println!("Summary");
+    println!("  Project type:    {}", project_type);
+    println!("  Files included:  {}", files.len());
+    
+    let mut by_reason: BTreeMap<SkipReason, usize> = BTreeMap::new();
+    for file in skipped {
+        *by_reason.entry(file.reason).or_insert(0) += 1;
+    }
+    if by_reason.is_empty() {
+        println!("  Files skipped:   0");
+    } else {
+        let reasons: Vec<String> = by_reason
+            .iter()
+            .map(|(reason, count)| format!("{} {}", count, reason.label()))
+            .collect();
+        println!("  Files skipped:   {} ({})", skipped.len(), reasons.join(", "));
+    }
+    
+    println!("  Output size:     {} bytes (~{} tokens)", output_bytes, output_tokens);
+    
+    let mut largest: Vec<&FileEntry> = files.iter().collect();
+    largest.sort_by(|a, b| b.content.len().cmp(&a.content.len()).then_with(|| a.path.cmp(&b.path)));
+    largest.truncate(LARGEST_FILES);
+    if !largest.is_empty() {
+        println!("  Largest files:");
+        let width = largest.iter().map(|file| file.path.len()).max().unwrap_or(0);
+        for file in largest {
+            println!(
+                "    {:<width$}  {:>9} bytes  ~{} tokens",
+                file.path,
+                file.content.len(),
+                tokens::estimate(&file.content),
+                width = width
+            );
+        }
+    }
+}
+
+// Writes the --summary-json report
+pub fn write_json(
+    path: &Path,
+    files: &[FileEntry],
+    skipped: &[SkippedFile],
+    (output_bytes, output_tokens): (usize, usize),
+    exit_code: u8,
+    duration: Duration,
+    destination: Value,
+) -> io::Result<()> {
+    let included: Vec<Value> = files
+        .iter()
+        .map(|file| {
+            json!({
+                "path": file.path,
+                "bytes": file.content.len(),
+                "tokens": tokens::estimate(&file.content),
+            })
+        })
+        .collect();
+    let omitted: Vec<Value> = skipped
+        .iter()
+        .map(|file| json!({ "path": file.path, "reason": file.reason.label() }))
+        .collect();
+    
+    let report = json!({
+        "exit_code": exit_code,
+        "cancelled": exit_code == cancel::EXIT_CANCELLED,
+        "duration_ms": duration.as_millis() as u64,
+        "output": {
+            "bytes": output_bytes,
+            "tokens": output_tokens,
+            "destination": destination,
+        },
+        "files": included,
+        "skipped": omitted,
+    });
+    
+    let text = serde_json::to_string_pretty(&report).map_err(io::Error::other)?;
+    fs::write(path, text + "\n")
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to write {}: {}", path.display(), e)))
+}
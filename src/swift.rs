@@ -0,0 +1,129 @@
+// src/swift.rs
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+// Summary of the Swift package whose Package.swift is at or above `start_dir`
+pub fn find_package_info(start_dir: &Path) -> Option<String> {
+    let manifest = start_dir.ancestors().map(|dir| dir.join("Package.swift")).find(|path| path.is_file())?;
+    let content = fs::read_to_string(&manifest).ok()?;
+    let mut info = String::from("Project Type: Swift package (Package.swift)\n");
+    
+    if let Some(name) = first_capture(r#"Package\s*\(\s*name:\s*"([^"]+)""#, &content) {
+        info.push_str(&format!("Project Name: {}\n", name));
+    }
+    if let Some(version) = first_capture(r"^//\s*swift-tools-version:\s*([\d.]+)", &content) {
+        info.push_str(&format!("Swift Tools Version: {}\n", version));
+    }
+    
+    // `.iOS(.v15)`, `.macOS("12.0")`
+    let platforms = section(&content, "platforms:")
+        .map(|platforms| {
+            captures(r#"\.(\w+)\(\s*(?:\.v([\d_]+)|"([^"]+)")"#, platforms, |caps| {
+                let version = caps.get(2).map(|v| v.as_str().replace('_', ".")).or_else(|| caps.get(3).map(|v| v.as_str().to_string()));
+                format!("{} {}", &caps[1], version.unwrap_or_default())
+            })
+        })
+        .unwrap_or_default();
+    if !platforms.is_empty() {
+        info.push_str(&format!("Platforms: {}\n", platforms.join(", ")));
+    }
+    
+    let products = section(&content, "products:")
+        .map(|products| captures(r#"\.(library|executable|plugin)\(\s*name:\s*"([^"]+)""#, products, |caps| {
+            format!("{} ({})", &caps[2], &caps[1])
+        }))
+        .unwrap_or_default();
+    list(&mut info, "Products", &products);
+    
+    let dependencies = section(&content, "dependencies:")
+        .map(|dependencies| captures(r#"\.package\(([^)]*(?:\([^)]*\)[^)]*)*)\)"#, dependencies, |caps| package_dependency(&caps[1])))
+        .unwrap_or_default();
+    list(&mut info, "Dependencies", &dependencies);
+    
+    let targets = section(&content, "targets:")
+        .map(|targets| captures(
+            r#"\.(target|executableTarget|testTarget|binaryTarget|macro|plugin|systemLibrary)\(\s*name:\s*"([^"]+)""#,
+            targets,
+            |caps| format!("{} ({})", &caps[2], &caps[1]),
+        ))
+        .unwrap_or_default();
+    list(&mut info, "Targets", &targets);
+    
+    Some(info)
+}
+
+// `url: "https://github.com/apple/swift-log.git", from: "1.5.0"` as
+// `swift-log (https://github.com/apple/swift-log.git) from 1.5.0`
+fn package_dependency(arguments: &str) -> String {
+    if let Some(path) = first_capture(r#"path:\s*"([^"]+)""#, arguments) {
+        return format!("{} (local)", path);
+    }
+    let url = first_capture(r#"url:\s*"([^"]+)""#, arguments).unwrap_or_default();
+    let name = url.trim_end_matches('/').trim_end_matches(".git").rsplit('/').next().unwrap_or(&url).to_string();
+    let requirement = first_capture(
+        r#"((?:from|exact|branch|revision|upToNextMajor\(from|upToNextMinor\(from):\s*"[^"]+"|"[^"]+"\s*\.\.[.<]\s*"[^"]+")"#,
+        arguments,
+    )
+    .map(|requirement| requirement.replace('"', "").replace('(', " ").replace(": ", " "));
+    match requirement {
+        Some(requirement) => format!("{} ({}) {}", name, url, requirement),
+        None => format!("{} ({})", name, url),
+    }
+}
+
+// The bracketed list following `label` among the arguments of `Package(`,
+// not the target and product lists of the same name nested in it
+fn section<'a>(content: &'a str, label: &str) -> Option<&'a str> {
+    let package = content.find("Package(")? + "Package(".len();
+    let mut depth = 0;
+    let mut start = None;
+    for (offset, c) in content[package..].char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ if depth == 0 && content[package + offset..].starts_with(label) => {
+                start = Some(package + offset + label.len());
+                break;
+            }
+            _ => {}
+        }
+    }
+    let start = start?;
+    let open = start + content[start..].find('[')?;
+    let mut depth = 0;
+    for (offset, c) in content[open..].char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&content[open + 1..open + offset]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn first_capture(pattern: &str, text: &str) -> Option<String> {
+    let regex = Regex::new(&format!("(?m){}", pattern)).ok()?;
+    regex.captures(text).map(|caps| caps[1].to_string())
+}
+
+fn captures(pattern: &str, text: &str, format: impl Fn(&regex::Captures) -> String) -> Vec<String> {
+    match Regex::new(pattern) {
+        Ok(regex) => regex.captures_iter(text).map(|caps| format(&caps)).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn list(info: &mut String, title: &str, items: &[String]) {
+    if !items.is_empty() {
+        info.push_str(&format!("\n{}:\n", title));
+        for item in items {
+            info.push_str(&format!("- {}\n", item));
+        }
+    }
+}
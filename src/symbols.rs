@@ -0,0 +1,57 @@
+// src/symbols.rs
+use std::path::Path;
+
+use crate::excerpt::preamble_start;
+use crate::outline::{self, Definition};
+
+// Line ranges of the definitions named by a `file::symbol` argument, with the
+// comments and attributes above them, or None when the file's language has no
+// tree-sitter grammar. `name` matches definitions at any depth, including a
+// Rust type's impl blocks; `Owner::name` only those directly inside `Owner`
+pub fn symbol_ranges(path: &Path, content: &str, symbol: &str) -> Option<Vec<(usize, usize)>> {
+    let definitions = outline::definitions(path, content)?;
+    let lines: Vec<&str> = content.lines().collect();
+    let (owner, name) = match symbol.rsplit_once("::") {
+        Some((owner, name)) => (Some(owner.rsplit("::").next().unwrap_or(owner)), name),
+        None => (None, symbol),
+    };
+    
+    let ranges = definitions
+        .iter()
+        .filter(|definition| base_name(definition) == name)
+        .filter(|definition| match owner {
+            None => true,
+            Some(owner) => definitions.iter().any(|container| {
+                base_name(container) == owner
+                    && container.depth + 1 == definition.depth
+                    && contains((container.start_line, container.end_line), definition.start_line)
+            }),
+        })
+        .map(|definition| (preamble_start(&lines, definition.start_line), definition.end_line))
+        .collect();
+    Some(ranges)
+}
+
+// Line range of the innermost definition containing `line`, so a method is
+// taken on its own rather than with its whole impl or class; None when there
+// is no grammar or `line` is outside every definition
+pub fn enclosing_range(path: &Path, content: &str, line: usize) -> Option<(usize, usize)> {
+    let definitions = outline::definitions(path, content)?;
+    let lines: Vec<&str> = content.lines().collect();
+    definitions
+        .iter()
+        .filter(|definition| contains((definition.start_line, definition.end_line), line))
+        .max_by_key(|definition| definition.depth)
+        .map(|definition| (preamble_start(&lines, definition.start_line), definition.end_line))
+}
+
+// The name without generics or a path, so `impl<'a> Lexer<'a>` and
+// `impl fmt::Display for crate::Lexer` are both found as `Lexer`
+fn base_name(definition: &Definition) -> &str {
+    let name = definition.name.split('<').next().unwrap_or("");
+    name.rsplit("::").next().unwrap_or(name).trim()
+}
+
+fn contains(range: (usize, usize), line: usize) -> bool {
+    range.0 <= line && line <= range.1
+}
@@ -0,0 +1,280 @@
+// src/test_code.rs
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::imports;
+use crate::FileEntry;
+use crate::paths;
+
+// Directories that only contain tests or benchmarks
+const TEST_DIRS: &[&str] = &["tests", "test", "__tests__", "benches"];
+
+pub fn is_test_dir(name: &str) -> bool {
+    TEST_DIRS.contains(&name)
+}
+
+// Test files recognized by naming convention
+pub fn is_test_file(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => name,
+        None => return false,
+    };
+    
+    // Python: test_*.py, *_test.py, conftest.py
+    if name.ends_with(".py") {
+        return name.starts_with("test_") || name.ends_with("_test.py") || name == "conftest.py";
+    }
+    
+    // Go: *_test.go
+    if name.ends_with("_test.go") {
+        return true;
+    }
+    
+    // Rust: *_test.rs / *_tests.rs
+    if name.ends_with("_test.rs") || name.ends_with("_tests.rs") {
+        return true;
+    }
+    
+    // JavaScript/TypeScript: *.test.ts, *.spec.js, ...
+    ["ts", "tsx", "js", "jsx", "mjs", "cjs"].iter().any(|ext| {
+        name.ends_with(&format!(".test.{}", ext)) || name.ends_with(&format!(".spec.{}", ext))
+    })
+}
+
+// Test files plus the source files they directly reference, for --tests-only
+pub fn select_tests_with_references(files: Vec<FileEntry>, project_root: &Path) -> Vec<FileEntry> {
+    let canonical: Vec<PathBuf> = files
+        .iter()
+        .map(|file| paths::canonicalize(&file.source_path).unwrap_or_else(|_| file.source_path.clone()))
+        .collect();
+    let index_by_path: HashMap<&PathBuf, usize> = canonical.iter().enumerate().map(|(i, p)| (p, i)).collect();
+    let crate_names = imports::rust_crate_names(project_root);
+    
+    let mut selected = HashSet::new();
+    for (i, file) in files.iter().enumerate() {
+        if !is_test_source(file, &canonical[i], project_root) {
+            continue;
+        }
+        selected.insert(i);
+        
+        // For Rust sources with inline tests, only the test items' imports count
+        let test_content = if is_inline_test_only(file, &canonical[i], project_root) {
+            rust_test_items(&file.content)
+        } else {
+            file.content.clone()
+        };
+        
+        for import in imports::extract_imports(&canonical[i], &test_content) {
+            for target in imports::resolve(&import, &canonical[i], project_root, &crate_names) {
+                let target = paths::canonicalize(&target).unwrap_or(target);
+                if let Some(&index) = index_by_path.get(&target) {
+                    selected.insert(index);
+                }
+            }
+        }
+    }
+    
+    files
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| selected.contains(i))
+        .map(|(_, file)| file)
+        .collect()
+}
+
+fn is_test_source(file: &FileEntry, canonical_path: &Path, project_root: &Path) -> bool {
+    is_dedicated_test_file(canonical_path, project_root) || has_inline_tests(file, canonical_path)
+}
+
+fn is_dedicated_test_file(canonical_path: &Path, project_root: &Path) -> bool {
+    let relative = canonical_path.strip_prefix(project_root).unwrap_or(canonical_path);
+    let in_test_dir = relative
+        .parent()
+        .is_some_and(|dir| dir.components().any(|c| c.as_os_str().to_str().is_some_and(is_test_dir)));
+    
+    in_test_dir || is_test_file(canonical_path)
+}
+
+fn has_inline_tests(file: &FileEntry, canonical_path: &Path) -> bool {
+    canonical_path.extension().is_some_and(|ext| ext == "rs") && find_cfg_test_attribute(&file.content).is_some()
+}
+
+fn is_inline_test_only(file: &FileEntry, canonical_path: &Path, project_root: &Path) -> bool {
+    has_inline_tests(file, canonical_path) && !is_dedicated_test_file(canonical_path, project_root)
+}
+
+// The text of all #[cfg(test)] items in a Rust source
+fn rust_test_items(content: &str) -> String {
+    let mut items = String::new();
+    let mut pos = 0;
+    
+    while let Some(offset) = find_cfg_test_attribute(&content[pos..]) {
+        let attr_start = pos + offset;
+        match find_item_end(content, attr_start) {
+            Some(item_end) => {
+                items.push_str(&content[attr_start..item_end]);
+                items.push('\n');
+                pos = item_end;
+            }
+            None => break,
+        }
+    }
+    
+    items
+}
+
+// Removes items annotated with #[cfg(test)] (typically `mod tests { ... }`) from Rust source
+pub fn strip_rust_test_code(content: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut pos = 0;
+    
+    while let Some(offset) = find_cfg_test_attribute(&content[pos..]) {
+        let attr_start = pos + offset;
+        let line_start = content[..attr_start].rfind('\n').map_or(0, |i| i + 1);
+        
+        match find_item_end(content, attr_start) {
+            Some(item_end) => {
+                output.push_str(&content[pos..line_start]);
+                
+                // Drop the rest of the closing line, including its newline
+                pos = match content[item_end..].find('\n') {
+                    Some(i) => item_end + i + 1,
+                    None => content.len(),
+                };
+                
+                // Avoid leaving a run of blank lines where the item was
+                while output.ends_with("\n\n") && content[pos..].starts_with('\n') {
+                    pos += 1;
+                }
+            }
+            None => {
+                // Unbalanced braces; leave the remainder untouched
+                break;
+            }
+        }
+    }
+    
+    output.push_str(&content[pos..]);
+    output
+}
+
+// Finds a #[cfg(test)] attribute that starts a line
+fn find_cfg_test_attribute(content: &str) -> Option<usize> {
+    let mut search_from = 0;
+    while let Some(offset) = content[search_from..].find("#[cfg(test)]") {
+        let index = search_from + offset;
+        let line_start = content[..index].rfind('\n').map_or(0, |i| i + 1);
+        if content[line_start..index].trim().is_empty() {
+            return Some(index);
+        }
+        search_from = index + 1;
+    }
+    None
+}
+
+// Returns the index just past the end of the item following the attribute:
+// either its terminating `;` or the brace closing its body
+fn find_item_end(content: &str, attr_start: usize) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut i = attr_start + "#[cfg(test)]".len();
+    let mut depth = 0usize;
+    // Square brackets of further attributes, so `#[should_panic]` etc. are skipped
+    let mut bracket_depth = 0usize;
+    
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = content[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = content[i + 2..].find("*/").map_or(bytes.len(), |n| i + 2 + n + 2);
+                continue;
+            }
+            b'"' => {
+                i = skip_string(bytes, i);
+                continue;
+            }
+            b'r' if is_raw_string_start(bytes, i) => {
+                i = skip_raw_string(content, i);
+                continue;
+            }
+            b'\'' => {
+                i = skip_char_literal(content, i);
+                continue;
+            }
+            b'[' => bracket_depth += 1,
+            b']' => bracket_depth = bracket_depth.saturating_sub(1),
+            b'{' if bracket_depth == 0 => depth += 1,
+            b'}' if bracket_depth == 0 => {
+                if depth == 0 {
+                    return None;
+                }
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            b';' if depth == 0 && bracket_depth == 0 => return Some(i + 1),
+            _ => {}
+        }
+        i += 1;
+    }
+    
+    None
+}
+
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn is_raw_string_start(bytes: &[u8], i: usize) -> bool {
+    // `r"` or `r#"`, not part of a longer identifier
+    if i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_') {
+        return false;
+    }
+    let mut j = i + 1;
+    while j < bytes.len() && bytes[j] == b'#' {
+        j += 1;
+    }
+    j < bytes.len() && bytes[j] == b'"'
+}
+
+fn skip_raw_string(content: &str, start: usize) -> usize {
+    let bytes = content.as_bytes();
+    let mut j = start + 1;
+    let mut hashes = 0;
+    while bytes[j] == b'#' {
+        hashes += 1;
+        j += 1;
+    }
+    let terminator = format!("\"{}", "#".repeat(hashes));
+    match content[j + 1..].find(&terminator) {
+        Some(n) => j + 1 + n + terminator.len(),
+        None => bytes.len(),
+    }
+}
+
+fn skip_char_literal(content: &str, start: usize) -> usize {
+    // Distinguish char literals ('{', '\n') from lifetimes ('a)
+    let rest = &content[start + 1..];
+    if let Some(escaped) = rest.strip_prefix('\\') {
+        return match escaped.find('\'') {
+            Some(n) => start + 1 + 1 + n + 1,
+            None => content.len(),
+        };
+    }
+    
+    match rest.chars().next() {
+        Some(c) if rest[c.len_utf8()..].starts_with('\'') => start + 1 + c.len_utf8() + 1,
+        _ => start + 1,
+    }
+}
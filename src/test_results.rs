@@ -0,0 +1,188 @@
+// src/test_results.rs
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::ProjectType;
+
+// Keep build or collection errors short enough to be useful in a prompt
+const MAX_ERROR_LINES: usize = 200;
+
+pub struct TestFailure {
+    pub name: String,
+    pub output: String,
+}
+
+pub struct TestRun {
+    pub command: String,
+    pub success: bool,
+    pub failures: Vec<TestFailure>,
+    // Output shown when the run failed without any parsed test failures,
+    // e.g. a compile error or a pytest collection error
+    pub error_output: Option<String>,
+}
+
+// Runs the project's test suite and collects the failing tests
+pub fn run(project_root: &Path, project_type: &ProjectType, filter: Option<&str>) -> io::Result<Option<TestRun>> {
+    let (program, mut args): (&str, Vec<String>) = match project_type {
+        ProjectType::Rust => ("cargo", vec!["test".to_string()]),
+        ProjectType::Python => (
+            if cfg!(windows) { "python" } else { "python3" },
+            vec!["-m".to_string(), "pytest".to_string(), "--tb=short".to_string()],
+        ),
+        ProjectType::Swift | ProjectType::Kotlin | ProjectType::Infrastructure | ProjectType::Unknown => return Ok(None),
+    };
+    
+    if let Some(filter) = filter {
+        if *project_type == ProjectType::Python {
+            args.push("-k".to_string());
+        }
+        args.push(filter.to_string());
+    }
+    
+    let command = format!("{} {}", program, args.join(" "));
+    let output = Command::new(program)
+        .args(&args)
+        .current_dir(project_root)
+        // Backtraces would dwarf the actual failure messages
+        .env("RUST_BACKTRACE", "0")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| io::Error::other(format!("Failed to run {}: {}", command, e)))?;
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    
+    let failures = match project_type {
+        ProjectType::Python => parse_pytest_failures(&stdout),
+        _ => parse_cargo_failures(&stdout),
+    };
+    
+    let success = output.status.success();
+    let error_output = if !success && failures.is_empty() {
+        let combined = format!("{}\n{}", stdout.trim(), stderr.trim());
+        Some(last_lines(combined.trim(), MAX_ERROR_LINES))
+    } else {
+        None
+    };
+    
+    Ok(Some(TestRun {
+        command,
+        success,
+        failures,
+        error_output,
+    }))
+}
+
+// Reads the "---- name stdout ----" blocks that libtest prints for failed tests
+fn parse_cargo_failures(stdout: &str) -> Vec<TestFailure> {
+    let mut failures: Vec<TestFailure> = Vec::new();
+    let mut current: Option<TestFailure> = None;
+    
+    for line in stdout.lines() {
+        if let Some(header) = line.strip_prefix("---- ") {
+            if let Some(failure) = current.take() {
+                failures.push(failure);
+            }
+            let name = header.trim_end_matches(" ----").trim_end_matches(" stdout").to_string();
+            current = Some(TestFailure { name, output: String::new() });
+        } else if line == "failures:" || line.starts_with("test result:") {
+            if let Some(failure) = current.take() {
+                failures.push(failure);
+            }
+        } else if let Some(ref mut failure) = current {
+            failure.output.push_str(line);
+            failure.output.push('\n');
+        }
+    }
+    if let Some(failure) = current.take() {
+        failures.push(failure);
+    }
+    
+    // Tests that failed without printing anything still get an entry
+    for line in stdout.lines() {
+        if let Some(rest) = line.strip_prefix("test ") {
+            if let Some(name) = rest.strip_suffix(" ... FAILED") {
+                if !failures.iter().any(|failure| failure.name == name) {
+                    failures.push(TestFailure { name: name.to_string(), output: String::new() });
+                }
+            }
+        }
+    }
+    
+    for failure in &mut failures {
+        failure.output = failure.output.trim().to_string();
+    }
+    failures
+}
+
+// Reads the per-test blocks of pytest's FAILURES section
+fn parse_pytest_failures(stdout: &str) -> Vec<TestFailure> {
+    let mut failures: Vec<TestFailure> = Vec::new();
+    let mut in_failures = false;
+    let mut current: Option<TestFailure> = None;
+    
+    for line in stdout.lines() {
+        if line.starts_with("===") {
+            in_failures = line.contains(" FAILURES ");
+            if let Some(failure) = current.take() {
+                failures.push(failure);
+            }
+            continue;
+        }
+        if !in_failures {
+            continue;
+        }
+        
+        if line.starts_with("___") && line.ends_with("___") {
+            if let Some(failure) = current.take() {
+                failures.push(failure);
+            }
+            let name = line.trim_matches('_').trim().to_string();
+            current = Some(TestFailure { name, output: String::new() });
+        } else if let Some(ref mut failure) = current {
+            failure.output.push_str(line);
+            failure.output.push('\n');
+        }
+    }
+    if let Some(failure) = current.take() {
+        failures.push(failure);
+    }
+    
+    for failure in &mut failures {
+        failure.output = failure.output.trim().to_string();
+    }
+    failures
+}
+
+fn last_lines(text: &str, count: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(count);
+    lines[start..].join("\n")
+}
+
+pub fn format_test_results_section(run: &TestRun) -> String {
+    let mut section = format!(
+        "<test_results command=\"{}\" failed=\"{}\">\n",
+        run.command,
+        run.failures.len()
+    );
+    
+    if run.success {
+        section.push_str("All tests passed\n");
+    }
+    for failure in &run.failures {
+        section.push_str(&format!("<failure name=\"{}\">\n", failure.name));
+        if !failure.output.is_empty() {
+            section.push_str(&failure.output);
+            section.push('\n');
+        }
+        section.push_str("</failure>\n");
+    }
+    if let Some(ref error_output) = run.error_output {
+        section.push_str(&format!("<error_output>\n{}\n</error_output>\n", error_output));
+    }
+    
+    section.push_str("</test_results>\n\n");
+    section
+}
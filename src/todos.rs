@@ -0,0 +1,82 @@
+// src/todos.rs
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::FileEntry;
+
+// Comments beyond this many are summarized as a count
+const MAX_LISTED_TODOS: usize = 200;
+
+// A TODO, FIXME or HACK comment in one of the copied files
+pub struct Todo {
+    pub path: String,
+    pub line: usize,
+    // The marker, with an owner like TODO(alice) when given
+    pub kind: String,
+    pub text: String,
+}
+
+// The marker right after a comment start in most languages: //, //!, /*, *,
+// #, --, ;, <!--
+fn todo_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?:(?://+|/\*+|#+|--|;+|<!--)[!*]?|^\s*\*)\s*((?:TODO|FIXME|HACK)\b(?:\([^)]*\))?)\s*:?\s*(.*)")
+            .unwrap()
+    })
+}
+
+// Scans the files before their contents are reduced, so line numbers refer
+// to the files on disk
+pub fn collect(files: &[FileEntry]) -> Vec<Todo> {
+    let mut todos = Vec::new();
+    for file in files {
+        let numbers = line_numbers(file);
+        for (index, line) in file.content.lines().enumerate() {
+            let (captures, number) = match (todo_regex().captures(line), numbers.get(index)) {
+                (Some(captures), Some(&Some(number))) => (captures, number),
+                _ => continue,
+            };
+            let text = captures[2].trim().trim_end_matches("*/").trim_end_matches("-->").trim();
+            todos.push(Todo {
+                path: file.path.clone(),
+                line: number,
+                kind: captures[1].to_string(),
+                text: text.to_string(),
+            });
+        }
+    }
+    todos
+}
+
+// The original line number of each line of the content; excerpts have a
+// separator line between their ranges
+fn line_numbers(file: &FileEntry) -> Vec<Option<usize>> {
+    if file.line_ranges.is_empty() {
+        return (1..=file.content.lines().count()).map(Some).collect();
+    }
+    let mut numbers = Vec::new();
+    for (index, &(start, end)) in file.line_ranges.iter().enumerate() {
+        if index > 0 {
+            numbers.push(None);
+        }
+        numbers.extend((start..=end).map(Some));
+    }
+    numbers
+}
+
+pub fn format_todos_section(todos: &[Todo]) -> String {
+    let mut section = format!("<todos count=\"{}\">\n", todos.len());
+    for todo in todos.iter().take(MAX_LISTED_TODOS) {
+        if todo.text.is_empty() {
+            section.push_str(&format!("- {}:{} {}\n", todo.path, todo.line, todo.kind));
+        } else {
+            section.push_str(&format!("- {}:{} {}: {}\n", todo.path, todo.line, todo.kind, todo.text));
+        }
+    }
+    if todos.len() > MAX_LISTED_TODOS {
+        section.push_str(&format!("- ... and {} more\n", todos.len() - MAX_LISTED_TODOS));
+    }
+    section.push_str("</todos>\n\n");
+    section
+}
@@ -0,0 +1,10 @@
+// src/tokens.rs
+
+// Rough token count for English text and code: about four characters per token
+pub fn estimate(text: &str) -> usize {
+    estimate_chars(text.chars().count())
+}
+
+pub fn estimate_chars(chars: usize) -> usize {
+    chars.div_ceil(4)
+}
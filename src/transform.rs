@@ -0,0 +1,210 @@
+// src/transform.rs
+use log::trace;
+use std::path::Path;
+
+use crate::{comments, content_hash, handlers, iac, redact, FileEntry};
+
+// Lines kept by `truncate` without a count
+const DEFAULT_TRUNCATE_LINES: usize = 500;
+
+// A stage named with --transforms
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stage {
+    Redact,
+    StripComments,
+    Truncate(usize),
+    Normalize,
+}
+
+// `redact`, `strip-comments`, `truncate[:LINES]` or `normalize`
+pub fn parse_stage(value: &str) -> Result<Stage, String> {
+    match value.trim() {
+        "redact" => Ok(Stage::Redact),
+        "strip-comments" => Ok(Stage::StripComments),
+        "normalize" => Ok(Stage::Normalize),
+        "truncate" => Ok(Stage::Truncate(DEFAULT_TRUNCATE_LINES)),
+        other => match other.strip_prefix("truncate:") {
+            Some(lines) => match lines.parse::<usize>() {
+                Ok(lines) if lines > 0 => Ok(Stage::Truncate(lines)),
+                _ => Err(format!("expected a positive line count after truncate:, got '{}'", lines)),
+            },
+            None => Err(format!(
+                "unknown transform '{}' (expected redact, strip-comments, truncate[:LINES] or normalize)",
+                other
+            )),
+        },
+    }
+}
+
+// One step of the pipeline every file's content goes through once collected.
+// Stages only rewrite the content in memory: since `redact` can be ordered
+// after any of them, a step that sends content out of the process (to an
+// embedding API, a handler or a hook) must not be a stage, and must run
+// after the whole pipeline
+pub trait Transform {
+    fn name(&self) -> &'static str;
+    
+    // Stages that add or remove lines leave excerpts alone, since their line
+    // numbers refer to the file on disk
+    fn keeps_lines(&self) -> bool {
+        false
+    }
+    
+    fn apply(&self, path: &Path, content: &str) -> String;
+}
+
+// The [redact] rules from the config
+struct Redact(redact::Redactor);
+
+impl Transform for Redact {
+    fn name(&self) -> &'static str {
+        "redact"
+    }
+    
+    fn keeps_lines(&self) -> bool {
+        true
+    }
+    
+    fn apply(&self, _path: &Path, content: &str) -> String {
+        self.0.apply(content)
+    }
+}
+
+// The values in Terraform variable files, on by default
+struct RedactTfvars;
+
+impl Transform for RedactTfvars {
+    fn name(&self) -> &'static str {
+        "redact-tfvars"
+    }
+    
+    fn keeps_lines(&self) -> bool {
+        true
+    }
+    
+    fn apply(&self, path: &Path, content: &str) -> String {
+        if iac::is_tfvars(path) {
+            iac::redact_tfvars(content)
+        } else {
+            content.to_string()
+        }
+    }
+}
+
+struct StripComments;
+
+impl Transform for StripComments {
+    fn name(&self) -> &'static str {
+        "strip-comments"
+    }
+    
+    fn apply(&self, path: &Path, content: &str) -> String {
+        comments::strip(path, content)
+    }
+}
+
+struct Truncate(usize);
+
+impl Transform for Truncate {
+    fn name(&self) -> &'static str {
+        "truncate"
+    }
+    
+    fn apply(&self, _path: &Path, content: &str) -> String {
+        handlers::truncate(content, self.0)
+    }
+}
+
+// Unix line endings, no trailing whitespace, no runs of blank lines and a
+// single newline at the end
+struct Normalize;
+
+impl Transform for Normalize {
+    fn name(&self) -> &'static str {
+        "normalize"
+    }
+    
+    fn apply(&self, path: &Path, content: &str) -> String {
+        // Two trailing spaces are a line break in Markdown
+        let markdown = path.extension().is_some_and(|ext| ext == "md" || ext == "markdown");
+        let content = content.replace("\r\n", "\n").replace('\r', "\n");
+        let mut normalized = String::with_capacity(content.len());
+        let mut blank = true;
+        for line in content.lines() {
+            let line = if markdown && line.ends_with("  ") && !line.trim().is_empty() { line } else { line.trim_end() };
+            if line.is_empty() {
+                if !blank {
+                    normalized.push('\n');
+                }
+                blank = true;
+                continue;
+            }
+            normalized.push_str(line);
+            normalized.push('\n');
+            blank = false;
+        }
+        // The blank line kept after the last text line
+        if normalized.ends_with("\n\n") {
+            normalized.pop();
+        }
+        normalized
+    }
+}
+
+pub struct Pipeline {
+    stages: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    // The stages in the order given; redaction runs first when it is not
+    // named and the config has [redact] rules, after tfvars values are
+    // redacted unless `redact_tfvars` is off
+    pub fn new(stages: &[Stage], redactor: redact::Redactor, redact_tfvars: bool) -> Pipeline {
+        let mut redactor = Some(redactor).filter(|redactor| !redactor.is_empty());
+        let mut pipeline: Vec<Box<dyn Transform>> = Vec::new();
+        if redact_tfvars {
+            pipeline.push(Box::new(RedactTfvars));
+        }
+        if !stages.contains(&Stage::Redact) {
+            if let Some(redactor) = redactor.take() {
+                pipeline.push(Box::new(Redact(redactor)));
+            }
+        }
+        for stage in stages {
+            match stage {
+                Stage::Redact => {
+                    if let Some(redactor) = redactor.take() {
+                        pipeline.push(Box::new(Redact(redactor)));
+                    }
+                }
+                Stage::StripComments => pipeline.push(Box::new(StripComments)),
+                Stage::Truncate(lines) => pipeline.push(Box::new(Truncate(*lines))),
+                Stage::Normalize => pipeline.push(Box::new(Normalize)),
+            }
+        }
+        Pipeline { stages: pipeline }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+    
+    pub fn apply(&self, file: &mut FileEntry) {
+        let excerpt = !file.line_ranges.is_empty();
+        let mut changed = false;
+        for stage in &self.stages {
+            if excerpt && !stage.keeps_lines() {
+                continue;
+            }
+            let content = stage.apply(&file.source_path, &file.content);
+            if content != file.content {
+                trace!("{} changed {}", stage.name(), file.path);
+                file.content = content;
+                changed = true;
+            }
+        }
+        if changed {
+            file.hash = content_hash(&file.content);
+        }
+    }
+}
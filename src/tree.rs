@@ -0,0 +1,113 @@
+// src/tree.rs
+use std::collections::BTreeMap;
+
+// Directory tree built from relative file paths; files keep their index in
+// the list the tree was built from
+#[derive(Default)]
+pub struct TreeNode {
+    pub dirs: BTreeMap<String, TreeNode>,
+    pub files: Vec<(String, usize)>,
+}
+
+impl TreeNode {
+    // Accepts both `/` and `\` as separators
+    pub fn build<'a>(paths: impl IntoIterator<Item = &'a str>) -> TreeNode {
+        let mut root = TreeNode::default();
+        for (index, path) in paths.into_iter().enumerate() {
+            let parts: Vec<&str> = path.split(['/', '\\']).filter(|part| !part.is_empty() && *part != ".").collect();
+            if let Some((name, dirs)) = parts.split_last() {
+                let mut node = &mut root;
+                for dir in dirs {
+                    node = node.dirs.entry(dir.to_string()).or_default();
+                }
+                node.files.push((name.to_string(), index));
+            }
+        }
+        
+        root.sort_files();
+        root
+    }
+    
+    fn sort_files(&mut self) {
+        self.files.sort();
+        for child in self.dirs.values_mut() {
+            child.sort_files();
+        }
+    }
+    
+    // Number of files in this directory and all of its subdirectories
+    pub fn file_count(&self) -> usize {
+        self.files.len() + self.dirs.values().map(TreeNode::file_count).sum::<usize>()
+    }
+    
+    // Renders directories first, then files, with box-drawing connectors
+    pub fn render(&self) -> String {
+        self.render_annotated(&|_| None)
+    }
+    
+    // Like `render`, with a note after each file name, looked up by file index
+    pub fn render_annotated(&self, annotate: &dyn Fn(usize) -> Option<String>) -> String {
+        self.render_annotated_dirs(annotate, &|_| None)
+    }
+    
+    // Like `render_annotated`, with a note after each directory as well,
+    // looked up by the indices of the files below it
+    pub fn render_annotated_dirs(
+        &self,
+        annotate: &dyn Fn(usize) -> Option<String>,
+        annotate_dir: &dyn Fn(&[usize]) -> Option<String>,
+    ) -> String {
+        let mut output = String::new();
+        self.render_into("", annotate, annotate_dir, &mut output);
+        output
+    }
+    
+    fn file_indices(&self, indices: &mut Vec<usize>) {
+        indices.extend(self.files.iter().map(|(_, index)| *index));
+        for child in self.dirs.values() {
+            child.file_indices(indices);
+        }
+    }
+    
+    fn render_into(
+        &self,
+        prefix: &str,
+        annotate: &dyn Fn(usize) -> Option<String>,
+        annotate_dir: &dyn Fn(&[usize]) -> Option<String>,
+        output: &mut String,
+    ) {
+        let entries = self.dirs.len() + self.files.len();
+        let mut position = 0;
+        
+        for (name, child) in &self.dirs {
+            position += 1;
+            let last = position == entries;
+            let count = child.file_count();
+            output.push_str(&format!(
+                "{}{}{}/ ({} {})",
+                prefix,
+                if last { "└── " } else { "├── " },
+                name,
+                count,
+                if count == 1 { "file" } else { "files" }
+            ));
+            let mut indices = Vec::new();
+            child.file_indices(&mut indices);
+            if let Some(note) = annotate_dir(&indices) {
+                output.push_str(&format!("  [{}]", note));
+            }
+            output.push('\n');
+            child.render_into(&format!("{}{}", prefix, if last { "    " } else { "│   " }), annotate, annotate_dir, output);
+        }
+        
+        for (name, index) in &self.files {
+            position += 1;
+            let last = position == entries;
+            output.push_str(&format!("{}{}{}", prefix, if last { "└── " } else { "├── " }, name));
+            if let Some(note) = annotate(*index) {
+                output.push_str(&format!("  [{}]", note));
+            }
+            output.push('\n');
+        }
+    }
+}
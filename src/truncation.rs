@@ -0,0 +1,54 @@
+// src/truncation.rs
+use clap::ValueEnum;
+
+// Which part of an oversized file to keep with --truncate-large
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Strategy {
+    Head,
+    Tail,
+    Middle,
+}
+
+// Keeps whole lines from the start, the end, or both ends (for `Middle`, the
+// middle is cut) so that the kept lines fit in `max_bytes`
+pub fn truncate_to_size(content: &str, strategy: Strategy, max_bytes: usize) -> String {
+    if content.len() <= max_bytes {
+        return content.to_string();
+    }
+    
+    let lines: Vec<&str> = content.lines().collect();
+    let (head_budget, tail_budget) = match strategy {
+        Strategy::Head => (max_bytes, 0),
+        Strategy::Tail => (0, max_bytes),
+        Strategy::Middle => (max_bytes / 2, max_bytes / 2),
+    };
+    
+    let head_count = lines_within(lines.iter(), head_budget);
+    let tail_count = lines_within(lines[head_count..].iter().rev(), tail_budget);
+    let omitted = lines.len() - head_count - tail_count;
+    
+    let mut output = String::new();
+    for line in &lines[..head_count] {
+        output.push_str(line);
+        output.push('\n');
+    }
+    output.push_str(&format!("[... truncated {} lines ...]\n", omitted));
+    for line in &lines[lines.len() - tail_count..] {
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+fn lines_within<'a>(lines: impl Iterator<Item = &'a &'a str>, budget: usize) -> usize {
+    let mut used = 0;
+    let mut count = 0;
+    for line in lines {
+        used += line.len() + 1;
+        if used > budget {
+            break;
+        }
+        count += 1;
+    }
+    count
+}